@@ -0,0 +1,21 @@
+//! Former name of [`kodegen_claude_agent`]
+//!
+//! This crate re-exports `kodegen_claude_agent` under its old name so that
+//! code written against `kodegen_tools_claude_agent::...` keeps compiling
+//! while a workspace migrates. Its features map one-to-one onto those of
+//! `kodegen_claude_agent`.
+//!
+//! # Migrating
+//!
+//! Depend on the new crate under the old name, then rename the paths at
+//! your own pace:
+//!
+//! ```toml
+//! [dependencies]
+//! kodegen_tools_claude_agent = { package = "kodegen_claude_agent", version = "0.10" }
+//! ```
+//!
+//! Once no `kodegen_tools_claude_agent::` paths remain, depend on
+//! `kodegen_claude_agent` directly. This facade receives no new API.
+
+pub use kodegen_claude_agent::*;
@@ -0,0 +1,45 @@
+//! Migration guard: the old crate paths must keep resolving to the new items
+
+use std::any::TypeId;
+
+#[test]
+fn test_old_paths_name_the_same_items() {
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::ClaudeAgentOptions>(),
+        TypeId::of::<kodegen_claude_agent::ClaudeAgentOptions>()
+    );
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::types::messages::Message>(),
+        TypeId::of::<kodegen_claude_agent::Message>()
+    );
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::ClaudeError>(),
+        TypeId::of::<kodegen_claude_agent::ClaudeError>()
+    );
+    assert_eq!(
+        kodegen_tools_claude_agent::VERSION,
+        kodegen_claude_agent::VERSION
+    );
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn test_old_paths_cover_the_client() {
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::ClaudeSDKClient>(),
+        TypeId::of::<kodegen_claude_agent::ClaudeSDKClient>()
+    );
+}
+
+#[cfg(feature = "tools")]
+#[test]
+fn test_old_paths_cover_the_tools() {
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::tools::ClaudeAgentTool>(),
+        TypeId::of::<kodegen_claude_agent::ClaudeAgentTool>()
+    );
+    assert_eq!(
+        TypeId::of::<kodegen_tools_claude_agent::AgentManager>(),
+        TypeId::of::<kodegen_claude_agent::manager::AgentManager>()
+    );
+}
@@ -0,0 +1,57 @@
+//! Agent swarm: plan, implement in parallel, review
+//!
+//! Runs a planner, `IMPLEMENTERS` parallel implementers and a code reviewer
+//! on one task in the current directory, sharing a budget and a blackboard,
+//! then prints each member's result and the swarm's cost.
+//!
+//! ```sh
+//! cargo run --example swarm --features manager -- "Add a --verbose flag"
+//! ```
+
+use std::time::Duration;
+
+use kodegen_claude_agent::AgentManager;
+use kodegen_claude_agent::manager::Swarm;
+
+/// Number of parallel implementers
+const IMPLEMENTERS: usize = 2;
+
+/// Spending limit for the whole swarm, in USD
+const BUDGET_USD: f64 = 5.0;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let task = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "Add a --verbose flag to the CLI".to_string());
+
+    let manager = AgentManager::new();
+    let swarm = Swarm::plan_implement_review(&task, IMPLEMENTERS)
+        .budget_usd(BUDGET_USD)
+        .stage_timeout(Duration::from_secs(20 * 60));
+    let report = manager.run_swarm(swarm).await?;
+
+    for stage in &report.stages {
+        println!("== {} ==", stage.name);
+        for member in &stage.members {
+            match (&member.result, &member.error) {
+                (Some(result), _) => println!("[{}] {result}\n", member.label),
+                (None, Some(error)) => println!("[{}] failed: {error}\n", member.label),
+                (None, None) => println!("[{}] no result\n", member.label),
+            }
+        }
+    }
+
+    println!(
+        "Total cost: ${:.4} of ${BUDGET_USD:.2}",
+        report.cost.total_cost_usd
+    );
+    for (session_id, cost) in &report.cost.sessions {
+        println!("  {session_id}: ${cost:.4}");
+    }
+
+    manager.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// JSON Schema generator for the public JSON types
+//
+// Usage: kodegen-claude-agent-schema [OUT_DIR]
+//
+// Writes one `<TypeName>.schema.json` file per type into OUT_DIR, or prints
+// all schemas as a single JSON object keyed by type name when no directory
+// is given.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use kodegen_claude_agent::schema;
+
+fn main() -> ExitCode {
+    let Some(dir) = std::env::args_os().nth(1).map(PathBuf::from) else {
+        match serde_json::to_string_pretty(&schema::schemas()) {
+            Ok(json) => {
+                println!("{json}");
+                return ExitCode::SUCCESS;
+            }
+            Err(e) => {
+                eprintln!("Failed to serialize schemas: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+
+    match schema::write_schemas(&dir) {
+        Ok(written) => {
+            for path in written {
+                println!("{}", path.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Failed to write schemas to {}: {e}", dir.display());
+            ExitCode::FAILURE
+        }
+    }
+}
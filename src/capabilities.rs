@@ -0,0 +1,124 @@
+//! Capabilities of this build of the SDK
+//!
+//! Cargo features decide which parts of the crate (the client, the session
+//! manager, the MCP tool and server, the HTTP transport) are compiled in.
+//! [`capabilities()`] reports them at runtime together with the supported
+//! tool actions, protocol versions and limits, so clients and orchestrating
+//! agents can adapt to the build they are talking to.
+//!
+//! # Example
+//!
+//! ```
+//! let caps = kodegen_claude_agent::capabilities();
+//! assert_eq!(caps.version, kodegen_claude_agent::VERSION);
+//! assert!(caps.control_methods.contains(&"interrupt"));
+//! if caps.has_feature("http") {
+//!     // HttpTransport is available
+//! }
+//! ```
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::control::protocol::{ControlRequest, PROTOCOL_VERSION};
+use crate::transport::subprocess::{DEFAULT_MAX_BUFFER_SIZE, MAX_COMMAND_LINE};
+use crate::types::options::MAX_TURNS;
+
+/// Cargo features of the crate, with whether each is compiled in
+const FEATURES: &[(&str, bool)] = &[
+    ("client", cfg!(feature = "client")),
+    ("manager", cfg!(feature = "manager")),
+    ("tools", cfg!(feature = "tools")),
+    ("server", cfg!(feature = "server")),
+    ("http", cfg!(feature = "http")),
+    ("schema", cfg!(feature = "schema")),
+    ("blocking", cfg!(feature = "blocking")),
+    ("toml", cfg!(feature = "toml")),
+];
+
+/// Actions of the `claude_agent` MCP tool
+const TOOL_ACTIONS: &[&str] = &["SPAWN", "SEND", "READ", "LIST", "KILL"];
+
+/// What this build of the SDK supports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// SDK version
+    pub version: &'static str,
+    /// Cargo features compiled into this build
+    pub features: Vec<&'static str>,
+    /// Actions of the `claude_agent` MCP tool; empty without the `tools` feature
+    pub tool_actions: Vec<&'static str>,
+    /// Control protocol versions spoken with the CLI
+    pub protocol_versions: Vec<&'static str>,
+    /// Control request methods the SDK can send to the CLI
+    pub control_methods: Vec<&'static str>,
+    /// Limits applied by the SDK
+    pub limits: Limits,
+}
+
+/// Limits applied by the SDK
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Limits {
+    /// Largest accepted `max_turns`
+    pub max_turns: u32,
+    /// Largest JSON message read from the CLI when `max_buffer_size` is unset
+    pub default_max_buffer_size: usize,
+    /// Longest command line (in bytes) before large arguments move to files
+    pub max_command_line: usize,
+}
+
+impl Capabilities {
+    /// Whether the Cargo feature `name` is compiled in
+    ///
+    /// Unknown feature names report `false`.
+    #[must_use]
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.contains(&name)
+    }
+}
+
+impl fmt::Display for Capabilities {
+    /// One-line summary, e.g. for a tool description
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "kodegen-claude-agent {} (features: {}; protocol {}; max_turns {})",
+            self.version,
+            self.features.join(", "),
+            self.protocol_versions.join(", "),
+            self.limits.max_turns
+        )
+    }
+}
+
+/// Capabilities of this build
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    let tool_actions = if cfg!(feature = "tools") {
+        TOOL_ACTIONS.to_vec()
+    } else {
+        Vec::new()
+    };
+
+    Capabilities {
+        version: crate::VERSION,
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+        tool_actions,
+        protocol_versions: vec![PROTOCOL_VERSION],
+        control_methods: ControlRequest::METHODS.to_vec(),
+        limits: Limits {
+            max_turns: MAX_TURNS,
+            default_max_buffer_size: DEFAULT_MAX_BUFFER_SIZE,
+            max_command_line: MAX_COMMAND_LINE,
+        },
+    }
+}
@@ -2,19 +2,48 @@
 //!
 //! This module contains the constructor and public API methods for `ClaudeSDKClient`.
 
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use futures::Stream;
+use tokio::sync::{Mutex, mpsc, watch};
+
+use super::health::HealthMonitor;
+use super::history::History;
+use super::lifecycle::{Lifecycle, LifecycleEvent, LifecycleEvents};
+use super::split::{AgentReceiver, AgentSender};
 
 use crate::control::ProtocolHandler;
 use crate::error::{ClaudeError, Result};
-use crate::hooks::HookManager;
-use crate::permissions::PermissionManager;
-use crate::transport::{PromptInput, SubprocessTransport, Transport};
-use crate::types::hooks::HookEvent;
-use crate::types::identifiers::RequestId;
-use crate::types::messages::Message;
+use crate::hooks::{HookId, HookManager, HookStats};
+use crate::permissions::{
+    PathSandbox, PermissionEvent, PermissionManager, PermissionStats, PolicyRule,
+};
+#[cfg(feature = "http")]
+use crate::transport::HttpTransport;
+use crate::transport::{
+    BoxedTransport, ContainerTransport, MetricsRecorder, PromptInput, SshTransport,
+    SubprocessTransport, Transport, TransportMetrics,
+};
+use crate::types::delta::{Delta, DeltaDecoder};
+use crate::types::hooks::{HookEvent, HookMatcher};
+use crate::types::identifiers::{RequestId, SessionId};
+use crate::types::messages::{
+    ContentBlock, Message, TurnOutcome, UsageTotals, image_media_type, unsupported_image,
+};
 use crate::types::options::ClaudeAgentOptions;
-use crate::types::permissions::{PermissionRequest, PermissionResult};
+use crate::types::permissions::{
+    CanUseToolCallback, PermissionMode, PermissionRequest, PermissionResult, PermissionUpdate,
+};
+use crate::types::transport::TransportConfig;
+
+/// How long `interrupt_and_send` waits for the interrupted turn to end
+const INTERRUPT_ACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long `wait_for_result` waits for a timed-out turn's result
+const INTERRUPT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
 
 impl super::ClaudeSDKClient {
     /// Create a new `ClaudeSDKClient`
@@ -29,48 +58,143 @@ impl super::ClaudeSDKClient {
         options: ClaudeAgentOptions,
         cli_path: Option<std::path::PathBuf>,
     ) -> Result<super::ClaudeSDKClient> {
-        // Initialize hook manager if hooks are configured
-        let (hook_manager, hook_rx) = options.hooks.as_ref().map_or_else(
-            || (None, Some(mpsc::unbounded_channel().1)),
-            |hooks_config| {
-                let mut manager = HookManager::new();
-                for matchers in hooks_config.values() {
+        let transport = Self::transport_for(&options, cli_path.clone())?;
+        let mut client = Self::with_transport(options, transport).await?;
+        client.cli_path = cli_path;
+        Ok(client)
+    }
+
+    /// Resume an earlier CLI session
+    ///
+    /// Starts the CLI with `options` and `--resume <session_id>`, continuing
+    /// the conversation captured with [`session_id`](Self::session_id). Set
+    /// `fork_session` in `options` to branch into a new session instead of
+    /// appending to the old one. Use [`ClaudeAgentOptionsBuilder::resume`]
+    /// with [`new`](Self::new) or [`with_transport`](Self::with_transport)
+    /// to pick the CLI or transport.
+    ///
+    /// [`ClaudeAgentOptionsBuilder::resume`]: crate::ClaudeAgentOptionsBuilder::resume
+    ///
+    /// # Errors
+    /// Returns error if CLI cannot be found or connection fails
+    pub async fn resume(
+        session_id: impl Into<SessionId>,
+        mut options: ClaudeAgentOptions,
+    ) -> Result<super::ClaudeSDKClient> {
+        options.resume = Some(session_id.into());
+        Self::new(options, None).await
+    }
+
+    /// Branch the conversation into a second client
+    ///
+    /// Starts another CLI with this client's options, resuming this
+    /// session with `--fork-session`: the fork starts from the conversation
+    /// so far under a new session ID, and neither client sees the other's
+    /// later turns. The fork uses the transport configured in the options
+    /// and the CLI path this client was created with. Hooks added with
+    /// [`add_hook`](Self::add_hook) and a callback set with
+    /// [`set_can_use_tool`](Self::set_can_use_tool) are not carried over.
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if the CLI has not announced a
+    /// session ID yet, or an error if the fork cannot be started
+    pub async fn fork(&self) -> Result<super::ClaudeSDKClient> {
+        let session_id = self.session_id().ok_or_else(|| {
+            ClaudeError::invalid_config("Cannot fork before the CLI has announced its session")
+        })?;
+        let mut options = self.options.clone();
+        options.resume = Some(session_id);
+        options.fork_session = true;
+        Self::new(options, self.cli_path.clone()).await
+    }
+
+    /// Create a new `ClaudeSDKClient` over a caller-provided transport
+    ///
+    /// The transport is connected by this call. `options.transport` is ignored.
+    ///
+    /// # Arguments
+    /// * `options` - Configuration options
+    /// * `transport` - Unconnected transport to communicate over
+    ///
+    /// # Errors
+    /// Returns error if connection fails
+    pub async fn with_transport<T: Transport + 'static>(
+        options: ClaudeAgentOptions,
+        transport: T,
+    ) -> Result<super::ClaudeSDKClient> {
+        // Initialize hook manager; hooks can also be added later
+        let mut hook_manager = HookManager::new();
+        hook_manager.set_timeout(options.hook_timeout);
+        hook_manager.set_parallel(options.parallel_hooks);
+        hook_manager.set_merge_strategy(options.hook_merge_strategy);
+        if let Some(output) = options.hook_timeout_output.clone() {
+            hook_manager.set_timeout_output(output);
+        }
+        let hook_rx = match &options.hooks {
+            Some(hooks_config) => {
+                for (event, matchers) in hooks_config {
                     for matcher in matchers {
-                        manager.register(matcher.clone());
+                        hook_manager.register_for(*event, matcher.clone());
                     }
                 }
-                (Some(Arc::new(Mutex::new(manager))), None)
-            },
-        );
+                None
+            }
+            None => Some(mpsc::unbounded_channel().1),
+        };
+        let hook_manager = Arc::new(Mutex::new(hook_manager));
 
-        // Initialize permission manager if callback is configured
-        let (permission_manager, permission_rx) = if options.can_use_tool.is_some() {
-            let mut manager = PermissionManager::new();
-            if let Some(callback) = options.can_use_tool.clone() {
-                manager.set_callback(callback);
+        // Initialize permission manager; the callback can also be set later
+        let mut permission_manager = PermissionManager::new();
+        let permission_rx = match options.can_use_tool.clone() {
+            Some(callback) => {
+                permission_manager.set_callback(callback);
+                None
             }
-            manager.set_allowed_tools(Some(options.allowed_tools.clone()));
-            manager.set_disallowed_tools(options.disallowed_tools.clone());
-            (Some(Arc::new(Mutex::new(manager))), None)
-        } else {
-            (None, Some(mpsc::unbounded_channel().1))
+            None => Some(mpsc::unbounded_channel().1),
         };
+        // An empty allow list places no restriction, as for the CLI
+        permission_manager.set_allowed_tools(
+            (!options.allowed_tools.is_empty()).then(|| options.allowed_tools.clone()),
+        );
+        permission_manager.set_disallowed_tools(options.disallowed_tools.clone());
+        permission_manager.set_policy(options.permission_policy.clone());
+        if options.sandbox_paths {
+            let base = match options.cwd.clone() {
+                Some(cwd) => cwd,
+                None => std::env::current_dir()?,
+            };
+            permission_manager.set_sandbox(Some(PathSandbox::new(base, options.add_dirs.clone())));
+        }
+        for (tool, &(max_calls, window)) in &options.tool_rate_limits {
+            permission_manager.set_rate_limit(tool.clone(), max_calls, window);
+        }
+        let permission_manager = Arc::new(Mutex::new(permission_manager));
 
-        // Create transport with streaming mode
-        let prompt_input = PromptInput::Stream;
-        let mut transport = SubprocessTransport::new(prompt_input, options, cli_path)?;
+        let health_check_interval = options.health_check_interval;
+        let history = options.history_limit.map(History::new);
+        let sdk_mcp_servers = options.sdk_mcp_servers.clone();
+        let id_generator = options.id_generator.clone();
 
         // Connect transport
+        let mut transport = BoxedTransport::new(transport);
         transport.connect().await?;
+        let metrics = transport.metrics();
 
         // Create protocol handler
         let mut protocol = ProtocolHandler::new();
+        if let Some(ids) = id_generator {
+            protocol.set_id_generator(ids);
+        }
 
         // Set up channels
         let (hook_tx, hook_rx_internal) = mpsc::unbounded_channel();
         let (permission_tx, permission_rx_internal) = mpsc::unbounded_channel();
         protocol.set_hook_channel(hook_tx);
         protocol.set_permission_channel(permission_tx);
+        let (mcp_tx, mcp_rx) = mpsc::unbounded_channel();
+        if !sdk_mcp_servers.is_empty() {
+            protocol.set_mcp_channel(mcp_tx);
+        }
 
         let (message_tx, message_rx) = mpsc::unbounded_channel();
         let (control_tx, control_rx) = mpsc::unbounded_channel();
@@ -82,76 +206,171 @@ impl super::ClaudeSDKClient {
 
         let transport = Arc::new(Mutex::new(transport));
         let protocol = Arc::new(Mutex::new(protocol));
+        let health = HealthMonitor::new();
+        let (turn_active, _) = watch::channel(false);
+        let (session_id, _) = watch::channel(None);
+        let (usage, _) = watch::channel(UsageTotals::default());
+        let lifecycle = Lifecycle::new();
+        lifecycle.emit(LifecycleEvent::Connected);
+        let mut tasks = Vec::new();
 
         // Spawn message reader task
         let transport_clone = transport.clone();
         let protocol_clone = protocol.clone();
-        let message_tx_clone = message_tx;
-        tokio::spawn(async move {
+        let message_tx_weak = message_tx.downgrade();
+        let reader_context = super::tasks::ReaderContext {
+            message_tx,
+            health: health.clone(),
+            turn_active: turn_active.clone(),
+            session_id: session_id.clone(),
+            usage: usage.clone(),
+            lifecycle: lifecycle.clone(),
+            history: history.clone(),
+        };
+        tasks.push(tokio::spawn(async move {
             super::ClaudeSDKClient::message_reader_task(
                 transport_clone,
                 protocol_clone,
-                message_tx_clone,
+                reader_context,
             )
             .await;
-        });
+        }));
 
         // Spawn control message writer task
         let transport_clone = transport.clone();
         let protocol_clone = protocol.clone();
-        tokio::spawn(async move {
+        tasks.push(tokio::spawn(async move {
             super::ClaudeSDKClient::control_writer_task(
                 transport_clone,
                 protocol_clone,
                 control_rx,
             )
             .await;
-        });
+        }));
+
+        // Spawn hook handler task
+        let manager_clone = hook_manager.clone();
+        let protocol_clone = protocol.clone();
+        let control_tx_clone = control_tx.clone();
+        tasks.push(tokio::spawn(async move {
+            super::ClaudeSDKClient::hook_handler_task(
+                manager_clone,
+                protocol_clone,
+                hook_rx_internal,
+                control_tx_clone,
+            )
+            .await;
+        }));
 
-        // Spawn hook handler task if hook manager is configured
-        if let Some(ref manager) = hook_manager {
-            let manager_clone = manager.clone();
+        // Spawn permission handler task
+        let manager_clone = permission_manager.clone();
+        let protocol_clone = protocol.clone();
+        let control_tx_clone = control_tx.clone();
+        tasks.push(tokio::spawn(async move {
+            super::ClaudeSDKClient::permission_handler_task(
+                manager_clone,
+                protocol_clone,
+                permission_rx_internal,
+                control_tx_clone,
+            )
+            .await;
+        }));
+
+        // Spawn MCP handler task if SDK MCP servers are configured
+        if !sdk_mcp_servers.is_empty() {
             let protocol_clone = protocol.clone();
             let control_tx_clone = control_tx.clone();
-            tokio::spawn(async move {
-                super::ClaudeSDKClient::hook_handler_task(
-                    manager_clone,
+            tasks.push(tokio::spawn(async move {
+                super::ClaudeSDKClient::mcp_handler_task(
+                    sdk_mcp_servers,
                     protocol_clone,
-                    hook_rx_internal,
+                    mcp_rx,
                     control_tx_clone,
                 )
                 .await;
-            });
+            }));
         }
 
-        // Spawn permission handler task if permission manager is configured
-        if let Some(ref manager) = permission_manager {
-            let manager_clone = manager.clone();
+        // Spawn health monitor task if periodic pings are enabled
+        if let Some(interval) = health_check_interval {
             let protocol_clone = protocol.clone();
-            let control_tx_clone = control_tx.clone();
-            tokio::spawn(async move {
-                super::ClaudeSDKClient::permission_handler_task(
-                    manager_clone,
+            let control_tx_weak = control_tx.downgrade();
+            let health_clone = health.clone();
+            tasks.push(tokio::spawn(async move {
+                super::health::health_monitor_task(
                     protocol_clone,
-                    permission_rx_internal,
-                    control_tx_clone,
+                    control_tx_weak,
+                    health_clone,
+                    interval,
                 )
                 .await;
-            });
+            }));
         }
 
         Ok(super::ClaudeSDKClient {
             transport,
             protocol,
             message_rx,
+            message_tx: message_tx_weak,
             control_tx,
             hook_rx,
             permission_rx,
+            health,
+            metrics,
+            turn_active,
+            closing: Arc::new(AtomicBool::new(false)),
+            session_id,
+            usage,
+            lifecycle,
+            history,
+            options,
+            cli_path: None,
+            tasks,
             hook_manager,
             permission_manager,
         })
     }
 
+    /// Build the transport selected by `options.transport`
+    ///
+    /// `cli_path` only applies to the local subprocess transport.
+    ///
+    /// # Errors
+    /// Returns error if the CLI cannot be found or the selected transport is
+    /// not compiled in
+    fn transport_for(
+        options: &ClaudeAgentOptions,
+        cli_path: Option<std::path::PathBuf>,
+    ) -> Result<BoxedTransport> {
+        match &options.transport {
+            TransportConfig::Subprocess => {
+                // Create transport with streaming mode
+                let transport =
+                    SubprocessTransport::new(PromptInput::Stream, options.clone(), cli_path)?;
+                Ok(BoxedTransport::new(transport))
+            }
+            #[cfg(feature = "http")]
+            TransportConfig::Http(config) => Ok(BoxedTransport::new(HttpTransport::new(
+                config.clone(),
+                options.max_buffer_size,
+            ))),
+            #[cfg(not(feature = "http"))]
+            TransportConfig::Http(_) => Err(ClaudeError::invalid_config(
+                "HTTP transport requires the `http` feature",
+            )),
+            TransportConfig::Ssh(config) => Ok(BoxedTransport::new(SshTransport::new(
+                PromptInput::Stream,
+                options.clone(),
+                config.clone(),
+            ))),
+            TransportConfig::Container(config) => Ok(BoxedTransport::new(ContainerTransport::new(
+                PromptInput::Stream,
+                options.clone(),
+                config.clone(),
+            ))),
+        }
+    }
+
     /// Send a message to Claude
     ///
     /// # Arguments
@@ -160,18 +379,165 @@ impl super::ClaudeSDKClient {
     /// # Errors
     /// Returns error if message cannot be sent
     pub async fn send_message(&mut self, content: impl Into<String>) -> Result<()> {
-        // Send a user message in the format the CLI expects
-        let message = serde_json::json!({
-            "type": "user",
-            "message": {
-                "role": "user",
-                "content": content.into()
-            }
+        self.send_user_content(serde_json::Value::String(content.into()))
+            .await
+    }
+
+    /// Send a user message and interrupt the turn if it runs past `deadline`
+    ///
+    /// Arms a timer for the turn started by this message. If its result has
+    /// not arrived within `deadline`, the turn is interrupted and a
+    /// `ClaudeError::Timeout` is put into the message stream, followed by
+    /// the result the CLI emits for the interrupted turn. Guards unattended
+    /// automation against runaway turns.
+    ///
+    /// # Errors
+    /// Returns error if the message cannot be sent
+    pub async fn send_message_with_deadline(
+        &mut self,
+        content: impl Into<String>,
+        deadline: Duration,
+    ) -> Result<()> {
+        self.send_message(content).await?;
+
+        let protocol = self.protocol.clone();
+        let control_tx = self.control_tx.downgrade();
+        let message_tx = self.message_tx.clone();
+        let turn_active = self.turn_active.subscribe();
+        tokio::spawn(async move {
+            super::ClaudeSDKClient::turn_deadline_task(
+                protocol,
+                control_tx,
+                message_tx,
+                turn_active,
+                deadline,
+            )
+            .await;
         });
-        let message_json = format!("{}\n", serde_json::to_string(&message)?);
+        Ok(())
+    }
 
-        let mut transport = self.transport.lock().await;
-        transport.write(&message_json).await
+    /// Send a user message made of content blocks
+    ///
+    /// Lets one user turn carry several text segments, tool results for
+    /// tools the caller runs itself, or any other block the CLI accepts.
+    ///
+    /// # Arguments
+    /// * `blocks` - Content blocks of the message, in order
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if `blocks` is empty, or an error
+    /// if the message cannot be sent
+    pub async fn send_content(&mut self, blocks: Vec<ContentBlock>) -> Result<()> {
+        self.sender().send_content(blocks).await
+    }
+
+    /// Answer a tool use with its result
+    ///
+    /// For tools the application runs itself: take the `id` of a
+    /// [`ContentBlock::ToolUse`] from an assistant message, run the tool and
+    /// send its output back, and the conversation continues from there.
+    ///
+    /// # Arguments
+    /// * `tool_use_id` - ID of the tool use being answered
+    /// * `content` - Tool output
+    /// * `is_error` - Whether the tool failed, with `content` describing why
+    ///
+    /// # Errors
+    /// Returns error if the message cannot be sent
+    pub async fn send_tool_result(
+        &mut self,
+        tool_use_id: impl Into<String>,
+        content: impl Into<String>,
+        is_error: bool,
+    ) -> Result<()> {
+        self.sender()
+            .send_tool_result(tool_use_id, content, is_error)
+            .await
+    }
+
+    /// Send a user message with image files attached
+    ///
+    /// Each file becomes a base64-encoded image block (see
+    /// [`ContentBlock::image_file`]); the images come first, followed by
+    /// `text`.
+    ///
+    /// # Arguments
+    /// * `text` - Message text
+    /// * `images` - Paths of png, jpeg, gif or webp files
+    ///
+    /// # Errors
+    /// Returns error if an image cannot be read or has an unsupported type,
+    /// or if the message cannot be sent
+    pub async fn send_message_with_images<P: AsRef<Path>>(
+        &mut self,
+        text: impl Into<String>,
+        images: &[P],
+    ) -> Result<()> {
+        let mut blocks = Vec::with_capacity(images.len() + 1);
+        for path in images {
+            let path = path.as_ref();
+            let media_type = image_media_type(path).ok_or_else(|| unsupported_image(path))?;
+            let bytes = tokio::fs::read(path).await?;
+            blocks.push(ContentBlock::image_bytes(media_type, &bytes));
+        }
+        blocks.push(ContentBlock::text(text));
+        self.send_content(blocks).await
+    }
+
+    /// Write a user message with `content` and mark a turn as started
+    async fn send_user_content(&mut self, content: serde_json::Value) -> Result<()> {
+        self.sender().send_user_content(content).await
+    }
+
+    /// Split the client into a cloneable sender and a receiver
+    ///
+    /// The [`AgentSender`] can be cloned into other tasks to send messages
+    /// and control requests while the [`AgentReceiver`] reads the
+    /// conversation, without sharing `&mut` access to the client.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
+    /// # async fn example() -> kodegen_claude_agent::Result<()> {
+    /// let client = ClaudeSDKClient::new(ClaudeAgentOptions::default(), None).await?;
+    /// let (sender, mut receiver) = client.split();
+    ///
+    /// tokio::spawn(async move { sender.send_message("Hello!").await });
+    /// let turn = receiver.receive_response().await?;
+    /// println!("{}", turn.text());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn split(self) -> (AgentSender, AgentReceiver) {
+        (self.sender(), AgentReceiver { client: self })
+    }
+
+    /// A sending handle for this client, as returned by [`split`](Self::split)
+    #[must_use]
+    pub fn sender(&self) -> AgentSender {
+        AgentSender {
+            transport: self.transport.clone(),
+            protocol: self.protocol.clone(),
+            control_tx: self.control_tx.clone(),
+            turn_active: self.turn_active.clone(),
+            closing: self.closing.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// Wait until every message written so far has reached the CLI
+    ///
+    /// Writes are delivered whole and in order even when a caller stops
+    /// waiting for one (e.g. after a write timeout); this waits for any
+    /// such write still in flight.
+    ///
+    /// # Errors
+    /// Returns error if the transport can no longer deliver writes
+    pub async fn flush(&self) -> Result<()> {
+        self.transport.lock().await.flush().await
     }
 
     /// Send an interrupt signal
@@ -193,6 +559,238 @@ impl super::ClaudeSDKClient {
             .map_err(|_| ClaudeError::transport("Control channel closed"))
     }
 
+    /// Switch the permission mode for the rest of the session
+    ///
+    /// Takes effect mid-session, e.g. to move from `Plan` to `AcceptEdits`
+    /// once a plan is approved, without restarting the CLI.
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn set_permission_mode(&mut self, mode: PermissionMode) -> Result<()> {
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_set_permission_mode_request(mode);
+
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+
+    /// Switch the model for the rest of the session
+    ///
+    /// Later turns run on `model` (e.g. draft on a fast model, then finish
+    /// on a stronger one) while keeping the conversation.
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn set_model(&mut self, model: &str) -> Result<()> {
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_set_model_request(model.to_string());
+
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+
+    /// Compact the conversation to free context
+    ///
+    /// The CLI replaces the conversation so far with a summary and then
+    /// sends a `compact_boundary` system message; read it with
+    /// [`Message::compact_boundary`].
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn compact(&mut self) -> Result<()> {
+        let request = self.protocol.lock().await.create_compact_request();
+
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+
+    /// Drop the latest `turns` turns from the conversation
+    ///
+    /// The next message continues from the conversation as it was before
+    /// those turns. Files the turns edited are not restored.
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if `turns` is zero, or an error
+    /// if the control request cannot be sent
+    pub async fn rewind(&mut self, turns: u32) -> Result<()> {
+        if turns == 0 {
+            return Err(ClaudeError::invalid_config("Rewind needs at least one turn"));
+        }
+        let request = self.protocol.lock().await.create_rewind_request(turns);
+
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+
+    /// Apply permission updates for the rest of the session
+    ///
+    /// Pushes rules, a mode or directories to the CLI without waiting for a
+    /// permission request to answer with
+    /// [`updated_permissions`](crate::PermissionResultAllow::updated_permissions).
+    /// Updates with a `session` destination last until the CLI exits.
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if `updates` is empty, or an error
+    /// if the control request cannot be sent
+    pub async fn update_permissions(&mut self, updates: Vec<PermissionUpdate>) -> Result<()> {
+        if updates.is_empty() {
+            return Err(ClaudeError::invalid_config(
+                "update_permissions needs at least one update",
+            ));
+        }
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_update_permissions_request(updates);
+
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+
+    /// Ping the CLI over the control channel
+    ///
+    /// Measures round-trip latency independently of conversation traffic and
+    /// updates the client's health state.
+    ///
+    /// # Arguments
+    /// * `timeout` - How long to wait for the response
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::Timeout` if the CLI does not answer in time, or a
+    /// transport error if the control channel is closed
+    pub async fn ping(&self, timeout: Duration) -> Result<Duration> {
+        super::health::send_ping(&self.protocol, &self.control_tx, &self.health, timeout).await
+    }
+
+    /// Snapshot of the transport's traffic counters
+    ///
+    /// Returns `None` for transports that do not collect metrics. Does not
+    /// wait on the transport, so it can be read while a write is stuck.
+    #[must_use]
+    pub fn transport_metrics(&self) -> Option<TransportMetrics> {
+        self.metrics.as_ref().map(MetricsRecorder::snapshot)
+    }
+
+    /// Get a shared handle to the transport's traffic counters
+    ///
+    /// The handle stays live after the client is moved into a background task.
+    #[must_use]
+    pub fn metrics_recorder(&self) -> Option<MetricsRecorder> {
+        self.metrics.clone()
+    }
+
+    /// ID of the CLI session, once the CLI has announced it
+    ///
+    /// Taken from the `init` system message and each turn's result, as soon
+    /// as the client receives them. Pass it to [`resume`](Self::resume) to
+    /// continue the conversation later.
+    #[must_use]
+    pub fn session_id(&self) -> Option<SessionId> {
+        self.session_id.borrow().clone()
+    }
+
+    /// Whether a turn is in progress: a message was sent and its result
+    /// has not arrived yet
+    #[must_use]
+    pub fn is_turn_active(&self) -> bool {
+        *self.turn_active.borrow()
+    }
+
+    /// Receiver following [`is_turn_active`](Self::is_turn_active)
+    #[cfg(feature = "manager")]
+    pub(crate) fn turn_watch(&self) -> watch::Receiver<bool> {
+        self.turn_active.subscribe()
+    }
+
+    /// Usage and cost accumulated from the session's result messages
+    ///
+    /// Counts every result the client has received, including those not yet
+    /// read with [`next_message`](Self::next_message).
+    #[must_use]
+    pub fn usage(&self) -> UsageTotals {
+        *self.usage.borrow()
+    }
+
+    /// Messages sent and received so far, oldest first
+    ///
+    /// Empty unless `history_limit` is set in the options, and holds at most
+    /// that many messages. Partial-message stream events are not kept.
+    #[must_use]
+    pub fn history(&self) -> Vec<Message> {
+        self.history
+            .as_ref()
+            .map(History::snapshot)
+            .unwrap_or_default()
+    }
+
+    /// Subscribe to this client's lifecycle events
+    ///
+    /// The subscription starts with every event emitted so far (at least
+    /// [`LifecycleEvent::Connected`]), so it may be taken at any time. A
+    /// [`LifecycleEvent::ProcessExited`] or [`LifecycleEvent::TransportError`]
+    /// means no further messages will arrive. Dropping the client without
+    /// [`close`](Self::close) emits no `Closed` event.
+    #[must_use]
+    pub fn lifecycle_events(&self) -> LifecycleEvents {
+        self.lifecycle.subscribe()
+    }
+
+    /// Get a shared handle to this client's health state
+    ///
+    /// The handle stays valid after the client is moved into a background task.
+    #[must_use]
+    pub fn health_monitor(&self) -> HealthMonitor {
+        self.health.clone()
+    }
+
+    /// Interrupt the current turn and send a redirecting prompt
+    ///
+    /// If a turn is in progress, sends an interrupt and waits for the turn to
+    /// end (the CLI emits a Result message) before sending `content`, so the
+    /// new prompt is never queued behind the abandoned turn. If no turn is in
+    /// progress the prompt is sent immediately.
+    ///
+    /// # Arguments
+    /// * `content` - Message content to send once the current turn has stopped
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::Timeout` if the turn does not end within
+    /// `INTERRUPT_ACK_TIMEOUT` (the prompt is not sent), or an error if the
+    /// interrupt or message cannot be sent
+    pub async fn interrupt_and_send(&mut self, content: impl Into<String>) -> Result<()> {
+        let mut turn_rx = self.turn_active.subscribe();
+        if *turn_rx.borrow_and_update() {
+            self.interrupt().await?;
+
+            match tokio::time::timeout(INTERRUPT_ACK_TIMEOUT, turn_rx.wait_for(|active| !active))
+                .await
+            {
+                Ok(Ok(_)) => {}
+                Ok(Err(_)) => return Err(ClaudeError::transport("Message reader stopped")),
+                Err(_) => {
+                    return Err(ClaudeError::timeout(format!(
+                        "Turn did not stop within {}s of interrupt",
+                        INTERRUPT_ACK_TIMEOUT.as_secs()
+                    )));
+                }
+            }
+        }
+
+        self.send_message(content).await
+    }
+
     /// Get the next message from the stream
     ///
     /// Returns None when the stream ends
@@ -200,6 +798,198 @@ impl super::ClaudeSDKClient {
         self.message_rx.recv().await
     }
 
+    /// Receive messages until the current turn's result
+    ///
+    /// Collects the content blocks of every assistant message up to the next
+    /// `Message::Result`; other messages (system, tool results, stream events)
+    /// are consumed and dropped.
+    ///
+    /// # Errors
+    /// Returns the first error in the message stream, or a transport error if
+    /// the stream ends before a result arrives
+    pub async fn receive_response(&mut self) -> Result<TurnOutcome> {
+        let mut assistant_blocks = Vec::new();
+        while let Some(message) = self.next_message().await {
+            let message = message?;
+            match &message {
+                Message::Assistant { message, .. } => {
+                    assistant_blocks.extend(message.content.iter().cloned());
+                }
+                Message::Result { usage, .. } => {
+                    let usage = usage.clone();
+                    return Ok(TurnOutcome {
+                        assistant_blocks,
+                        result: message,
+                        usage,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Err(ClaudeError::transport(
+            "Message stream ended before the turn's result",
+        ))
+    }
+
+    /// Stream the current turn's output as typed deltas
+    ///
+    /// Decodes the partial-message stream events of the turn into text,
+    /// thinking and tool input deltas; other messages are consumed and
+    /// dropped. The stream ends after the turn's result, or after the first
+    /// error. Requires `include_partial_messages` in the options; without it
+    /// the stream ends at the result without yielding anything.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient, Delta};
+    /// # use futures::StreamExt;
+    /// # async fn example() -> kodegen_claude_agent::Result<()> {
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .include_partial_messages(true)
+    ///     .build();
+    /// let mut client = ClaudeSDKClient::new(options, None).await?;
+    /// client.send_message("Write a haiku").await?;
+    ///
+    /// let mut deltas = Box::pin(client.stream_deltas());
+    /// while let Some(delta) = deltas.next().await {
+    ///     if let Delta::Text(text) = delta? {
+    ///         print!("{}", text.text);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream_deltas(&mut self) -> impl Stream<Item = Result<Delta>> + '_ {
+        async_stream::stream! {
+            let mut decoder = DeltaDecoder::new();
+            while let Some(message) = self.next_message().await {
+                match message {
+                    Ok(Message::Result { .. }) => break,
+                    Ok(message) => {
+                        if let Some(delta) = decoder.decode_message(&message) {
+                            yield Ok(delta);
+                        }
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Receive messages until the current turn's result or a deadline
+    ///
+    /// Like [`receive_response`](Self::receive_response), but gives up after
+    /// `timeout`. On timeout the turn is interrupted and its messages are
+    /// dropped, up to and including the result the CLI emits for it, so the
+    /// next read starts with the next turn. Waiting for that result is capped
+    /// at `INTERRUPT_DRAIN_TIMEOUT`.
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::Timeout` if no result arrives within `timeout`,
+    /// or the errors of [`receive_response`](Self::receive_response)
+    pub async fn wait_for_result(&mut self, timeout: Duration) -> Result<TurnOutcome> {
+        if let Ok(outcome) = tokio::time::timeout(timeout, self.receive_response()).await {
+            return outcome;
+        }
+
+        self.interrupt().await?;
+        let drain = async {
+            while let Some(message) = self.next_message().await {
+                if matches!(message, Ok(Message::Result { .. })) {
+                    break;
+                }
+            }
+        };
+        if tokio::time::timeout(INTERRUPT_DRAIN_TIMEOUT, drain).await.is_err() {
+            log::warn!("Interrupted turn sent no result within {INTERRUPT_DRAIN_TIMEOUT:?}");
+        }
+        Err(ClaudeError::timeout(format!(
+            "No result within {timeout:?}; the turn was interrupted"
+        )))
+    }
+
+    /// Register a hook matcher for `event` on the live session
+    ///
+    /// Takes effect for the next hook event, without reconnecting. Returns
+    /// the ID to pass to [`remove_hook`](Self::remove_hook).
+    pub async fn add_hook(&self, event: HookEvent, matcher: HookMatcher) -> HookId {
+        self.hook_manager.lock().await.register_for(event, matcher)
+    }
+
+    /// Remove a hook matcher, including those registered through the options
+    ///
+    /// Returns `false` if no matcher is registered under `id`.
+    pub async fn remove_hook(&self, id: HookId) -> bool {
+        self.hook_manager.lock().await.remove(id)
+    }
+
+    /// Remove the hook matchers named `name`
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub async fn remove_named_hook(&self, name: &str) -> bool {
+        self.hook_manager.lock().await.remove_named(name)
+    }
+
+    /// Resume running the hook matchers named `name`
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub async fn enable_hook(&self, name: &str) -> bool {
+        self.hook_manager.lock().await.enable(name)
+    }
+
+    /// Skip the hook matchers named `name`, e.g. to pause an audit hook
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub async fn disable_hook(&self, name: &str) -> bool {
+        self.hook_manager.lock().await.disable(name)
+    }
+
+    /// Invocation counters of each registered hook matcher
+    pub async fn hook_stats(&self) -> std::collections::HashMap<HookId, HookStats> {
+        self.hook_manager.lock().await.stats()
+    }
+
+    /// Replace the permission callback for the rest of the session
+    ///
+    /// Applies to the next permission request, e.g. to switch from
+    /// interactive prompting to denying everything when the user walks away.
+    /// The allowed and disallowed tool lists still apply first; an empty
+    /// allowed list restricts nothing, so every tool not disallowed reaches
+    /// the callback.
+    pub async fn set_can_use_tool(&self, callback: CanUseToolCallback) {
+        self.permission_manager.lock().await.set_callback(callback);
+    }
+
+    /// Apply a permission rule for the next `ttl`
+    ///
+    /// See [`PermissionManager::grant`]; e.g. allow `Bash` for ten minutes
+    /// of unattended automation, after which it needs approval again.
+    pub async fn grant_permission(&self, rule: PolicyRule, ttl: std::time::Duration) {
+        self.permission_manager.lock().await.grant(rule, ttl);
+    }
+
+    /// Drop every permission grant before it expires
+    pub async fn revoke_permission_grants(&self) {
+        self.permission_manager.lock().await.revoke_grants();
+    }
+
+    /// Subscribe to the permission decisions made from now on
+    ///
+    /// Each event names the tool, the outcome, the check that decided it and
+    /// the reason for a denial.
+    pub async fn permission_events(&self) -> tokio::sync::broadcast::Receiver<PermissionEvent> {
+        self.permission_manager.lock().await.subscribe()
+    }
+
+    /// Permission decisions counted for this session
+    pub async fn permission_stats(&self) -> PermissionStats {
+        self.permission_manager.lock().await.stats()
+    }
+
     /// Take the hook event receiver
     ///
     /// This allows the caller to handle hook events independently
@@ -264,16 +1054,62 @@ impl super::ClaudeSDKClient {
 
     /// Close the client and clean up resources
     ///
+    /// Stops all background tasks (reader, writer, hook, permission and health
+    /// tasks) and waits for them to finish before closing the transport, so no
+    /// task keeps the transport alive afterwards. Safe to call more than once;
+    /// if the returned future is dropped early, the tasks are still aborted.
+    ///
     /// # Errors
     /// Returns error if cleanup fails
     pub async fn close(&mut self) -> Result<()> {
+        self.closing.store(true, Ordering::Release);
+
+        // Abort everything up front so cancellation of this future cannot
+        // leave tasks running
+        let tasks = std::mem::take(&mut self.tasks);
+        for task in &tasks {
+            task.abort();
+        }
+        for task in tasks {
+            let _ = task.await;
+        }
+
         let mut transport = self.transport.lock().await;
-        transport.close().await
+        let result = transport.close().await;
+        self.lifecycle.emit(LifecycleEvent::Closed);
+        result
+    }
+
+    /// Close the client once the current turn has finished
+    ///
+    /// New user messages are refused from the start, from this client and
+    /// every [`AgentSender`]. If a turn is in progress, waits up to `timeout`
+    /// for its result message, then closes as [`close`](Self::close) does.
+    /// Messages that arrived meanwhile can still be read from the stream
+    /// after closing.
+    ///
+    /// # Errors
+    /// Returns error if cleanup fails
+    pub async fn close_gracefully(&mut self, timeout: Duration) -> Result<()> {
+        self.closing.store(true, Ordering::Release);
+
+        let mut turn_rx = self.turn_active.subscribe();
+        if tokio::time::timeout(timeout, turn_rx.wait_for(|active| !active))
+            .await
+            .is_err()
+        {
+            log::warn!("Turn still in progress after {timeout:?}; closing anyway");
+        }
+
+        self.close().await
     }
 }
 
 impl Drop for super::ClaudeSDKClient {
     fn drop(&mut self) {
-        // Channel senders will be dropped, causing background tasks to exit
+        // Background tasks hold clones of the transport; stop them so it drops
+        for task in &self.tasks {
+            task.abort();
+        }
     }
 }
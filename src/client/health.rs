@@ -0,0 +1,190 @@
+//! Control channel health monitoring
+//!
+//! Tracks liveness of the CLI process independently of conversation traffic.
+//! When `ClaudeAgentOptions::health_check_interval` is set, a lightweight
+//! `ping` control request is sent periodically and its round-trip latency
+//! recorded. Any output from the CLI also counts as a sign of life, so a CLI
+//! that ignores pings is only reported as wedged when it is also silent.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, mpsc};
+
+use crate::control::{ControlRequest, ProtocolHandler};
+use crate::error::{ClaudeError, Result};
+
+/// Default time to wait for a ping response
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Consecutive unanswered pings (with no other output) before a CLI is considered wedged
+const WEDGED_FAILURE_THRESHOLD: u32 = 3;
+
+/// Point-in-time view of control channel health
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    /// Round-trip latency of the most recent answered ping
+    pub last_rtt: Option<Duration>,
+    /// Time since the CLI last produced any output
+    pub idle: Duration,
+    /// Pings in a row that went unanswered while the CLI was silent
+    pub consecutive_failures: u32,
+    /// Total pings sent
+    pub pings_sent: u64,
+    /// Total pings answered
+    pub pings_answered: u64,
+    /// Whether the CLI appears wedged (silent and not answering pings)
+    pub wedged: bool,
+}
+
+/// Mutable health counters shared between the reader task and pingers
+struct HealthState {
+    last_activity: Instant,
+    last_rtt: Option<Duration>,
+    consecutive_failures: u32,
+    pings_sent: u64,
+    pings_answered: u64,
+}
+
+/// Shared handle to a client's health state
+///
+/// Cheap to clone; every clone observes the same counters. Obtain one with
+/// [`ClaudeSDKClient::health_monitor`](super::ClaudeSDKClient::health_monitor).
+#[derive(Clone)]
+pub struct HealthMonitor {
+    state: Arc<Mutex<HealthState>>,
+}
+
+impl HealthMonitor {
+    /// Create a monitor with no pings recorded and activity starting now
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HealthState {
+                last_activity: Instant::now(),
+                last_rtt: None,
+                consecutive_failures: 0,
+                pings_sent: 0,
+                pings_answered: 0,
+            })),
+        }
+    }
+
+    /// Record that the CLI produced output
+    pub(crate) async fn record_activity(&self) {
+        let mut state = self.state.lock().await;
+        state.last_activity = Instant::now();
+        state.consecutive_failures = 0;
+    }
+
+    /// Take a snapshot of the current health state
+    pub async fn snapshot(&self) -> HealthSnapshot {
+        let state = self.state.lock().await;
+        HealthSnapshot {
+            last_rtt: state.last_rtt,
+            idle: state.last_activity.elapsed(),
+            consecutive_failures: state.consecutive_failures,
+            pings_sent: state.pings_sent,
+            pings_answered: state.pings_answered,
+            wedged: state.consecutive_failures >= WEDGED_FAILURE_THRESHOLD,
+        }
+    }
+
+    async fn record_ping_sent(&self) -> Instant {
+        self.state.lock().await.pings_sent += 1;
+        Instant::now()
+    }
+
+    async fn record_pong(&self, rtt: Duration) {
+        let mut state = self.state.lock().await;
+        state.last_rtt = Some(rtt);
+        state.pings_answered += 1;
+        state.consecutive_failures = 0;
+    }
+
+    async fn record_timeout(&self, sent_at: Instant) {
+        let mut state = self.state.lock().await;
+        // Output arriving while we waited proves the CLI is alive even if it
+        // does not understand pings
+        if state.last_activity < sent_at {
+            state.consecutive_failures += 1;
+        }
+    }
+}
+
+impl std::fmt::Debug for HealthMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthMonitor").finish_non_exhaustive()
+    }
+}
+
+/// Send a single ping and wait for its response
+///
+/// # Errors
+/// Returns `ClaudeError::Timeout` if no response arrives within `timeout`, or a
+/// transport error if the control channel is closed.
+pub(super) async fn send_ping(
+    protocol: &Mutex<ProtocolHandler>,
+    control_tx: &mpsc::UnboundedSender<ControlRequest>,
+    monitor: &HealthMonitor,
+    timeout: Duration,
+) -> Result<Duration> {
+    let (request, response_rx) = {
+        let protocol_guard = protocol.lock().await;
+        let request = protocol_guard.create_ping_request();
+        let response_rx = protocol_guard.send_request(request.clone()).await?;
+        (request, response_rx)
+    };
+    let id = ProtocolHandler::get_request_id(&request);
+
+    let sent_at = monitor.record_ping_sent().await;
+    if control_tx.send(request).is_err() {
+        protocol.lock().await.cancel_request(&id).await;
+        return Err(ClaudeError::transport("Control channel closed"));
+    }
+
+    match tokio::time::timeout(timeout, response_rx).await {
+        Ok(Ok(_response)) => {
+            // Success and error responses both prove the CLI is reading input
+            let rtt = sent_at.elapsed();
+            monitor.record_pong(rtt).await;
+            Ok(rtt)
+        }
+        Ok(Err(_)) => Err(ClaudeError::transport("Ping response channel closed")),
+        Err(_) => {
+            protocol.lock().await.cancel_request(&id).await;
+            monitor.record_timeout(sent_at).await;
+            Err(ClaudeError::timeout(format!(
+                "No ping response within {}ms",
+                timeout.as_millis()
+            )))
+        }
+    }
+}
+
+/// Health monitor task - periodically pings the CLI
+///
+/// Holds only a weak reference to the control channel so it exits once the
+/// client is dropped.
+pub(super) async fn health_monitor_task(
+    protocol: Arc<Mutex<ProtocolHandler>>,
+    control_tx: mpsc::WeakUnboundedSender<ControlRequest>,
+    monitor: HealthMonitor,
+    interval: Duration,
+) {
+    let timeout = interval.min(DEFAULT_PING_TIMEOUT);
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let Some(control_tx) = control_tx.upgrade() else {
+            break;
+        };
+
+        match send_ping(&protocol, &control_tx, &monitor, timeout).await {
+            Ok(rtt) => log::trace!("Health ping answered in {}ms", rtt.as_millis()),
+            Err(ClaudeError::Timeout(msg)) => log::debug!("Health ping unanswered: {msg}"),
+            Err(e) => {
+                log::debug!("Health monitor stopping: {e}");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Conversation history kept by the client
+//!
+//! With `ClaudeAgentOptions::history_limit` set, the client keeps the latest
+//! messages it sent and received, so simple applications can look back at
+//! the conversation without collecting the message stream themselves.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::types::messages::Message;
+
+/// Bounded history shared between the client and its reader task
+#[derive(Clone)]
+pub(crate) struct History {
+    limit: usize,
+    messages: Arc<Mutex<VecDeque<Message>>>,
+}
+
+impl History {
+    /// Create an empty history keeping at most `limit` messages
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            messages: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Append a message, dropping the oldest beyond the limit
+    ///
+    /// Partial-message stream events are not kept; the complete message
+    /// follows them.
+    pub(crate) fn record(&self, message: &Message) {
+        if self.limit == 0 || matches!(message, Message::StreamEvent { .. }) {
+            return;
+        }
+        let mut messages = self.messages();
+        if messages.len() == self.limit {
+            messages.pop_front();
+        }
+        messages.push_back(message.clone());
+    }
+
+    /// Messages kept so far, oldest first
+    pub(crate) fn snapshot(&self) -> Vec<Message> {
+        self.messages().iter().cloned().collect()
+    }
+
+    fn messages(&self) -> MutexGuard<'_, VecDeque<Message>> {
+        self.messages.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
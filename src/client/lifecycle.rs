@@ -0,0 +1,117 @@
+//! Client lifecycle events
+//!
+//! The client reports when its transport connects, when the CLI's output
+//! ends or fails, and when the client is closed. Subscribers can tell a
+//! crashed CLI apart from a conversation that is merely quiet, instead of
+//! finding out from a message stream that has silently ended.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Capacity of the event channel; slow subscribers skip older events
+const LIFECYCLE_CHANNEL_CAPACITY: usize = 16;
+
+/// Change in a client's connection to the CLI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LifecycleEvent {
+    /// The transport connected
+    Connected,
+    /// The CLI's output ended
+    ProcessExited {
+        /// Exit code, if the CLI exited with a failure; `None` when the
+        /// output simply ended
+        code: Option<i32>,
+    },
+    /// Reading from the transport failed
+    TransportError {
+        /// Description of the failure
+        message: String,
+    },
+    /// The client was closed with `close`
+    Closed,
+}
+
+/// Events recorded so far and the channel announcing new ones
+struct LifecycleState {
+    history: Vec<LifecycleEvent>,
+    tx: broadcast::Sender<LifecycleEvent>,
+}
+
+/// Shared emitter of a client's lifecycle events
+///
+/// Keeps every event emitted, so a subscription made late still starts
+/// with `Connected`. A client emits only a handful of events.
+#[derive(Clone)]
+pub(crate) struct Lifecycle {
+    state: Arc<Mutex<LifecycleState>>,
+}
+
+impl Lifecycle {
+    /// Create an emitter with no events
+    pub(crate) fn new() -> Self {
+        let (tx, _) = broadcast::channel(LIFECYCLE_CHANNEL_CAPACITY);
+        Self {
+            state: Arc::new(Mutex::new(LifecycleState {
+                history: Vec::new(),
+                tx,
+            })),
+        }
+    }
+
+    /// Record an event and announce it to subscribers
+    pub(crate) fn emit(&self, event: LifecycleEvent) {
+        log::debug!("Client lifecycle: {event:?}");
+        let mut state = self.state();
+        state.history.push(event.clone());
+        // No subscribers is fine
+        let _ = state.tx.send(event);
+    }
+
+    /// Subscribe to the events emitted so far and from now on
+    pub(crate) fn subscribe(&self) -> LifecycleEvents {
+        let state = self.state();
+        LifecycleEvents {
+            backlog: state.history.iter().cloned().collect(),
+            rx: state.tx.subscribe(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, LifecycleState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Subscription to a client's lifecycle events
+///
+/// Obtain one with
+/// [`ClaudeSDKClient::lifecycle_events`](super::ClaudeSDKClient::lifecycle_events).
+pub struct LifecycleEvents {
+    backlog: VecDeque<LifecycleEvent>,
+    rx: broadcast::Receiver<LifecycleEvent>,
+}
+
+impl LifecycleEvents {
+    /// Wait for the next event
+    ///
+    /// Returns `None` once the client has been dropped and every event has
+    /// been received.
+    pub async fn recv(&mut self) -> Option<LifecycleEvent> {
+        if let Some(event) = self.backlog.pop_front() {
+            return Some(event);
+        }
+        loop {
+            match self.rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Lifecycle subscriber skipped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
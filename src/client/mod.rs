@@ -73,18 +73,39 @@
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! let options = ClaudeAgentOptions::default();
-//! let mut client = ClaudeSDKClient::new(options, None).await?;
+//! let client = ClaudeSDKClient::new(options, None).await?;
+//! let (sender, mut receiver) = client.split();
 //!
 //! // Send first message
-//! client.send_message("First question").await?;
+//! sender.send_message("First question").await?;
 //!
 //! // Can send another message while reading responses
 //! // No blocking due to lock-free architecture
 //! tokio::spawn(async move {
 //!     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-//!     client.send_message("Second question").await
+//!     sender.send_message("Second question").await
 //! });
 //!
+//! while let Some(message) = receiver.next_message().await {
+//!     log::info!("{:?}", message?);
+//! }
+//!
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Example: One Turn at a Time
+//!
+//! ```no_run
+//! use kodegen_claude_agent::{ClaudeSDKClient, ClaudeAgentOptions};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = ClaudeSDKClient::new(ClaudeAgentOptions::default(), None).await?;
+//!
+//! client.send_message("What is 2 + 2?").await?;
+//! let turn = client.receive_response().await?;
+//! log::info!("Answer: {} (usage: {:?})", turn.text(), turn.usage);
+//!
 //! # Ok(())
 //! # }
 //! ```
@@ -144,19 +165,33 @@
 //! ```
 
 mod client_impl;
+mod health;
+mod history;
+mod lifecycle;
+mod split;
 mod tasks;
 
+pub use health::{DEFAULT_PING_TIMEOUT, HealthMonitor, HealthSnapshot};
+pub use lifecycle::{LifecycleEvent, LifecycleEvents};
+pub use split::{AgentReceiver, AgentSender};
+
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
+use std::sync::atomic::AtomicBool;
+use tokio::sync::{Mutex, mpsc, watch};
+use tokio::task::JoinHandle;
 
+use self::history::History;
+use self::lifecycle::Lifecycle;
 use crate::control::ProtocolHandler;
 use crate::error::Result;
 use crate::hooks::HookManager;
 use crate::permissions::PermissionManager;
-use crate::transport::SubprocessTransport;
+use crate::transport::{BoxedTransport, MetricsRecorder};
 use crate::types::hooks::HookEvent;
-use crate::types::identifiers::RequestId;
-use crate::types::messages::Message;
+use crate::types::identifiers::{RequestId, SessionId};
+use crate::types::messages::{Message, UsageTotals};
+use crate::types::options::ClaudeAgentOptions;
 use crate::types::permissions::PermissionRequest;
 
 /// Client for bidirectional communication with Claude Code
@@ -186,23 +221,43 @@ use crate::types::permissions::PermissionRequest;
 /// ```
 pub struct ClaudeSDKClient {
     /// Transport layer
-    transport: Arc<Mutex<SubprocessTransport>>,
+    transport: Arc<Mutex<BoxedTransport>>,
     /// Control protocol handler
     protocol: Arc<Mutex<ProtocolHandler>>,
     /// Message stream receiver
     message_rx: mpsc::UnboundedReceiver<Result<Message>>,
+    /// Weak sender into the message stream, for errors raised outside the reader
+    message_tx: mpsc::WeakUnboundedSender<Result<Message>>,
     /// Control message sender
     control_tx: mpsc::UnboundedSender<crate::control::ControlRequest>,
     /// Hook event receiver (if not using automatic handler)
     hook_rx: Option<mpsc::UnboundedReceiver<(String, HookEvent, serde_json::Value)>>,
     /// Permission request receiver (if not using automatic handler)
     permission_rx: Option<mpsc::UnboundedReceiver<(RequestId, PermissionRequest)>>,
-    /// Hook manager for automatic hook handling (kept alive for background tasks)
-    #[allow(dead_code)]
-    // APPROVED BY DAVID MAPLE on 2025-10-14: Required to keep Arc alive for background tasks
-    hook_manager: Option<Arc<Mutex<HookManager>>>,
-    /// Permission manager for automatic permission handling (kept alive for background tasks)
-    #[allow(dead_code)]
-    // APPROVED BY DAVID MAPLE on 2025-10-14: Required to keep Arc alive for background tasks
-    permission_manager: Option<Arc<Mutex<PermissionManager>>>,
+    /// Control channel health state (fed by the reader task and pings)
+    health: HealthMonitor,
+    /// Traffic counters of the transport, if it collects them
+    metrics: Option<MetricsRecorder>,
+    /// Whether a turn is in progress (set on send, cleared on Result)
+    turn_active: watch::Sender<bool>,
+    /// Set once closing has begun; refuses further user messages
+    closing: Arc<AtomicBool>,
+    /// CLI session ID, once announced by the CLI
+    session_id: watch::Sender<Option<SessionId>>,
+    /// Usage and cost accumulated from result messages
+    usage: watch::Sender<UsageTotals>,
+    /// Lifecycle events emitted so far, shared with the reader task
+    lifecycle: Lifecycle,
+    /// Messages sent and received, if `history_limit` is set
+    history: Option<History>,
+    /// Options the client was created with, for [`fork`](Self::fork)
+    options: ClaudeAgentOptions,
+    /// CLI path the client was created with, for [`fork`](Self::fork)
+    cli_path: Option<PathBuf>,
+    /// Background tasks (reader, writer, hooks, permissions, health)
+    tasks: Vec<JoinHandle<()>>,
+    /// Hook manager shared with the hook handler task
+    hook_manager: Arc<Mutex<HookManager>>,
+    /// Permission manager shared with the permission handler task
+    permission_manager: Arc<Mutex<PermissionManager>>,
 }
@@ -0,0 +1,198 @@
+//! Separate sending and receiving halves of a client
+//!
+//! [`ClaudeSDKClient::split`](super::ClaudeSDKClient::split) turns a client
+//! into a cloneable [`AgentSender`] and an [`AgentReceiver`], so one task can
+//! read the conversation while others send messages and control requests.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::{Mutex, mpsc, watch};
+
+use super::ClaudeSDKClient;
+use super::history::History;
+use crate::control::{ControlRequest, ProtocolHandler};
+use crate::error::{ClaudeError, Result};
+use crate::message::parse_message;
+use crate::transport::{BoxedTransport, Transport};
+use crate::types::messages::ContentBlock;
+use crate::types::permissions::{PermissionMode, PermissionUpdate};
+
+/// Sending half of a split client
+///
+/// Cheap to clone; every clone writes to the same CLI. Sends fail once the
+/// [`AgentReceiver`] has started closing.
+#[derive(Clone)]
+pub struct AgentSender {
+    pub(super) transport: Arc<Mutex<BoxedTransport>>,
+    pub(super) protocol: Arc<Mutex<ProtocolHandler>>,
+    pub(super) control_tx: mpsc::UnboundedSender<ControlRequest>,
+    pub(super) turn_active: watch::Sender<bool>,
+    pub(super) closing: Arc<AtomicBool>,
+    pub(super) history: Option<History>,
+}
+
+impl AgentSender {
+    /// Send a user message
+    ///
+    /// # Errors
+    /// Returns error if the message cannot be sent
+    pub async fn send_message(&self, content: impl Into<String>) -> Result<()> {
+        self.send_user_content(serde_json::Value::String(content.into()))
+            .await
+    }
+
+    /// Send a user message made of content blocks
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if `blocks` is empty, or an error
+    /// if the message cannot be sent
+    pub async fn send_content(&self, blocks: Vec<ContentBlock>) -> Result<()> {
+        if blocks.is_empty() {
+            return Err(ClaudeError::invalid_config(
+                "A user message needs at least one content block",
+            ));
+        }
+        self.send_user_content(serde_json::to_value(blocks)?).await
+    }
+
+    /// Answer a tool use with its result
+    ///
+    /// # Errors
+    /// Returns error if the message cannot be sent
+    pub async fn send_tool_result(
+        &self,
+        tool_use_id: impl Into<String>,
+        content: impl Into<String>,
+        is_error: bool,
+    ) -> Result<()> {
+        self.send_content(vec![ContentBlock::tool_result(
+            tool_use_id,
+            content,
+            is_error,
+        )])
+        .await
+    }
+
+    /// Interrupt the current turn
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn interrupt(&self) -> Result<()> {
+        let request = self.protocol.lock().await.create_interrupt_request();
+        self.send_control(request)
+    }
+
+    /// Switch the permission mode for the rest of the session
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn set_permission_mode(&self, mode: PermissionMode) -> Result<()> {
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_set_permission_mode_request(mode);
+        self.send_control(request)
+    }
+
+    /// Switch the model for the rest of the session
+    ///
+    /// # Errors
+    /// Returns error if the control request cannot be sent
+    pub async fn set_model(&self, model: &str) -> Result<()> {
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_set_model_request(model.to_string());
+        self.send_control(request)
+    }
+
+    /// Apply permission updates for the rest of the session
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if `updates` is empty, or an error
+    /// if the control request cannot be sent
+    pub async fn update_permissions(&self, updates: Vec<PermissionUpdate>) -> Result<()> {
+        if updates.is_empty() {
+            return Err(ClaudeError::invalid_config(
+                "update_permissions needs at least one update",
+            ));
+        }
+        let request = self
+            .protocol
+            .lock()
+            .await
+            .create_update_permissions_request(updates);
+        self.send_control(request)
+    }
+
+    /// Write a user message with `content` and mark a turn as started
+    pub(super) async fn send_user_content(&self, content: serde_json::Value) -> Result<()> {
+        if self.closing.load(Ordering::Acquire) {
+            return Err(ClaudeError::transport("Client is closing"));
+        }
+
+        // Send a user message in the format the CLI expects
+        let message = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": content
+            }
+        });
+        let message_json = format!("{}\n", serde_json::to_string(&message)?);
+
+        let mut transport = self.transport.lock().await;
+        transport.write(&message_json).await?;
+        drop(transport);
+
+        if let Some(ref history) = self.history
+            && let Ok(sent) = parse_message(message)
+        {
+            history.record(&sent);
+        }
+        self.turn_active.send_replace(true);
+        Ok(())
+    }
+
+    fn send_control(&self, request: ControlRequest) -> Result<()> {
+        self.control_tx
+            .send(request)
+            .map_err(|_| ClaudeError::transport("Control channel closed"))
+    }
+}
+
+/// Receiving half of a split client
+///
+/// Owns the client, so it keeps the connection alive; closing or dropping
+/// it ends the session for every [`AgentSender`]. Dereferences to the
+/// client, so all of its receiving methods (`next_message`,
+/// `receive_response`, `stream_deltas`, ...) and accessors are available.
+pub struct AgentReceiver {
+    pub(super) client: ClaudeSDKClient,
+}
+
+impl AgentReceiver {
+    /// Get the client back, e.g. to split it again
+    #[must_use]
+    pub fn into_inner(self) -> ClaudeSDKClient {
+        self.client
+    }
+}
+
+impl Deref for AgentReceiver {
+    type Target = ClaudeSDKClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+impl DerefMut for AgentReceiver {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.client
+    }
+}
@@ -3,27 +3,54 @@
 //! This module contains the async task implementations that run in the background
 //! to handle message reading, control writing, hooks, and permissions.
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{Mutex, mpsc};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc, watch};
 
+use super::health::HealthMonitor;
+use super::history::History;
+use super::lifecycle::{Lifecycle, LifecycleEvent};
 use crate::control::{ControlMessage, ControlRequest, ProtocolHandler};
-use crate::error::Result;
+use crate::error::{ClaudeError, Result};
 use crate::hooks::HookManager;
+use crate::mcp::SdkMcpServer;
 use crate::message::parse_message;
 use crate::permissions::PermissionManager;
-use crate::transport::{SubprocessTransport, Transport};
-use crate::types::hooks::{HookContext, HookEvent};
-use crate::types::identifiers::RequestId;
-use crate::types::messages::Message;
+use crate::transport::{BoxedTransport, Transport};
+use crate::types::hooks::{HookContext, HookErrorPolicy, HookEvent};
+use crate::types::identifiers::{RequestId, SessionId};
+use crate::types::messages::{Message, UsageTotals};
 use crate::types::permissions::PermissionRequest;
 
+/// Client state updated by the message reader task
+pub(super) struct ReaderContext {
+    pub message_tx: mpsc::UnboundedSender<Result<Message>>,
+    pub health: HealthMonitor,
+    pub turn_active: watch::Sender<bool>,
+    pub session_id: watch::Sender<Option<SessionId>>,
+    pub usage: watch::Sender<UsageTotals>,
+    pub lifecycle: Lifecycle,
+    pub history: Option<History>,
+}
+
 impl super::ClaudeSDKClient {
     /// Message reader task - reads from transport and processes messages
     pub(super) async fn message_reader_task(
-        transport: Arc<Mutex<SubprocessTransport>>,
+        transport: Arc<Mutex<BoxedTransport>>,
         protocol: Arc<Mutex<ProtocolHandler>>,
-        message_tx: mpsc::UnboundedSender<Result<Message>>,
+        ctx: ReaderContext,
     ) {
+        let ReaderContext {
+            message_tx,
+            health,
+            turn_active,
+            session_id,
+            usage,
+            lifecycle,
+            history,
+        } = ctx;
+
         // Get the message receiver from the transport without holding the lock
         let mut msg_stream = {
             let mut transport_guard = transport.lock().await;
@@ -33,6 +60,8 @@ impl super::ClaudeSDKClient {
         while let Some(result) = msg_stream.recv().await {
             match result {
                 Ok(value) => {
+                    health.record_activity().await;
+
                     // Try to parse as control message first
                     let protocol_guard = protocol.lock().await;
                     if let Ok(control_msg) = protocol_guard
@@ -43,7 +72,7 @@ impl super::ClaudeSDKClient {
                                 if let Err(e) = protocol_guard.handle_init_response(&init_response)
                                 {
                                     let _ = message_tx.send(Err(e));
-                                    break;
+                                    return;
                                 }
                             }
                             ControlMessage::Response(response) => {
@@ -63,8 +92,24 @@ impl super::ClaudeSDKClient {
                     // Otherwise parse as regular message
                     match parse_message(value) {
                         Ok(msg) => {
+                            match &msg {
+                                Message::System { subtype, data } if subtype == "init" => {
+                                    if let Some(id) = data["session_id"].as_str() {
+                                        session_id.send_replace(Some(SessionId::new(id)));
+                                    }
+                                }
+                                Message::Result { session_id: id, .. } => {
+                                    turn_active.send_replace(false);
+                                    session_id.send_replace(Some(id.clone()));
+                                    usage.send_modify(|totals| totals.record(&msg));
+                                }
+                                _ => {}
+                            }
+                            if let Some(ref history) = history {
+                                history.record(&msg);
+                            }
                             if message_tx.send(Ok(msg)).is_err() {
-                                break;
+                                return;
                             }
                         }
                         Err(e) => {
@@ -72,17 +117,61 @@ impl super::ClaudeSDKClient {
                         }
                     }
                 }
+                // The transport keeps reading past output it cannot decode
+                Err(e @ (ClaudeError::JsonDecode(_) | ClaudeError::MessageParse { .. })) => {
+                    if message_tx.send(Err(e)).is_err() {
+                        return;
+                    }
+                }
                 Err(e) => {
+                    lifecycle.emit(match &e {
+                        ClaudeError::Process { exit_code, .. } => LifecycleEvent::ProcessExited {
+                            code: Some(*exit_code),
+                        },
+                        _ => LifecycleEvent::TransportError {
+                            message: e.to_string(),
+                        },
+                    });
                     let _ = message_tx.send(Err(e));
-                    break;
+                    return;
                 }
             }
         }
+
+        lifecycle.emit(LifecycleEvent::ProcessExited { code: None });
+    }
+
+    /// Turn deadline task - interrupts a turn still running at `deadline`
+    ///
+    /// Ends early once the turn's result arrives. On expiry, sends an
+    /// interrupt and puts a timeout error into the message stream.
+    pub(super) async fn turn_deadline_task(
+        protocol: Arc<Mutex<ProtocolHandler>>,
+        control_tx: mpsc::WeakUnboundedSender<ControlRequest>,
+        message_tx: mpsc::WeakUnboundedSender<Result<Message>>,
+        mut turn_active: watch::Receiver<bool>,
+        deadline: Duration,
+    ) {
+        tokio::select! {
+            _ = turn_active.wait_for(|active| !active) => return,
+            () = tokio::time::sleep(deadline) => {}
+        }
+
+        log::warn!("Turn still running after {deadline:?}, interrupting");
+        if let Some(control_tx) = control_tx.upgrade() {
+            let request = protocol.lock().await.create_interrupt_request();
+            let _ = control_tx.send(request);
+        }
+        if let Some(message_tx) = message_tx.upgrade() {
+            let _ = message_tx.send(Err(ClaudeError::timeout(format!(
+                "No result within {deadline:?}; the turn was interrupted"
+            ))));
+        }
     }
 
     /// Control message writer task - writes control requests to transport
     pub(super) async fn control_writer_task(
-        transport: Arc<Mutex<SubprocessTransport>>,
+        transport: Arc<Mutex<BoxedTransport>>,
         protocol: Arc<Mutex<ProtocolHandler>>,
         mut control_rx: mpsc::UnboundedReceiver<ControlRequest>,
     ) {
@@ -109,7 +198,15 @@ impl super::ClaudeSDKClient {
                 }
 
                 // Full control protocol for bidirectional messages
-                ControlRequest::HookResponse { .. } | ControlRequest::PermissionResponse { .. } => {
+                ControlRequest::HookResponse { .. }
+                | ControlRequest::PermissionResponse { .. }
+                | ControlRequest::Ping { .. }
+                | ControlRequest::SetPermissionMode { .. }
+                | ControlRequest::SetModel { .. }
+                | ControlRequest::Compact { .. }
+                | ControlRequest::Rewind { .. }
+                | ControlRequest::UpdatePermissions { .. }
+                | ControlRequest::McpResponse { .. } => {
                     let protocol_guard = protocol.lock().await;
                     let message = ControlMessage::Request(request.clone());
                     let result = protocol_guard.serialize_message(&message).ok();
@@ -166,28 +263,29 @@ impl super::ClaudeSDKClient {
             let manager_guard = manager.lock().await;
             let context = HookContext {};
 
-            match manager_guard
-                .invoke(event_data.clone(), tool_name, context)
+            let output = match manager_guard
+                .invoke_event(event, event_data.clone(), tool_name, context)
                 .await
             {
-                Ok(output) => {
-                    drop(manager_guard);
-
-                    // Send hook response
-                    let protocol_guard = protocol.lock().await;
-                    let response = serde_json::to_value(&output).unwrap_or_default();
-                    let request = protocol_guard.create_hook_response(hook_id, response);
-                    drop(protocol_guard);
-
-                    if let Err(e) = control_tx.send(request) {
-                        log::error!("Failed to send hook response: {e}");
-                    }
-                    log::debug!("Hook processed for event {event:?}");
-                }
+                Ok(output) => output,
                 Err(e) => {
+                    // Still answer, so the CLI is not left waiting, and fail closed
                     log::error!("Hook processing error: {e}");
+                    HookErrorPolicy::Block.output(&e)
                 }
+            };
+            drop(manager_guard);
+
+            // Send hook response
+            let protocol_guard = protocol.lock().await;
+            let response = output.to_response(event);
+            let request = protocol_guard.create_hook_response(hook_id, response);
+            drop(protocol_guard);
+
+            if let Err(e) = control_tx.send(request) {
+                log::error!("Failed to send hook response: {e}");
             }
+            log::debug!("Hook processed for event {event:?}");
         }
     }
 
@@ -200,6 +298,16 @@ impl super::ClaudeSDKClient {
     ) {
         while let Some((request_id, request)) = permission_rx.recv().await {
             let manager_guard = manager.lock().await;
+            if !manager_guard
+                .can_decide(&request.tool_name, &request.tool_input)
+                .await
+            {
+                log::debug!(
+                    "Permission {} left unanswered: no callback",
+                    request_id.as_str()
+                );
+                continue;
+            }
 
             match manager_guard
                 .can_use_tool(
@@ -229,4 +337,42 @@ impl super::ClaudeSDKClient {
             }
         }
     }
+
+    /// MCP handler task - answers messages addressed to SDK MCP servers
+    pub(super) async fn mcp_handler_task(
+        servers: HashMap<String, SdkMcpServer>,
+        protocol: Arc<Mutex<ProtocolHandler>>,
+        mut mcp_rx: mpsc::UnboundedReceiver<(RequestId, String, serde_json::Value)>,
+        control_tx: mpsc::UnboundedSender<ControlRequest>,
+    ) {
+        while let Some((request_id, server_name, message)) = mcp_rx.recv().await {
+            let response = match servers.get(&server_name) {
+                Some(server) => server.handle_message(message).await,
+                None => message.get("id").map(|id| {
+                    serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": -32601,
+                            "message": format!("Unknown SDK MCP server: {server_name}"),
+                        },
+                    })
+                }),
+            };
+
+            // Notifications take no response
+            let Some(response) = response else {
+                continue;
+            };
+
+            let protocol_guard = protocol.lock().await;
+            let request = protocol_guard.create_mcp_response(request_id, response);
+            drop(protocol_guard);
+
+            if let Err(e) = control_tx.send(request) {
+                log::error!("Failed to send MCP response: {e}");
+            }
+            log::debug!("MCP message processed for server {server_name}");
+        }
+    }
 }
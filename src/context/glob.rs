@@ -0,0 +1,86 @@
+//! Minimal glob matching for context selection
+//!
+//! Supports `*` (any run of characters within a path segment), `?` (a single
+//! character) and `**` (zero or more whole segments). Patterns without a `/`
+//! match against the file name at any depth, mirroring `.gitignore`.
+
+/// Compiled glob pattern
+#[derive(Debug, Clone)]
+pub(super) struct GlobPattern {
+    segments: Vec<String>,
+    basename_only: bool,
+}
+
+impl GlobPattern {
+    /// Parse a pattern such as `src/**/*.rs` or `*.toml`
+    pub(super) fn new(pattern: &str) -> Self {
+        let pattern = pattern.trim_start_matches("./");
+        let basename_only = !pattern.contains('/');
+        let segments = pattern
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+        Self {
+            segments,
+            basename_only,
+        }
+    }
+
+    /// Check whether a relative, `/`-separated path matches
+    pub(super) fn matches(&self, path: &str) -> bool {
+        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if self.basename_only {
+            return match (self.segments.first(), parts.last()) {
+                (Some(pattern), Some(name)) => segment_matches(pattern, name),
+                _ => false,
+            };
+        }
+        match_segments(&self.segments, &parts)
+    }
+}
+
+/// Match pattern segments against path segments, expanding `**`
+fn match_segments(patterns: &[String], parts: &[&str]) -> bool {
+    match patterns.split_first() {
+        None => parts.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=parts.len()).any(|skip| match_segments(rest, &parts[skip..]))
+        }
+        Some((first, rest)) => match parts.split_first() {
+            Some((part, remaining)) => {
+                segment_matches(first, part) && match_segments(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match a single path segment against a pattern segment
+///
+/// Also used for permission rule patterns, where `*` and `?` carry the same
+/// meaning but `/` is not special.
+pub(crate) fn segment_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Iterative wildcard matching with single-star backtracking
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,435 @@
+//! Context packs for agent prompts
+//!
+//! This module gathers repository context (a file tree plus selected file
+//! contents) within byte and token budgets and formats it for an agent's
+//! initial prompt, or writes it to a directory that can be passed via
+//! `add_dirs`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kodegen_claude_agent::context::ContextPack;
+//!
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let pack = ContextPack::builder(".")
+//!     .include("src/**/*.rs")
+//!     .include("Cargo.toml")
+//!     .exclude("**/tests/**")
+//!     .max_tokens(20_000)
+//!     .build()?;
+//!
+//! let prompt = pack.prepend_to("Review the error handling in this crate.");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Budgets
+//!
+//! Token counts are estimated at four bytes per token. Files are visited in
+//! path order; each is cut to `max_file_bytes` (preferring a line boundary)
+//! and files that no longer fit the remaining budget are listed as omitted,
+//! as are files and subdirectories that cannot be read.
+
+pub(crate) mod glob;
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::{ClaudeError, Result};
+
+use glob::GlobPattern;
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Default budget for the whole pack (200KB)
+pub const DEFAULT_MAX_TOTAL_BYTES: usize = 200 * 1024;
+
+/// Default budget for a single file (32KB)
+pub const DEFAULT_MAX_FILE_BYTES: usize = 32 * 1024;
+
+/// Rough bytes-per-token ratio used for token budgets
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Smallest partial file worth including when the budget is nearly spent
+const MIN_PARTIAL_FILE_BYTES: usize = 512;
+
+/// Bytes inspected when detecting binary files
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Directories never descended into
+const EXCLUDED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// File name used by `write_to_dir`
+const CONTEXT_FILE_NAME: &str = "CONTEXT.md";
+
+// ============================================================================
+// CONTEXT PACK
+// ============================================================================
+
+/// A file included in a context pack
+#[derive(Debug, Clone)]
+pub struct ContextFile {
+    /// Path relative to the pack root, `/`-separated
+    pub path: String,
+    /// Size of the file on disk in bytes
+    pub size: u64,
+    /// Included content (possibly truncated)
+    pub content: String,
+    /// Whether `content` is shorter than the file
+    pub truncated: bool,
+}
+
+/// Repository context assembled within a budget
+#[derive(Debug, Clone)]
+pub struct ContextPack {
+    /// Root directory the pack was built from
+    pub root: PathBuf,
+    /// Rendered file tree listing, if enabled
+    pub tree: Option<String>,
+    /// Files whose content is included
+    pub files: Vec<ContextFile>,
+    /// Matching files left out because the budget was exhausted, and
+    /// unreadable files and subdirectories (the latter ending in `/`)
+    pub omitted: Vec<String>,
+}
+
+impl ContextPack {
+    /// Create a builder rooted at `root`
+    #[must_use]
+    pub fn builder(root: impl Into<PathBuf>) -> ContextPackBuilder {
+        ContextPackBuilder::new(root)
+    }
+
+    /// Render the pack as Markdown
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = format!("# Repository context\n\nRoot: {}\n", self.root.display());
+
+        if let Some(tree) = &self.tree {
+            out.push_str("\n## File tree\n\n```text\n");
+            out.push_str(tree);
+            out.push_str("```\n");
+        }
+
+        if !self.files.is_empty() {
+            out.push_str("\n## Files\n");
+        }
+        for file in &self.files {
+            out.push_str(&render_file(file));
+        }
+
+        if !self.omitted.is_empty() {
+            out.push_str("\n## Omitted (budget exhausted or unreadable)\n\n");
+            for path in &self.omitted {
+                out.push_str("- ");
+                out.push_str(path);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Estimated token count of the rendered pack
+    #[must_use]
+    pub fn estimated_tokens(&self) -> usize {
+        self.render().len().div_ceil(BYTES_PER_TOKEN)
+    }
+
+    /// Prepend the rendered pack to a prompt
+    #[must_use]
+    pub fn prepend_to(&self, prompt: &str) -> String {
+        format!("{}\n---\n\n{prompt}", self.render())
+    }
+
+    /// Write the rendered pack to `CONTEXT.md` in `dir`
+    ///
+    /// Pass `dir` in `add_dirs` so the agent can read the file on demand
+    /// instead of receiving it in the prompt.
+    ///
+    /// # Errors
+    /// Returns error if the directory or file cannot be written
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(CONTEXT_FILE_NAME);
+        std::fs::write(&path, self.render())?;
+        Ok(path)
+    }
+
+    /// Write the rendered pack to a fresh directory under the system temp dir
+    ///
+    /// Returns the directory (suitable for `add_dirs`). The caller is
+    /// responsible for removing it when the session ends.
+    ///
+    /// # Errors
+    /// Returns error if the directory or file cannot be written
+    pub fn write_to_temp_dir(&self) -> Result<PathBuf> {
+        let dir = std::env::temp_dir().join(format!("kodegen-context-{}", uuid::Uuid::new_v4()));
+        self.write_to_dir(&dir)?;
+        Ok(dir)
+    }
+}
+
+/// Render a single file section
+fn render_file(file: &ContextFile) -> String {
+    let mut out = format!("\n### {}\n\n```\n{}", file.path, file.content);
+    if !file.content.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str("```\n");
+    if file.truncated {
+        out.push_str(&format!(
+            "[truncated: showing {} of {} bytes]\n",
+            file.content.len(),
+            file.size
+        ));
+    }
+    out
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Builder for [`ContextPack`]
+#[derive(Debug, Clone)]
+pub struct ContextPackBuilder {
+    root: PathBuf,
+    include: Vec<GlobPattern>,
+    exclude: Vec<GlobPattern>,
+    max_total_bytes: usize,
+    max_file_bytes: usize,
+    max_tokens: Option<usize>,
+    include_tree: bool,
+}
+
+impl ContextPackBuilder {
+    /// Create a builder rooted at `root` with default budgets
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_tokens: None,
+            include_tree: true,
+        }
+    }
+
+    /// Include files matching a glob (all files if none are given)
+    #[must_use]
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(GlobPattern::new(pattern));
+        self
+    }
+
+    /// Exclude files matching a glob
+    #[must_use]
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(GlobPattern::new(pattern));
+        self
+    }
+
+    /// Set the byte budget for the whole pack
+    #[must_use]
+    pub const fn max_total_bytes(mut self, bytes: usize) -> Self {
+        self.max_total_bytes = bytes;
+        self
+    }
+
+    /// Set the byte budget for a single file
+    #[must_use]
+    pub const fn max_file_bytes(mut self, bytes: usize) -> Self {
+        self.max_file_bytes = bytes;
+        self
+    }
+
+    /// Set an (estimated) token budget for the whole pack
+    #[must_use]
+    pub const fn max_tokens(mut self, tokens: usize) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    /// Enable or disable the file tree listing (default: enabled)
+    #[must_use]
+    pub const fn include_tree(mut self, include: bool) -> Self {
+        self.include_tree = include;
+        self
+    }
+
+    /// Walk the root and assemble the pack
+    ///
+    /// Performs blocking file I/O; use `tokio::task::spawn_blocking` from
+    /// async contexts when packing large trees.
+    ///
+    /// # Errors
+    /// Returns error if the root is not a directory or cannot be read
+    pub fn build(self) -> Result<ContextPack> {
+        if !self.root.is_dir() {
+            return Err(ClaudeError::invalid_config(format!(
+                "Context root is not a directory: {}",
+                self.root.display()
+            )));
+        }
+
+        let mut candidates = Vec::new();
+        let mut omitted = Vec::new();
+        self.collect(&self.root, "", &mut candidates, &mut omitted)?;
+
+        let mut budget = self.budget();
+
+        let tree = if self.include_tree {
+            let tree = render_tree(&candidates, budget / 2);
+            budget = budget.saturating_sub(tree.len());
+            Some(tree)
+        } else {
+            None
+        };
+
+        let mut files = Vec::new();
+        for (path, size) in candidates {
+            // Section header, fences and truncation note
+            let overhead = path.len() + 64;
+            let available = budget.saturating_sub(overhead).min(self.max_file_bytes);
+            if available < MIN_PARTIAL_FILE_BYTES.min(size as usize).max(1) {
+                omitted.push(path);
+                continue;
+            }
+
+            match read_text(&self.root.join(&path), available) {
+                Ok(Some((content, truncated))) => {
+                    budget = budget.saturating_sub(content.len() + overhead);
+                    files.push(ContextFile {
+                        path,
+                        size,
+                        content,
+                        truncated,
+                    });
+                }
+                Ok(None) => log::debug!("Skipping binary file in context pack: {path}"),
+                Err(e) => {
+                    log::debug!("Skipping unreadable file in context pack: {path}: {e}");
+                    omitted.push(path);
+                }
+            }
+        }
+
+        Ok(ContextPack {
+            root: self.root,
+            tree,
+            files,
+            omitted,
+        })
+    }
+
+    /// Effective byte budget combining byte and token limits
+    fn budget(&self) -> usize {
+        self.max_tokens.map_or(self.max_total_bytes, |tokens| {
+            self.max_total_bytes
+                .min(tokens.saturating_mul(BYTES_PER_TOKEN))
+        })
+    }
+
+    /// Check include/exclude globs against a relative path
+    fn selects(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(path)))
+            && !self.exclude.iter().any(|p| p.matches(path))
+    }
+
+    /// Recursively collect matching regular files in path order
+    ///
+    /// Only an unreadable root is an error; unreadable entries below it are
+    /// added to `unreadable`.
+    fn collect(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        out: &mut Vec<(String, u64)>,
+        unreadable: &mut Vec<String>,
+    ) -> Result<()> {
+        let entries = std::fs::read_dir(dir).and_then(|entries| entries.collect());
+        let mut entries: Vec<std::fs::DirEntry> = match entries {
+            Ok(entries) => entries,
+            Err(e) if !prefix.is_empty() => {
+                log::debug!("Skipping unreadable directory in context pack: {prefix}: {e}");
+                unreadable.push(format!("{prefix}/"));
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{prefix}/{name}")
+            };
+
+            // Symlinks are skipped to avoid cycles and escaping the root
+            let Ok(file_type) = entry.file_type() else {
+                unreadable.push(relative);
+                continue;
+            };
+            if file_type.is_dir() {
+                if !EXCLUDED_DIRS.contains(&name.as_str()) {
+                    self.collect(&entry.path(), &relative, out, unreadable)?;
+                }
+            } else if file_type.is_file() && self.selects(&relative) {
+                match entry.metadata() {
+                    Ok(metadata) => out.push((relative, metadata.len())),
+                    Err(_) => unreadable.push(relative),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Render the file listing, cut to `max_bytes`
+fn render_tree(files: &[(String, u64)], max_bytes: usize) -> String {
+    let mut tree = String::new();
+    for (index, (path, size)) in files.iter().enumerate() {
+        let line = format!("{path} ({size} bytes)\n");
+        if tree.len() + line.len() > max_bytes {
+            tree.push_str(&format!("... ({} more files)\n", files.len() - index));
+            break;
+        }
+        tree.push_str(&line);
+    }
+    tree
+}
+
+/// Read up to `limit` bytes of a text file
+///
+/// Returns `None` for binary files. Truncated content is cut at the last
+/// newline within the limit when there is one.
+fn read_text(path: &Path, limit: usize) -> Result<Option<(String, bool)>> {
+    let mut bytes = Vec::new();
+    File::open(path)?
+        .take(limit as u64 + 1)
+        .read_to_end(&mut bytes)?;
+
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Ok(None);
+    }
+
+    let truncated = bytes.len() > limit;
+    if truncated {
+        bytes.truncate(limit);
+        if let Some(newline) = bytes.iter().rposition(|&b| b == b'\n') {
+            bytes.truncate(newline + 1);
+        }
+    }
+    Ok(Some((
+        String::from_utf8_lossy(&bytes).into_owned(),
+        truncated,
+    )))
+}
@@ -76,6 +76,33 @@ impl<'de> Deserialize<'de> for ClientCapabilities {
     }
 }
 
+/// Wire shape of `ClientCapabilities`, for schema generation
+#[cfg(feature = "schema")]
+#[derive(schemars::JsonSchema)]
+#[allow(dead_code, clippy::struct_excessive_bools)]
+#[schemars(rename = "ClientCapabilities")]
+struct ClientCapabilitiesSchema {
+    /// Supports bidirectional communication
+    bidirectional: bool,
+    /// Supports hooks
+    hooks: bool,
+    /// Supports permissions
+    permissions: bool,
+    /// Supports interrupts
+    interrupts: bool,
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ClientCapabilities {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        ClientCapabilitiesSchema::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        ClientCapabilitiesSchema::json_schema(generator)
+    }
+}
+
 bitflags! {
     /// Server capabilities advertised by CLI
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -138,3 +165,28 @@ impl<'de> Deserialize<'de> for ServerCapabilities {
         Ok(caps)
     }
 }
+
+/// Wire shape of `ServerCapabilities`, for schema generation
+#[cfg(feature = "schema")]
+#[derive(schemars::JsonSchema)]
+#[allow(dead_code)]
+#[schemars(rename = "ServerCapabilities")]
+struct ServerCapabilitiesSchema {
+    /// Supports streaming responses
+    streaming: bool,
+    /// Supports tool use
+    tools: bool,
+    /// Supports MCP servers
+    mcp: bool,
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ServerCapabilities {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        ServerCapabilitiesSchema::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        ServerCapabilitiesSchema::json_schema(generator)
+    }
+}
@@ -2,17 +2,22 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::{Mutex, mpsc, oneshot};
 
 use crate::error::{ClaudeError, Result};
 use crate::types::hooks::HookEvent;
-use crate::types::identifiers::RequestId;
-use crate::types::permissions::{PermissionRequest, PermissionResult};
+use crate::types::identifiers::{IdGenerator, RandomIdGenerator, RequestId};
+use crate::types::permissions::{
+    PermissionMode, PermissionRequest, PermissionResult, PermissionUpdate,
+};
 
 use super::capabilities::ClientCapabilities;
 use super::messages::{ControlMessage, ControlRequest, ControlResponse, InitRequest, InitResponse};
 
+/// Control protocol version spoken by this SDK
+pub(crate) const PROTOCOL_VERSION: &str = "1.0";
+
 /// Pending request awaiting response
 struct PendingRequest {
     /// Response channel
@@ -21,8 +26,8 @@ struct PendingRequest {
 
 /// Protocol handler for managing control protocol communication
 pub struct ProtocolHandler {
-    /// Request ID counter
-    next_request_id: Arc<AtomicU64>,
+    /// Request ID source
+    ids: Arc<dyn IdGenerator>,
     /// Pending requests awaiting responses
     pending_requests: Arc<Mutex<HashMap<RequestId, PendingRequest>>>,
     /// Initialized flag
@@ -31,6 +36,8 @@ pub struct ProtocolHandler {
     hook_tx: Option<mpsc::UnboundedSender<(String, HookEvent, serde_json::Value)>>,
     /// Permission callback channel
     permission_tx: Option<mpsc::UnboundedSender<(RequestId, PermissionRequest)>>,
+    /// SDK MCP server message channel
+    mcp_tx: Option<mpsc::UnboundedSender<(RequestId, String, serde_json::Value)>>,
 }
 
 impl ProtocolHandler {
@@ -38,14 +45,20 @@ impl ProtocolHandler {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            next_request_id: Arc::new(AtomicU64::new(1)),
+            ids: Arc::new(RandomIdGenerator::new()),
             pending_requests: Arc::new(Mutex::new(HashMap::new())),
             initialized: Arc::new(AtomicBool::new(false)),
             hook_tx: None,
             permission_tx: None,
+            mcp_tx: None,
         }
     }
 
+    /// Set the source of request IDs (default: `req-1`, `req-2`, ...)
+    pub fn set_id_generator(&mut self, ids: Arc<dyn IdGenerator>) {
+        self.ids = ids;
+    }
+
     /// Set hook callback channel
     pub fn set_hook_channel(
         &mut self,
@@ -62,6 +75,14 @@ impl ProtocolHandler {
         self.permission_tx = Some(tx);
     }
 
+    /// Set SDK MCP server message channel
+    pub fn set_mcp_channel(
+        &mut self,
+        tx: mpsc::UnboundedSender<(RequestId, String, serde_json::Value)>,
+    ) {
+        self.mcp_tx = Some(tx);
+    }
+
     /// Check if protocol is initialized
     #[must_use]
     pub fn is_initialized(&self) -> bool {
@@ -78,15 +99,14 @@ impl ProtocolHandler {
     /// Generates a unique request ID for each request. Used internally and in tests.
     #[must_use]
     pub fn next_id(&self) -> RequestId {
-        let id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
-        RequestId::new(format!("req-{id}"))
+        self.ids.request_id()
     }
 
     /// Create initialization request
     #[must_use]
     pub fn create_init_request(&self) -> InitRequest {
         InitRequest {
-            protocol_version: "1.0".to_string(),
+            protocol_version: PROTOCOL_VERSION.to_string(),
             sdk_version: crate::VERSION.to_string(),
             capabilities: ClientCapabilities::all_features(),
         }
@@ -98,7 +118,7 @@ impl ProtocolHandler {
     /// Returns error if protocol version is unsupported
     pub fn handle_init_response(&self, response: &InitResponse) -> Result<()> {
         // Validate protocol version
-        if response.protocol_version != "1.0" {
+        if response.protocol_version != PROTOCOL_VERSION {
             return Err(ClaudeError::protocol_error(format!(
                 "Unsupported protocol version: {}",
                 response.protocol_version
@@ -136,6 +156,14 @@ impl ProtocolHandler {
         Ok(response_rx)
     }
 
+    /// Stop waiting for a response to a previously sent request
+    ///
+    /// Returns true if the request was still pending. Used when a caller gives
+    /// up waiting (e.g. a ping timed out) so the pending map does not grow.
+    pub async fn cancel_request(&self, id: &RequestId) -> bool {
+        self.pending_requests.lock().await.remove(id).is_some()
+    }
+
     /// Extract request ID from a control request
     ///
     /// # Examples
@@ -155,7 +183,14 @@ impl ProtocolHandler {
             ControlRequest::Interrupt { id }
             | ControlRequest::SendMessage { id, .. }
             | ControlRequest::HookResponse { id, .. }
-            | ControlRequest::PermissionResponse { id, .. } => id.clone(),
+            | ControlRequest::PermissionResponse { id, .. }
+            | ControlRequest::Ping { id }
+            | ControlRequest::SetPermissionMode { id, .. }
+            | ControlRequest::SetModel { id, .. }
+            | ControlRequest::Compact { id }
+            | ControlRequest::Rewind { id, .. }
+            | ControlRequest::UpdatePermissions { id, .. }
+            | ControlRequest::McpResponse { id, .. } => id.clone(),
         }
     }
 
@@ -192,6 +227,17 @@ impl ProtocolHandler {
                 }
                 Ok(())
             }
+            ControlResponse::McpMessage {
+                id,
+                server_name,
+                message,
+            } => {
+                if let Some(ref tx) = self.mcp_tx {
+                    tx.send((id.clone(), server_name.clone(), message.clone()))
+                        .map_err(|_| ClaudeError::protocol_error("MCP channel closed"))?;
+                }
+                Ok(())
+            }
         }
     }
 
@@ -201,6 +247,57 @@ impl ProtocolHandler {
         ControlRequest::Interrupt { id: self.next_id() }
     }
 
+    /// Create ping request for control channel health checks
+    #[must_use]
+    pub fn create_ping_request(&self) -> ControlRequest {
+        ControlRequest::Ping { id: self.next_id() }
+    }
+
+    /// Create a request switching the session's permission mode
+    #[must_use]
+    pub fn create_set_permission_mode_request(&self, mode: PermissionMode) -> ControlRequest {
+        ControlRequest::SetPermissionMode {
+            id: self.next_id(),
+            mode,
+        }
+    }
+
+    /// Create a request switching the session's model
+    #[must_use]
+    pub fn create_set_model_request(&self, model: String) -> ControlRequest {
+        ControlRequest::SetModel {
+            id: self.next_id(),
+            model,
+        }
+    }
+
+    /// Create a request compacting the conversation
+    #[must_use]
+    pub fn create_compact_request(&self) -> ControlRequest {
+        ControlRequest::Compact { id: self.next_id() }
+    }
+
+    /// Create a request dropping the latest `turns` turns
+    #[must_use]
+    pub fn create_rewind_request(&self, turns: u32) -> ControlRequest {
+        ControlRequest::Rewind {
+            id: self.next_id(),
+            turns,
+        }
+    }
+
+    /// Create a request applying permission updates for the session
+    #[must_use]
+    pub fn create_update_permissions_request(
+        &self,
+        updates: Vec<PermissionUpdate>,
+    ) -> ControlRequest {
+        ControlRequest::UpdatePermissions {
+            id: self.next_id(),
+            updates,
+        }
+    }
+
     /// Create send message request
     #[must_use]
     pub fn create_send_message_request(&self, content: String) -> ControlRequest {
@@ -238,6 +335,20 @@ impl ProtocolHandler {
         }
     }
 
+    /// Create SDK MCP server response
+    #[must_use]
+    pub fn create_mcp_response(
+        &self,
+        request_id: RequestId,
+        response: serde_json::Value,
+    ) -> ControlRequest {
+        ControlRequest::McpResponse {
+            id: self.next_id(),
+            request_id,
+            response,
+        }
+    }
+
     /// Serialize control message to JSON
     ///
     /// # Errors
@@ -7,12 +7,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::types::hooks::HookEvent;
 use crate::types::identifiers::RequestId;
-use crate::types::permissions::{PermissionRequest, PermissionResult};
+use crate::types::permissions::{
+    PermissionMode, PermissionRequest, PermissionResult, PermissionUpdate,
+};
 
 use super::capabilities::{ClientCapabilities, ServerCapabilities};
 
 /// Control message envelope for all protocol messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type")]
 pub enum ControlMessage {
     /// Request from SDK to CLI
@@ -31,7 +34,9 @@ pub enum ControlMessage {
 
 /// Request from SDK to CLI
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "method", content = "params")]
+#[non_exhaustive]
 pub enum ControlRequest {
     /// Interrupt the current operation
     #[serde(rename = "interrupt")]
@@ -67,11 +72,84 @@ pub enum ControlRequest {
         /// Permission result (Allow/Deny)
         result: PermissionResult,
     },
+    /// Lightweight liveness probe; any response with the same id counts as a pong
+    #[serde(rename = "ping")]
+    Ping {
+        /// Unique request identifier
+        id: RequestId,
+    },
+    /// Switch the permission mode for the rest of the session
+    #[serde(rename = "set_permission_mode")]
+    SetPermissionMode {
+        /// Unique request identifier
+        id: RequestId,
+        /// Permission mode to switch to
+        mode: PermissionMode,
+    },
+    /// Switch the model for the rest of the session
+    #[serde(rename = "set_model")]
+    SetModel {
+        /// Unique request identifier
+        id: RequestId,
+        /// Model to use for the following turns
+        model: String,
+    },
+    /// Summarize the conversation so far to free context
+    #[serde(rename = "compact")]
+    Compact {
+        /// Unique request identifier
+        id: RequestId,
+    },
+    /// Drop the latest turns from the conversation
+    #[serde(rename = "rewind")]
+    Rewind {
+        /// Unique request identifier
+        id: RequestId,
+        /// Number of turns to drop
+        turns: u32,
+    },
+    /// Apply permission rule, mode and directory updates for the session
+    #[serde(rename = "update_permissions")]
+    UpdatePermissions {
+        /// Unique request identifier
+        id: RequestId,
+        /// Updates to apply, in order
+        updates: Vec<PermissionUpdate>,
+    },
+    /// Answer a JSON-RPC message addressed to an SDK MCP server
+    #[serde(rename = "mcp_response")]
+    McpResponse {
+        /// Unique request identifier
+        id: RequestId,
+        /// MCP message ID being responded to
+        request_id: RequestId,
+        /// JSON-RPC response from the server
+        response: serde_json::Value,
+    },
+}
+
+impl ControlRequest {
+    /// Wire names of the request methods, in declaration order
+    pub const METHODS: &'static [&'static str] = &[
+        "interrupt",
+        "send_message",
+        "hook_response",
+        "permission_response",
+        "ping",
+        "set_permission_mode",
+        "set_model",
+        "compact",
+        "rewind",
+        "update_permissions",
+        "mcp_response",
+    ];
 }
 
 /// Response from CLI to SDK
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "status")]
+#[non_exhaustive]
 pub enum ControlResponse {
     /// Successful response
     #[serde(rename = "success")]
@@ -110,10 +188,21 @@ pub enum ControlResponse {
         /// Permission request details
         request: PermissionRequest,
     },
+    /// JSON-RPC message for an SDK MCP server
+    #[serde(rename = "mcp_message")]
+    McpMessage {
+        /// MCP message ID
+        id: RequestId,
+        /// Name of the SDK MCP server addressed
+        server_name: String,
+        /// JSON-RPC message
+        message: serde_json::Value,
+    },
 }
 
 /// Initialization request sent from SDK to CLI
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct InitRequest {
     /// Protocol version
     pub protocol_version: String,
@@ -125,6 +214,7 @@ pub struct InitRequest {
 
 /// Initialization response from CLI to SDK
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct InitResponse {
     /// Protocol version accepted
     pub protocol_version: String,
@@ -85,4 +85,5 @@ mod messages;
 // Re-export public types
 pub use capabilities::{ClientCapabilities, ServerCapabilities};
 pub use handler::ProtocolHandler;
+pub(crate) use handler::PROTOCOL_VERSION;
 pub use messages::{ControlMessage, ControlRequest, ControlResponse, InitRequest, InitResponse};
@@ -4,6 +4,7 @@ use thiserror::Error;
 
 /// Main error type for the Claude Agent SDK
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ClaudeError {
     /// Claude Code CLI not found or not installed
     #[error("Claude Code CLI not found: {0}")]
@@ -73,14 +74,35 @@ pub enum ClaudeError {
     #[error("Agent session {0} is already complete")]
     SessionComplete(String),
 
+    /// Session is attached to a terminal and not taking orchestrator input
+    #[error("Session is attached to a terminal: {0}")]
+    SessionAttached(String),
+
     /// Maximum active sessions limit reached
     #[error("Maximum active sessions reached: {0}")]
     MaxSessionsReached(usize),
 
+    /// Session group not found
+    #[error("Session group not found: {0}")]
+    GroupNotFound(String),
+
+    /// Session group has spent its shared budget
+    #[error("Session group {0} has exhausted its budget")]
+    GroupBudgetExceeded(String),
+
     /// Invalid agent session configuration
     #[error("Invalid agent configuration: {0}")]
     InvalidAgentConfiguration(String),
 
+    /// Transcript does not match its hash chain
+    #[error("Transcript integrity check failed at entry {index}: {message}")]
+    TranscriptIntegrity {
+        /// Index of the first entry that failed verification
+        index: usize,
+        /// What did not match
+        message: String,
+    },
+
     /// Failed to render prompt template
     #[error("Failed to render prompt template '{template}': {message}")]
     PromptTemplateError {
@@ -199,13 +221,48 @@ impl ClaudeError {
         Self::MaxSessionsReached(max)
     }
 
+    /// Create a group not found error
+    pub fn group_not_found(group_id: impl Into<String>) -> Self {
+        Self::GroupNotFound(group_id.into())
+    }
+
+    /// Create a transcript integrity error
+    pub fn transcript_integrity(index: usize, msg: impl Into<String>) -> Self {
+        Self::TranscriptIntegrity {
+            index,
+            message: msg.into(),
+        }
+    }
+
+    /// Create a group budget exceeded error
+    pub fn group_budget_exceeded(group_id: impl Into<String>) -> Self {
+        Self::GroupBudgetExceeded(group_id.into())
+    }
+
     /// Create an invalid agent configuration error
     pub fn invalid_agent_config(msg: impl Into<String>) -> Self {
         Self::InvalidAgentConfiguration(msg.into())
     }
+
+    /// Whether the error may go away by trying again
+    ///
+    /// True for failures of the connection, the CLI process, the transport,
+    /// I/O and timeouts; false for configuration, parse and session errors.
+    #[must_use]
+    pub const fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Connection(_)
+                | Self::Process { .. }
+                | Self::Transport(_)
+                | Self::Io(_)
+                | Self::Timeout(_)
+        )
+    }
 }
 
 // Conversion to kodegen_tool McpError
+#[cfg(feature = "tools")]
 impl From<ClaudeError> for kodegen_mcp_schema::McpError {
     fn from(err: ClaudeError) -> Self {
         use kodegen_mcp_schema::McpError;
@@ -233,10 +290,20 @@ impl From<ClaudeError> for kodegen_mcp_schema::McpError {
             ClaudeError::SessionComplete(msg) => {
                 McpError::InvalidArguments(format!("Session complete: {msg}"))
             }
+            ClaudeError::SessionAttached(msg) => {
+                McpError::InvalidArguments(format!("Session attached to a terminal: {msg}"))
+            }
             ClaudeError::MaxSessionsReached(max) => {
                 McpError::Other(anyhow::anyhow!("Max sessions reached: {max}"))
             }
+            ClaudeError::GroupNotFound(msg) => McpError::ResourceNotFound(msg),
+            ClaudeError::GroupBudgetExceeded(msg) => {
+                McpError::Other(anyhow::anyhow!("Group budget exhausted: {msg}"))
+            }
             ClaudeError::InvalidAgentConfiguration(msg) => McpError::InvalidArguments(msg),
+            ClaudeError::TranscriptIntegrity { index, message } => McpError::Other(
+                anyhow::anyhow!("Transcript integrity check failed at entry {index}: {message}"),
+            ),
             ClaudeError::PromptTemplateError { template, message } => {
                 McpError::Other(anyhow::anyhow!("Template '{template}' error: {message}"))
             }
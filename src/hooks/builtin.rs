@@ -0,0 +1,210 @@
+//! Ready-made hooks
+//!
+//! Each function returns a named [`HookMatcher`] to register for
+//! `PreToolUse` (or, for the audit logger, any tool event):
+//!
+//! ```no_run
+//! use kodegen_claude_agent::hooks::{HookManager, builtin};
+//! use kodegen_claude_agent::HookEvent;
+//!
+//! let mut manager = HookManager::new();
+//! manager.register_for(HookEvent::PreToolUse, builtin::path_guard(["/srv/project"]));
+//! manager.register_for(HookEvent::PreToolUse, builtin::bash_allowlist(["ls", "cargo", "git"]));
+//! ```
+
+use std::collections::VecDeque;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::AsyncWriteExt;
+
+use super::{HookManager, HookMatcherBuilder};
+use crate::permissions::BashGuard;
+use crate::types::hooks::{HookDecision, HookMatcher, HookOutput};
+
+/// Tools that write files, as matched by [`path_guard`]
+pub const WRITE_TOOLS: &str = "Write|Edit|MultiEdit|NotebookEdit";
+
+/// Block file writes outside `roots`
+///
+/// Relative roots resolve against the current directory when the guard is
+/// created; a root that cannot be resolved allows nothing. Relative paths
+/// being written resolve against the session's `cwd` from the hook input,
+/// or the current directory. Paths are compared after resolving `.` and `..`,
+/// without following symlinks. Named `builtin.path_guard`.
+pub fn path_guard<I, P>(roots: I) -> HookMatcher
+where
+    I: IntoIterator<Item = P>,
+    P: Into<PathBuf>,
+{
+    let roots: Arc<Vec<PathBuf>> = Arc::new(
+        roots
+            .into_iter()
+            .filter_map(|root| std::path::absolute(root.into()).ok())
+            .map(|root| normalize(&root))
+            .collect(),
+    );
+    let hook = HookManager::callback(move |event_data, _tool_name, _context| {
+        let roots = Arc::clone(&roots);
+        async move {
+            let input = tool_input(&event_data);
+            let Some(path) = ["file_path", "notebook_path", "path"]
+                .iter()
+                .find_map(|key| input.get(*key).and_then(|v| v.as_str()))
+            else {
+                return Ok(HookOutput::default());
+            };
+            let base = match event_data.get("cwd").and_then(|v| v.as_str()) {
+                Some(cwd) => PathBuf::from(cwd),
+                None => std::env::current_dir()?,
+            };
+            let target = normalize(&base.join(path));
+            if roots.iter().any(|root| target.starts_with(root)) {
+                Ok(HookOutput::default())
+            } else {
+                Ok(block(format!(
+                    "Write to {} is outside the allowed directories",
+                    target.display()
+                )))
+            }
+        }
+    });
+    HookMatcherBuilder::new(Some(WRITE_TOOLS))
+        .name("builtin.path_guard")
+        .add_hook(hook)
+        .build()
+}
+
+/// Block Bash commands whose programs are not in `allowed`
+///
+/// Commands are checked with a [`BashGuard`] allowing only `allowed`: every
+/// command of a pipeline or list must be allowed, including those run by
+/// wrappers such as `env`, and command and process substitution and command
+/// strings (`sh -c`) are always blocked, since the commands they run cannot
+/// be checked. Named `builtin.bash_allowlist`.
+pub fn bash_allowlist<I, S>(allowed: I) -> HookMatcher
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let guard = Arc::new(BashGuard::new().allow(allowed));
+    let hook = HookManager::callback(move |event_data, _tool_name, _context| {
+        let guard = Arc::clone(&guard);
+        async move {
+            let command = tool_input(&event_data)
+                .get("command")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            Ok(match guard.check(command) {
+                Ok(()) => HookOutput::default(),
+                Err(reason) => block(reason),
+            })
+        }
+    });
+    HookMatcherBuilder::new(Some("Bash"))
+        .name("builtin.bash_allowlist")
+        .add_hook(hook)
+        .build()
+}
+
+/// Append every matched hook input to a JSONL file
+///
+/// Each line holds a `timestamp`, the `tool_name`, and the hook `input`.
+/// Named `builtin.audit_log`.
+pub fn audit_log(path: impl Into<PathBuf>) -> HookMatcher {
+    let path = Arc::new(path.into());
+    let hook = HookManager::callback(move |event_data, tool_name, _context| {
+        let path = Arc::clone(&path);
+        async move {
+            let entry = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "tool_name": tool_name,
+                "input": event_data,
+            });
+            let mut line = serde_json::to_string(&entry)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path.as_path())
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            // Tokio finishes file writes in the background; wait for this one
+            file.flush().await?;
+            Ok(HookOutput::default())
+        }
+    });
+    HookMatcherBuilder::new(None::<String>)
+        .name("builtin.audit_log")
+        .add_hook(hook)
+        .build()
+}
+
+/// Block tool calls beyond `max_calls` in any `window`
+///
+/// The limit is shared by every tool the matcher sees; set
+/// [`HookMatcher::matcher`] to limit specific tools. Blocked calls do not
+/// count. Named `builtin.rate_limit`.
+#[must_use]
+pub fn rate_limit(max_calls: usize, window: Duration) -> HookMatcher {
+    let calls = Arc::new(Mutex::new(VecDeque::<Instant>::new()));
+    let hook = HookManager::callback(move |_event_data, _tool_name, _context| {
+        let calls = Arc::clone(&calls);
+        async move {
+            let now = Instant::now();
+            let mut calls = calls
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            while calls
+                .front()
+                .is_some_and(|call| now.duration_since(*call) >= window)
+            {
+                calls.pop_front();
+            }
+            if calls.len() >= max_calls {
+                return Ok(block(format!(
+                    "Rate limit of {max_calls} tool calls per {window:?} reached"
+                )));
+            }
+            calls.push_back(now);
+            Ok(HookOutput::default())
+        }
+    });
+    HookMatcherBuilder::new(None::<String>)
+        .name("builtin.rate_limit")
+        .add_hook(hook)
+        .build()
+}
+
+/// Output blocking the action for `reason`
+fn block(reason: String) -> HookOutput {
+    HookOutput {
+        decision: Some(HookDecision::Block),
+        system_message: Some(reason),
+        ..HookOutput::default()
+    }
+}
+
+/// Tool input of a tool event's hook input
+fn tool_input(event_data: &serde_json::Value) -> &serde_json::Value {
+    event_data
+        .get("tool_input")
+        .or_else(|| event_data.get("toolInput"))
+        .unwrap_or(&serde_json::Value::Null)
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
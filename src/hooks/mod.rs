@@ -3,15 +3,98 @@
 //! This module provides the hook system that allows users to intercept
 //! and respond to various events in the agent lifecycle.
 
-use std::sync::Arc;
+pub mod builtin;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::error::Result;
-use crate::types::hooks::{HookCallback, HookContext, HookDecision, HookMatcher, HookOutput};
+use crate::types::hooks::{
+    HookCallback, HookContext, HookDecision, HookErrorPolicy, HookEvent, HookMatcher,
+    HookMergeStrategy, HookOutput, HookPayload,
+};
+
+/// Handle of a registered hook matcher, used to remove it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+impl fmt::Display for HookId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hook-{}", self.0)
+    }
+}
+
+/// Invocation counters of a registered matcher
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HookStats {
+    /// Callbacks run
+    pub invocations: u64,
+    /// Callbacks whose output blocked the action, or would have in shadow mode
+    pub blocked: u64,
+    /// Callbacks that failed
+    pub errors: u64,
+    /// Callbacks abandoned after the hook timeout
+    pub timeouts: u64,
+    /// Outputs served from the matcher's cache instead of running a callback
+    pub cache_hits: u64,
+    /// Time spent in callbacks
+    pub total_latency: Duration,
+    /// Longest single callback
+    pub max_latency: Duration,
+}
+
+impl HookStats {
+    /// Average time per callback, `None` before any ran
+    #[must_use]
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let invocations = u32::try_from(self.invocations).ok().filter(|n| *n > 0)?;
+        Some(self.total_latency / invocations)
+    }
+}
+
+/// A registered matcher and the event it is limited to
+struct Registration {
+    id: HookId,
+    /// `None` for matchers that apply to every event
+    event: Option<HookEvent>,
+    matcher: HookMatcher,
+    /// Disabled matchers stay registered but are skipped
+    enabled: bool,
+    stats: Mutex<HookStats>,
+    /// Cached outputs by input key, with when they were stored
+    cache: Mutex<HashMap<u64, (Instant, HookOutput)>>,
+}
+
+impl Registration {
+    fn stats(&self) -> std::sync::MutexGuard<'_, HookStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<u64, (Instant, HookOutput)>> {
+        self.cache
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
 
 /// Hook manager for registering and invoking hooks
 pub struct HookManager {
-    /// Registered hook matchers
-    matchers: Vec<HookMatcher>,
+    /// Registered hook matchers, in registration order
+    matchers: Vec<Registration>,
+    /// Next registration ID
+    next_id: u64,
+    /// Longest a single callback may run (unbounded when `None`)
+    timeout: Option<Duration>,
+    /// Output used for a callback that times out
+    timeout_output: HookOutput,
+    /// Whether matching callbacks run concurrently
+    parallel: bool,
+    /// How the outputs of several callbacks combine
+    merge_strategy: HookMergeStrategy,
 }
 
 impl HookManager {
@@ -20,19 +103,152 @@ impl HookManager {
     pub const fn new() -> Self {
         Self {
             matchers: Vec::new(),
+            next_id: 0,
+            timeout: None,
+            timeout_output: HookOutput {
+                decision: None,
+                system_message: None,
+                hook_specific_output: None,
+                updated_input: None,
+                additional_context: None,
+            },
+            parallel: false,
+            merge_strategy: HookMergeStrategy::BlockWins,
         }
     }
 
+    /// Run the callbacks matching an event concurrently
+    ///
+    /// Suits independent hooks such as audit loggers. Outputs are still
+    /// merged in registration order, but every callback sees the original
+    /// input rather than one rewritten by an earlier hook, and all of them
+    /// run even when an earlier one decides.
+    pub const fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Set how the outputs of several callbacks combine
+    pub const fn set_merge_strategy(&mut self, strategy: HookMergeStrategy) {
+        self.merge_strategy = strategy;
+    }
+
+    /// Limit how long a single hook callback may run
+    ///
+    /// A callback still running after `timeout` is abandoned and the
+    /// [timeout output](Self::set_timeout_output) is used as its result.
+    /// `None` removes the limit.
+    pub const fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Set the output used for a hook callback that times out
+    ///
+    /// Defaults to an empty output, which lets the action proceed.
+    pub fn set_timeout_output(&mut self, output: HookOutput) {
+        self.timeout_output = output;
+    }
+
     /// Register a hook with a matcher
     ///
+    /// The matcher applies to every event.
+    ///
+    /// # Arguments
+    /// * `matcher` - Hook matcher configuration
+    pub fn register(&mut self, matcher: HookMatcher) -> HookId {
+        self.add(None, matcher)
+    }
+
+    /// Register a hook with a matcher for one event
+    ///
     /// # Arguments
+    /// * `event` - Event the matcher applies to
     /// * `matcher` - Hook matcher configuration
-    pub fn register(&mut self, matcher: HookMatcher) {
-        self.matchers.push(matcher);
+    pub fn register_for(&mut self, event: HookEvent, matcher: HookMatcher) -> HookId {
+        self.add(Some(event), matcher)
+    }
+
+    /// Remove a registered matcher
+    ///
+    /// Returns `false` if no matcher is registered under `id`.
+    pub fn remove(&mut self, id: HookId) -> bool {
+        let before = self.matchers.len();
+        self.matchers.retain(|registration| registration.id != id);
+        self.matchers.len() < before
+    }
+
+    /// Remove the matchers named `name`
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub fn remove_named(&mut self, name: &str) -> bool {
+        let before = self.matchers.len();
+        self.matchers
+            .retain(|registration| registration.matcher.name.as_deref() != Some(name));
+        self.matchers.len() < before
+    }
+
+    /// Resume running the matchers named `name`
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, true)
+    }
+
+    /// Skip the matchers named `name` until they are enabled again
+    ///
+    /// Returns `false` if no matcher has that name.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, false)
+    }
+
+    /// Switch the matchers named `name` into or out of shadow mode
+    ///
+    /// See [`HookMatcherBuilder::shadow`]. Returns `false` if no matcher has
+    /// that name.
+    pub fn set_shadow(&mut self, name: &str, shadow: bool) -> bool {
+        let mut found = false;
+        for registration in &mut self.matchers {
+            if registration.matcher.name.as_deref() == Some(name) {
+                registration.matcher.shadow = shadow;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Whether the matchers named `name` run, `None` if none has that name
+    #[must_use]
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.named(name)
+            .next()
+            .map(|registration| registration.enabled)
+    }
+
+    /// Number of registered matchers
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.matchers.len()
+    }
+
+    /// Whether no matchers are registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+    }
+
+    /// Invocation counters of each registered matcher
+    #[must_use]
+    pub fn stats(&self) -> HashMap<HookId, HookStats> {
+        self.matchers
+            .iter()
+            .map(|registration| (registration.id, registration.stats().clone()))
+            .collect()
     }
 
     /// Invoke hooks for a given event
     ///
+    /// Runs every matcher regardless of the event it was registered for; use
+    /// [`invoke_event`](Self::invoke_event) to respect registrations.
+    ///
     /// # Arguments
     /// * `event_data` - Event data (JSON value)
     /// * `tool_name` - Optional tool name
@@ -42,45 +258,324 @@ impl HookManager {
     /// Hook output with optional decision and modifications
     ///
     /// # Errors
-    /// Returns error if hook callback execution fails
+    /// Currently never fails: a failed callback is answered by its matcher's
+    /// [error policy](HookMatcherBuilder::on_error) instead
     pub async fn invoke(
         &self,
         event_data: serde_json::Value,
         tool_name: Option<String>,
         context: HookContext,
+    ) -> Result<HookOutput> {
+        self.run(None, event_data, tool_name, context).await
+    }
+
+    /// Invoke the hooks registered for `event` or for every event
+    ///
+    /// # Errors
+    /// Currently never fails: a failed callback is answered by its matcher's
+    /// [error policy](HookMatcherBuilder::on_error) instead
+    pub async fn invoke_event(
+        &self,
+        event: HookEvent,
+        event_data: serde_json::Value,
+        tool_name: Option<String>,
+        context: HookContext,
+    ) -> Result<HookOutput> {
+        self.run(Some(event), event_data, tool_name, context).await
+    }
+
+    fn named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Registration> {
+        self.matchers
+            .iter()
+            .filter(move |registration| registration.matcher.name.as_deref() == Some(name))
+    }
+
+    fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for registration in &mut self.matchers {
+            if registration.matcher.name.as_deref() == Some(name) {
+                registration.enabled = enabled;
+                found = true;
+            }
+        }
+        found
+    }
+
+    fn add(&mut self, event: Option<HookEvent>, matcher: HookMatcher) -> HookId {
+        let id = HookId(self.next_id);
+        self.next_id += 1;
+        self.matchers.push(Registration {
+            id,
+            event,
+            matcher,
+            enabled: true,
+            stats: Mutex::default(),
+            cache: Mutex::default(),
+        });
+        id
+    }
+
+    /// Run matching hooks, limited to those for `event` when given
+    async fn run(
+        &self,
+        event: Option<HookEvent>,
+        mut event_data: serde_json::Value,
+        tool_name: Option<String>,
+        context: HookContext,
     ) -> Result<HookOutput> {
         let mut output = HookOutput::default();
 
         // Find matching hooks
-        for matcher in &self.matchers {
-            if Self::matches(matcher.matcher.as_ref(), tool_name.as_ref()) {
-                // Invoke each hook callback
-                for hook in &matcher.hooks {
-                    let result =
-                        hook(event_data.clone(), tool_name.clone(), context.clone()).await?;
-
-                    // Merge hook results
-                    if result.decision.is_some() {
-                        output.decision = result.decision;
-                    }
-                    if result.system_message.is_some() {
-                        output.system_message = result.system_message;
-                    }
-                    if result.hook_specific_output.is_some() {
-                        output.hook_specific_output = result.hook_specific_output;
-                    }
-
-                    // If decision is Block, stop processing
-                    if matches!(output.decision, Some(HookDecision::Block)) {
-                        return Ok(output);
-                    }
+        let hooks = self
+            .matchers
+            .iter()
+            .filter(|registration| {
+                registration.enabled
+                    && (event.is_none()
+                        || registration.event.is_none()
+                        || registration.event == event)
+                    && Self::matches(registration.matcher.matcher.as_ref(), tool_name.as_ref())
+            })
+            .flat_map(|registration| {
+                (0..registration.matcher.hooks.len()).map(move |index| (registration, index))
+            })
+            .collect::<Vec<_>>();
+
+        if self.parallel {
+            // Every hook sees the original input; results merge in registration order
+            let results =
+                futures::future::join_all(hooks.into_iter().map(|(registration, index)| {
+                    self.invoke_one(
+                        registration,
+                        index,
+                        event,
+                        event_data.clone(),
+                        tool_name.clone(),
+                        context.clone(),
+                    )
+                }))
+                .await;
+            for result in results {
+                if self.merge(&mut output, result) {
+                    break;
                 }
             }
+            return Ok(output);
+        }
+
+        let mut settled = false;
+        for (registration, index) in hooks {
+            let result = self
+                .invoke_one(
+                    registration,
+                    index,
+                    event,
+                    event_data.clone(),
+                    tool_name.clone(),
+                    context.clone(),
+                )
+                .await;
+            if settled {
+                // Still run, e.g. for audit hooks, but the outcome is decided
+                continue;
+            }
+            if let Some(updated_input) = &result.updated_input {
+                // Later hooks see the rewritten input
+                if let Some(fields) = event_data.as_object_mut() {
+                    let key = if fields.contains_key("toolInput") {
+                        "toolInput"
+                    } else {
+                        "tool_input"
+                    };
+                    fields.insert(key.to_string(), updated_input.clone());
+                }
+            }
+            settled = self.merge(&mut output, result);
+            if settled && self.merge_strategy == HookMergeStrategy::FirstDecision {
+                break;
+            }
         }
 
         Ok(output)
     }
 
+    /// Run callback `index` of `registration`, recording stats, applying its
+    /// error policy, and caching its output when the matcher caches
+    ///
+    /// A failed callback is answered by the matcher's error policy, or
+    /// blocks the action when it has none.
+    async fn invoke_one(
+        &self,
+        registration: &Registration,
+        index: usize,
+        event: Option<HookEvent>,
+        event_data: serde_json::Value,
+        tool_name: Option<String>,
+        context: HookContext,
+    ) -> HookOutput {
+        let cache_key = registration.matcher.cache_ttl.map(|ttl| {
+            (
+                ttl,
+                Self::cache_key(index, event, &event_data, tool_name.as_ref()),
+            )
+        });
+        if let Some((ttl, key)) = cache_key
+            && let Some((stored, output)) = registration.cache().get(&key)
+            && stored.elapsed() < ttl
+        {
+            let output = output.clone();
+            let mut stats = registration.stats();
+            stats.cache_hits += 1;
+            if output.decision == Some(HookDecision::Block) {
+                stats.blocked += 1;
+            }
+            if registration.matcher.shadow {
+                return HookOutput::default();
+            }
+            return output;
+        }
+
+        let started = Instant::now();
+        let hook = &registration.matcher.hooks[index];
+        let result = self
+            .call(registration, hook, event_data, tool_name.clone(), context)
+            .await;
+        {
+            let elapsed = started.elapsed();
+            let mut stats = registration.stats();
+            stats.invocations += 1;
+            stats.total_latency += elapsed;
+            stats.max_latency = stats.max_latency.max(elapsed);
+            if result.is_err() {
+                stats.errors += 1;
+            }
+        }
+        let result = match result {
+            Ok(Some(result)) => {
+                // Only outputs the callback produced are cached
+                if let Some((ttl, key)) = cache_key {
+                    let mut cache = registration.cache();
+                    cache.retain(|_, (stored, _)| stored.elapsed() < ttl);
+                    cache.insert(key, (Instant::now(), result.clone()));
+                }
+                result
+            }
+            Ok(None) => self.timeout_output.clone(),
+            Err(e) => {
+                log::warn!("Hook callback for {tool_name:?} failed: {e}");
+                registration
+                    .matcher
+                    .on_error
+                    .as_ref()
+                    .unwrap_or(&HookErrorPolicy::Block)
+                    .output(&e)
+            }
+        };
+
+        if result.decision == Some(HookDecision::Block) {
+            registration.stats().blocked += 1;
+        }
+        if registration.matcher.shadow {
+            log::info!(
+                "Shadow hook {} for {tool_name:?} decided {:?}; not enforced",
+                registration.matcher.name.as_deref().unwrap_or("(unnamed)"),
+                result.decision
+            );
+            return HookOutput::default();
+        }
+        result
+    }
+
+    /// Cache key of callback `index` for an input
+    ///
+    /// Only the tool input is hashed when present, since the rest of a tool
+    /// event's input (such as the tool use ID) differs between calls.
+    fn cache_key(
+        index: usize,
+        event: Option<HookEvent>,
+        event_data: &serde_json::Value,
+        tool_name: Option<&String>,
+    ) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let input = event_data
+            .get("tool_input")
+            .or_else(|| event_data.get("toolInput"))
+            .unwrap_or(event_data);
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        index.hash(&mut hasher);
+        event.hash(&mut hasher);
+        tool_name.hash(&mut hasher);
+        input.to_string().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Merge one hook's result into `output` using the merge strategy
+    ///
+    /// Returns `true` once later results are to be ignored.
+    fn merge(&self, output: &mut HookOutput, result: HookOutput) -> bool {
+        if let Some(decision) = result.decision {
+            output.decision = match (self.merge_strategy, output.decision) {
+                (HookMergeStrategy::FirstDecision, Some(first)) => Some(first),
+                (_, Some(HookDecision::Block)) => Some(HookDecision::Block),
+                _ => Some(decision),
+            };
+        }
+        if let Some(message) = result.system_message {
+            output.system_message = Some(match output.system_message.take() {
+                Some(earlier) if self.merge_strategy == HookMergeStrategy::Collect => {
+                    format!("{earlier}\n{message}")
+                }
+                _ => message,
+            });
+        }
+        if result.hook_specific_output.is_some() {
+            output.hook_specific_output = result.hook_specific_output;
+        }
+        if let Some(context) = result.additional_context {
+            // Context from every hook is kept
+            output.additional_context = Some(match output.additional_context.take() {
+                Some(earlier) => format!("{earlier}\n\n{context}"),
+                None => context,
+            });
+        }
+        if result.updated_input.is_some() {
+            output.updated_input = result.updated_input;
+        }
+
+        match self.merge_strategy {
+            HookMergeStrategy::BlockWins => output.decision == Some(HookDecision::Block),
+            HookMergeStrategy::FirstDecision => output.decision.is_some(),
+            HookMergeStrategy::Collect => false,
+        }
+    }
+
+    /// Run one callback, enforcing the timeout
+    ///
+    /// Returns `None` when the callback timed out.
+    async fn call(
+        &self,
+        registration: &Registration,
+        hook: &HookCallback,
+        event_data: serde_json::Value,
+        tool_name: Option<String>,
+        context: HookContext,
+    ) -> Result<Option<HookOutput>> {
+        let call = hook(event_data, tool_name.clone(), context);
+        let Some(limit) = self.timeout else {
+            return call.await.map(Some);
+        };
+        if let Ok(result) = tokio::time::timeout(limit, call).await {
+            result.map(Some)
+        } else {
+            registration.stats().timeouts += 1;
+            log::warn!(
+                "Hook callback for {tool_name:?} exceeded {limit:?}; using the timeout output"
+            );
+            Ok(None)
+        }
+    }
+
     /// Check if a matcher matches a tool name
     ///
     /// # Security Note
@@ -126,6 +621,20 @@ impl HookManager {
     {
         Arc::new(move |event_data, tool_name, context| Box::pin(f(event_data, tool_name, context)))
     }
+
+    /// Create a hook callback from a closure taking a typed payload
+    ///
+    /// The event is read from the input's `hook_event_name` field; see
+    /// [`HookPayload::from_value`].
+    pub fn typed_callback<F, Fut>(f: F) -> HookCallback
+    where
+        F: Fn(HookPayload, Option<String>, HookContext) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<HookOutput>> + Send + 'static,
+    {
+        Arc::new(move |event_data, tool_name, context| {
+            Box::pin(f(HookPayload::from_value(event_data), tool_name, context))
+        })
+    }
 }
 
 impl Default for HookManager {
@@ -136,8 +645,12 @@ impl Default for HookManager {
 
 /// Builder for creating hook matchers
 pub struct HookMatcherBuilder {
+    name: Option<String>,
     matcher: Option<String>,
     hooks: Vec<HookCallback>,
+    on_error: Option<HookErrorPolicy>,
+    cache_ttl: Option<Duration>,
+    shadow: bool,
 }
 
 impl HookMatcherBuilder {
@@ -147,11 +660,22 @@ impl HookMatcherBuilder {
     /// * `pattern` - Matcher pattern (None for all, or specific tool name/pattern)
     pub fn new(pattern: Option<impl Into<String>>) -> Self {
         Self {
+            name: None,
             matcher: pattern.map(std::convert::Into::into),
             hooks: Vec::new(),
+            on_error: None,
+            cache_ttl: None,
+            shadow: false,
         }
     }
 
+    /// Name the matcher, so it can be enabled, disabled, or removed by name
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
     /// Add a hook callback
     #[must_use]
     pub fn add_hook(mut self, hook: HookCallback) -> Self {
@@ -159,12 +683,47 @@ impl HookMatcherBuilder {
         self
     }
 
+    /// Set the response used when a hook callback fails
+    ///
+    /// Without a policy a failed callback blocks the action. The other
+    /// callbacks still run and their outputs are merged as usual.
+    #[must_use]
+    pub fn on_error(mut self, policy: HookErrorPolicy) -> Self {
+        self.on_error = Some(policy);
+        self
+    }
+
+    /// Reuse a callback's output for identical inputs for `ttl`
+    ///
+    /// Suits expensive, side-effect free callbacks such as policy lookups,
+    /// not audit loggers. Inputs match on event, tool name, and tool input.
+    #[must_use]
+    pub const fn cache(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Run the matcher in shadow mode
+    ///
+    /// Its callbacks run and their decisions are logged and counted in
+    /// [`HookStats`], but an empty output is used in their place, so nothing
+    /// is enforced. Useful for trying a new guard hook against real traffic.
+    #[must_use]
+    pub const fn shadow(mut self, shadow: bool) -> Self {
+        self.shadow = shadow;
+        self
+    }
+
     /// Build the hook matcher
     #[must_use]
     pub fn build(self) -> HookMatcher {
         HookMatcher {
+            name: self.name,
             matcher: self.matcher,
             hooks: self.hooks,
+            on_error: self.on_error,
+            cache_ttl: self.cache_ttl,
+            shadow: self.shadow,
         }
     }
 }
@@ -83,7 +83,7 @@
 //!
 //! Create in-process tools that Claude can invoke directly:
 //!
-//! ```ignore
+//! ```no_run
 //! # use kodegen_claude_agent::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
 //! # use serde_json::json;
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
@@ -177,6 +177,7 @@
 //! - [`types`]: Core type definitions, newtypes, and builders
 //! - [`query()`]: Simple one-shot query function
 //! - [`client`]: Interactive bidirectional client
+//! - [`context`]: Repository context packs for initial prompts
 //! - [`mcp`]: SDK MCP server for custom tools
 //! - [`hooks`]: Hook system for intercepting events
 //! - [`permissions`]: Permission control for tool usage
@@ -184,12 +185,22 @@
 //! - [`control`]: Control protocol handler
 //! - [`message`]: Message parsing and types
 //! - [`error`]: Error types and handling
+//! - [`prelude`]: Glob import of the commonly used types
 //!
 //! ## Feature Flags
 //!
 //! This crate supports the following feature flags:
 //!
-//! - `http` - Enables HTTP transport support (requires `reqwest`)
+//! - `client` (default) - `ClaudeSDKClient` and [`query()`]
+//! - `manager` - `AgentManager` for running many sessions concurrently
+//! - `tools` - MCP tool layer (`ClaudeAgentTool`, `AgentRegistry`, prompt templates)
+//! - `server` - Embedded HTTP server (`start_server`) and the `kodegen-claude-agent` binary
+//! - `http` - Enables `HttpTransport` for hosted CLI endpoints,
+//!   selected with [`TransportConfig::Http`] (requires `reqwest`)
+//! - `blocking` - `query_blocking` for synchronous callers (runs its own runtime)
+//! - `schema` - JSON Schemas for the public JSON types (`schema` module), the
+//!   `kodegen-claude-agent-schema` generator binary and `query_json` for typed answers
+//! - `toml` - Loading [`PermissionPolicy`] files written in TOML
 //! - `tracing-support` - Enables structured logging with `tracing`
 //!
 //! ## Examples
@@ -248,7 +259,8 @@
 //!
 //! - **Environment variable filtering** - Dangerous variables like `LD_PRELOAD`, `PATH`, `NODE_OPTIONS` are blocked
 //! - **Argument validation** - CLI flags are validated against an allowlist
-//! - **Timeout protection** - All I/O operations have 30-second timeouts
+//! - **Timeout protection** - Reads time out after 30 seconds by default; read, write and
+//!   shutdown timeouts are configurable through `ClaudeAgentOptions`
 //! - **Buffer limits** - Configurable max buffer size (default 1MB) prevents memory exhaustion
 //! - **Bounds checking** - Limits on configurable values (e.g., `max_turns` ≤ 1000)
 //! - **Secure logging** - Sensitive data only logged in debug builds with proper feature flags
@@ -269,38 +281,81 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod capabilities;
+#[cfg(feature = "client")]
 pub mod client;
+pub mod context;
 pub mod control;
 pub mod error;
 pub mod hooks;
+#[cfg(feature = "manager")]
 pub mod manager;
+pub mod mcp;
 pub mod message;
 pub mod permissions;
+pub mod prelude;
+#[cfg(feature = "client")]
 pub mod query;
+#[cfg(feature = "tools")]
 pub mod registry;
+#[cfg(feature = "schema")]
+pub mod schema;
 pub mod transport;
 pub mod types;
 
 // Re-export commonly used types for external API
+pub use capabilities::{Capabilities, capabilities};
+#[cfg(feature = "client")]
 pub use client::ClaudeSDKClient;
 pub use error::{ClaudeError, Result};
-pub use hooks::{HookManager, HookMatcherBuilder};
+pub use hooks::{HookId, HookManager, HookMatcherBuilder, HookStats};
 pub use message::parse_message;
-pub use permissions::{PermissionManager, PermissionManagerBuilder};
-pub use query::query;
-pub use transport::{PromptInput as TransportPromptInput, SubprocessTransport, Transport};
+pub use permissions::{
+    BashGuard, DecisionSource, PathSandbox, PermissionEvent, PermissionManager,
+    PermissionManagerBuilder, PermissionPolicy, PermissionStats, PolicyAction, PolicyRule, Preset,
+};
+#[cfg(feature = "client")]
+pub use query::{
+    parse_structured_output, query, query_batch, query_continue, query_text, query_with_transport,
+};
+#[cfg(all(feature = "client", feature = "schema"))]
+pub use query::query_json;
+#[cfg(all(feature = "client", feature = "blocking"))]
+pub use query::query_blocking;
+#[cfg(feature = "tools")]
+pub use query::query_prompt;
+pub use transport::{
+    BoxedTransport, ContainerTransport, MockTransport, PromptInput as TransportPromptInput,
+    RecordingTransport, ReplayTransport, SshTransport, SubprocessTransport, Transport,
+    TransportFixture, TransportMetrics,
+};
+#[cfg(feature = "http")]
+pub use transport::HttpTransport;
 
 // Re-export type submodules for flat public API
 pub use types::agent::{AgentDefinition, SystemPrompt, SystemPromptPreset};
+pub use types::delta::{Delta, DeltaDecoder, TextDelta, ThinkingDelta, ToolUseDelta};
 pub use types::hooks::{
-    HookCallback, HookContext, HookDecision, HookEvent, HookMatcher, HookOutput,
+    HookCallback, HookContext, HookDecision, HookErrorPolicy, HookEvent, HookMatcher,
+    HookMergeStrategy, HookOutput, HookPayload, PostToolUsePayload, PreToolUsePayload, UserPromptSubmitPayload,
 };
 pub use types::identifiers::{RequestId, SessionId, ToolName};
 pub use types::mcp::{
     McpHttpServerConfig, McpServerConfig, McpServers, McpStreamableHttpConfig, McpStdioServerConfig,
 };
-pub use types::messages::{ContentBlock, ContentValue, Message, UserContent};
-pub use types::options::{ClaudeAgentOptions, ClaudeAgentOptionsBuilder};
+pub use types::messages::{
+    CompactBoundary, CompactTrigger, ContentBlock, ContentValue, ImageSource, Message, TurnOutcome,
+    UsageTotals, UserContent,
+};
+pub use types::options::{
+    ClaudeAgentOptions, ClaudeAgentOptionsBuilder, PreSpawnHook, ProgressCallback, RetryPolicy,
+    StderrCallback,
+};
+pub use types::role::AgentRole;
+pub use types::transport::{
+    ContainerMount, ContainerTransportConfig, HttpTransportConfig, Priority, ReconnectPolicy,
+    SshTransportConfig, TransportConfig,
+};
 pub use types::permissions::{
     CanUseToolCallback, PermissionBehavior, PermissionMode, PermissionRequest, PermissionResult,
     PermissionResultAllow, PermissionResultDeny, PermissionRuleValue, PermissionUpdate,
@@ -313,27 +368,37 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 /// Agent Tool trait implementations
 ///
 /// Provides MCP tools for spawning, managing, and interacting with Claude agent sessions.
+#[cfg(feature = "tools")]
 pub mod tools;
+#[cfg(feature = "tools")]
 pub use tools::ClaudeAgentTool;
 
 // Agent session management
+#[cfg(feature = "manager")]
 pub use manager::AgentManager;
+#[cfg(feature = "tools")]
 pub use registry::AgentRegistry;
 
 // Prompt input types
-pub use types::{PromptInput, PromptTemplateInput};
+#[cfg(feature = "tools")]
+pub use types::{PromptInput, PromptRenderer, PromptTemplateInput};
 
 // ============================================================================
 // EMBEDDED SERVER FUNCTION
 // ============================================================================
 
+#[cfg(feature = "server")]
 use std::pin::Pin;
+#[cfg(feature = "server")]
 use std::future::Future;
+#[cfg(feature = "server")]
 use std::sync::Arc;
 
 // Wrapper to implement ShutdownHook for AgentManager
+#[cfg(feature = "server")]
 struct AgentManagerWrapper(Arc<crate::AgentManager>);
 
+#[cfg(feature = "server")]
 impl kodegen_server_http::ShutdownHook for AgentManagerWrapper {
     fn shutdown(&self) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + '_>> {
         let manager = self.0.clone();
@@ -355,6 +420,7 @@ impl kodegen_server_http::ShutdownHook for AgentManagerWrapper {
 ///
 /// # Returns
 /// ServerHandle for graceful shutdown, or error if startup fails
+#[cfg(feature = "server")]
 pub async fn start_server(
     addr: std::net::SocketAddr,
     tls_cert: Option<std::path::PathBuf>,
@@ -385,6 +451,7 @@ pub async fn start_server(
 ///
 /// # Returns
 /// ServerHandle for graceful shutdown, or error if startup fails
+#[cfg(feature = "server")]
 pub async fn start_server_with_listener(
     listener: tokio::net::TcpListener,
     tls_config: Option<(std::path::PathBuf, std::path::PathBuf)>,
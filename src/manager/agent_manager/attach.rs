@@ -0,0 +1,144 @@
+//! Terminal attach
+//!
+//! Lets a developer step into a running session: lines typed on the terminal
+//! go to the agent as user messages and the agent's replies are printed,
+//! while the orchestrator's `send_message` and `interrupt_and_send` are
+//! rejected. Detaching hands control back to the manager; the session keeps
+//! running throughout and its transcript records the whole exchange.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, oneshot};
+
+use crate::error::{ClaudeError, Result};
+
+use super::super::commands::SessionCommand;
+use super::super::helpers::render_for_terminal;
+use super::core::AgentManager;
+
+/// Input line that ends an attach
+pub const DETACH_COMMAND: &str = "/detach";
+
+/// Clears a session's attached flag when the attach ends, however it ends
+struct AttachGuard(Arc<AtomicBool>);
+
+impl Drop for AttachGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl AgentManager {
+    /// Attach `input` and `output` to an active session
+    ///
+    /// Each non-empty line read from `input` is sent to the agent as a user
+    /// message, and assistant text, tool calls and turn ends are written to
+    /// `output` as they arrive. Returns (detaching) when `input` reaches end
+    /// of file, a [`DETACH_COMMAND`] line is read, or the session ends.
+    /// Lines are subject to the `max_turns` and group budget checks of
+    /// [`send_message`](Self::send_message).
+    ///
+    /// # Errors
+    /// Returns `SessionNotFound` or `SessionComplete` if the session is not
+    /// active or reaches `max_turns`, `GroupBudgetExceeded` if a line is
+    /// typed once its group has spent its budget, `SessionAttached` if
+    /// another terminal is attached, or an I/O error from `input` or `output`
+    pub async fn attach<R, W>(&self, session_id: &str, input: R, mut output: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let (command_tx, mut messages, _guard) = {
+            let active = self.active_sessions.lock().await;
+            let session = active
+                .get(session_id)
+                .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+
+            if !session.is_active() || *session.is_complete.lock().await {
+                return Err(ClaudeError::SessionComplete(session_id.to_string()));
+            }
+            if session.attached.swap(true, Ordering::AcqRel) {
+                return Err(ClaudeError::SessionAttached(session_id.to_string()));
+            }
+
+            (
+                session.command_tx.clone(),
+                session.message_tx.subscribe(),
+                AttachGuard(Arc::clone(&session.attached)),
+            )
+        };
+
+        let mut lines = input.lines();
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        break;
+                    };
+                    let line = line.trim_end();
+                    if line == DETACH_COMMAND {
+                        break;
+                    }
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    self.check_can_prompt(session_id).await?;
+
+                    let (response_tx, response_rx) = oneshot::channel();
+                    let cmd = SessionCommand::SendMessage {
+                        prompt: line.to_string(),
+                        response_tx,
+                    };
+                    command_tx
+                        .send(cmd)
+                        .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))?;
+                    response_rx
+                        .await
+                        .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))??;
+                }
+                message = messages.recv() => {
+                    match message {
+                        Ok(message) => {
+                            if let Some(text) = render_for_terminal(&message) {
+                                output.write_all(text.as_bytes()).await?;
+                                output.flush().await?;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            let notice = format!("[{skipped} messages skipped]\n");
+                            output.write_all(notice.as_bytes()).await?;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Attach the process's stdin and stdout to an active session
+    ///
+    /// See [`attach`](Self::attach); type [`DETACH_COMMAND`] to hand control
+    /// back to the manager.
+    ///
+    /// # Errors
+    /// Same as [`attach`](Self::attach)
+    pub async fn attach_terminal(&self, session_id: &str) -> Result<()> {
+        let stdin = BufReader::new(tokio::io::stdin());
+        self.attach(session_id, stdin, tokio::io::stdout()).await
+    }
+
+    /// Whether a terminal is currently attached to a session
+    ///
+    /// # Errors
+    /// Returns `SessionNotFound` if no active session has this ID
+    pub async fn is_attached(&self, session_id: &str) -> Result<bool> {
+        let active = self.active_sessions.lock().await;
+        let session = active
+            .get(session_id)
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+        Ok(session.attached.load(Ordering::Acquire))
+    }
+}
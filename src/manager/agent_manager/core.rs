@@ -2,22 +2,30 @@
 //!
 //! Provides the main `AgentManager` struct with initialization, cleanup, and shutdown.
 
-use chrono::Utc;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
 use std::time::Duration;
+use futures::future::join_all;
 use tokio::sync::Mutex;
 
 use crate::error::Result;
+use crate::transport::TransportPool;
+use crate::types::identifiers::{IdGenerator, RandomIdGenerator};
 
-use super::super::session::{AgentSessionInfo, CompletedAgentSession};
+use super::super::background::DEFAULT_BUFFER_SIZE;
+use super::super::clock::{Clock, SystemClock};
+use super::super::projects::claude_config_dir;
+use super::super::session::{AgentSessionInfo, CompletedAgentSession, GroupState};
+use super::reaper::spawn_reaper;
 
 // ============================================================================
 // CONSTANTS
 // ============================================================================
 
 /// Time threshold for considering an agent "working" (2 seconds)
-pub(crate) const WORKING_THRESHOLD_MS: u64 = 2000;
+const WORKING_THRESHOLD_MS: u64 = 2000;
 
 /// Retention time for completed sessions before cleanup (1 minute)
 const COMPLETED_RETENTION_MS: u64 = 60000;
@@ -25,6 +33,18 @@ const COMPLETED_RETENTION_MS: u64 = 60000;
 /// Interval for cleanup task execution (1 minute)
 const CLEANUP_INTERVAL_SECS: u64 = 60;
 
+/// Longest `shutdown` waits for active sessions to terminate (15 seconds)
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Interval between sweeps of the orphan process reaper (30 seconds)
+const REAPER_INTERVAL_SECS: u64 = 30;
+
+/// Default rendering for operator notes; `{note}` is replaced by the note text
+const DEFAULT_SYSTEM_NOTE_FORMAT: &str = "[Operator guidance]\n{note}";
+
+/// Default model for grading agents spawned by `grade_output`
+const DEFAULT_GRADER_MODEL: &str = "haiku";
+
 // ============================================================================
 // AGENT MANAGER CORE
 // ============================================================================
@@ -36,16 +56,40 @@ const CLEANUP_INTERVAL_SECS: u64 = 60;
 /// - Message buffering with circular buffers
 /// - Working status detection
 /// - Automatic cleanup of completed sessions
+/// - Killing orphaned CLI processes
+/// - Session groups with shared budgets
 pub struct AgentManager {
     pub(in crate::manager) active_sessions: Arc<Mutex<HashMap<String, AgentSessionInfo>>>,
     pub(in crate::manager) completed_sessions: Arc<Mutex<HashMap<String, CompletedAgentSession>>>,
+    pub(in crate::manager) groups: Arc<Mutex<HashMap<String, GroupState>>>,
     cleanup_handle: Option<tokio::task::JoinHandle<()>>,
+    reaper_handle: Option<tokio::task::JoinHandle<()>>,
+    pub(in crate::manager) orphans_reaped: Arc<AtomicU64>,
+    pub(in crate::manager) system_note_format: String,
+    pub(in crate::manager) grader_model: String,
+    pub(in crate::manager) clock: Arc<dyn Clock>,
+    pub(in crate::manager) ids: Arc<dyn IdGenerator>,
+    pub(in crate::manager) pools: Vec<TransportPool>,
+    pub(in crate::manager) buffer_size: usize,
+    pub(in crate::manager) cli_path: Option<PathBuf>,
+    pub(in crate::manager) claude_dir: Option<PathBuf>,
 }
 
 impl AgentManager {
     /// Create a new `AgentManager` with background cleanup task
     #[must_use]
     pub fn new() -> Self {
+        Self::new_with_clock(Arc::new(SystemClock))
+    }
+
+    /// Create a new `AgentManager` that reads the time from `clock`
+    ///
+    /// Working detection, runtimes, transcript timestamps and retention of
+    /// completed sessions all follow the clock; with
+    /// [`TokioClock`](crate::manager::TokioClock) and `tokio::time::pause()`
+    /// they can be tested without waiting in real time.
+    #[must_use]
+    pub fn new_with_clock(clock: Arc<dyn Clock>) -> Self {
         let active: Arc<Mutex<HashMap<String, AgentSessionInfo>>> =
             Arc::new(Mutex::new(HashMap::new()));
         let completed: Arc<Mutex<HashMap<String, CompletedAgentSession>>> =
@@ -53,12 +97,13 @@ impl AgentManager {
 
         // Spawn cleanup background task
         let completed_clone = Arc::clone(&completed);
+        let cleanup_clock = Arc::clone(&clock);
         let cleanup_handle = tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_secs(CLEANUP_INTERVAL_SECS)).await;
 
                 let mut sessions = completed_clone.lock().await;
-                let now = Utc::now();
+                let now = cleanup_clock.utc_now();
 
                 // Remove sessions older than retention period
                 sessions.retain(|_id, session| {
@@ -70,12 +115,161 @@ impl AgentManager {
             }
         });
 
+        let orphans_reaped = Arc::new(AtomicU64::new(0));
+        let reaper_handle = spawn_reaper(
+            Duration::from_secs(REAPER_INTERVAL_SECS),
+            Arc::clone(&orphans_reaped),
+        );
+
         Self {
             active_sessions: active,
             completed_sessions: completed,
+            groups: Arc::new(Mutex::new(HashMap::new())),
             cleanup_handle: Some(cleanup_handle),
+            reaper_handle: Some(reaper_handle),
+            orphans_reaped,
+            system_note_format: DEFAULT_SYSTEM_NOTE_FORMAT.to_string(),
+            grader_model: DEFAULT_GRADER_MODEL.to_string(),
+            clock,
+            ids: Arc::new(RandomIdGenerator::new()),
+            pools: Vec::new(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            cli_path: None,
+            claude_dir: None,
+        }
+    }
+
+    /// Set how operator notes from `inject_system_note` are rendered
+    ///
+    /// `{note}` in the format is replaced by the note text; if the format has
+    /// no placeholder it is used as a prefix.
+    #[must_use]
+    pub fn with_system_note_format(mut self, format: impl Into<String>) -> Self {
+        self.system_note_format = format.into();
+        self
+    }
+
+    /// Set the model used by grading agents spawned by `grade_output`
+    #[must_use]
+    pub fn with_grader_model(mut self, model: impl Into<String>) -> Self {
+        self.grader_model = model.into();
+        self
+    }
+
+    /// Set the source of session IDs and of the sessions' control request IDs
+    ///
+    /// The default generates UUIDv4 session IDs; a
+    /// [`SequentialIdGenerator`](crate::types::SequentialIdGenerator) gives
+    /// stable IDs for snapshot tests and replays.
+    #[must_use]
+    pub fn with_id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Set how many messages each session's transcript buffer keeps
+    ///
+    /// Once full, the oldest messages are dropped and a
+    /// `transcript.truncated` event is logged. Defaults to 1000; raise it for
+    /// long-running agents whose early context must stay readable. A
+    /// [`SpawnSessionRequest::buffer_size`](crate::manager::SpawnSessionRequest::buffer_size)
+    /// overrides it per session. Values below 1 are treated as 1.
+    #[must_use]
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size.max(1);
+        self
+    }
+
+    /// Start sessions on pre-started CLI processes from `pool`
+    ///
+    /// Local sessions whose options match the pool (see
+    /// [`SpawnSessionRequest::options`](crate::manager::SpawnSessionRequest::options))
+    /// take a warm process instead of starting the CLI; others start as usual.
+    /// Several pools may be added for different kinds of sessions. The pool
+    /// is warmed in the background.
+    #[must_use]
+    pub fn with_transport_pool(mut self, pool: TransportPool) -> Self {
+        let warming = pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = warming.warm().await {
+                log::warn!("Failed to warm transport pool: {e}");
+            }
+        });
+        self.pools.push(pool);
+        self
+    }
+
+    /// Start local sessions with this Claude Code CLI instead of searching
+    /// `PATH` for `claude`
+    #[must_use]
+    pub fn with_cli_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cli_path = Some(path.into());
+        self
+    }
+
+    /// Keep the Claude Code state of local sessions in `dir`
+    ///
+    /// The directory is passed to their CLI as `CLAUDE_CONFIG_DIR` (so it
+    /// must hold a login, or the sessions need an API key) and receives the
+    /// transcripts written by
+    /// [`export_to_claude_projects`](Self::export_to_claude_projects) and
+    /// [`spawn_with_history`](Self::spawn_with_history). Defaults to
+    /// `$CLAUDE_CONFIG_DIR`, else `~/.claude`.
+    #[must_use]
+    pub fn with_claude_config_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.claude_dir = Some(dir.into());
+        self
+    }
+
+    /// Set how often the reaper looks for orphaned CLI processes
+    ///
+    /// An orphan is a CLI process whose session no longer exists, e.g. one
+    /// left running by a manager dropped without [`shutdown`](Self::shutdown).
+    /// The reaper kills orphans of every manager in the process.
+    #[must_use]
+    pub fn with_reaper_interval(mut self, interval: Duration) -> Self {
+        if let Some(handle) = self.reaper_handle.take() {
+            handle.abort();
+        }
+        self.reaper_handle = Some(spawn_reaper(interval, Arc::clone(&self.orphans_reaped)));
+        self
+    }
+
+    /// Claude Code config directory of local sessions
+    pub(in crate::manager) fn claude_dir(&self) -> Option<PathBuf> {
+        self.claude_dir.clone().or_else(claude_config_dir)
+    }
+
+    /// Render an operator note using the configured format
+    pub(in crate::manager) fn format_system_note(&self, note: &str) -> String {
+        if self.system_note_format.contains("{note}") {
+            self.system_note_format.replace("{note}", note)
+        } else {
+            format!("{}{note}", self.system_note_format)
         }
     }
+
+    /// Whether an active session is working
+    ///
+    /// A session is working while it produces output, and also while a turn
+    /// is in progress on a CLI that answers health pings (e.g. during a
+    /// long tool run). A silent CLI that stops answering pings is not.
+    pub(in crate::manager) async fn session_working(&self, session: &AgentSessionInfo) -> bool {
+        if *session.is_complete.lock().await {
+            return false;
+        }
+        let last_msg_time = *session.last_message_at.lock().await;
+        let elapsed_ms = self.clock.elapsed(last_msg_time).as_millis() as u64;
+        if elapsed_ms < WORKING_THRESHOLD_MS {
+            return true;
+        }
+
+        if !*session.turn_active.borrow() {
+            return false;
+        }
+        let health = session.health.snapshot().await;
+        health.last_rtt.is_some() && health.consecutive_failures == 0
+    }
 }
 
 impl Default for AgentManager {
@@ -89,29 +283,46 @@ impl Drop for AgentManager {
         if let Some(handle) = self.cleanup_handle.take() {
             handle.abort();
         }
+        if let Some(handle) = self.reaper_handle.take() {
+            handle.abort();
+        }
     }
 }
 
 impl AgentManager {
     /// Gracefully shutdown the AgentManager
     ///
-    /// Terminates all active sessions and cancels the cleanup task.
+    /// Terminates all active sessions, stops idle pooled CLI processes and
+    /// cancels the cleanup task.
     /// Should be called before dropping to ensure clean shutdown.
+    ///
+    /// Sessions are terminated concurrently, so their in-flight turns drain
+    /// in parallel, and shutdown waits at most `SHUTDOWN_TIMEOUT` for them;
+    /// terminations still running then finish in the background. Sessions
+    /// already being terminated elsewhere are waited for rather than closed twice.
     pub async fn shutdown(&self) -> Result<()> {
         log::info!("Shutting down AgentManager...");
 
-        // Get all active session IDs
         let session_ids: Vec<String> = {
             let sessions = self.active_sessions.lock().await;
             sessions.keys().cloned().collect()
         };
 
         // Terminate all active sessions
-        for session_id in session_ids {
+        let terminates = join_all(session_ids.iter().map(|session_id| async move {
             log::debug!("Terminating session: {}", session_id);
-            if let Err(e) = self.terminate_session(&session_id).await {
+            if let Err(e) = self.terminate_session(session_id).await {
                 log::warn!("Failed to terminate session {}: {}", session_id, e);
             }
+        }));
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, terminates).await.is_err() {
+            log::warn!(
+                "Sessions still terminating after {SHUTDOWN_TIMEOUT:?}, not waiting for them"
+            );
+        }
+
+        for pool in &self.pools {
+            pool.close().await;
         }
 
         log::info!("AgentManager shutdown complete");
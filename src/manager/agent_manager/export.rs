@@ -0,0 +1,62 @@
+//! Session export
+//!
+//! Converts a session's transcript into Claude Code's own conversation
+//! format, so a headless run can be continued interactively.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{ClaudeError, Result};
+use crate::types::agent::SerializedMessage;
+
+use super::super::projects::ProjectsTranscript;
+use super::core::AgentManager;
+
+impl AgentManager {
+    /// Convert a session's transcript to Claude Code's "projects" format
+    ///
+    /// Works for active and completed sessions; only the messages still in
+    /// the session's buffer are exported. `cwd` overrides the working
+    /// directory recorded by the CLI.
+    pub async fn export_projects_transcript(
+        &self,
+        session_id: &str,
+        cwd: Option<&Path>,
+    ) -> Result<ProjectsTranscript> {
+        let messages = self.transcript(session_id).await?;
+        ProjectsTranscript::from_messages(&messages, cwd)
+    }
+
+    /// Export a session into Claude Code's config directory
+    ///
+    /// Writes `~/.claude/projects/<project>/<session-id>.jsonl` (honoring
+    /// `CLAUDE_CONFIG_DIR` and
+    /// [`with_claude_config_dir`](Self::with_claude_config_dir)) and returns
+    /// the transcript, whose `session_id` can be passed to `claude --resume`.
+    pub async fn export_to_claude_projects(
+        &self,
+        session_id: &str,
+        cwd: Option<&Path>,
+    ) -> Result<(ProjectsTranscript, PathBuf)> {
+        let claude_dir = self.claude_dir().ok_or_else(|| {
+            ClaudeError::invalid_config("Cannot locate the Claude config directory")
+        })?;
+        let transcript = self.export_projects_transcript(session_id, cwd).await?;
+        let path = transcript.write(&claude_dir)?;
+        Ok((transcript, path))
+    }
+
+    /// Snapshot of an active or completed session's message buffer
+    async fn transcript(&self, session_id: &str) -> Result<Vec<SerializedMessage>> {
+        let active = self.active_sessions.lock().await;
+        if let Some(session) = active.get(session_id) {
+            return Ok(session.messages.lock().await.iter().cloned().collect());
+        }
+        drop(active);
+
+        let completed = self.completed_sessions.lock().await;
+        completed
+            .get(session_id)
+            .map(|session| session.messages.iter().cloned().collect())
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))
+    }
+}
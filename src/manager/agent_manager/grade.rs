@@ -0,0 +1,116 @@
+//! Result grading
+//!
+//! Scores a session's final result against a rubric using a short-lived
+//! grading agent, as a building block for automatic retry/accept loops.
+
+use std::time::Duration;
+
+use crate::error::{ClaudeError, Result};
+use crate::query::parse_structured_output;
+use crate::types::agent::SessionGrade;
+
+use super::super::helpers::extract_final_result;
+use super::core::AgentManager;
+use super::spawn::SpawnSessionRequest;
+
+/// Maximum time to wait for the grading agent to answer
+const GRADE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// System prompt for grading agents
+const GRADER_SYSTEM_PROMPT: &str = "You grade the output of another agent against a \
+rubric. Do not use any tools. Reply with a single JSON object and nothing else: \
+{\"score\": <integer 0-100>, \"rationale\": \"<one or two sentences>\"}";
+
+impl AgentManager {
+    /// Grade a session's final result against a rubric
+    ///
+    /// Spawns a single-turn grading agent on the configured grader model, stores
+    /// the resulting grade on the session (visible in `get_session_info` and
+    /// `list_sessions`) and returns it. The grading session is removed once it
+    /// answers.
+    pub async fn grade_output(&self, session_id: &str, rubric: &str) -> Result<SessionGrade> {
+        let output = self.final_result(session_id).await?;
+
+        let request = SpawnSessionRequest {
+            prompt: format!("## Rubric\n{rubric}\n\n## Output to grade\n{output}"),
+            system_prompt: Some(GRADER_SYSTEM_PROMPT.to_string()),
+            max_turns: 1,
+            model: Some(self.grader_model.clone()),
+            label: format!("grader:{session_id}"),
+            ..Default::default()
+        };
+        let grader_id = self.spawn_session(request).await?;
+
+        let answer = self.wait_for_result(&grader_id, GRADE_TIMEOUT).await;
+        let _ = self.terminate_session(&grader_id).await;
+        self.completed_sessions.lock().await.remove(&grader_id);
+
+        let (score, rationale) = parse_grade(&answer?)?;
+        let grade = SessionGrade {
+            score,
+            rationale,
+            rubric: rubric.to_string(),
+            graded_at: self.clock.utc_now(),
+        };
+        self.store_grade(session_id, grade.clone()).await?;
+
+        Ok(grade)
+    }
+
+    /// Final result text of an active or completed session
+    async fn final_result(&self, session_id: &str) -> Result<String> {
+        let active = self.active_sessions.lock().await;
+        let result = if let Some(session) = active.get(session_id) {
+            extract_final_result(&*session.messages.lock().await)
+        } else {
+            drop(active);
+            let completed = self.completed_sessions.lock().await;
+            let session = completed
+                .get(session_id)
+                .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+            extract_final_result(&session.messages)
+        };
+
+        result.ok_or_else(|| {
+            ClaudeError::invalid_agent_config(format!(
+                "Session {session_id} has no final result to grade"
+            ))
+        })
+    }
+
+    /// Attach a grade to an active or completed session
+    async fn store_grade(&self, session_id: &str, grade: SessionGrade) -> Result<()> {
+        let active = self.active_sessions.lock().await;
+        if let Some(session) = active.get(session_id) {
+            *session.grade.lock().await = Some(grade);
+            return Ok(());
+        }
+        drop(active);
+
+        let mut completed = self.completed_sessions.lock().await;
+        let session = completed
+            .get_mut(session_id)
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+        session.grade = Some(grade);
+        Ok(())
+    }
+}
+
+/// The grader's JSON answer
+#[derive(serde::Deserialize)]
+struct Grade {
+    score: f64,
+    #[serde(default)]
+    rationale: String,
+}
+
+/// Parse the grader's JSON answer into a score and rationale
+///
+/// Tolerates prose or code fences around the JSON object, as
+/// [`parse_structured_output`] does.
+fn parse_grade(answer: &str) -> Result<(u8, String)> {
+    let grade: Grade = parse_structured_output(answer).map_err(|_| {
+        ClaudeError::json_decode(format!("Grader returned no valid grade: {answer}"))
+    })?;
+    Ok((grade.score.round().clamp(0.0, 100.0) as u8, grade.rationale))
+}
@@ -0,0 +1,301 @@
+//! Session groups
+//!
+//! Groups related sessions (a "team of agents on one task") under a shared
+//! budget and labels, with optional sharing of siblings' final results.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::{ClaudeError, Result};
+use crate::mcp::Blackboard;
+use crate::types::agent::{GroupCost, ListSessionsResponse, SessionGroup, TerminateResponse};
+
+use super::super::helpers::{extract_final_result, extract_total_cost};
+use super::super::session::GroupState;
+use super::core::AgentManager;
+
+/// Heading placed before the sibling results digest
+const DIGEST_HEADING: &str = "[Results from other agents in this group]";
+
+/// Interval between checks for a session's result in `wait_for_result`
+const RESULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+impl AgentManager {
+    /// Create a session group
+    ///
+    /// Spawn members by setting `group` on the `SpawnSessionRequest`. Any
+    /// members listed on `group` are ignored.
+    ///
+    /// Returns the group ID.
+    pub async fn create_group(&self, mut group: SessionGroup) -> Result<String> {
+        if let Some(budget) = group.budget_usd
+            && !(budget.is_finite() && budget >= 0.0)
+        {
+            return Err(ClaudeError::invalid_agent_config(format!(
+                "Group budget must be a non-negative amount, got {budget}"
+            )));
+        }
+
+        let mut groups = self.groups.lock().await;
+        if groups.contains_key(&group.group_id) {
+            return Err(ClaudeError::invalid_agent_config(format!(
+                "Session group already exists: {}",
+                group.group_id
+            )));
+        }
+
+        group.members.clear();
+        let group_id = group.group_id.clone();
+        groups.insert(
+            group_id.clone(),
+            GroupState {
+                group,
+                final_costs: HashMap::new(),
+                blackboard: Blackboard::new(),
+            },
+        );
+        Ok(group_id)
+    }
+
+    /// Get a session group, including its current members
+    pub async fn get_group(&self, group_id: &str) -> Result<SessionGroup> {
+        self.groups
+            .lock()
+            .await
+            .get(group_id)
+            .map(|state| state.group.clone())
+            .ok_or_else(|| ClaudeError::group_not_found(group_id))
+    }
+
+    /// Get the blackboard shared by a group's members
+    ///
+    /// Members read and write it through the `blackboard` MCP server added to
+    /// every session spawned into the group; the orchestrator can seed or
+    /// inspect it through the returned handle.
+    pub async fn group_blackboard(&self, group_id: &str) -> Result<Blackboard> {
+        self.groups
+            .lock()
+            .await
+            .get(group_id)
+            .map(|state| state.blackboard.clone())
+            .ok_or_else(|| ClaudeError::group_not_found(group_id))
+    }
+
+    /// List all session groups, oldest first
+    pub async fn list_groups(&self) -> Vec<SessionGroup> {
+        let mut groups: Vec<SessionGroup> = self
+            .groups
+            .lock()
+            .await
+            .values()
+            .map(|state| state.group.clone())
+            .collect();
+        groups.sort_by_key(|group| group.created_at);
+        groups
+    }
+
+    /// List the sessions of a group
+    ///
+    /// Same ordering and fields as `list_sessions`, restricted to the group's
+    /// members (active and completed).
+    pub async fn list_group_sessions(
+        &self,
+        group_id: &str,
+        last_output_lines: usize,
+    ) -> Result<ListSessionsResponse> {
+        let members = self.get_group(group_id).await?.members;
+        let mut response = self.list_sessions(true, last_output_lines).await?;
+
+        response
+            .agents
+            .retain(|agent| members.contains(&agent.session_id));
+        response.total_active = response
+            .agents
+            .iter()
+            .filter(|agent| agent.completion_time.is_none())
+            .count();
+        response.total_completed = response.agents.len() - response.total_active;
+        Ok(response)
+    }
+
+    /// Total spend of a group and each of its members
+    ///
+    /// Costs come from the `total_cost_usd` of each member's latest result.
+    pub async fn group_cost(&self, group_id: &str) -> Result<GroupCost> {
+        let (members, final_costs, budget_usd) = {
+            let groups = self.groups.lock().await;
+            let state = groups
+                .get(group_id)
+                .ok_or_else(|| ClaudeError::group_not_found(group_id))?;
+            (
+                state.group.members.clone(),
+                state.final_costs.clone(),
+                state.group.budget_usd,
+            )
+        };
+
+        let mut sessions = HashMap::new();
+        for session_id in members {
+            let cost = match final_costs.get(&session_id) {
+                Some(cost) => *cost,
+                None => self.session_cost(&session_id).await,
+            };
+            sessions.insert(session_id, cost);
+        }
+
+        let total_cost_usd: f64 = sessions.values().sum();
+        Ok(GroupCost {
+            group_id: group_id.to_string(),
+            total_cost_usd,
+            budget_usd,
+            remaining_usd: budget_usd.map(|budget| (budget - total_cost_usd).max(0.0)),
+            sessions,
+        })
+    }
+
+    /// Terminate every member of a group
+    ///
+    /// Members are terminated in spawn order. The group itself is kept so its
+    /// cost and membership can still be read.
+    pub async fn terminate_group(&self, group_id: &str) -> Result<Vec<TerminateResponse>> {
+        let members = self.get_group(group_id).await?.members;
+
+        let mut responses = Vec::with_capacity(members.len());
+        for session_id in members {
+            match self.terminate_session(&session_id).await {
+                Ok(response) => responses.push(response),
+                // Completed members may already have been cleaned up
+                Err(ClaudeError::SessionNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(responses)
+    }
+
+    /// Fail if a group has spent its budget
+    pub(in crate::manager) async fn check_group_budget(&self, group_id: &str) -> Result<()> {
+        let cost = self.group_cost(group_id).await?;
+        match cost.budget_usd {
+            Some(budget) if cost.total_cost_usd >= budget => {
+                Err(ClaudeError::group_budget_exceeded(group_id))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail if the group of an active session has spent its budget
+    ///
+    /// A refusal is recorded in the session's event log.
+    pub(in crate::manager) async fn check_session_budget(&self, session_id: &str) -> Result<()> {
+        let session = self
+            .active_sessions
+            .lock()
+            .await
+            .get(session_id)
+            .map(|session| (session.group_id.clone(), session.events.clone()));
+
+        let Some((Some(group_id), events)) = session else {
+            return Ok(());
+        };
+        let result = self.check_group_budget(&group_id).await;
+        if let Err(ref e) = result {
+            events.warn(
+                "budget.exceeded",
+                format!("Message refused: {e}"),
+                serde_json::json!({ "group": group_id }),
+            );
+        }
+        result
+    }
+
+    /// Add a spawned session to its group
+    pub(in crate::manager) async fn add_group_member(&self, group_id: &str, session_id: &str) {
+        if let Some(state) = self.groups.lock().await.get_mut(group_id) {
+            state.group.members.push(session_id.to_string());
+        }
+    }
+
+    /// Digest of the final results produced by a group's members so far
+    ///
+    /// Returns `None` when no member has produced a result yet.
+    pub(in crate::manager) async fn group_context_digest(&self, group_id: &str) -> Option<String> {
+        let members = self.get_group(group_id).await.ok()?.members;
+
+        let mut digest = String::new();
+        for session_id in members {
+            let Some((label, result)) = self.session_final_result(&session_id).await else {
+                continue;
+            };
+            digest.push_str(&format!("\n## {label} ({session_id})\n\n{result}\n"));
+        }
+
+        (!digest.is_empty()).then(|| format!("{DIGEST_HEADING}\n{digest}"))
+    }
+
+    /// Label and latest result text of an active or completed session
+    pub(super) async fn session_final_result(&self, session_id: &str) -> Option<(String, String)> {
+        let active = self.active_sessions.lock().await;
+        if let Some(session) = active.get(session_id) {
+            let label = session.label.clone();
+            let messages = session.messages.lock().await;
+            return extract_final_result(&messages).map(|result| (label, result));
+        }
+        drop(active);
+
+        let completed = self.completed_sessions.lock().await;
+        let session = completed.get(session_id)?;
+        extract_final_result(&session.messages).map(|result| (session.label.clone(), result))
+    }
+
+    /// Wait until a session produces a result, stops, or `timeout` passes
+    ///
+    /// Used by swarms and result grading, whose agents answer with a result.
+    pub(super) async fn wait_for_result(
+        &self,
+        session_id: &str,
+        timeout: Duration,
+    ) -> Result<String> {
+        let started = self.clock.now();
+        loop {
+            if let Some((_, result)) = self.session_final_result(session_id).await {
+                return Ok(result);
+            }
+
+            let running = match self.active_sessions.lock().await.get(session_id) {
+                Some(session) => !*session.is_complete.lock().await,
+                None => false,
+            };
+            if !running {
+                // The result may have arrived just before the session stopped
+                return self
+                    .session_final_result(session_id)
+                    .await
+                    .map(|(_, result)| result)
+                    .ok_or_else(|| ClaudeError::SessionComplete(session_id.to_string()));
+            }
+
+            if self.clock.elapsed(started) >= timeout {
+                return Err(ClaudeError::timeout(format!(
+                    "Session {session_id} did not answer within {}s",
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(RESULT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Latest reported cost of an active or completed session
+    async fn session_cost(&self, session_id: &str) -> f64 {
+        let active = self.active_sessions.lock().await;
+        if let Some(session) = active.get(session_id) {
+            return extract_total_cost(&*session.messages.lock().await);
+        }
+        drop(active);
+
+        self.completed_sessions
+            .lock()
+            .await
+            .get(session_id)
+            .map_or(0.0, |session| extract_total_cost(&session.messages))
+    }
+}
@@ -0,0 +1,64 @@
+//! Sessions seeded with a prior transcript
+//!
+//! Lets a new session pick up a conversation that happened elsewhere: in
+//! another manager, another tool, or an earlier run of this process.
+
+use crate::error::Result;
+use crate::types::agent::SerializedMessage;
+use crate::types::transport::TransportConfig;
+
+use super::super::helpers::render_history;
+use super::super::projects::ProjectsTranscript;
+use super::core::AgentManager;
+use super::spawn::SpawnSessionRequest;
+
+impl AgentManager {
+    /// Spawn a session that continues the conversation in `history`
+    ///
+    /// Local subprocess sessions resume the conversation for real: `history`
+    /// is written to Claude Code's config directory as a new transcript and
+    /// the CLI is started with `--resume`. Sessions on other transports (or
+    /// without a config directory) get the user and assistant turns replayed
+    /// as text ahead of the request's prompt.
+    ///
+    /// The written transcript is left in place when the session ends, like
+    /// the conversations the CLI records itself, so it also shows up in
+    /// `claude --resume`. Use
+    /// [`with_claude_config_dir`](Self::with_claude_config_dir) to keep
+    /// managed conversations out of the user's `~/.claude`.
+    ///
+    /// `history` uses the format returned by
+    /// [`get_output`](Self::get_output), so one session's output can seed
+    /// another session directly.
+    ///
+    /// # Errors
+    /// Returns error if the transcript cannot be written or the session
+    /// cannot be spawned
+    pub async fn spawn_with_history(
+        &self,
+        history: &[SerializedMessage],
+        mut request: SpawnSessionRequest,
+    ) -> Result<String> {
+        if matches!(request.transport, TransportConfig::Subprocess)
+            && request.resume.is_none()
+            && let Some(claude_dir) = self.claude_dir()
+        {
+            // The CLI files the transcript under its working directory as
+            // `getcwd` reports it: absolute, with symlinks resolved
+            let cwd = match &request.cwd {
+                Some(cwd) => std::fs::canonicalize(cwd)?,
+                None => std::env::current_dir()?,
+            };
+            let transcript = ProjectsTranscript::from_messages(history, Some(&cwd))?
+                .with_session_id(uuid::Uuid::new_v4().to_string());
+            if !transcript.entries.is_empty() {
+                transcript.write(&claude_dir)?;
+                request.resume = Some(transcript.session_id);
+            }
+        } else if let Some(replay) = render_history(history) {
+            request.prompt = format!("{replay}\n---\n\n{}", request.prompt);
+        }
+
+        self.spawn_session(request).await
+    }
+}
@@ -1,12 +1,15 @@
 //! Session information and status queries
 //!
-//! Provides methods for querying session info and working status.
+//! Provides methods for querying session info, working status and events.
+
+use std::sync::atomic::Ordering;
 
 use crate::error::{ClaudeError, Result};
-use crate::types::agent::AgentInfo;
+use crate::transport::MetricsRecorder;
+use crate::types::agent::{AgentInfo, SessionEvent, SessionHealth};
 
-use super::super::helpers::extract_last_output_lines;
-use super::core::{AgentManager, WORKING_THRESHOLD_MS};
+use super::super::helpers::{extract_last_output_lines, transcript_head};
+use super::core::AgentManager;
 
 impl AgentManager {
     /// Get information about a specific agent session
@@ -17,7 +20,7 @@ impl AgentManager {
         // Check active sessions first
         let active = self.active_sessions.lock().await;
         if let Some(session) = active.get(session_id) {
-            let runtime_ms = session.created_at.elapsed().as_millis() as u64;
+            let runtime_ms = self.clock.elapsed(session.created_at).as_millis() as u64;
             let turn_count = *session.turn_count.lock().await;
             let is_complete = *session.is_complete.lock().await;
             let messages = session.messages.lock().await;
@@ -25,13 +28,7 @@ impl AgentManager {
             let last_output = extract_last_output_lines(&messages, 3);
 
             // Calculate working status
-            let working = if is_complete {
-                false
-            } else {
-                let last_msg_time = *session.last_message_at.lock().await;
-                let elapsed_ms = last_msg_time.elapsed().as_millis() as u64;
-                elapsed_ms < WORKING_THRESHOLD_MS
-            };
+            let working = self.session_working(session).await;
 
             return Ok(AgentInfo {
                 session_id: session.session_id.clone(),
@@ -44,6 +41,15 @@ impl AgentManager {
                 is_complete,
                 last_output,
                 completion_time: None,
+                grade: session.grade.lock().await.clone(),
+                transcript_head: transcript_head(&messages),
+                disk_usage_bytes: session
+                    .disk_usage
+                    .as_ref()
+                    .map(|usage| usage.load(Ordering::Relaxed)),
+                memory_bytes: session.memory.as_ref().map(|memory| memory.current()),
+                peak_memory_bytes: session.memory.as_ref().map(|memory| memory.peak()),
+                termination_reason: *session.termination_reason.lock().await,
             });
         }
         drop(active);
@@ -64,6 +70,12 @@ impl AgentManager {
                 is_complete: true,
                 last_output,
                 completion_time: Some(session.completed_at),
+                grade: session.grade.clone(),
+                transcript_head: transcript_head(&session.messages),
+                disk_usage_bytes: session.disk_usage_bytes,
+                memory_bytes: None,
+                peak_memory_bytes: session.peak_memory_bytes,
+                termination_reason: session.termination_reason,
             });
         }
 
@@ -72,21 +84,71 @@ impl AgentManager {
 
     /// Check if an agent session is actively working
     ///
-    /// Returns true if the agent has received a message within the working threshold
-    /// and the session is not complete.
+    /// Returns true if the session is not complete and has received a message
+    /// within the working threshold, or has a turn in progress on a CLI that
+    /// answers health pings (see `health_check_interval`).
     pub async fn is_working(&self, session_id: &str) -> Result<bool> {
         let active = self.active_sessions.lock().await;
         if let Some(session) = active.get(session_id) {
-            if *session.is_complete.lock().await {
-                return Ok(false);
+            Ok(self.session_working(session).await)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Get control channel health for an active agent session
+    ///
+    /// Reports ping round-trip latency, idle time and whether the CLI appears
+    /// wedged. Ping data is only collected when the session was spawned with a
+    /// `health_check_interval`; idle time is always tracked.
+    pub async fn get_session_health(&self, session_id: &str) -> Result<SessionHealth> {
+        let (health, metrics, memory) = {
+            let active = self.active_sessions.lock().await;
+            match active.get(session_id) {
+                Some(session) => (
+                    session.health.clone(),
+                    session.metrics.clone(),
+                    session.memory.clone(),
+                ),
+                None => {
+                    drop(active);
+                    if self.completed_sessions.lock().await.contains_key(session_id) {
+                        return Err(ClaudeError::SessionComplete(session_id.to_string()));
+                    }
+                    return Err(ClaudeError::SessionNotFound(session_id.to_string()));
+                }
             }
+        };
 
-            let last_msg_time = *session.last_message_at.lock().await;
-            let elapsed_ms = last_msg_time.elapsed().as_millis() as u64;
+        let snapshot = health.snapshot().await;
+        Ok(SessionHealth {
+            session_id: session_id.to_string(),
+            last_rtt_ms: snapshot.last_rtt.map(|rtt| rtt.as_millis() as u64),
+            idle_ms: snapshot.idle.as_millis() as u64,
+            consecutive_failures: snapshot.consecutive_failures,
+            pings_sent: snapshot.pings_sent,
+            pings_answered: snapshot.pings_answered,
+            wedged: snapshot.wedged,
+            transport: metrics.as_ref().map(MetricsRecorder::snapshot),
+            memory_bytes: memory.map(|memory| memory.current()),
+        })
+    }
 
-            Ok(elapsed_ms < WORKING_THRESHOLD_MS)
-        } else {
-            Ok(false)
+    /// Operational events the manager recorded for a session, oldest first
+    ///
+    /// Covers spawn, CLI restarts, transcript truncation, skipped output,
+    /// budget refusals, resource limits and termination; the transcript
+    /// itself is read with [`get_output`](Self::get_output). Available for
+    /// active and completed sessions.
+    pub async fn events(&self, session_id: &str) -> Result<Vec<SessionEvent>> {
+        if let Some(session) = self.active_sessions.lock().await.get(session_id) {
+            return Ok(session.events.snapshot());
         }
+        self.completed_sessions
+            .lock()
+            .await
+            .get(session_id)
+            .map(|session| session.events.clone())
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))
     }
 }
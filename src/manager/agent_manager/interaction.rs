@@ -2,27 +2,39 @@
 //!
 //! Handles sending messages to sessions and terminating sessions.
 
-use chrono::Utc;
-use tokio::sync::{oneshot, broadcast};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::sync::{Mutex, oneshot, broadcast};
 
 use crate::error::{ClaudeError, Result};
 use crate::types::agent::{TerminateResponse, SerializedMessage};
 
 use super::super::commands::SessionCommand;
-use super::super::session::CompletedAgentSession;
+use super::super::helpers::extract_total_cost;
+use super::super::clock::Clock;
+use super::super::session::{AgentSessionInfo, CompletedAgentSession, GroupState};
 use super::core::AgentManager;
 
+/// How long `terminate_session` lets an in-flight turn finish before closing
+const TERMINATE_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl AgentManager {
-    /// Send a follow-up message to an active agent session
+    /// Fail unless a session may take another prompt
     ///
-    /// Only works for active, non-completed sessions that haven't reached `max_turns`.
-    pub async fn send_message(&self, session_id: &str, prompt: &str) -> Result<()> {
+    /// The session must be active, below its `max_turns`, and in a group
+    /// (if any) within its budget. Applies to the orchestrator's prompts and
+    /// to lines typed on an attached terminal alike.
+    pub(in crate::manager) async fn check_can_prompt(&self, session_id: &str) -> Result<()> {
+        self.check_session_budget(session_id).await?;
+
         let active = self.active_sessions.lock().await;
         let session = active
             .get(session_id)
             .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
 
-        if *session.is_complete.lock().await {
+        if !session.is_active() || *session.is_complete.lock().await {
             return Err(ClaudeError::SessionComplete(session_id.to_string()));
         }
 
@@ -30,6 +42,26 @@ impl AgentManager {
             return Err(ClaudeError::SessionComplete(session_id.to_string()));
         }
 
+        Ok(())
+    }
+
+    /// Send a follow-up message to an active agent session
+    ///
+    /// Only works for active, non-completed sessions that haven't reached `max_turns`,
+    /// whose group (if any) is within its budget and that are not attached to a
+    /// terminal (see [`attach`](Self::attach)).
+    pub async fn send_message(&self, session_id: &str, prompt: &str) -> Result<()> {
+        self.check_can_prompt(session_id).await?;
+
+        let active = self.active_sessions.lock().await;
+        let session = active
+            .get(session_id)
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+
+        if session.attached.load(Ordering::Acquire) {
+            return Err(ClaudeError::SessionAttached(session_id.to_string()));
+        }
+
         let (response_tx, response_rx) = oneshot::channel();
         let cmd = SessionCommand::SendMessage {
             prompt: prompt.to_string(),
@@ -48,50 +80,132 @@ impl AgentManager {
         Ok(())
     }
 
-    /// Terminate an agent session gracefully
+    /// Interrupt an agent's current turn and redirect it with a new prompt
     ///
-    /// Closes the client connection, moves the session to completed state, and returns
-    /// final statistics. The session will be retained for `COMPLETED_RETENTION_MS` before cleanup.
-    pub async fn terminate_session(&self, session_id: &str) -> Result<TerminateResponse> {
-        let mut active = self.active_sessions.lock().await;
+    /// Waits for the interrupted turn to end before sending, so the new prompt
+    /// is not queued behind the abandoned work. Subject to the same checks as
+    /// `send_message`.
+    pub async fn interrupt_and_send(&self, session_id: &str, prompt: &str) -> Result<()> {
+        self.check_can_prompt(session_id).await?;
+
+        let active = self.active_sessions.lock().await;
         let session = active
-            .remove(session_id)
+            .get(session_id)
             .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
-        drop(active);
+
+        if session.attached.load(Ordering::Acquire) {
+            return Err(ClaudeError::SessionAttached(session_id.to_string()));
+        }
 
         let (response_tx, response_rx) = oneshot::channel();
-        let cmd = SessionCommand::Shutdown { response_tx };
+        let cmd = SessionCommand::InterruptAndSend {
+            prompt: prompt.to_string(),
+            response_tx,
+        };
 
-        if session.command_tx.send(cmd).is_ok() {
-            let _ = response_rx.await;
+        session
+            .command_tx
+            .send(cmd)
+            .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))?;
+        drop(active);
+
+        response_rx
+            .await
+            .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))??;
+
+        Ok(())
+    }
+
+    /// Inject operator guidance into an active agent session
+    ///
+    /// Sends the note as a user message rendered with the manager's system note
+    /// format so the agent can tell it apart from the orchestrator's prompts.
+    /// Notes skip the `max_turns` check of `send_message`, but the CLI counts
+    /// the turn a note starts like any other: it moves the session towards
+    /// its limit, and a session that reaches the limit is complete and takes
+    /// no more notes. Notes are recorded in the transcript with message type
+    /// `operator_note`.
+    pub async fn inject_system_note(&self, session_id: &str, note: &str) -> Result<()> {
+        let active = self.active_sessions.lock().await;
+        let session = active
+            .get(session_id)
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))?;
+
+        if !session.is_active() || *session.is_complete.lock().await {
+            return Err(ClaudeError::SessionComplete(session_id.to_string()));
         }
 
-        let runtime_ms = session.created_at.elapsed().as_millis() as u64;
-        let messages = session.messages.lock().await;
-        let total_messages = messages.len();
-        let final_turn_count = *session.turn_count.lock().await;
-
-        let completed = CompletedAgentSession {
-            session_id: session.session_id.clone(),
-            label: session.label.clone(),
-            messages: messages.clone(),
-            final_turn_count,
-            runtime_ms,
-            completed_at: Utc::now(),
+        let (response_tx, response_rx) = oneshot::channel();
+        let cmd = SessionCommand::InjectNote {
+            note: note.to_string(),
+            formatted: self.format_system_note(note),
+            response_tx,
+        };
+
+        session
+            .command_tx
+            .send(cmd)
+            .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))?;
+        drop(active);
+
+        response_rx
+            .await
+            .map_err(|_| ClaudeError::SessionComplete(session_id.to_string()))??;
+
+        Ok(())
+    }
+
+    /// Terminate an agent session gracefully
+    ///
+    /// Closes the client connection, moves the session to completed state, and returns
+    /// final statistics. The session will be retained for `COMPLETED_RETENTION_MS` before cleanup.
+    ///
+    /// If the agent is writing a turn, it gets up to `TERMINATE_DRAIN_TIMEOUT`
+    /// to finish, so it is not cut off mid-write; its result joins the
+    /// transcript. Idle sessions and turns with no output yet close at once.
+    ///
+    /// Terminating is idempotent: concurrent callers wait for the first one to
+    /// finish, and terminating an already completed session returns its final
+    /// statistics again. The session stays visible (as active, then completed)
+    /// throughout, so readers never observe it missing.
+    pub async fn terminate_session(&self, session_id: &str) -> Result<TerminateResponse> {
+        let active = self.active_sessions.lock().await;
+        let Some(session) = active.get(session_id).cloned() else {
+            drop(active);
+            return self.completed_response(session_id).await;
         };
+        drop(active);
+
+        if !session.begin_terminate() {
+            // Another caller owns the shutdown; report its result
+            session.wait_completed().await;
+            return self.completed_response(session_id).await;
+        }
+
+        // Finish in a task of its own, so a caller that stops waiting does
+        // not leave the session stuck in `Terminating`
+        let task = tokio::spawn(complete_session(
+            session,
+            Arc::clone(&self.active_sessions),
+            Arc::clone(&self.completed_sessions),
+            Arc::clone(&self.groups),
+            Arc::clone(&self.clock),
+        ));
+        match task.await {
+            Ok(response) => Ok(response),
+            // The task panicked; report whatever it recorded
+            Err(_) => self.completed_response(session_id).await,
+        }
+    }
 
+    /// Final statistics of a completed session
+    async fn completed_response(&self, session_id: &str) -> Result<TerminateResponse> {
         self.completed_sessions
             .lock()
             .await
-            .insert(session_id.to_string(), completed);
-
-        Ok(TerminateResponse {
-            session_id: session_id.to_string(),
-            success: true,
-            final_turn_count,
-            total_messages,
-            runtime_ms,
-        })
+            .get(session_id)
+            .map(CompletedAgentSession::terminate_response)
+            .ok_or_else(|| ClaudeError::SessionNotFound(session_id.to_string()))
     }
 
     /// Subscribe to real-time message events for a session
@@ -107,3 +221,71 @@ impl AgentManager {
         Ok(session.message_tx.subscribe())
     }
 }
+
+/// Shut a terminating session down and move it to the completed set
+async fn complete_session(
+    session: AgentSessionInfo,
+    active_sessions: Arc<Mutex<HashMap<String, AgentSessionInfo>>>,
+    completed_sessions: Arc<Mutex<HashMap<String, CompletedAgentSession>>>,
+    groups: Arc<Mutex<HashMap<String, GroupState>>>,
+    clock: Arc<dyn Clock>,
+) -> TerminateResponse {
+    let (response_tx, response_rx) = oneshot::channel();
+    let cmd = SessionCommand::Shutdown {
+        drain: Some(TERMINATE_DRAIN_TIMEOUT),
+        response_tx,
+    };
+
+    if session.command_tx.send(cmd).is_ok() {
+        let _ = response_rx.await;
+    }
+
+    let runtime_ms = clock.elapsed(session.created_at).as_millis() as u64;
+    let messages = session.messages.lock().await.clone();
+    let final_turn_count = *session.turn_count.lock().await;
+    let termination_reason = *session.termination_reason.lock().await;
+    session.events.info(
+        "session.terminated",
+        format!("Session terminated after {final_turn_count} turns"),
+        serde_json::json!({
+            "turns": final_turn_count,
+            "runtime_ms": runtime_ms,
+            "reason": termination_reason,
+        }),
+    );
+
+    let completed = CompletedAgentSession {
+        session_id: session.session_id.clone(),
+        label: session.label.clone(),
+        messages,
+        final_turn_count,
+        runtime_ms,
+        completed_at: clock.utc_now(),
+        grade: session.grade.lock().await.clone(),
+        disk_usage_bytes: session
+            .disk_usage
+            .as_ref()
+            .map(|usage| usage.load(Ordering::Relaxed)),
+        peak_memory_bytes: session.memory.as_ref().map(|memory| memory.peak()),
+        termination_reason,
+        events: session.events.snapshot(),
+    };
+    let response = completed.terminate_response();
+
+    if let Some(ref group_id) = session.group_id
+        && let Some(state) = groups.lock().await.get_mut(group_id)
+    {
+        let cost = extract_total_cost(&completed.messages);
+        state.final_costs.insert(session.session_id.clone(), cost);
+    }
+
+    // Publish the completed record before removing the active entry
+    completed_sessions
+        .lock()
+        .await
+        .insert(session.session_id.clone(), completed);
+    active_sessions.lock().await.remove(&session.session_id);
+    session.finish_terminate();
+
+    response
+}
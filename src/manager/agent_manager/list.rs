@@ -2,11 +2,13 @@
 //!
 //! Provides methods for listing all active and completed sessions.
 
+use std::sync::atomic::Ordering;
+
 use crate::error::Result;
 use crate::types::agent::{AgentInfo, ListSessionsResponse};
 
-use super::super::helpers::extract_last_output_lines;
-use super::core::{AgentManager, WORKING_THRESHOLD_MS};
+use super::super::helpers::{extract_last_output_lines, transcript_head};
+use super::core::AgentManager;
 
 impl AgentManager {
     /// List all agent sessions
@@ -23,20 +25,14 @@ impl AgentManager {
         // Collect active sessions
         let active = self.active_sessions.lock().await;
         for (_, session) in active.iter() {
-            let runtime_ms = session.created_at.elapsed().as_millis() as u64;
+            let runtime_ms = self.clock.elapsed(session.created_at).as_millis() as u64;
             let turn_count = *session.turn_count.lock().await;
             let is_complete = *session.is_complete.lock().await;
             let messages = session.messages.lock().await;
             let message_count = messages.len();
             let last_output = extract_last_output_lines(&messages, last_output_lines);
 
-            let working = if is_complete {
-                false
-            } else {
-                let last_msg_time = *session.last_message_at.lock().await;
-                let elapsed_ms = last_msg_time.elapsed().as_millis() as u64;
-                elapsed_ms < WORKING_THRESHOLD_MS
-            };
+            let working = self.session_working(session).await;
 
             agents.push(AgentInfo {
                 session_id: session.session_id.clone(),
@@ -49,6 +45,15 @@ impl AgentManager {
                 is_complete,
                 last_output,
                 completion_time: None,
+                grade: session.grade.lock().await.clone(),
+                transcript_head: transcript_head(&messages),
+                disk_usage_bytes: session
+                    .disk_usage
+                    .as_ref()
+                    .map(|usage| usage.load(Ordering::Relaxed)),
+                memory_bytes: session.memory.as_ref().map(|memory| memory.current()),
+                peak_memory_bytes: session.memory.as_ref().map(|memory| memory.peak()),
+                termination_reason: *session.termination_reason.lock().await,
             });
         }
 
@@ -75,6 +80,12 @@ impl AgentManager {
                     is_complete: true,
                     last_output,
                     completion_time: Some(session.completed_at),
+                    grade: session.grade.clone(),
+                    transcript_head: transcript_head(&session.messages),
+                    disk_usage_bytes: session.disk_usage_bytes,
+                    memory_bytes: None,
+                    peak_memory_bytes: session.peak_memory_bytes,
+                    termination_reason: session.termination_reason,
                 });
             }
         }
@@ -7,6 +7,13 @@
 //! - `output`: Output retrieval with pagination
 //! - `list`: Session listing
 //! - `interaction`: Message sending and termination
+//! - `grade`: Result grading with a short-lived grading agent
+//! - `group`: Session groups with shared budgets and context
+//! - `swarm`: Staged teams of agents run on a session group
+//! - `export`: Export to Claude Code's conversation format
+//! - `history`: Spawning sessions that continue a prior transcript
+//! - `attach`: Attaching a terminal to a running session
+//! - `reaper`: Killing orphaned CLI processes
 //! - `pagination`: Pagination utilities
 
 // Module declarations
@@ -16,8 +23,17 @@ mod info;
 mod output;
 mod list;
 mod interaction;
+mod grade;
+mod group;
+mod swarm;
+mod export;
+mod history;
+mod attach;
+mod reaper;
 mod pagination;
 
 // Re-export public API
 pub use core::AgentManager;
-pub use spawn::SpawnSessionRequest;
+pub use spawn::{PermissionCallback, SpawnSessionRequest};
+pub use swarm::{Swarm, SwarmStage};
+pub use attach::DETACH_COMMAND;
@@ -5,7 +5,7 @@
 use crate::error::{ClaudeError, Result};
 use crate::types::agent::GetOutputResponse;
 
-use super::core::{AgentManager, WORKING_THRESHOLD_MS};
+use super::core::AgentManager;
 use super::pagination::{calculate_has_more, paginate_messages};
 
 impl AgentManager {
@@ -31,13 +31,7 @@ impl AgentManager {
             let max_turns = session.max_turns;
 
             // Calculate working status
-            let working = if is_complete {
-                false
-            } else {
-                let last_msg_time = *session.last_message_at.lock().await;
-                let elapsed_ms = last_msg_time.elapsed().as_millis() as u64;
-                elapsed_ms < WORKING_THRESHOLD_MS
-            };
+            let working = self.session_working(session).await;
 
             // Handle pagination
             let output = paginate_messages(&messages, offset, length);
@@ -0,0 +1,68 @@
+//! Orphan process reaper
+//!
+//! Every CLI process started for a session holds a weak lease on it. A
+//! process whose session no longer exists (its manager was dropped without
+//! `shutdown`, or its task outlived the session) is an orphan: it keeps
+//! running and holding memory until the whole program exits. The reaper
+//! periodically kills such processes.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::transport::subprocess::{kill_tracked, orphans};
+
+use super::core::AgentManager;
+
+/// Start the background reaper, sweeping every `interval`
+pub(super) fn spawn_reaper(interval: Duration, reaped: Arc<AtomicU64>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            reap(&reaped).await;
+        }
+    })
+}
+
+/// Kill every orphaned CLI process, counting them in `reaped`
+///
+/// Covers orphans of every manager in the process, including managers that
+/// have been dropped. Returns the number of processes killed.
+pub(super) async fn reap(reaped: &AtomicU64) -> usize {
+    let orphans = orphans();
+    if orphans.is_empty() {
+        return 0;
+    }
+
+    let pids: Vec<u32> = orphans.iter().map(|process| process.pid).collect();
+    let killed = tokio::task::spawn_blocking(move || kill_tracked(pids))
+        .await
+        .unwrap_or_default();
+    for process in orphans.iter().filter(|p| killed.contains(&p.pid)) {
+        let session = process.owner.as_ref().map_or("", |o| o.session.as_str());
+        log::warn!(
+            "Killed orphaned CLI process {} of ended session {session}",
+            process.pid
+        );
+    }
+    let killed = killed.len();
+
+    reaped.fetch_add(killed as u64, Ordering::Relaxed);
+    killed
+}
+
+impl AgentManager {
+    /// Kill orphaned CLI processes now instead of waiting for the reaper
+    ///
+    /// Returns the number of processes killed.
+    pub async fn reap_orphans(&self) -> usize {
+        reap(&self.orphans_reaped).await
+    }
+
+    /// Number of orphaned CLI processes this manager's reaper has killed
+    #[must_use]
+    pub fn orphans_reaped(&self) -> u64 {
+        self.orphans_reaped.load(Ordering::Relaxed)
+    }
+}
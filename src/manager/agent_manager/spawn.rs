@@ -2,28 +2,65 @@
 //!
 //! Handles creation of new agent sessions with background message collection.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::{Mutex, mpsc};
-use uuid::Uuid;
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::time::Duration;
+use tokio::sync::{Mutex, mpsc, watch};
 
 use crate::client::ClaudeSDKClient;
 use crate::error::Result;
+use crate::permissions::PermissionPolicy;
 use crate::types::agent::SystemPrompt;
-use crate::types::identifiers::ToolName;
+use crate::types::hooks::{HookEvent, HookMatcher};
+use crate::types::identifiers::{SessionId, ToolName};
 use crate::types::options::ClaudeAgentOptions;
+use crate::types::permissions::{CanUseToolCallback, PermissionMode};
+use crate::types::role::AgentRole;
+use crate::transport::SubprocessTransport;
+use crate::transport::subprocess::ProcessOwner;
+use crate::types::transport::{Priority, TransportConfig};
 
-use super::super::background::{CollectorContext, spawn_message_collector};
-use super::super::session::AgentSessionInfo;
+use super::super::background::{
+    CollectorContext, DEFAULT_BUFFER_SIZE, DiskMonitorContext, HealthWatchdogContext,
+    MemoryMonitorContext, record_prompt, spawn_disk_monitor, spawn_health_watchdog,
+    spawn_memory_monitor, spawn_message_collector,
+};
+use super::super::events::EventLog;
+use super::super::memory::{MemoryTracking, MemoryUsage};
+use super::super::quota::{DiskQuota, dir_size};
+use super::super::session::{AgentSessionInfo, SessionState};
 use super::core::AgentManager;
 
 // ============================================================================
 // REQUEST TYPES
 // ============================================================================
 
+/// Tool permission callback of a [`SpawnSessionRequest`]
+///
+/// Wraps the callback so the request can derive `Debug`.
+#[derive(Clone)]
+pub struct PermissionCallback(pub CanUseToolCallback);
+
+impl std::fmt::Debug for PermissionCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<callback>")
+    }
+}
+
+impl From<CanUseToolCallback> for PermissionCallback {
+    fn from(callback: CanUseToolCallback) -> Self {
+        Self(callback)
+    }
+}
+
 /// Request parameters for spawning a new agent session
+///
+/// Unlike `ClaudeAgentOptions`, the request is plain data with public
+/// fields. Build it with struct update syntax from `Default::default()` or
+/// [`SpawnSessionRequest::for_role`], so fields added later take their
+/// defaults instead of breaking the build.
 #[derive(Debug, Clone)]
 pub struct SpawnSessionRequest {
     /// Initial prompt to send to the agent
@@ -44,6 +81,137 @@ pub struct SpawnSessionRequest {
     pub add_dirs: Vec<String>,
     /// Label for identifying the session
     pub label: String,
+    /// Interval between control channel health pings (disabled when `None`)
+    pub health_check_interval: Option<Duration>,
+    /// Transport used to reach the CLI (e.g. ssh to a build server)
+    pub transport: TransportConfig,
+    /// Built-in role supplying the system prompt and tools when not given
+    ///
+    /// `max_turns` is always taken from the request; use
+    /// [`SpawnSessionRequest::for_role`] to start from the role's default.
+    pub role: Option<AgentRole>,
+    /// Session group to join (see `AgentManager::create_group`)
+    ///
+    /// Spawning fails once the group has spent its budget. Without a label,
+    /// the session takes the group's label. Members get the group's
+    /// blackboard as the `blackboard` MCP server.
+    pub group: Option<String>,
+    /// Claude Code session to resume (`--resume`)
+    pub resume: Option<String>,
+    /// Limit on bytes written to the working directory (local sessions only)
+    ///
+    /// Measured against `cwd`; ignored when `cwd` is unset, since the
+    /// current directory is shared by every such session. A session over its
+    /// quota is stopped and reported as complete with
+    /// [`TerminationReason::DiskQuotaExceeded`](crate::types::TerminationReason).
+    pub disk_quota: Option<DiskQuota>,
+    /// Sample the RSS of the session's CLI process (local subprocess only)
+    ///
+    /// The latest and peak samples are reported in `AgentInfo`. With a cap,
+    /// a session over it is stopped and reported as complete with
+    /// [`TerminationReason::MemoryLimitExceeded`](crate::types::TerminationReason).
+    pub memory_tracking: Option<MemoryTracking>,
+    /// Scheduling priority of the CLI process (local subprocess only)
+    ///
+    /// Use [`Priority::Low`] or [`Priority::Idle`] for background batches so
+    /// they do not starve interactive work on a shared machine.
+    pub process_priority: Option<Priority>,
+    /// Hooks run for the session's events, e.g. audit or guard hooks
+    pub hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
+    /// Callback deciding the session's tool permission requests
+    pub can_use_tool: Option<PermissionCallback>,
+    /// Permission mode the session starts in (CLI default when `None`)
+    pub permission_mode: Option<PermissionMode>,
+    /// Declarative permission rules tried before `can_use_tool`
+    pub permission_policy: Option<PermissionPolicy>,
+    /// Most messages kept in the session's transcript buffer (the manager's
+    /// default when `None`)
+    pub buffer_size: Option<usize>,
+}
+
+impl Default for SpawnSessionRequest {
+    fn default() -> Self {
+        Self {
+            prompt: String::new(),
+            system_prompt: None,
+            allowed_tools: Vec::new(),
+            disallowed_tools: Vec::new(),
+            max_turns: 10,
+            model: None,
+            cwd: None,
+            add_dirs: Vec::new(),
+            label: String::new(),
+            health_check_interval: None,
+            transport: TransportConfig::default(),
+            role: None,
+            group: None,
+            resume: None,
+            disk_quota: None,
+            memory_tracking: None,
+            process_priority: None,
+            hooks: None,
+            can_use_tool: None,
+            permission_mode: None,
+            permission_policy: None,
+            buffer_size: None,
+        }
+    }
+}
+
+impl SpawnSessionRequest {
+    /// Create a request for a built-in role using its default max turns
+    #[must_use]
+    pub fn for_role(role: AgentRole, prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            max_turns: role.default_max_turns(),
+            label: role.name().to_string(),
+            role: Some(role),
+            ..Default::default()
+        }
+    }
+
+    /// CLI options a session spawned from this request starts with
+    ///
+    /// Role defaults are applied; group membership adds the blackboard
+    /// server at spawn time. Useful for building a
+    /// [`TransportPool`](crate::transport::TransportPool) that serves these
+    /// requests.
+    #[must_use]
+    pub fn options(&self) -> ClaudeAgentOptions {
+        let mut system_prompt = self.system_prompt.clone();
+        let mut allowed_tools = self.allowed_tools.clone();
+        if let Some(role) = self.role {
+            system_prompt.get_or_insert_with(|| role.system_prompt().to_string());
+            if allowed_tools.is_empty() {
+                allowed_tools = role.allowed_tools().iter().map(ToString::to_string).collect();
+            }
+        }
+
+        ClaudeAgentOptions {
+            allowed_tools: allowed_tools.into_iter().map(ToolName::from).collect(),
+            disallowed_tools: self
+                .disallowed_tools
+                .iter()
+                .cloned()
+                .map(ToolName::from)
+                .collect(),
+            system_prompt: system_prompt.map(SystemPrompt::from),
+            max_turns: Some(self.max_turns),
+            model: self.model.clone(),
+            cwd: self.cwd.clone().map(PathBuf::from),
+            add_dirs: self.add_dirs.iter().map(PathBuf::from).collect(),
+            health_check_interval: self.health_check_interval,
+            transport: self.transport.clone(),
+            resume: self.resume.clone().map(SessionId::from),
+            process_priority: self.process_priority,
+            hooks: self.hooks.clone(),
+            can_use_tool: self.can_use_tool.clone().map(|callback| callback.0),
+            permission_mode: self.permission_mode,
+            permission_policy: self.permission_policy.clone(),
+            ..Default::default()
+        }
+    }
 }
 
 // ============================================================================
@@ -59,33 +227,94 @@ impl AgentManager {
     /// Returns the session ID for subsequent operations.
     pub async fn spawn_session(&self, request: SpawnSessionRequest) -> Result<String> {
         // Generate unique session ID
-        let session_id = Uuid::new_v4().to_string();
+        let session_id = self.ids.session_id().as_str().to_string();
 
-        // Build ClaudeAgentOptions
-        let options = ClaudeAgentOptions {
-            allowed_tools: request
-                .allowed_tools
-                .into_iter()
-                .map(ToolName::from)
-                .collect(),
-            disallowed_tools: request
-                .disallowed_tools
-                .into_iter()
-                .map(ToolName::from)
-                .collect(),
-            system_prompt: request.system_prompt.map(SystemPrompt::from),
-            max_turns: Some(request.max_turns),
-            model: request.model,
-            cwd: request.cwd.map(PathBuf::from),
-            add_dirs: request.add_dirs.into_iter().map(PathBuf::from).collect(),
-            ..Default::default()
+        // Build ClaudeAgentOptions, with role defaults for anything unset
+        let mut options = request.options();
+        options.id_generator = Some(Arc::clone(&self.ids));
+        if matches!(request.transport, TransportConfig::Subprocess) {
+            options.cli_path.clone_from(&self.cli_path);
+            if let Some(dir) = &self.claude_dir {
+                options.env.insert(
+                    "CLAUDE_CONFIG_DIR".to_string(),
+                    dir.to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        // Tie the session's CLI processes to the session for the orphan reaper
+        let lease = Arc::new(());
+        options.process_owner = Some(ProcessOwner {
+            session: session_id.clone(),
+            lease: Arc::downgrade(&lease),
+        });
+
+        // Resolve the group before starting anything
+        let mut prompt = request.prompt;
+        let mut label = request.label;
+        let mut blackboard = None;
+        if let Some(ref group_id) = request.group {
+            let group = self.get_group(group_id).await?;
+            blackboard = Some(self.group_blackboard(group_id).await?);
+            self.check_group_budget(group_id).await?;
+            if label.is_empty() {
+                label = group.label;
+            }
+            if group.share_context
+                && let Some(digest) = self.group_context_digest(group_id).await
+            {
+                prompt = format!("{digest}\n---\n\n{prompt}");
+            }
+        }
+
+        // Expose the group blackboard, allowing its tools when tools are restricted
+        if let Some(blackboard) = blackboard {
+            let server = blackboard.mcp_server(session_id.clone());
+            if !options.allowed_tools.is_empty() {
+                options
+                    .allowed_tools
+                    .extend(server.tool_names().into_iter().map(ToolName::from));
+            }
+            options
+                .sdk_mcp_servers
+                .insert(server.name().to_string(), server);
+        }
+
+        // Measure the working directory before the agent can write to it
+        let local = matches!(request.transport, TransportConfig::Subprocess);
+        let disk_quota = match (request.disk_quota, &request.cwd) {
+            (Some(quota), Some(cwd)) if local => {
+                let dir = PathBuf::from(cwd);
+                let measured = dir.clone();
+                let baseline = tokio::task::spawn_blocking(move || dir_size(&measured))
+                    .await
+                    .unwrap_or(0);
+                Some((dir, quota, baseline))
+            }
+            // Without `cwd` the session shares the current directory with
+            // every other such session, so none of it is the session's own
+            (Some(_), None) if local => {
+                log::warn!("Disk quota ignored: it requires a working directory (cwd)");
+                None
+            }
+            _ => None,
         };
 
-        // Create client
-        let mut client = ClaudeSDKClient::new(options, None).await?;
+        // Create client, on a pre-started CLI process when a pool matches
+        let model = options.model.clone();
+        let pooled = self.pooled_transport(&options).await;
+        let from_pool = pooled.is_some();
+        let mut client = match pooled {
+            Some(transport) => ClaudeSDKClient::with_transport(options, transport?).await?,
+            None => ClaudeSDKClient::new(options, None).await?,
+        };
 
         // Send initial prompt
-        client.send_message(&request.prompt).await?;
+        client.send_message(&prompt).await?;
+
+        let health = client.health_monitor();
+        let turn_active = client.turn_watch();
+        let metrics = client.metrics_recorder();
 
         // Create command channel
         let (command_tx, command_rx) = mpsc::unbounded_channel();
@@ -94,23 +323,58 @@ impl AgentManager {
         let (message_tx, _) = tokio::sync::broadcast::channel(100);
 
         // Create shared state for background task
-        let messages_arc = Arc::new(Mutex::new(VecDeque::with_capacity(1000)));
-        let last_message_arc = Arc::new(Mutex::new(Instant::now()));
+        let buffer_size = request
+            .buffer_size
+            .map_or(self.buffer_size, |size| size.max(1));
+        let messages_arc = Arc::new(Mutex::new(VecDeque::with_capacity(
+            buffer_size.min(DEFAULT_BUFFER_SIZE),
+        )));
+        let now = self.clock.now();
+        let last_message_arc = Arc::new(Mutex::new(now));
         let turn_count_arc = Arc::new(Mutex::new(0));
         let is_complete_arc = Arc::new(Mutex::new(false));
+        let disk_usage = disk_quota.as_ref().map(|_| Arc::new(AtomicU64::new(0)));
+        let termination_reason = Arc::new(Mutex::new(None));
+        let memory_tracking = request
+            .memory_tracking
+            .filter(|_| matches!(request.transport, TransportConfig::Subprocess));
+        let memory_usage = memory_tracking.map(|_| Arc::new(MemoryUsage::default()));
+        let events = EventLog::new(&session_id, Arc::clone(&self.clock));
+        events.info(
+            "session.spawned",
+            format!("Session spawned as \"{label}\""),
+            serde_json::json!({
+                "label": label,
+                "model": model,
+                "group": request.group,
+                "pooled": from_pool,
+            }),
+        );
 
         // Create session info
         let session_info = AgentSessionInfo {
             session_id: session_id.clone(),
-            label: request.label,
+            label,
             command_tx: command_tx.clone(),
             messages: Arc::clone(&messages_arc),
             message_tx: message_tx.clone(),
-            created_at: Instant::now(),
+            created_at: now,
             last_message_at: Arc::clone(&last_message_arc),
             turn_count: Arc::clone(&turn_count_arc),
             max_turns: request.max_turns,
             is_complete: Arc::clone(&is_complete_arc),
+            health: health.clone(),
+            turn_active,
+            metrics,
+            grade: Arc::new(Mutex::new(None)),
+            state: Arc::new(watch::channel(SessionState::Active).0),
+            group_id: request.group.clone(),
+            attached: Arc::new(AtomicBool::new(false)),
+            _lease: lease,
+            disk_usage: disk_usage.clone(),
+            memory: memory_usage.clone(),
+            termination_reason: Arc::clone(&termination_reason),
+            events: events.clone(),
         };
 
         // Store in active sessions
@@ -125,12 +389,70 @@ impl AgentManager {
             message_tx,
             last_message: last_message_arc,
             turn_count: turn_count_arc,
-            is_complete: is_complete_arc,
+            is_complete: Arc::clone(&is_complete_arc),
             max_turns: request.max_turns,
-            session_id: session_id.clone(),
+            buffer_size,
+            clock: Arc::clone(&self.clock),
+            events: events.clone(),
+            truncated: AtomicBool::new(false),
         };
+        record_prompt(&ctx, &prompt).await;
         spawn_message_collector(client, command_rx, ctx);
 
+        if let Some(interval) = request.health_check_interval {
+            spawn_health_watchdog(HealthWatchdogContext {
+                health,
+                interval,
+                command_tx: command_tx.clone(),
+                events: events.clone(),
+            });
+        }
+
+        if let (Some((dir, quota, baseline)), Some(usage)) = (disk_quota, disk_usage) {
+            spawn_disk_monitor(DiskMonitorContext {
+                dir,
+                quota,
+                baseline,
+                usage,
+                command_tx: command_tx.clone(),
+                is_complete: Arc::clone(&is_complete_arc),
+                termination_reason: Arc::clone(&termination_reason),
+                events: events.clone(),
+            });
+        }
+
+        if let (Some(tracking), Some(usage)) = (memory_tracking, memory_usage) {
+            spawn_memory_monitor(MemoryMonitorContext {
+                tracking,
+                usage,
+                command_tx,
+                is_complete: is_complete_arc,
+                termination_reason,
+                events,
+            });
+        }
+
+        if let Some(ref group_id) = request.group {
+            self.add_group_member(group_id, &session_id).await;
+        }
+
         Ok(session_id)
     }
+
+    /// Transport from the first pool serving sessions with `options`
+    ///
+    /// Only local subprocess sessions are pooled.
+    async fn pooled_transport(
+        &self,
+        options: &ClaudeAgentOptions,
+    ) -> Option<Result<SubprocessTransport>> {
+        if !matches!(options.transport, TransportConfig::Subprocess) {
+            return None;
+        }
+        let pool = self.pools.iter().find(|pool| pool.matches(options))?;
+        Some(pool.acquire().await.map(|mut transport| {
+            transport.set_process_owner(options.process_owner.clone());
+            transport
+        }))
+    }
 }
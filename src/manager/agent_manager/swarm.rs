@@ -0,0 +1,215 @@
+//! Agent swarms
+//!
+//! Runs a team of agents in stages on top of session groups: every member
+//! joins one group, so the stages share a budget and a blackboard, and each
+//! member's prompt starts with a digest of the results of the stages before
+//! it. A typical swarm plans, implements in parallel, then reviews.
+
+use std::time::Duration;
+
+use futures::future::join_all;
+
+use crate::error::{ClaudeError, Result};
+use crate::types::agent::{SessionGroup, SwarmMemberReport, SwarmReport, SwarmStageReport};
+use crate::types::role::AgentRole;
+
+use super::core::AgentManager;
+use super::spawn::SpawnSessionRequest;
+
+/// Default longest wait for the members of one stage
+const DEFAULT_STAGE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// System prompt for the planning agent
+const PLANNER_PROMPT: &str = "You plan work for a team of implementing agents. Read the \
+code you need, then reply with a numbered plan of independent work items, each with \
+the files it touches and how to verify it. Do not edit files.";
+
+/// System prompt for implementing agents
+const IMPLEMENTER_PROMPT: &str = "You are one of several agents implementing a shared \
+plan, given in the results above your task. Implement only the work items assigned to \
+you, verify them, and reply with a short summary of what you changed. Before editing a \
+file, take it with the blackboard's claim tool under the key `file:<path>`; if another \
+agent holds it, leave that file to them.";
+
+/// One stage of a swarm: members that run in parallel
+#[derive(Debug, Clone)]
+pub struct SwarmStage {
+    /// Name of the stage (e.g. "plan")
+    pub name: String,
+    /// Members to spawn; `group` is set by the swarm
+    pub members: Vec<SpawnSessionRequest>,
+}
+
+/// A team of agents run in stages
+///
+/// Stages run in order; the members of a stage run in parallel. A stage
+/// starts once every member of the previous stage has produced a result,
+/// failed or timed out.
+///
+/// # Example
+///
+/// ```no_run
+/// # use kodegen_claude_agent::AgentManager;
+/// # use kodegen_claude_agent::manager::Swarm;
+/// # async fn example() -> kodegen_claude_agent::Result<()> {
+/// let manager = AgentManager::new();
+/// let swarm = Swarm::plan_implement_review("Add a --verbose flag to the CLI", 2)
+///     .budget_usd(5.0);
+/// let report = manager.run_swarm(swarm).await?;
+/// println!("{:?} (${:.2})", report.final_result(), report.cost.total_cost_usd);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Swarm {
+    /// Group the members join; context sharing is always enabled
+    pub group: SessionGroup,
+    /// Stages in the order they run
+    pub stages: Vec<SwarmStage>,
+    /// Longest wait for the members of one stage
+    pub stage_timeout: Duration,
+}
+
+impl Swarm {
+    /// Create a swarm without stages
+    #[must_use]
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            group: SessionGroup::new(label),
+            stages: Vec::new(),
+            stage_timeout: DEFAULT_STAGE_TIMEOUT,
+        }
+    }
+
+    /// Swarm with a planner, `implementers` parallel implementers and a reviewer
+    #[must_use]
+    pub fn plan_implement_review(task: &str, implementers: usize) -> Self {
+        let planner = SpawnSessionRequest {
+            prompt: format!("Plan this task for {implementers} implementer(s):\n\n{task}"),
+            system_prompt: Some(PLANNER_PROMPT.to_string()),
+            allowed_tools: ["Read", "Grep", "Glob"].map(String::from).to_vec(),
+            max_turns: 20,
+            label: "planner".to_string(),
+            ..Default::default()
+        };
+        let implementers = (1..=implementers)
+            .map(|n| SpawnSessionRequest {
+                prompt: format!(
+                    "Task: {task}\n\nYou are implementer {n} of {implementers}. Take work \
+                     items {n}, {}, {} and so on.",
+                    n + implementers,
+                    n + 2 * implementers
+                ),
+                system_prompt: Some(IMPLEMENTER_PROMPT.to_string()),
+                max_turns: 40,
+                label: format!("implementer-{n}"),
+                ..Default::default()
+            })
+            .collect();
+        let reviewer = SpawnSessionRequest::for_role(
+            AgentRole::CodeReviewer,
+            format!(
+                "Review the changes made for this task against the plan and the \
+                 implementers' summaries above:\n\n{task}"
+            ),
+        );
+
+        Self::new(task.chars().take(60).collect::<String>())
+            .stage("plan", vec![planner])
+            .stage("implement", implementers)
+            .stage("review", vec![reviewer])
+    }
+
+    /// Append a stage
+    #[must_use]
+    pub fn stage(mut self, name: impl Into<String>, members: Vec<SpawnSessionRequest>) -> Self {
+        self.stages.push(SwarmStage {
+            name: name.into(),
+            members,
+        });
+        self
+    }
+
+    /// Set the spending limit shared by every member, in USD
+    #[must_use]
+    pub fn budget_usd(mut self, budget: f64) -> Self {
+        self.group = self.group.budget_usd(budget);
+        self
+    }
+
+    /// Set the longest wait for the members of one stage
+    #[must_use]
+    pub const fn stage_timeout(mut self, timeout: Duration) -> Self {
+        self.stage_timeout = timeout;
+        self
+    }
+}
+
+impl AgentManager {
+    /// Run a swarm to completion
+    ///
+    /// Creates the swarm's group, then spawns each stage's members into it
+    /// and waits for their results. Members are terminated once they have
+    /// answered, so their results and costs stay readable through the group.
+    /// A member that fails or times out is reported with an error and does
+    /// not stop the swarm.
+    ///
+    /// # Errors
+    /// Returns error if the group cannot be created or a member cannot be
+    /// spawned (e.g. the budget is spent); members already running are
+    /// terminated first
+    pub async fn run_swarm(&self, swarm: Swarm) -> Result<SwarmReport> {
+        let group_id = self.create_group(swarm.group.share_context(true)).await?;
+
+        let mut stages = Vec::with_capacity(swarm.stages.len());
+        for stage in swarm.stages {
+            let mut spawned = Vec::with_capacity(stage.members.len());
+            for mut request in stage.members {
+                request.group = Some(group_id.clone());
+                let label = request.label.clone();
+                match self.spawn_session(request).await {
+                    Ok(session_id) => spawned.push((session_id, label)),
+                    Err(e) => {
+                        let _ = self.terminate_group(&group_id).await;
+                        return Err(e);
+                    }
+                }
+            }
+
+            let results = join_all(
+                spawned
+                    .iter()
+                    .map(|(session_id, _)| self.wait_for_result(session_id, swarm.stage_timeout)),
+            )
+            .await;
+
+            let mut members = Vec::with_capacity(spawned.len());
+            for ((session_id, label), result) in spawned.into_iter().zip(results) {
+                match self.terminate_session(&session_id).await {
+                    Ok(_) | Err(ClaudeError::SessionNotFound(_)) => {}
+                    Err(e) => log::warn!("Failed to terminate swarm member {session_id}: {e}"),
+                }
+                let (result, error) = match result {
+                    Ok(result) => (Some(result), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                members.push(SwarmMemberReport {
+                    session_id,
+                    label,
+                    result,
+                    error,
+                });
+            }
+            stages.push(SwarmStageReport {
+                name: stage.name,
+                members,
+            });
+        }
+
+        Ok(SwarmReport {
+            cost: self.group_cost(&group_id).await?,
+            group_id,
+            stages,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+//! Tamper-evident transcript hash chain
+//!
+//! Every message recorded for a session is hashed together with the hash of
+//! the message before it (SHA-256, hex encoded), so the latest hash — the
+//! transcript head — commits to the whole transcript. Recording the head
+//! (from `AgentInfo` or `TerminateResponse`) and later re-running
+//! [`verify_transcript`] over an exported transcript proves that no entry
+//! was modified, dropped or reordered.
+
+use ring::digest::{SHA256, digest};
+use serde_json::{Map, Value, json};
+
+use crate::error::{ClaudeError, Result};
+use crate::types::agent::SerializedMessage;
+
+/// Hash the first entry of every transcript chains from
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hash of a transcript entry given the hash of the entry before it
+///
+/// Covers the entry's type, content, turn and timestamp; the entry's own
+/// `prev_hash` and `hash` fields are ignored.
+#[must_use]
+pub fn entry_hash(prev_hash: &str, message: &SerializedMessage) -> String {
+    let entry = canonical(&json!({
+        "message_type": message.message_type,
+        "content": message.content,
+        "turn": message.turn,
+        "timestamp": message.timestamp,
+    }));
+
+    let mut input = Vec::with_capacity(prev_hash.len() + 256);
+    input.extend_from_slice(prev_hash.as_bytes());
+    input.push(b'\n');
+    input.extend_from_slice(entry.to_string().as_bytes());
+
+    digest(&SHA256, &input)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Copy of `value` with every object's keys in sorted order
+///
+/// serde_json keeps insertion order when any crate in the build enables its
+/// `preserve_order` feature, so the encoding is only canonical after this.
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            let sorted: Map<String, Value> = keys
+                .into_iter()
+                .map(|key| (key.clone(), canonical(&object[key])))
+                .collect();
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Verify a contiguous run of transcript entries
+///
+/// The run may start anywhere in the transcript: the first entry's
+/// `prev_hash` anchors the chain (a full transcript starts at
+/// [`GENESIS_HASH`]). Returns the head hash, which must equal the head
+/// recorded for the session for the transcript to be unmodified, or `None`
+/// for an empty run.
+///
+/// # Errors
+/// Returns `ClaudeError::TranscriptIntegrity` for the first entry that is
+/// unhashed, does not link to its predecessor, or whose content no longer
+/// matches its hash.
+pub fn verify_transcript(messages: &[SerializedMessage]) -> Result<Option<String>> {
+    let mut head: Option<&str> = None;
+
+    for (index, message) in messages.iter().enumerate() {
+        let (Some(prev_hash), Some(hash)) = (&message.prev_hash, &message.hash) else {
+            return Err(ClaudeError::transcript_integrity(
+                index,
+                "entry is not hashed",
+            ));
+        };
+        if head.is_some_and(|head| head != prev_hash) {
+            return Err(ClaudeError::transcript_integrity(
+                index,
+                "entry does not link to the previous entry",
+            ));
+        }
+        if entry_hash(prev_hash, message) != *hash {
+            return Err(ClaudeError::transcript_integrity(
+                index,
+                "entry content does not match its hash",
+            ));
+        }
+        head = Some(hash);
+    }
+
+    Ok(head.map(str::to_string))
+}
+
+/// Chain a new entry onto a transcript whose latest hash is `prev_hash`
+pub(super) fn chain_message(prev_hash: Option<&str>, message: &mut SerializedMessage) {
+    let prev_hash = prev_hash.unwrap_or(GENESIS_HASH).to_string();
+    message.hash = Some(entry_hash(&prev_hash, message));
+    message.prev_hash = Some(prev_hash);
+}
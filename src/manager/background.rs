@@ -4,18 +4,30 @@
 //! collection and command processing for agent sessions.
 
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::{Mutex, mpsc, broadcast};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, mpsc, broadcast, oneshot};
 
+use super::clock::Clock;
 use super::commands::SessionCommand;
-use super::helpers::serialize_message;
-use crate::client::ClaudeSDKClient;
-use crate::types::agent::SerializedMessage;
+use super::audit::chain_message;
+use super::events::EventLog;
+use super::helpers::{prompt_message, serialize_message};
+use super::memory::{MemoryTracking, MemoryUsage, rss_bytes};
+use super::quota::{DiskQuota, dir_size};
+use crate::client::{ClaudeSDKClient, HealthMonitor, LifecycleEvent};
+use crate::error::ClaudeError;
+use crate::transport::subprocess::session_pids;
+use crate::types::agent::{SerializedMessage, TerminationReason};
 use crate::types::messages::Message;
 
-/// Circular buffer capacity for messages
-const BUFFER_SIZE: usize = 1000;
+/// Default circular buffer capacity for messages
+pub(super) const DEFAULT_BUFFER_SIZE: usize = 1000;
+
+/// Transcript message type for operator notes injected via `inject_system_note`
+const OPERATOR_NOTE_TYPE: &str = "operator_note";
 
 /// Shared state for message collector task
 pub(super) struct CollectorContext {
@@ -25,7 +37,12 @@ pub(super) struct CollectorContext {
     pub turn_count: Arc<Mutex<u32>>,
     pub is_complete: Arc<Mutex<bool>>,
     pub max_turns: u32,
-    pub session_id: String,
+    /// Most messages kept in `messages`; older ones are dropped
+    pub buffer_size: usize,
+    pub clock: Arc<dyn Clock>,
+    pub events: EventLog,
+    /// Set once the transcript buffer has started dropping messages
+    pub truncated: AtomicBool,
 }
 
 /// Spawn a background task to collect messages from an agent session
@@ -49,6 +66,10 @@ pub(super) fn spawn_message_collector(
     ctx: CollectorContext,
 ) {
     tokio::spawn(async move {
+        let mut initialized = false;
+        // Whether the agent has written output in a turn still in progress
+        let mut writing = false;
+        let mut lifecycle = client.lifecycle_events();
         loop {
             tokio::select! {
                 // Handle commands from other tasks
@@ -57,15 +78,79 @@ pub(super) fn spawn_message_collector(
                         SessionCommand::SendMessage { prompt, response_tx } => {
                             let result = client.send_message(&prompt).await;
                             if result.is_ok() {
-                                *ctx.last_message.lock().await = Instant::now();
+                                *ctx.last_message.lock().await = ctx.clock.now();
+                                record_prompt(&ctx, &prompt).await;
+                            }
+                            let _ = response_tx.send(result);
+                        }
+                        SessionCommand::InterruptAndSend { prompt, response_tx } => {
+                            let result = client.interrupt_and_send(&prompt).await;
+                            if result.is_ok() {
+                                *ctx.last_message.lock().await = ctx.clock.now();
+                                record_prompt(&ctx, &prompt).await;
                             }
                             let _ = response_tx.send(result);
                         }
-                        SessionCommand::Shutdown { response_tx } => {
+                        SessionCommand::InjectNote { note, formatted, response_tx } => {
+                            let result = client.send_message(&formatted).await;
+                            if result.is_ok() {
+                                *ctx.last_message.lock().await = ctx.clock.now();
+                                let record = SerializedMessage {
+                                    message_type: OPERATOR_NOTE_TYPE.to_string(),
+                                    content: serde_json::json!({
+                                        "note": note,
+                                        "formatted": formatted,
+                                    }),
+                                    turn: 0,
+                                    timestamp: ctx.clock.utc_now(),
+                                    prev_hash: None,
+                                    hash: None,
+                                };
+                                record_message(&ctx, record).await;
+                            }
+                            let _ = response_tx.send(result);
+                        }
+                        SessionCommand::Shutdown { drain: None, response_tx } => {
                             let result = client.close().await;
                             let _ = response_tx.send(result);
                             break;
                         }
+                        SessionCommand::Shutdown { drain: Some(timeout), response_tx } => {
+                            // Only a turn the agent is writing is worth waiting for
+                            let result = if writing && client.is_turn_active() {
+                                client.close_gracefully(timeout).await
+                            } else {
+                                client.close().await
+                            };
+                            record_drained_messages(&mut client, &ctx).await;
+                            let _ = response_tx.send(result);
+                            break;
+                        }
+                    }
+                }
+                // Notice a CLI that stopped without a word
+                Some(event) = lifecycle.recv() => {
+                    match event {
+                        LifecycleEvent::ProcessExited { code } => {
+                            ctx.events.error(
+                                "cli.exited",
+                                match code {
+                                    Some(code) => format!("CLI process exited with code {code}"),
+                                    None => "CLI process output ended".to_string(),
+                                },
+                                serde_json::json!({ "code": code }),
+                            );
+                            *ctx.is_complete.lock().await = true;
+                        }
+                        LifecycleEvent::TransportError { message } => {
+                            ctx.events.error(
+                                "transport.failed",
+                                format!("Transport failed: {message}"),
+                                serde_json::Value::Null,
+                            );
+                            *ctx.is_complete.lock().await = true;
+                        }
+                        _ => {}
                     }
                 }
                 // Process incoming messages
@@ -73,36 +158,71 @@ pub(super) fn spawn_message_collector(
                     match msg_result {
                         Ok(msg) => {
                             // Convert Message to SerializedMessage
-                            let serialized = serialize_message(&msg);
-
-                            // Push to circular buffer
-                            {
-                                let mut messages = ctx.messages.lock().await;
-                                if messages.len() == BUFFER_SIZE {
-                                    messages.pop_front();  // Remove oldest
-                                }
-                                messages.push_back(serialized.clone());
-                            }
+                            let serialized = serialize_message(&msg, ctx.clock.utc_now());
 
-                            // Broadcast message for real-time streaming (ignore errors if no receivers)
-                            let _ = ctx.message_tx.send(serialized);
+                            record_message(&ctx, serialized).await;
 
                             // Update timestamp
-                            *ctx.last_message.lock().await = Instant::now();
+                            *ctx.last_message.lock().await = ctx.clock.now();
 
-                            // Check for completion
-                            if let Message::Result { num_turns, .. } = msg {
-                                *ctx.turn_count.lock().await = num_turns;
+                            writing = match msg {
+                                Message::Result { .. } => false,
+                                Message::User { .. }
+                                | Message::Assistant { .. }
+                                | Message::StreamEvent { .. } => true,
+                                _ => writing,
+                            };
 
-                                // Only mark complete if we've reached ctx.max_turns
-                                if num_turns >= ctx.max_turns {
-                                    *ctx.is_complete.lock().await = true;
-                                    break;
+                            match msg {
+                                // The CLI announces itself again after a restart
+                                Message::System { ref subtype, .. }
+                                    if subtype == "init" && std::mem::replace(&mut initialized, true) =>
+                                {
+                                    ctx.events.warn(
+                                        "cli.restarted",
+                                        "CLI process restarted and resumed the session",
+                                        serde_json::Value::Null,
+                                    );
                                 }
+                                // Check for completion
+                                Message::Result { num_turns, is_error, ref subtype, .. } => {
+                                    *ctx.turn_count.lock().await = num_turns;
+                                    if is_error {
+                                        ctx.events.warn(
+                                            "turn.failed",
+                                            format!("Turn {num_turns} ended with {subtype}"),
+                                            serde_json::json!({ "turn": num_turns, "subtype": subtype }),
+                                        );
+                                    }
+
+                                    // Only mark complete if we've reached ctx.max_turns
+                                    if num_turns >= ctx.max_turns {
+                                        ctx.events.info(
+                                            "session.turn_limit",
+                                            format!("Reached the limit of {} turns", ctx.max_turns),
+                                            serde_json::json!({ "max_turns": ctx.max_turns }),
+                                        );
+                                        *ctx.is_complete.lock().await = true;
+                                        break;
+                                    }
+                                }
+                                _ => {}
                             }
                         }
+                        // Unparseable output is skipped; the stream continues
+                        Err(e @ (ClaudeError::JsonDecode(_) | ClaudeError::MessageParse { .. })) => {
+                            ctx.events.warn(
+                                "output.skipped",
+                                format!("Skipped CLI output: {e}"),
+                                serde_json::Value::Null,
+                            );
+                        }
                         Err(e) => {
-                            log::error!("[{}] Message error: {}", ctx.session_id, e);
+                            ctx.events.error(
+                                "stream.failed",
+                                format!("Message stream failed: {e}"),
+                                serde_json::Value::Null,
+                            );
                             *ctx.is_complete.lock().await = true;
                             break;
                         }
@@ -112,3 +232,242 @@ pub(super) fn spawn_message_collector(
         }
     });
 }
+
+/// Record the messages still buffered in a closed client
+///
+/// Keeps the result of a turn that finished while the client was closing.
+async fn record_drained_messages(client: &mut ClaudeSDKClient, ctx: &CollectorContext) {
+    while let Some(msg_result) = client.next_message().await {
+        let Ok(msg) = msg_result else {
+            continue;
+        };
+        record_message(ctx, serialize_message(&msg, ctx.clock.utc_now())).await;
+        *ctx.last_message.lock().await = ctx.clock.now();
+        if let Message::Result { num_turns, .. } = msg {
+            *ctx.turn_count.lock().await = num_turns;
+        }
+    }
+}
+
+/// Record a prompt sent to the agent as a user message
+pub(super) async fn record_prompt(ctx: &CollectorContext, prompt: &str) {
+    record_message(ctx, prompt_message(prompt, ctx.clock.utc_now())).await;
+}
+
+/// Chain a message onto the transcript, push it into the session's circular
+/// buffer and broadcast it
+async fn record_message(ctx: &CollectorContext, mut message: SerializedMessage) {
+    // Push to circular buffer
+    {
+        let mut messages = ctx.messages.lock().await;
+        let prev_hash = messages.back().and_then(|last| last.hash.as_deref());
+        chain_message(prev_hash, &mut message);
+        if messages.len() >= ctx.buffer_size {
+            messages.pop_front();  // Remove oldest
+            if !ctx.truncated.swap(true, Ordering::Relaxed) {
+                ctx.events.warn(
+                    "transcript.truncated",
+                    format!(
+                        "Transcript buffer full; dropping messages beyond the latest {}",
+                        ctx.buffer_size
+                    ),
+                    serde_json::json!({ "capacity": ctx.buffer_size }),
+                );
+            }
+        }
+        messages.push_back(message.clone());
+    }
+
+    // Broadcast message for real-time streaming (ignore errors if no receivers)
+    let _ = ctx.message_tx.send(message);
+}
+
+/// Shared state for a session's disk quota monitor
+pub(super) struct DiskMonitorContext {
+    pub dir: PathBuf,
+    pub quota: DiskQuota,
+    /// Size of the directory when the session was spawned
+    pub baseline: u64,
+    pub usage: Arc<AtomicU64>,
+    pub command_tx: mpsc::UnboundedSender<SessionCommand>,
+    pub is_complete: Arc<Mutex<bool>>,
+    pub termination_reason: Arc<Mutex<Option<TerminationReason>>>,
+    pub events: EventLog,
+}
+
+/// Spawn a background task enforcing a session's disk quota
+///
+/// Measures the working directory every `check_interval`, warns once usage
+/// passes `warn_bytes` and shuts the session down once it reaches
+/// `max_bytes`, marking it complete with `DiskQuotaExceeded`. The task ends
+/// with the session's collector.
+pub(super) fn spawn_disk_monitor(ctx: DiskMonitorContext) {
+    tokio::spawn(async move {
+        let mut warned = false;
+        loop {
+            tokio::time::sleep(ctx.quota.check_interval).await;
+            if ctx.command_tx.is_closed() {
+                return;
+            }
+
+            let dir = ctx.dir.clone();
+            let Ok(size) = tokio::task::spawn_blocking(move || dir_size(&dir)).await else {
+                return;
+            };
+            let used = size.saturating_sub(ctx.baseline);
+            ctx.usage.store(used, Ordering::Relaxed);
+
+            if used >= ctx.quota.max_bytes {
+                ctx.events.error(
+                    "disk.quota_exceeded",
+                    format!(
+                        "Disk quota exceeded ({used} of {} bytes written), stopping session",
+                        ctx.quota.max_bytes
+                    ),
+                    serde_json::json!({ "used_bytes": used, "max_bytes": ctx.quota.max_bytes }),
+                );
+                stop_session(
+                    &ctx.command_tx,
+                    &ctx.is_complete,
+                    &ctx.termination_reason,
+                    TerminationReason::DiskQuotaExceeded,
+                )
+                .await;
+                return;
+            }
+
+            if used >= ctx.quota.warn_bytes && !warned {
+                ctx.events.warn(
+                    "disk.quota_warning",
+                    format!("Disk usage at {used} of {} bytes", ctx.quota.max_bytes),
+                    serde_json::json!({ "used_bytes": used, "max_bytes": ctx.quota.max_bytes }),
+                );
+                warned = true;
+            }
+        }
+    });
+}
+
+/// Shared state for a session's memory monitor
+pub(super) struct MemoryMonitorContext {
+    pub tracking: MemoryTracking,
+    pub usage: Arc<MemoryUsage>,
+    pub command_tx: mpsc::UnboundedSender<SessionCommand>,
+    pub is_complete: Arc<Mutex<bool>>,
+    pub termination_reason: Arc<Mutex<Option<TerminationReason>>>,
+    pub events: EventLog,
+}
+
+/// Spawn a background task sampling the RSS of a session's CLI process
+///
+/// Samples every `sample_interval`, skipping samples while no CLI process is
+/// running (e.g. during a restart). With a cap, shuts the session down once
+/// it is reached, marking it complete with `MemoryLimitExceeded`. The task
+/// ends with the session's collector.
+pub(super) fn spawn_memory_monitor(ctx: MemoryMonitorContext) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(ctx.tracking.sample_interval).await;
+            if ctx.command_tx.is_closed() {
+                return;
+            }
+
+            let pids = session_pids(ctx.events.session_id());
+            let Ok(rss) = tokio::task::spawn_blocking(move || {
+                pids.into_iter().filter_map(rss_bytes).reduce(|a, b| a + b)
+            })
+            .await
+            else {
+                return;
+            };
+            let Some(rss) = rss else {
+                continue;
+            };
+            ctx.usage.record(rss);
+
+            if let Some(max_bytes) = ctx.tracking.max_bytes
+                && rss >= max_bytes
+            {
+                ctx.events.error(
+                    "memory.limit_exceeded",
+                    format!("Memory cap exceeded ({rss} of {max_bytes} bytes resident), stopping session"),
+                    serde_json::json!({ "rss_bytes": rss, "max_bytes": max_bytes }),
+                );
+                stop_session(
+                    &ctx.command_tx,
+                    &ctx.is_complete,
+                    &ctx.termination_reason,
+                    TerminationReason::MemoryLimitExceeded,
+                )
+                .await;
+                return;
+            }
+        }
+    });
+}
+
+/// Shared state for a session's health watchdog
+pub(super) struct HealthWatchdogContext {
+    pub health: HealthMonitor,
+    pub interval: Duration,
+    pub command_tx: mpsc::UnboundedSender<SessionCommand>,
+    pub events: EventLog,
+}
+
+/// Spawn a background task flagging a CLI that is alive but silent
+///
+/// Checks the client's health every `interval` and records a warning when
+/// the CLI stops answering health pings while producing no output, and a
+/// note when it recovers. The task ends with the session's collector.
+pub(super) fn spawn_health_watchdog(ctx: HealthWatchdogContext) {
+    tokio::spawn(async move {
+        let mut silent = false;
+        loop {
+            tokio::time::sleep(ctx.interval).await;
+            if ctx.command_tx.is_closed() {
+                return;
+            }
+
+            let snapshot = ctx.health.snapshot().await;
+            if snapshot.wedged && !silent {
+                ctx.events.warn(
+                    "cli.silent",
+                    format!(
+                        "CLI is running but silent for {}ms and not answering pings",
+                        snapshot.idle.as_millis()
+                    ),
+                    serde_json::json!({
+                        "idle_ms": snapshot.idle.as_millis() as u64,
+                        "consecutive_failures": snapshot.consecutive_failures,
+                    }),
+                );
+            } else if !snapshot.wedged && silent {
+                ctx.events.info(
+                    "cli.responsive",
+                    "CLI is responding again",
+                    serde_json::Value::Null,
+                );
+            }
+            silent = snapshot.wedged;
+        }
+    });
+}
+
+/// Mark a session complete for `reason` and shut its client down
+async fn stop_session(
+    command_tx: &mpsc::UnboundedSender<SessionCommand>,
+    is_complete: &Mutex<bool>,
+    termination_reason: &Mutex<Option<TerminationReason>>,
+    reason: TerminationReason,
+) {
+    *termination_reason.lock().await = Some(reason);
+    *is_complete.lock().await = true;
+
+    let (response_tx, response_rx) = oneshot::channel();
+    if command_tx
+        .send(SessionCommand::Shutdown { drain: None, response_tx })
+        .is_ok()
+    {
+        let _ = response_rx.await;
+    }
+}
@@ -0,0 +1,77 @@
+//! Time source for the agent manager
+//!
+//! Working detection, runtimes, transcript timestamps and completed-session
+//! retention all read the time through a [`Clock`], so tests can drive them
+//! deterministically instead of sleeping through real thresholds.
+
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// Source of monotonic and wall-clock time
+pub trait Clock: Debug + Send + Sync {
+    /// Current monotonic time
+    fn now(&self) -> Instant;
+
+    /// Current wall-clock time
+    fn utc_now(&self) -> DateTime<Utc>;
+
+    /// Time elapsed since `earlier`, zero if `earlier` is in the future
+    fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+/// The real system clock (default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock following tokio's time, for tests
+///
+/// Under `tokio::time::pause()` both monotonic and wall-clock time stand
+/// still until `tokio::time::advance` moves them; wall-clock time starts at
+/// the real time when the clock was created.
+#[derive(Debug, Clone, Copy)]
+pub struct TokioClock {
+    started: tokio::time::Instant,
+    started_utc: DateTime<Utc>,
+}
+
+impl TokioClock {
+    /// Create a clock anchored at the current time
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            started: tokio::time::Instant::now(),
+            started_utc: Utc::now(),
+        }
+    }
+}
+
+impl Default for TokioClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        let elapsed = tokio::time::Instant::now().duration_since(self.started);
+        self.started_utc + chrono::Duration::from_std(elapsed).unwrap_or_default()
+    }
+}
@@ -3,6 +3,8 @@
 //! Defines the command messages that can be sent to agent background tasks
 //! via channels for non-blocking communication.
 
+use std::time::Duration;
+
 use tokio::sync::oneshot;
 
 use crate::error::Result;
@@ -21,8 +23,29 @@ pub(super) enum SessionCommand {
         response_tx: oneshot::Sender<Result<()>>,
     },
 
+    /// Interrupt the current turn, wait for it to stop, then send a prompt
+    InterruptAndSend {
+        /// The redirecting prompt text to send
+        prompt: String,
+        /// Channel to send the operation result back
+        response_tx: oneshot::Sender<Result<()>>,
+    },
+
+    /// Inject operator guidance as a synthetic user message
+    InjectNote {
+        /// The note text as provided by the operator
+        note: String,
+        /// The note rendered with the manager's note format (sent to the agent)
+        formatted: String,
+        /// Channel to send the operation result back
+        response_tx: oneshot::Sender<Result<()>>,
+    },
+
     /// Shutdown the agent session gracefully
     Shutdown {
+        /// How long to let a turn the agent is writing finish first; `None`
+        /// closes at once
+        drain: Option<Duration>,
         /// Channel to send the shutdown confirmation back
         response_tx: oneshot::Sender<Result<()>>,
     },
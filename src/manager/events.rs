@@ -0,0 +1,87 @@
+//! Operational event log of a session
+//!
+//! The manager records what happens to each session — spawn, CLI restarts,
+//! transcript truncation, budget and resource limits, skipped output — as
+//! severity-tagged [`SessionEvent`]s, kept apart from the transcript.
+//! Every event is also written to the `log` facade.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde_json::Value;
+
+use super::clock::Clock;
+use crate::types::agent::{EventSeverity, SessionEvent};
+
+/// Events kept per session; the oldest are dropped beyond this
+const EVENT_LOG_CAPACITY: usize = 500;
+
+/// Bounded, shared event log of one session
+#[derive(Clone)]
+pub(super) struct EventLog {
+    session_id: String,
+    events: Arc<Mutex<VecDeque<SessionEvent>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl EventLog {
+    /// Create an empty log for `session_id`
+    pub fn new(session_id: &str, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            events: Arc::new(Mutex::new(VecDeque::new())),
+            clock,
+        }
+    }
+
+    /// ID of the session the log belongs to
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Record an informational event
+    pub fn info(&self, name: &str, body: impl Into<String>, attributes: Value) {
+        self.record(EventSeverity::Info, name, body.into(), attributes);
+    }
+
+    /// Record a warning
+    pub fn warn(&self, name: &str, body: impl Into<String>, attributes: Value) {
+        self.record(EventSeverity::Warn, name, body.into(), attributes);
+    }
+
+    /// Record an error
+    pub fn error(&self, name: &str, body: impl Into<String>, attributes: Value) {
+        self.record(EventSeverity::Error, name, body.into(), attributes);
+    }
+
+    /// Events recorded so far, oldest first
+    pub fn snapshot(&self) -> Vec<SessionEvent> {
+        self.events().iter().cloned().collect()
+    }
+
+    fn record(&self, severity: EventSeverity, name: &str, body: String, attributes: Value) {
+        let level = match severity {
+            EventSeverity::Info => log::Level::Info,
+            EventSeverity::Warn => log::Level::Warn,
+            EventSeverity::Error => log::Level::Error,
+        };
+        log::log!(level, "[{}] {name}: {body}", self.session_id);
+
+        let event = SessionEvent {
+            timestamp: self.clock.utc_now(),
+            severity,
+            name: name.to_string(),
+            body,
+            attributes,
+        };
+        let mut events = self.events();
+        if events.len() == EVENT_LOG_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    fn events(&self) -> MutexGuard<'_, VecDeque<SessionEvent>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -2,11 +2,11 @@
 //!
 //! Pure functions for converting and extracting data from messages.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::VecDeque;
 
 use crate::types::agent::SerializedMessage;
-use crate::types::messages::{ContentBlock, Message};
+use crate::types::messages::{ContentBlock, Message, UserContent, UserMessageContent};
 
 /// Convert a Message enum to `SerializedMessage` for storage
 ///
@@ -16,9 +16,11 @@ use crate::types::messages::{ContentBlock, Message};
 /// # Arguments
 /// * `msg` - The message to serialize
 ///
+/// * `timestamp` - When the message was received
+///
 /// # Returns
 /// A `SerializedMessage` with type, content, turn, and timestamp
-pub(super) fn serialize_message(msg: &Message) -> SerializedMessage {
+pub(super) fn serialize_message(msg: &Message, timestamp: DateTime<Utc>) -> SerializedMessage {
     let (message_type, turn) = match msg {
         Message::User { .. } => ("user".to_string(), 0),
         Message::Assistant { .. } => ("assistant".to_string(), 0),
@@ -34,10 +36,33 @@ pub(super) fn serialize_message(msg: &Message) -> SerializedMessage {
         message_type,
         content,
         turn,
-        timestamp: Utc::now(),
+        timestamp,
+        prev_hash: None,
+        hash: None,
     }
 }
 
+/// Convert a prompt sent to the agent to a user `SerializedMessage`
+///
+/// The CLI does not echo prompts back, so the manager records them itself
+/// to keep the user side of the conversation in the transcript.
+pub(super) fn prompt_message(prompt: &str, timestamp: DateTime<Utc>) -> SerializedMessage {
+    let msg = Message::User {
+        parent_tool_use_id: None,
+        message: UserMessageContent {
+            role: "user".to_string(),
+            content: Some(UserContent::String(prompt.to_string())),
+        },
+        session_id: None,
+    };
+    serialize_message(&msg, timestamp)
+}
+
+/// Hash of the latest entry in a transcript buffer
+pub(super) fn transcript_head(messages: &VecDeque<SerializedMessage>) -> Option<String> {
+    messages.back().and_then(|msg| msg.hash.clone())
+}
+
 /// Extract last N lines of text from assistant messages
 ///
 /// Scans messages in reverse chronological order, filters for assistant
@@ -78,3 +103,104 @@ pub(super) fn extract_last_output_lines(
         .take(n)
         .collect()
 }
+
+/// Extract the text of the most recent result message
+///
+/// # Arguments
+/// * `messages` - The message buffer to scan
+///
+/// # Returns
+/// The `result` field of the last result message, if any
+pub(super) fn extract_final_result(messages: &VecDeque<SerializedMessage>) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|msg| msg.message_type == "result")
+        .and_then(|msg| msg.content.get("result"))
+        .and_then(|result| result.as_str())
+        .map(String::from)
+}
+
+/// Extract the session cost reported by the most recent result message
+///
+/// # Arguments
+/// * `messages` - The message buffer to scan
+///
+/// # Returns
+/// The `total_cost_usd` field of the last result message, or 0 if none reported one
+pub(super) fn extract_total_cost(messages: &VecDeque<SerializedMessage>) -> f64 {
+    messages
+        .iter()
+        .rev()
+        .filter(|msg| msg.message_type == "result")
+        .find_map(|msg| msg.content.get("total_cost_usd"))
+        .and_then(serde_json::Value::as_f64)
+        .unwrap_or(0.0)
+}
+
+/// Heading introducing replayed conversation turns
+const HISTORY_HEADING: &str = "## Conversation so far";
+
+/// Render the user and assistant turns of a transcript as text
+///
+/// Text blocks are kept; tool calls are reduced to the tool's name and tool
+/// results are left out.
+///
+/// # Returns
+/// The rendered turns, or `None` if the transcript has no text to replay
+pub(super) fn render_history(messages: &[SerializedMessage]) -> Option<String> {
+    let mut rendered = String::new();
+    for msg in messages {
+        let (speaker, text) = match serde_json::from_value::<Message>(msg.content.clone()) {
+            Ok(Message::User { message, .. }) => {
+                let text = match message.content {
+                    Some(UserContent::String(text)) => text,
+                    Some(UserContent::Blocks(blocks)) => blocks_text(&blocks),
+                    None => String::new(),
+                };
+                ("User", text)
+            }
+            Ok(Message::Assistant { message, .. }) => ("Assistant", blocks_text(&message.content)),
+            _ => continue,
+        };
+        if !text.trim().is_empty() {
+            rendered.push_str(&format!("\n**{speaker}:** {}\n", text.trim()));
+        }
+    }
+
+    (!rendered.is_empty()).then(|| format!("{HISTORY_HEADING}\n{rendered}"))
+}
+
+/// Render a live message for a terminal attached to the session
+///
+/// Shows assistant text and tool calls, and marks the end of each turn.
+///
+/// # Returns
+/// Text to print, or `None` for messages not shown on the terminal
+pub(super) fn render_for_terminal(msg: &SerializedMessage) -> Option<String> {
+    match serde_json::from_value::<Message>(msg.content.clone()).ok()? {
+        Message::Assistant { message, .. } => {
+            let text = blocks_text(&message.content);
+            (!text.is_empty()).then(|| format!("{text}\n"))
+        }
+        Message::Result { is_error, .. } => Some(if is_error {
+            "[turn failed]\n".to_string()
+        } else {
+            "[turn complete]\n".to_string()
+        }),
+        _ => None,
+    }
+}
+
+/// Text of a message's content blocks
+fn blocks_text(blocks: &[ContentBlock]) -> String {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.clone()),
+            ContentBlock::ToolUse { name, .. } => Some(format!("[called tool {name}]")),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
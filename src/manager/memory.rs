@@ -0,0 +1,159 @@
+//! Memory usage tracking for session CLI processes
+//!
+//! With [`MemoryTracking`], the manager samples the resident set size (RSS)
+//! of a session's CLI process on an interval, reports the latest and peak
+//! values in `AgentInfo` and, when a cap is set, stops the session once the
+//! process grows past it.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Default interval between memory samples (5 seconds)
+const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Memory sampling (and optional cap) for a session's CLI process
+///
+/// Only local subprocess sessions are sampled. Usage is the RSS of the CLI
+/// process itself; processes started by its tools are not counted.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use kodegen_claude_agent::manager::MemoryTracking;
+///
+/// let tracking = MemoryTracking::new()
+///     .cap(2 * 1024 * 1024 * 1024)
+///     .sample_every(Duration::from_secs(1));
+/// assert_eq!(tracking.max_bytes, Some(2 * 1024 * 1024 * 1024));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryTracking {
+    /// RSS at which the session is stopped (None to only track)
+    pub max_bytes: Option<u64>,
+    /// Interval between samples
+    pub sample_interval: Duration,
+}
+
+impl MemoryTracking {
+    /// Track memory every 5 seconds, without a cap
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_bytes: None,
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+        }
+    }
+
+    /// Stop the session once its RSS reaches `bytes`
+    #[must_use]
+    pub const fn cap(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the interval between samples
+    #[must_use]
+    pub const fn sample_every(mut self, interval: Duration) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+}
+
+impl Default for MemoryTracking {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Latest and peak RSS of a session's CLI process
+#[derive(Debug, Default)]
+pub(super) struct MemoryUsage {
+    current: AtomicU64,
+    peak: AtomicU64,
+}
+
+impl MemoryUsage {
+    /// Record a sample
+    pub fn record(&self, bytes: u64) {
+        self.current.store(bytes, Ordering::Relaxed);
+        self.peak.fetch_max(bytes, Ordering::Relaxed);
+    }
+
+    /// Most recent sample
+    pub fn current(&self) -> u64 {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Largest sample so far
+    pub fn peak(&self) -> u64 {
+        self.peak.load(Ordering::Relaxed)
+    }
+}
+
+/// Resident set size of process `pid` in bytes
+///
+/// Returns None if the process is gone or its memory cannot be read.
+/// Blocks while reading procfs or running the platform's process tool.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(super) fn rss_bytes(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let kib = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Resident set size of process `pid` in bytes
+///
+/// Returns None if the process is gone or its memory cannot be read.
+/// Blocks while reading procfs or running the platform's process tool.
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+pub(super) fn rss_bytes(pid: u32) -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let kib = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Resident set size of process `pid` in bytes
+///
+/// Returns None if the process is gone or its memory cannot be read.
+/// Blocks while reading procfs or running the platform's process tool.
+#[cfg(windows)]
+pub(super) fn rss_bytes(pid: u32) -> Option<u64> {
+    // One CSV row: "image","pid","session","#","12,345 K"
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let kib: String = stdout
+        .trim()
+        .rsplit("\",\"")
+        .next()?
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    Some(kib.parse::<u64>().ok()? * 1024)
+}
+
+/// Resident set size of process `pid` in bytes (unsupported platform)
+#[cfg(not(any(unix, windows)))]
+pub(super) fn rss_bytes(_pid: u32) -> Option<u64> {
+    None
+}
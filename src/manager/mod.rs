@@ -8,14 +8,33 @@
 //!
 //! - `agent_manager` - Core `AgentManager` with public API
 //! - `session` - Session state structures
+//! - `audit` - Tamper-evident transcript hash chain
+//! - `clock` - Injectable time source
+//! - `projects` - Export in Claude Code's "projects" format
+//! - `quota` - Disk quotas for session working directories
+//! - `memory` - Memory tracking for session CLI processes
+//! - `events` - Operational event log of each session
 //! - `commands` - Command protocol for agent communication
 //! - `background` - Background task spawning
 //! - `helpers` - Pure helper functions for message processing
 
 mod agent_manager;
+mod audit;
+mod clock;
 mod background;
 mod commands;
+mod events;
 mod helpers;
+mod memory;
+mod projects;
+mod quota;
 mod session;
 
-pub use agent_manager::{AgentManager, SpawnSessionRequest};
+pub use agent_manager::{
+    AgentManager, DETACH_COMMAND, PermissionCallback, SpawnSessionRequest, Swarm, SwarmStage,
+};
+pub use audit::{GENESIS_HASH, entry_hash, verify_transcript};
+pub use clock::{Clock, SystemClock, TokioClock};
+pub use memory::MemoryTracking;
+pub use projects::{ProjectsTranscript, claude_config_dir, project_dir_name};
+pub use quota::DiskQuota;
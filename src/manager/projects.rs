@@ -0,0 +1,186 @@
+//! Export of session transcripts in Claude Code's "projects" format
+//!
+//! Claude Code keeps every interactive conversation as a JSON Lines file at
+//! `~/.claude/projects/<project>/<session-id>.jsonl`, where `<project>` is the
+//! working directory with every non-alphanumeric character replaced by `-`.
+//! Writing a managed session there lets a user continue it with
+//! `claude --resume <session-id>` in their own terminal.
+//!
+//! Only user and assistant messages are part of that format; system, result
+//! and stream event messages are left out. Each entry links to the one
+//! before it through `parentUuid`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::error::{ClaudeError, Result};
+use crate::types::agent::SerializedMessage;
+
+/// A session transcript converted to Claude Code's on-disk format
+#[derive(Debug, Clone)]
+pub struct ProjectsTranscript {
+    /// Session ID to resume the conversation with
+    pub session_id: String,
+    /// Working directory the conversation belongs to
+    pub cwd: PathBuf,
+    /// Conversation entries, one JSON object per line of the file
+    pub entries: Vec<Value>,
+}
+
+impl ProjectsTranscript {
+    /// Convert recorded session messages
+    ///
+    /// The session ID and working directory are taken from the CLI's init
+    /// message; `cwd` overrides the directory, and a fresh session ID is
+    /// generated when the init message is no longer in the buffer.
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` if no working directory is given
+    /// or recorded
+    pub fn from_messages(messages: &[SerializedMessage], cwd: Option<&Path>) -> Result<Self> {
+        let init = messages
+            .iter()
+            .find(|msg| msg.message_type == "system_init")
+            .map(|msg| &msg.content);
+
+        let cwd = cwd
+            .map(Path::to_path_buf)
+            .or_else(|| {
+                init.and_then(|c| c.get("cwd"))
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+            })
+            .ok_or_else(|| {
+                ClaudeError::invalid_config(
+                    "Session has no recorded working directory; pass one to export it",
+                )
+            })?;
+        let session_id = init
+            .and_then(|c| c.get("session_id"))
+            .and_then(Value::as_str)
+            .map_or_else(|| uuid::Uuid::new_v4().to_string(), str::to_string);
+        let cwd_str = cwd.to_string_lossy().into_owned();
+
+        let mut entries = Vec::new();
+        let mut parent: Option<String> = None;
+        for msg in messages {
+            let Some(message) = entry_message(msg) else {
+                continue;
+            };
+            let uuid = uuid::Uuid::new_v4().to_string();
+            entries.push(json!({
+                "parentUuid": parent,
+                "isSidechain": false,
+                "userType": "external",
+                "cwd": cwd_str,
+                "sessionId": session_id,
+                "type": msg.message_type,
+                "message": message,
+                "uuid": uuid,
+                "timestamp": msg.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            }));
+            parent = Some(uuid);
+        }
+
+        Ok(Self {
+            session_id,
+            cwd,
+            entries,
+        })
+    }
+
+    /// Give the transcript another session ID
+    #[must_use]
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = session_id.into();
+        for entry in &mut self.entries {
+            entry["sessionId"] = Value::String(self.session_id.clone());
+        }
+        self
+    }
+
+    /// Path of the transcript file, relative to the Claude config directory
+    #[must_use]
+    pub fn relative_path(&self) -> PathBuf {
+        PathBuf::from("projects")
+            .join(project_dir_name(&self.cwd))
+            .join(format!("{}.jsonl", self.session_id))
+    }
+
+    /// The transcript as JSON Lines
+    #[must_use]
+    pub fn to_jsonl(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{entry}\n"))
+            .collect()
+    }
+
+    /// Write the transcript under `claude_dir` (usually `~/.claude`)
+    ///
+    /// An existing file is left untouched: when the CLI ran on this machine
+    /// it already recorded the conversation itself. Returns the file's path.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file cannot be written
+    pub fn write(&self, claude_dir: &Path) -> Result<PathBuf> {
+        let path = claude_dir.join(self.relative_path());
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => file.write_all(self.to_jsonl().as_bytes())?,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e.into()),
+        }
+        Ok(path)
+    }
+}
+
+/// Claude Code's config directory: `$CLAUDE_CONFIG_DIR`, else `~/.claude`
+#[must_use]
+pub fn claude_config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("CLAUDE_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".claude"))
+}
+
+/// Name of the projects subdirectory for a working directory
+#[must_use]
+pub fn project_dir_name(cwd: &Path) -> String {
+    cwd.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// The API message stored for a user or assistant transcript entry
+fn entry_message(msg: &SerializedMessage) -> Option<Value> {
+    let message = msg.content.get("message")?;
+    match msg.message_type.as_str() {
+        "user" => Some(json!({
+            "role": "user",
+            "content": message.get("content").cloned().unwrap_or_else(|| json!("")),
+        })),
+        "assistant" => Some(json!({
+            "id": format!("msg_{}", uuid::Uuid::new_v4().simple()),
+            "type": "message",
+            "role": "assistant",
+            "model": message.get("model").cloned().unwrap_or(Value::Null),
+            "content": message.get("content").cloned().unwrap_or_else(|| json!([])),
+            "stop_reason": Value::Null,
+            "stop_sequence": Value::Null,
+        })),
+        _ => None,
+    }
+}
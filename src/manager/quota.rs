@@ -0,0 +1,87 @@
+//! Disk quotas for session working directories
+//!
+//! A runaway agent can fill the host disk by writing to its working
+//! directory. With a [`DiskQuota`], the manager periodically measures how
+//! much the directory has grown since the session started, logs a warning
+//! past the warning threshold and stops the session past the limit.
+
+use std::path::Path;
+use std::time::Duration;
+
+/// Default interval between disk usage checks (5 seconds)
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Limit on how much a session may write to its working directory
+///
+/// Usage is the growth of the directory's total file size since the session
+/// was spawned, so files already present do not count. Only local working
+/// directories are measured.
+///
+/// # Example
+///
+/// ```
+/// use kodegen_claude_agent::manager::DiskQuota;
+///
+/// let quota = DiskQuota::new(1_000_000_000);
+/// assert_eq!(quota.warn_bytes, 800_000_000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskQuota {
+    /// Usage at which the session is stopped
+    pub max_bytes: u64,
+    /// Usage at which a warning is logged
+    pub warn_bytes: u64,
+    /// Interval between usage checks
+    pub check_interval: Duration,
+}
+
+impl DiskQuota {
+    /// Quota of `max_bytes`, warning at 80% and checking every 5 seconds
+    #[must_use]
+    pub const fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            warn_bytes: max_bytes / 5 * 4,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+        }
+    }
+
+    /// Set the usage at which a warning is logged
+    #[must_use]
+    pub const fn warn_at(mut self, bytes: u64) -> Self {
+        self.warn_bytes = bytes;
+        self
+    }
+
+    /// Set the interval between usage checks
+    #[must_use]
+    pub const fn check_every(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+}
+
+/// Total size of the files under `dir`
+///
+/// Symlinks are not followed and unreadable entries are skipped. Blocks
+/// while walking the tree.
+pub(super) fn dir_size(dir: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    total
+}
@@ -3,13 +3,38 @@
 //! Defines the data structures for tracking active and completed agent sessions.
 
 use chrono::{DateTime, Utc};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::time::Instant;
-use tokio::sync::{Mutex, mpsc, broadcast};
+use tokio::sync::{Mutex, mpsc, broadcast, watch};
 
 use super::commands::SessionCommand;
-use crate::types::agent::SerializedMessage;
+use super::events::EventLog;
+use super::helpers::transcript_head;
+use super::memory::MemoryUsage;
+use crate::client::HealthMonitor;
+use crate::mcp::Blackboard;
+use crate::transport::MetricsRecorder;
+use crate::types::agent::{
+    SerializedMessage, SessionEvent, SessionGrade, SessionGroup, TerminateResponse,
+    TerminationReason,
+};
+
+/// Lifecycle state of a managed session
+///
+/// Transitions only move forward (`Active` → `Terminating` → `Completed`), so
+/// exactly one caller performs the shutdown of a session and everyone else
+/// waits for its completed record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SessionState {
+    /// Session accepts commands
+    Active,
+    /// A terminate is in progress; new commands are rejected
+    Terminating,
+    /// Session has been moved to the completed set
+    Completed,
+}
 
 /// Active session data (stored while client is running)
 ///
@@ -46,6 +71,77 @@ pub(super) struct AgentSessionInfo {
 
     /// Whether the session has completed
     pub is_complete: Arc<Mutex<bool>>,
+
+    /// Control channel health of the underlying client
+    pub health: HealthMonitor,
+
+    /// Whether the underlying client has a turn in progress
+    pub turn_active: watch::Receiver<bool>,
+
+    /// Traffic counters of the session's transport, if it collects them
+    pub metrics: Option<MetricsRecorder>,
+
+    /// Grade assigned by `grade_output`
+    pub grade: Arc<Mutex<Option<SessionGrade>>>,
+
+    /// Lifecycle state, shared by every clone of this session
+    pub state: Arc<watch::Sender<SessionState>>,
+
+    /// Session group this session belongs to
+    pub group_id: Option<String>,
+
+    /// Whether a terminal is attached and owns the session's input
+    pub attached: Arc<AtomicBool>,
+
+    /// Held while the session exists; its CLI processes are orphans once
+    /// every clone is dropped
+    pub _lease: Arc<()>,
+
+    /// Bytes written to the working directory, when a disk quota is set
+    pub disk_usage: Option<Arc<AtomicU64>>,
+
+    /// RSS samples of the CLI process, when memory is tracked
+    pub memory: Option<Arc<MemoryUsage>>,
+
+    /// Why the manager stopped the session on its own
+    pub termination_reason: Arc<Mutex<Option<TerminationReason>>>,
+
+    /// Operational events of the session
+    pub events: EventLog,
+}
+
+impl AgentSessionInfo {
+    /// Whether the session still accepts commands
+    pub fn is_active(&self) -> bool {
+        *self.state.borrow() == SessionState::Active
+    }
+
+    /// Move the session from `Active` to `Terminating`
+    ///
+    /// Returns `false` if another caller already started terminating it.
+    pub fn begin_terminate(&self) -> bool {
+        self.state.send_if_modified(|state| {
+            if *state == SessionState::Active {
+                *state = SessionState::Terminating;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Mark the session as moved to the completed set
+    pub fn finish_terminate(&self) {
+        self.state.send_replace(SessionState::Completed);
+    }
+
+    /// Wait until the terminating caller has moved the session to the completed set
+    pub async fn wait_completed(&self) {
+        let mut state_rx = self.state.subscribe();
+        let _ = state_rx
+            .wait_for(|state| *state == SessionState::Completed)
+            .await;
+    }
 }
 
 /// Completed session data (retained for final reads before cleanup)
@@ -71,4 +167,49 @@ pub(super) struct CompletedAgentSession {
 
     /// When the session completed (wall-clock time)
     pub completed_at: DateTime<Utc>,
+
+    /// Grade assigned by `grade_output`
+    pub grade: Option<SessionGrade>,
+
+    /// Final bytes written to the working directory, when a disk quota was set
+    pub disk_usage_bytes: Option<u64>,
+
+    /// Highest RSS of the CLI process, when memory was tracked
+    pub peak_memory_bytes: Option<u64>,
+
+    /// Why the manager stopped the session on its own
+    pub termination_reason: Option<TerminationReason>,
+
+    /// Final snapshot of the session's operational events
+    pub events: Vec<SessionEvent>,
+}
+
+impl CompletedAgentSession {
+    /// Final statistics reported by `terminate_session`
+    pub fn terminate_response(&self) -> TerminateResponse {
+        TerminateResponse {
+            session_id: self.session_id.clone(),
+            success: true,
+            final_turn_count: self.final_turn_count,
+            total_messages: self.messages.len(),
+            runtime_ms: self.runtime_ms,
+            transcript_head: transcript_head(&self.messages),
+            reason: self.termination_reason,
+        }
+    }
+}
+
+/// Session group data
+///
+/// Member costs are snapshotted when a member is terminated, so the group's
+/// spend stays accurate after completed sessions are cleaned up.
+pub(super) struct GroupState {
+    /// Public description of the group
+    pub group: SessionGroup,
+
+    /// Final cost in USD of members that have been terminated
+    pub final_costs: HashMap<String, f64>,
+
+    /// Key-value store shared by the members
+    pub blackboard: Blackboard,
 }
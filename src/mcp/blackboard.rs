@@ -0,0 +1,251 @@
+//! Shared key-value store for cooperating agents
+//!
+//! A [`Blackboard`] is exposed to each agent through an SDK MCP server with
+//! `get`, `set`, `claim` and `list` tools, so agents can post intermediate
+//! findings for each other, and take ownership of work such as files,
+//! without the orchestrator relaying them through prompts.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{SdkMcpServer, SdkMcpTool, ToolResult};
+
+/// Name of the MCP server serving a blackboard
+const SERVER_NAME: &str = "blackboard";
+
+/// A value posted to a blackboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlackboardEntry {
+    /// Stored value
+    pub value: Value,
+    /// Who wrote the value (a session ID for agents)
+    pub author: String,
+    /// When the value was written
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Shared key-value store
+///
+/// Clones share the same entries. Keys are listed in lexical order.
+#[derive(Debug, Clone, Default)]
+pub struct Blackboard {
+    entries: Arc<RwLock<BTreeMap<String, BlackboardEntry>>>,
+}
+
+impl Blackboard {
+    /// Create an empty blackboard
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the entry for a key
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<BlackboardEntry> {
+        self.read().get(key).cloned()
+    }
+
+    /// Store a value, replacing any previous entry for the key
+    pub fn set(&self, key: impl Into<String>, value: Value, author: impl Into<String>) {
+        let entry = BlackboardEntry {
+            value,
+            author: author.into(),
+            updated_at: Utc::now(),
+        };
+        self.write().insert(key.into(), entry);
+    }
+
+    /// Store a value unless another author holds the key
+    ///
+    /// The check and the write happen under one lock, so of several agents
+    /// claiming the same key exactly one succeeds. Claiming a key again as
+    /// its holder updates the value.
+    ///
+    /// # Errors
+    /// Returns the entry of the other author holding the key
+    pub fn claim(
+        &self,
+        key: impl Into<String>,
+        value: Value,
+        author: impl Into<String>,
+    ) -> Result<(), BlackboardEntry> {
+        let (key, author) = (key.into(), author.into());
+        let mut entries = self.write();
+        if let Some(held) = entries.get(&key)
+            && held.author != author
+        {
+            return Err(held.clone());
+        }
+        entries.insert(
+            key,
+            BlackboardEntry {
+                value,
+                author,
+                updated_at: Utc::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove the entry for a key, returning it
+    pub fn remove(&self, key: &str) -> Option<BlackboardEntry> {
+        self.write().remove(key)
+    }
+
+    /// Keys starting with `prefix` (all keys for an empty prefix)
+    #[must_use]
+    pub fn keys(&self, prefix: &str) -> Vec<String> {
+        self.read()
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Snapshot of every entry
+    #[must_use]
+    pub fn entries(&self) -> BTreeMap<String, BlackboardEntry> {
+        self.read().clone()
+    }
+
+    /// Number of entries
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether the blackboard has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// MCP server exposing this blackboard as `get`, `set`, `claim` and
+    /// `list` tools
+    ///
+    /// Values written through the server are attributed to `author`.
+    #[must_use]
+    pub fn mcp_server(&self, author: impl Into<String>) -> SdkMcpServer {
+        let author = author.into();
+        let get_board = self.clone();
+        let set_board = self.clone();
+        let claim_board = self.clone();
+        let list_board = self.clone();
+        let claim_author = author.clone();
+
+        SdkMcpServer::new(SERVER_NAME)
+            .tool(SdkMcpTool::new(
+                "get",
+                "Read a value that you or another agent posted to the shared blackboard",
+                json!({
+                    "type": "object",
+                    "properties": {"key": {"type": "string"}},
+                    "required": ["key"],
+                }),
+                move |input| {
+                    let board = get_board.clone();
+                    Box::pin(async move {
+                        let key = input["key"].as_str().unwrap_or_default();
+                        Ok(match board.get(key) {
+                            Some(entry) => ToolResult::text(
+                                serde_json::to_string_pretty(&entry).unwrap_or_default(),
+                            ),
+                            None => {
+                                ToolResult::error(format!("No blackboard entry for key: {key}"))
+                            }
+                        })
+                    })
+                },
+            ))
+            .tool(SdkMcpTool::new(
+                "set",
+                "Post a value to the shared blackboard for other agents to read",
+                json!({
+                    "type": "object",
+                    "properties": {"key": {"type": "string"}, "value": {}},
+                    "required": ["key", "value"],
+                }),
+                move |input| {
+                    let board = set_board.clone();
+                    let author = author.clone();
+                    Box::pin(async move {
+                        let Some(key) = input["key"].as_str().filter(|key| !key.is_empty()) else {
+                            return Ok(ToolResult::error("A non-empty key is required"));
+                        };
+                        board.set(key, input["value"].clone(), author);
+                        Ok(ToolResult::text(format!("Stored {key}")))
+                    })
+                },
+            ))
+            .tool(SdkMcpTool::new(
+                "claim",
+                "Take a key on the shared blackboard (e.g. `file:src/lib.rs` before editing \
+                 that file) unless another agent holds it; fails with the holder otherwise",
+                json!({
+                    "type": "object",
+                    "properties": {"key": {"type": "string"}, "value": {}},
+                    "required": ["key"],
+                }),
+                move |input| {
+                    let board = claim_board.clone();
+                    let author = claim_author.clone();
+                    Box::pin(async move {
+                        let Some(key) = input["key"].as_str().filter(|key| !key.is_empty()) else {
+                            return Ok(ToolResult::error("A non-empty key is required"));
+                        };
+                        Ok(match board.claim(key, input["value"].clone(), author) {
+                            Ok(()) => ToolResult::text(format!("Claimed {key}")),
+                            Err(held) => {
+                                ToolResult::error(format!("{key} is held by {}", held.author))
+                            }
+                        })
+                    })
+                },
+            ))
+            .tool(SdkMcpTool::new(
+                "list",
+                "List the keys on the shared blackboard",
+                json!({
+                    "type": "object",
+                    "properties": {"prefix": {"type": "string"}},
+                }),
+                move |input| {
+                    let board = list_board.clone();
+                    Box::pin(async move {
+                        let prefix = input["prefix"].as_str().unwrap_or_default();
+                        let listing: Vec<Value> = board
+                            .read()
+                            .range(prefix.to_string()..)
+                            .take_while(|(key, _)| key.starts_with(prefix))
+                            .map(|(key, entry)| {
+                                json!({
+                                    "key": key,
+                                    "author": entry.author,
+                                    "updated_at": entry.updated_at,
+                                })
+                            })
+                            .collect();
+                        Ok(ToolResult::text(
+                            serde_json::to_string_pretty(&listing).unwrap_or_default(),
+                        ))
+                    })
+                },
+            ))
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<String, BlackboardEntry>> {
+        self.entries
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<String, BlackboardEntry>> {
+        self.entries
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
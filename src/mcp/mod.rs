@@ -0,0 +1,287 @@
+//! In-process SDK MCP servers
+//!
+//! An [`SdkMcpServer`] exposes tools implemented in Rust to Claude without a
+//! separate MCP server process. The CLI forwards the server's JSON-RPC traffic
+//! over the control protocol and the client answers it in-process.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kodegen_claude_agent::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
+//! use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
+//! use serde_json::json;
+//!
+//! # async fn example() -> kodegen_claude_agent::Result<()> {
+//! let greeter = SdkMcpServer::new("greeter").tool(SdkMcpTool::new(
+//!     "greet",
+//!     "Greet someone by name",
+//!     json!({"type": "object", "properties": {"name": {"type": "string"}}}),
+//!     |input| {
+//!         Box::pin(async move {
+//!             let name = input["name"].as_str().unwrap_or("there");
+//!             Ok(ToolResult::text(format!("Hello, {name}!")))
+//!         })
+//!     },
+//! ));
+//!
+//! let options = ClaudeAgentOptions::builder()
+//!     .sdk_mcp_server(greeter)
+//!     .add_allowed_tool("mcp__greeter__greet")
+//!     .build();
+//! let client = ClaudeSDKClient::new(options, None).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Tools are addressed by Claude as `mcp__<server>__<tool>`.
+
+mod blackboard;
+
+pub use blackboard::{Blackboard, BlackboardEntry};
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::error::Result;
+use crate::types::mcp::{McpServerConfig, SdkMcpServerMarker};
+
+/// MCP protocol version answered to `initialize`
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error code for unknown methods
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for invalid parameters (including unknown tools)
+const INVALID_PARAMS: i64 = -32602;
+
+// ============================================================================
+// TOOLS
+// ============================================================================
+
+/// Async handler invoked with a tool call's arguments
+pub type ToolHandler =
+    Arc<dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<ToolResult>> + Send>> + Send + Sync>;
+
+/// Result of a tool call, in MCP `tools/call` shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolResult {
+    /// Content blocks returned to Claude
+    pub content: Vec<Value>,
+    /// Whether the call failed
+    #[serde(
+        rename = "isError",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    /// Successful result with a single text block
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![json!({"type": "text", "text": text.into()})],
+            is_error: false,
+        }
+    }
+
+    /// Failed result with a single text block explaining the failure
+    #[must_use]
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            is_error: true,
+            ..Self::text(message)
+        }
+    }
+}
+
+/// A tool served by an [`SdkMcpServer`]
+#[derive(Clone)]
+pub struct SdkMcpTool {
+    /// Tool name, unique within its server
+    pub name: String,
+    /// Description shown to Claude
+    pub description: String,
+    /// JSON Schema of the tool's arguments
+    pub input_schema: Value,
+    handler: ToolHandler,
+}
+
+impl SdkMcpTool {
+    /// Create a tool
+    ///
+    /// # Arguments
+    /// * `name` - Tool name, unique within its server
+    /// * `description` - Description shown to Claude
+    /// * `input_schema` - JSON Schema of the tool's arguments
+    /// * `handler` - Called with the arguments of each call
+    pub fn new<F>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Pin<Box<dyn Future<Output = Result<ToolResult>> + Send>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+            handler: Arc::new(handler),
+        }
+    }
+
+    /// Invoke the tool
+    ///
+    /// # Errors
+    /// Returns the handler's error
+    pub async fn call(&self, input: Value) -> Result<ToolResult> {
+        (self.handler)(input).await
+    }
+}
+
+impl std::fmt::Debug for SdkMcpTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SdkMcpTool")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("input_schema", &self.input_schema)
+            .field("handler", &"<callback>")
+            .finish()
+    }
+}
+
+// ============================================================================
+// SERVER
+// ============================================================================
+
+/// MCP server running inside the SDK process
+#[derive(Debug, Clone)]
+pub struct SdkMcpServer {
+    name: String,
+    version: String,
+    tools: Vec<SdkMcpTool>,
+}
+
+impl SdkMcpServer {
+    /// Create a server with no tools
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: "1.0.0".to_string(),
+            tools: Vec::new(),
+        }
+    }
+
+    /// Set the version reported to the CLI (default: `1.0.0`)
+    #[must_use]
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Add a tool, replacing any tool with the same name
+    #[must_use]
+    pub fn tool(mut self, tool: SdkMcpTool) -> Self {
+        self.tools.retain(|existing| existing.name != tool.name);
+        self.tools.push(tool);
+        self
+    }
+
+    /// Server name
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tools served, in the order they were added
+    #[must_use]
+    pub fn tools(&self) -> &[SdkMcpTool] {
+        &self.tools
+    }
+
+    /// Names Claude uses to call this server's tools (`mcp__<server>__<tool>`)
+    #[must_use]
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tools
+            .iter()
+            .map(|tool| format!("mcp__{}__{}", self.name, tool.name))
+            .collect()
+    }
+
+    /// Configuration entry announcing this server to the CLI
+    #[must_use]
+    pub fn config(&self) -> McpServerConfig {
+        McpServerConfig::Sdk(SdkMcpServerMarker {
+            name: self.name.clone(),
+        })
+    }
+
+    /// Handle one JSON-RPC message from the CLI
+    ///
+    /// Returns the JSON-RPC response, or `None` for notifications, which
+    /// take no response. Tool failures are reported as results with
+    /// `isError` set so Claude can see them.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let outcome = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": self.name, "version": self.version},
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({
+                "tools": self.tools.iter().map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "inputSchema": tool.input_schema,
+                })).collect::<Vec<_>>(),
+            })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+        };
+
+        Some(match outcome {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        })
+    }
+
+    /// Run a `tools/call` request
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+        let tool = self
+            .tools
+            .iter()
+            .find(|tool| tool.name == name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {name}")))?;
+
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        let result = tool
+            .call(arguments)
+            .await
+            .unwrap_or_else(|e| ToolResult::error(e.to_string()));
+        Ok(serde_json::to_value(result).unwrap_or_default())
+    }
+}
@@ -0,0 +1,450 @@
+//! Bash command inspection
+//!
+//! A [`BashGuard`] tokenizes a `Bash` tool call's `command` the way a POSIX
+//! shell splits words, honouring quotes and escapes, and checks each simple
+//! command of its pipelines and lists against executable and flag rules.
+//! Command and process substitution, command strings such as `sh -c` and
+//! `eval`, and `$` expansions in a command name or in arguments a rule
+//! inspects are blocked by default, since what they run cannot be checked.
+//!
+//! ```
+//! use kodegen_claude_agent::permissions::BashGuard;
+//!
+//! let guard = BashGuard::new()
+//!     .deny(["sudo", "curl"])
+//!     .deny_flag("rm", "-rf")
+//!     .deny_flag("git", "--force");
+//!
+//! assert!(guard.check("ls -la && git push").is_ok());
+//! assert!(guard.check("ls; rm -rf /").is_err());
+//! assert!(guard.check("echo $(cat ~/.ssh/id_rsa)").is_err());
+//! assert!(guard.check("rm${IFS}-rf${IFS}/").is_err());
+//! ```
+
+use std::sync::Arc;
+
+use super::remembering;
+use super::shell::{Word, is_assignment, parse};
+use crate::types::identifiers::ToolName;
+use crate::types::permissions::{
+    CanUseToolCallback, PermissionResult, PermissionResultAllow, PermissionResultDeny,
+};
+
+/// Commands that run the command given as their arguments, with their
+/// options that take the next word as a value
+const WRAPPERS: [(&str, &[&str]); 8] = [
+    (
+        "sudo",
+        &[
+            "-C",
+            "-D",
+            "-g",
+            "-p",
+            "-R",
+            "-r",
+            "-t",
+            "-T",
+            "-U",
+            "-u",
+            "--close-from",
+            "--chdir",
+            "--group",
+            "--prompt",
+            "--chroot",
+            "--role",
+            "--type",
+            "--command-timeout",
+            "--other-user",
+            "--user",
+        ],
+    ),
+    ("env", ENV_VALUE_OPTIONS),
+    ("nohup", &[]),
+    ("time", &["-f", "-o", "--format", "--output"]),
+    ("nice", &["-n", "--adjustment"]),
+    (
+        "xargs",
+        &[
+            "-a",
+            "-d",
+            "-E",
+            "-I",
+            "-L",
+            "-n",
+            "-P",
+            "-s",
+            "--arg-file",
+            "--delimiter",
+            "--eof",
+            "--replace",
+            "--max-lines",
+            "--max-args",
+            "--max-procs",
+            "--max-chars",
+            "--process-slot-var",
+        ],
+    ),
+    ("command", &[]),
+    ("exec", &["-a"]),
+];
+
+/// Options of `env` that take the next word as a value
+const ENV_VALUE_OPTIONS: &[&str] = &["-u", "-C", "-S", "--unset", "--chdir", "--split-string"];
+
+/// Shells that run a command string given with `-c`
+const SHELLS: [&str; 5] = ["sh", "bash", "zsh", "dash", "ksh"];
+
+/// Rules for the commands of `Bash` tool calls
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BashGuard {
+    /// Executables that may run (None = any not denied)
+    allowed: Option<Vec<String>>,
+    /// Executables that may not run
+    denied: Vec<String>,
+    /// Flags that may not be passed together, as (executable or `*`,
+    /// spellings of each flag)
+    denied_flags: Vec<(String, Vec<Vec<String>>)>,
+    /// Argument prefixes that may not be passed, as (executable or `*`, prefix)
+    denied_prefixes: Vec<(String, String)>,
+    /// Whether `$(...)`, backticks, `<(...)`, command strings and `$`
+    /// expansions in checked words are let through
+    allow_substitution: bool,
+}
+
+impl BashGuard {
+    /// Guard that only blocks command and process substitution
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only let these executables run, by name or path
+    #[must_use]
+    pub fn allow<I, S>(mut self, executables: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed
+            .get_or_insert_with(Vec::new)
+            .extend(executables.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never let these executables run, by name or path
+    #[must_use]
+    pub fn deny<I, S>(mut self, executables: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.denied.extend(executables.into_iter().map(Into::into));
+        self
+    }
+
+    /// Never pass `flag` to `executable` (`*` for any executable)
+    ///
+    /// Short flags match when combined, so denying `-rf` also catches
+    /// `-fr` and `-r -f`; long flags match with or without `=value`.
+    /// An executable followed by a subcommand, such as `git push`, only
+    /// checks the arguments after the subcommand.
+    #[must_use]
+    pub fn deny_flag(mut self, executable: impl Into<String>, flag: impl Into<String>) -> Self {
+        self.denied_flags
+            .push((executable.into(), vec![vec![flag.into()]]));
+        self
+    }
+
+    /// Never pass `executable` one flag of each of `flags` together
+    ///
+    /// Each entry lists the spellings of one flag, so recursive forced
+    /// removal in any form is denied with:
+    ///
+    /// ```
+    /// # use kodegen_claude_agent::permissions::BashGuard;
+    /// let guard = BashGuard::new()
+    ///     .deny_flags("rm", &[&["-r", "-R", "--recursive"], &["-f", "--force"]]);
+    ///
+    /// assert!(guard.check("rm -R --force build").is_err());
+    /// assert!(guard.check("rm -r build").is_ok());
+    /// ```
+    #[must_use]
+    pub fn deny_flags(mut self, executable: impl Into<String>, flags: &[&[&str]]) -> Self {
+        let flags = flags
+            .iter()
+            .map(|spellings| spellings.iter().map(ToString::to_string).collect())
+            .collect();
+        self.denied_flags.push((executable.into(), flags));
+        self
+    }
+
+    /// Never pass `executable` an argument starting with `prefix`
+    ///
+    /// Covers arguments that are not flags, such as the `+` of a forced
+    /// `git push` refspec. Subcommands are scoped as for
+    /// [`deny_flag`](Self::deny_flag).
+    #[must_use]
+    pub fn deny_arg_prefix(
+        mut self,
+        executable: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Self {
+        self.denied_prefixes
+            .push((executable.into(), prefix.into()));
+        self
+    }
+
+    /// Let command substitution (`$(...)`, backticks), process
+    /// substitution (`<(...)`, `>(...)`), command strings (`sh -c`,
+    /// `eval`, `env -S`) and `$` expansions (`$cmd`, `${IFS}`, `$'...'`)
+    /// in the words the rules check through
+    #[must_use]
+    pub const fn allow_substitution(mut self, allow: bool) -> Self {
+        self.allow_substitution = allow;
+        self
+    }
+
+    /// Check a command line, returning why it is denied
+    ///
+    /// # Errors
+    /// Returns the reason if the command has unbalanced quotes, uses blocked
+    /// substitution or expansion, or runs an executable or flag the rules deny
+    pub fn check(&self, command: &str) -> std::result::Result<(), String> {
+        let parsed = parse(command)?;
+        if parsed.substitution && !self.allow_substitution {
+            return Err("Command and process substitution are not allowed".to_string());
+        }
+        parsed
+            .commands
+            .iter()
+            .try_for_each(|words| self.check_simple(words))
+    }
+
+    /// Callback denying `Bash` calls that fail [`check`](Self::check) and
+    /// allowing every other call
+    #[must_use]
+    pub fn callback(self) -> CanUseToolCallback {
+        let guard = Arc::new(self);
+        Arc::new(move |tool_name, tool_input, _context| {
+            let result = guard
+                .decide(&tool_name, &tool_input)
+                .unwrap_or(PermissionResult::Allow(PermissionResultAllow {
+                    updated_input: None,
+                    updated_permissions: None,
+                }));
+            Box::pin(async move { Ok(result) })
+        })
+    }
+
+    /// Callback denying `Bash` calls that fail [`check`](Self::check) and
+    /// passing every other call to `next`
+    ///
+    /// `AllowAlways` and `DenyAlways` answers of `next` are remembered here,
+    /// behind the guard, so an "always allow Bash" never skips the check.
+    #[must_use]
+    pub fn callback_then(self, next: CanUseToolCallback) -> CanUseToolCallback {
+        let guard = Arc::new(self);
+        let next = remembering(next);
+        Arc::new(move |tool_name, tool_input, context| {
+            match guard.decide(&tool_name, &tool_input) {
+                Some(result) => Box::pin(async move { Ok(result) }),
+                None => next(tool_name, tool_input, context),
+            }
+        })
+    }
+
+    /// Denial for a call the guard rejects
+    fn decide(
+        &self,
+        tool_name: &ToolName,
+        tool_input: &serde_json::Value,
+    ) -> Option<PermissionResult> {
+        if tool_name.as_str() != "Bash" {
+            return None;
+        }
+        let command = tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        self.check(command).err().map(|message| {
+            PermissionResult::Deny(PermissionResultDeny {
+                message,
+                interrupt: false,
+            })
+        })
+    }
+
+    /// Check one simple command, and the command a wrapper runs
+    fn check_simple(&self, words: &[Word]) -> std::result::Result<(), String> {
+        // Skip leading `VAR=value` assignments
+        let mut words = words.iter().skip_while(|word| is_assignment(&word.text));
+        let Some(Word {
+            text: program,
+            expanded,
+        }) = words.next()
+        else {
+            return Ok(());
+        };
+        if *expanded && !self.allow_substitution {
+            return Err(format!(
+                "Command `{program}` is built by a `$` expansion, which cannot be checked"
+            ));
+        }
+        let name = program.rsplit('/').next().unwrap_or(program);
+        let named = |executable: &String| executable == name || executable == program;
+
+        if self.denied.iter().any(named) {
+            return Err(format!("Command `{name}` is not allowed"));
+        }
+        if let Some(ref allowed) = self.allowed
+            && !allowed.iter().any(named)
+        {
+            return Err(format!("Command `{name}` is not in the allowed list"));
+        }
+
+        let words: Vec<Word> = words.cloned().collect();
+        let args: Vec<String> = words.iter().map(|word| word.text.clone()).collect();
+        if !self.allow_substitution {
+            // Arguments a rule inspects must be known before the shell runs
+            let rules = self
+                .denied_flags
+                .iter()
+                .map(|(executable, _)| executable)
+                .chain(
+                    self.denied_prefixes
+                        .iter()
+                        .map(|(executable, _)| executable),
+                );
+            for executable in rules {
+                let Some(scoped) = scoped_args(executable, program, &args) else {
+                    continue;
+                };
+                if let Some(word) = words[args.len() - scoped.len()..]
+                    .iter()
+                    .find(|word| word.expanded)
+                {
+                    return Err(format!(
+                        "Argument `{}` of `{name}` is built by a `$` expansion, which cannot be checked",
+                        word.text
+                    ));
+                }
+            }
+        }
+        for (executable, flags) in &self.denied_flags {
+            let Some(scoped) = scoped_args(executable, program, &args) else {
+                continue;
+            };
+            let given: Option<Vec<&String>> = flags
+                .iter()
+                .map(|spellings| spellings.iter().find(|flag| has_flag(scoped, flag)))
+                .collect();
+            match given.as_deref() {
+                Some([flag]) => return Err(format!("Flag `{flag}` of `{name}` is not allowed")),
+                Some(given) if !given.is_empty() => {
+                    let given: Vec<String> = given.iter().map(|flag| format!("`{flag}`")).collect();
+                    return Err(format!(
+                        "Flags {} of `{name}` are not allowed together",
+                        given.join(" and ")
+                    ));
+                }
+                _ => {}
+            }
+        }
+        for (executable, prefix) in &self.denied_prefixes {
+            if let Some(arg) = scoped_args(executable, program, &args)
+                .and_then(|scoped| scoped.iter().find(|arg| arg.starts_with(prefix.as_str())))
+            {
+                return Err(format!("Argument `{arg}` of `{name}` is not allowed"));
+            }
+        }
+
+        if !self.allow_substitution && runs_command_string(name, &args) {
+            return Err(format!(
+                "`{name}` runs a command string, which cannot be checked"
+            ));
+        }
+        if let Some((_, takes_value)) = WRAPPERS.iter().find(|(wrapper, _)| *wrapper == name) {
+            self.check_simple(&words[args.len() - wrapped(&args, takes_value).len()..])?;
+        }
+        Ok(())
+    }
+}
+
+/// Arguments a rule for `executable` inspects when `program` runs with
+/// `args`, `None` if the rule does not apply
+///
+/// `executable` is a name, a path, `*`, or a name and subcommand such as
+/// `git push`, whose rule inspects the arguments after the subcommand.
+fn scoped_args<'a>(executable: &str, program: &str, args: &'a [String]) -> Option<&'a [String]> {
+    let (executable, subcommand) = match executable.split_once(' ') {
+        Some((executable, subcommand)) => (executable, Some(subcommand)),
+        None => (executable, None),
+    };
+    let name = program.rsplit('/').next().unwrap_or(program);
+    if executable != "*" && executable != name && executable != program {
+        return None;
+    }
+    match subcommand {
+        Some(subcommand) => args
+            .iter()
+            .position(|arg| arg == subcommand)
+            .map(|index| &args[index + 1..]),
+        None => Some(args),
+    }
+}
+
+/// Whether `program` run with `args` runs a command string: `sh -c`,
+/// `eval`, or `env -S`
+fn runs_command_string(program: &str, args: &[String]) -> bool {
+    match program {
+        "eval" => true,
+        "env" => {
+            let options = &args[..args.len() - wrapped(args, ENV_VALUE_OPTIONS).len()];
+            options.iter().any(|arg| {
+                arg.starts_with("--split-string")
+                    || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains('S'))
+            })
+        }
+        _ => SHELLS.contains(&program) && has_flag(args, "-c"),
+    }
+}
+
+/// Arguments of a wrapper that make up the command it runs
+///
+/// Skips the wrapper's options, and the values of those in `takes_value`,
+/// so that in `nice -n 5 sudo reboot` the command is `sudo reboot`.
+fn wrapped<'a>(args: &'a [String], takes_value: &[&str]) -> &'a [String] {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if arg == "--" {
+            return &args[index + 1..];
+        }
+        if !arg.starts_with('-') || arg == "-" {
+            break;
+        }
+        index += if takes_value.contains(&arg.as_str()) {
+            2
+        } else {
+            1
+        };
+    }
+    args.get(index..).unwrap_or_default()
+}
+
+/// Whether `args` pass `flag`
+fn has_flag(args: &[String], flag: &str) -> bool {
+    let mut args = args.iter().take_while(|arg| *arg != "--");
+    if let Some(long) = flag.strip_prefix("--") {
+        return args
+            .filter_map(|arg| arg.strip_prefix("--"))
+            .any(|arg| arg == long || arg.split_once('=').is_some_and(|(name, _)| name == long));
+    }
+    let Some(short) = flag.strip_prefix('-') else {
+        return args.any(|arg| arg == flag);
+    };
+    let given: String = args
+        .filter(|arg| arg.starts_with('-') && !arg.starts_with("--"))
+        .flat_map(|arg| arg.chars().skip(1))
+        .collect();
+    !short.is_empty() && short.chars().all(|c| given.contains(c))
+}
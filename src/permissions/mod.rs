@@ -3,7 +3,26 @@
 //! This module provides the permission system for controlling which tools
 //! Claude can use and with what parameters.
 
-use std::sync::Arc;
+pub mod bash;
+pub mod policy;
+pub mod preset;
+pub mod sandbox;
+mod shell;
+pub mod telemetry;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub use bash::BashGuard;
+pub use policy::{PermissionPolicy, PolicyAction, PolicyRule};
+pub use preset::Preset;
+pub use sandbox::PathSandbox;
+pub use telemetry::{DecisionSource, PermissionEvent, PermissionStats, ToolDecisions};
+
+use telemetry::Telemetry;
+use tokio::sync::broadcast;
 
 use crate::error::Result;
 use crate::types::identifiers::ToolName;
@@ -20,6 +39,20 @@ pub struct PermissionManager {
     allowed_tools: Option<Vec<ToolName>>,
     /// Disallowed tools
     disallowed_tools: Vec<ToolName>,
+    /// Declarative rules tried before the callback
+    policy: Option<PermissionPolicy>,
+    /// Directories tool input may refer to (None = anywhere)
+    sandbox: Option<PathSandbox>,
+    /// Answers the callback asked to remember, newest last
+    remembered: Mutex<Vec<PolicyRule>>,
+    /// Time-limited rules with their expiry, newest last
+    grants: Mutex<Vec<(Instant, PolicyRule)>>,
+    /// Most calls allowed per window, by tool
+    rate_limits: HashMap<ToolName, (usize, Duration)>,
+    /// When each rate-limited tool was last allowed, oldest first
+    tool_calls: Mutex<HashMap<ToolName, VecDeque<Instant>>>,
+    /// Announces and counts decisions
+    telemetry: Telemetry,
 }
 
 impl PermissionManager {
@@ -30,12 +63,113 @@ impl PermissionManager {
             callback: None,
             allowed_tools: None,
             disallowed_tools: Vec::new(),
+            policy: None,
+            sandbox: None,
+            remembered: Mutex::default(),
+            grants: Mutex::default(),
+            rate_limits: HashMap::new(),
+            tool_calls: Mutex::default(),
+            telemetry: Telemetry::new(),
         }
     }
 
     /// Set the permission callback
+    ///
+    /// Answers remembered from the previous callback are forgotten.
     pub fn set_callback(&mut self, callback: CanUseToolCallback) {
         self.callback = Some(callback);
+        self.clear_remembered();
+    }
+
+    /// Whether a permission callback is set
+    #[must_use]
+    pub const fn has_callback(&self) -> bool {
+        self.callback.is_some()
+    }
+
+    /// Set the policy tried before the callback (None = callback only)
+    pub fn set_policy(&mut self, policy: Option<PermissionPolicy>) {
+        self.policy = policy;
+    }
+
+    /// Whether a permission policy is set
+    #[must_use]
+    pub const fn has_policy(&self) -> bool {
+        self.policy.is_some()
+    }
+
+    /// Set the directories tool input may refer to (None = anywhere)
+    pub fn set_sandbox(&mut self, sandbox: Option<PathSandbox>) {
+        self.sandbox = sandbox;
+    }
+
+    /// First path in `tool_input` outside the sandbox, if one is set
+    ///
+    /// Resolving canonicalizes each path component, so it runs on the
+    /// blocking pool rather than the async runtime.
+    pub async fn sandbox_violation(&self, tool_input: &serde_json::Value) -> Option<PathBuf> {
+        let sandbox = self.sandbox.clone()?;
+        let tool_input = tool_input.clone();
+        match tokio::task::spawn_blocking(move || sandbox.violation(&tool_input)).await {
+            Ok(violation) => violation,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            // The runtime is shutting down; nothing will be answered
+            Err(_) => None,
+        }
+    }
+
+    /// Answers remembered from `AllowAlways` and `DenyAlways` results
+    #[must_use]
+    pub fn remembered(&self) -> Vec<PolicyRule> {
+        self.remembered_rules().clone()
+    }
+
+    /// Forget remembered answers, so the callback is asked again
+    pub fn clear_remembered(&self) {
+        self.remembered_rules().clear();
+    }
+
+    /// Apply `rule` for the next `ttl`, before the policy and the callback
+    ///
+    /// Grants are tried newest first. Once a grant expires, calls it covered
+    /// go back to the policy and callback. An `ask` grant defers to them.
+    pub fn grant(&self, rule: PolicyRule, ttl: Duration) {
+        self.active_grants_mut().push((Instant::now() + ttl, rule));
+    }
+
+    /// Allow every use of `tool` (`|`-separated alternatives, or `*`) for the
+    /// next `ttl`
+    pub fn grant_tool(&self, tool: impl Into<String>, ttl: Duration) {
+        self.grant(
+            PolicyRule {
+                tool: tool.into(),
+                input: BTreeMap::new(),
+                action: PolicyAction::Allow,
+                message: None,
+            },
+            ttl,
+        );
+    }
+
+    /// Unexpired grants with the time each has left, oldest first
+    #[must_use]
+    pub fn grants(&self) -> Vec<(PolicyRule, Duration)> {
+        let now = Instant::now();
+        self.active_grants_mut()
+            .iter()
+            .map(|(expiry, rule)| (rule.clone(), expiry.duration_since(now)))
+            .collect()
+    }
+
+    /// Whether an unexpired grant covers a call
+    #[must_use]
+    pub fn has_grant_for(&self, tool_name: &str, tool_input: &serde_json::Value) -> bool {
+        self.matching_grant(tool_name, tool_input).is_some()
+    }
+
+    /// Drop every grant before it expires
+    pub fn revoke_grants(&self) {
+        self.active_grants_mut().clear();
     }
 
     /// Set allowed tools (None = all allowed)
@@ -48,6 +182,22 @@ impl PermissionManager {
         self.disallowed_tools = tools;
     }
 
+    /// Allow at most `max_calls` uses of `tool` in any `window`
+    ///
+    /// Calls beyond the limit are denied before the grants, policy and
+    /// callback are consulted. Denied calls do not count towards it.
+    pub fn set_rate_limit(&mut self, tool: ToolName, max_calls: usize, window: Duration) {
+        self.rate_limits.insert(tool, (max_calls, window));
+    }
+
+    /// Whether `tool_name` has reached its rate limit
+    #[must_use]
+    pub fn is_rate_limited(&self, tool_name: &ToolName) -> bool {
+        self.rate_limits
+            .get(tool_name)
+            .is_some_and(|&(max_calls, window)| self.recent_calls(tool_name, window) >= max_calls)
+    }
+
     /// Check if a tool can be used
     ///
     /// # Arguments
@@ -66,38 +216,227 @@ impl PermissionManager {
         tool_input: serde_json::Value,
         context: ToolPermissionContext,
     ) -> Result<PermissionResult> {
+        let (result, source) = self.check(tool_name.clone(), tool_input, context).await?;
+        self.telemetry.record(tool_name.as_str(), &result, source);
+        Ok(result)
+    }
+
+    /// Whether [`can_use_tool`](Self::can_use_tool) has anything to decide a
+    /// call with
+    ///
+    /// False until a callback, policy or covering grant is set, unless a rate
+    /// limit or the sandbox would deny the call. Callers leave such requests
+    /// for the CLI to answer.
+    pub async fn can_decide(&self, tool_name: &ToolName, tool_input: &serde_json::Value) -> bool {
+        self.has_callback()
+            || self.has_policy()
+            || self.has_grant_for(tool_name.as_str(), tool_input)
+            || self.is_rate_limited(tool_name)
+            || self.sandbox_violation(tool_input).await.is_some()
+    }
+
+    /// Subscribe to the decisions made from now on
+    ///
+    /// Slow subscribers skip older events; [`stats`](Self::stats) keeps
+    /// counting every decision.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<PermissionEvent> {
+        self.telemetry.subscribe()
+    }
+
+    /// Decisions counted since the manager was created
+    #[must_use]
+    pub fn stats(&self) -> PermissionStats {
+        self.telemetry.snapshot()
+    }
+
+    /// Decide a call, naming the check that decided
+    async fn check(
+        &self,
+        tool_name: ToolName,
+        tool_input: serde_json::Value,
+        context: ToolPermissionContext,
+    ) -> Result<(PermissionResult, DecisionSource)> {
         // Check disallowed list first
         if self.disallowed_tools.contains(&tool_name) {
-            return Ok(PermissionResult::Deny(PermissionResultDeny {
-                message: format!("Tool {} is disallowed", tool_name.as_str()),
-                interrupt: false,
-            }));
+            return Ok((
+                deny(format!("Tool {} is disallowed", tool_name.as_str())),
+                DecisionSource::DisallowedTools,
+            ));
         }
 
         // Check allowed list if set
         if let Some(ref allowed) = self.allowed_tools
             && !allowed.contains(&tool_name)
         {
-            return Ok(PermissionResult::Deny(PermissionResultDeny {
-                message: format!("Tool {} is not in allowed list", tool_name.as_str()),
-                interrupt: false,
-            }));
+            return Ok((
+                deny(format!(
+                    "Tool {} is not in allowed list",
+                    tool_name.as_str()
+                )),
+                DecisionSource::AllowedTools,
+            ));
+        }
+
+        // Deny paths outside the sandbox
+        if let Some(path) = self.sandbox_violation(&tool_input).await {
+            return Ok((
+                deny(format!(
+                    "Tool {} may not access {}: it is outside the allowed directories",
+                    tool_name.as_str(),
+                    path.display()
+                )),
+                DecisionSource::Sandbox,
+            ));
+        }
+
+        // Deny calls beyond the tool's rate limit
+        let limit = self.rate_limits.get(&tool_name).copied();
+        if let Some((max_calls, window)) = limit
+            && self.is_rate_limited(&tool_name)
+        {
+            return Ok((
+                deny(format!(
+                    "Tool {} is rate limited to {max_calls} calls per {window:?}",
+                    tool_name.as_str()
+                )),
+                DecisionSource::RateLimit,
+            ));
+        }
+
+        let (result, source) = self.decide(tool_name.clone(), tool_input, context).await?;
+        if limit.is_some() && matches!(result, PermissionResult::Allow(_)) {
+            self.tool_calls()
+                .entry(tool_name)
+                .or_default()
+                .push_back(Instant::now());
+        }
+        Ok((result, source))
+    }
+
+    /// Decide a call that passed the tool lists and rate limits
+    async fn decide(
+        &self,
+        tool_name: ToolName,
+        tool_input: serde_json::Value,
+        context: ToolPermissionContext,
+    ) -> Result<(PermissionResult, DecisionSource)> {
+        // Apply unexpired grants; `ask` falls through to the policy
+        if let Some(rule) = self.matching_grant(tool_name.as_str(), &tool_input) {
+            match rule.action {
+                PolicyAction::Allow => return Ok((allow(), DecisionSource::Grant)),
+                PolicyAction::Deny => {
+                    let message = rule.message.unwrap_or_else(|| {
+                        format!(
+                            "Tool {} is denied by a permission grant",
+                            tool_name.as_str()
+                        )
+                    });
+                    return Ok((deny(message), DecisionSource::Grant));
+                }
+                PolicyAction::Ask => {}
+            }
+        }
+
+        // Apply the policy; `ask` falls through to the callback
+        if let Some(ref policy) = self.policy {
+            let rule = policy.matching_rule(tool_name.as_str(), &tool_input);
+            match rule.map_or(policy.default, |rule| rule.action) {
+                PolicyAction::Allow => return Ok((allow(), DecisionSource::Policy)),
+                PolicyAction::Deny => {
+                    let message = rule
+                        .and_then(|rule| rule.message.clone())
+                        .unwrap_or_else(|| {
+                            format!(
+                                "Tool {} is denied by the permission policy",
+                                tool_name.as_str()
+                            )
+                        });
+                    return Ok((deny(message), DecisionSource::Policy));
+                }
+                PolicyAction::Ask if self.callback.is_none() => {
+                    return Ok((
+                        deny(format!(
+                            "Tool {} needs approval, but no permission callback is set",
+                            tool_name.as_str()
+                        )),
+                        DecisionSource::Policy,
+                    ));
+                }
+                PolicyAction::Ask => {}
+            }
+        }
+
+        // Apply answers remembered earlier in the session
+        if let Some(result) = recall(&self.remembered, tool_name.as_str(), &tool_input) {
+            return Ok((result, DecisionSource::Remembered));
         }
 
         // Invoke callback if set
         if let Some(ref callback) = self.callback {
-            callback(tool_name, tool_input, context).await
+            let result = callback(tool_name.clone(), tool_input, context).await?;
+            Ok((
+                remember(&self.remembered, &tool_name, result),
+                DecisionSource::Callback,
+            ))
         } else {
             // If there's an allowed_tools list and we've passed the check, allow it
             // Otherwise, default to allow for backward compatibility
             // Note: For stricter security, consider changing this to deny-by-default
-            Ok(PermissionResult::Allow(PermissionResultAllow {
-                updated_input: None,
-                updated_permissions: None,
-            }))
+            Ok((allow(), DecisionSource::Default))
         }
     }
 
+    /// Newest unexpired grant covering a call
+    fn matching_grant(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<PolicyRule> {
+        self.active_grants_mut()
+            .iter()
+            .rev()
+            .find(|(_, rule)| rule.matches(tool_name, tool_input))
+            .map(|(_, rule)| rule.clone())
+    }
+
+    /// Grants, with expired ones dropped
+    fn active_grants_mut(&self) -> std::sync::MutexGuard<'_, Vec<(Instant, PolicyRule)>> {
+        let mut grants = self
+            .grants
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let now = Instant::now();
+        grants.retain(|(expiry, _)| *expiry > now);
+        grants
+    }
+
+    /// Calls of `tool_name` allowed within the last `window`
+    fn recent_calls(&self, tool_name: &ToolName, window: Duration) -> usize {
+        let mut tool_calls = self.tool_calls();
+        let Some(calls) = tool_calls.get_mut(tool_name) else {
+            return 0;
+        };
+        let now = Instant::now();
+        while calls
+            .front()
+            .is_some_and(|call| now.duration_since(*call) >= window)
+        {
+            calls.pop_front();
+        }
+        calls.len()
+    }
+
+    fn tool_calls(&self) -> std::sync::MutexGuard<'_, HashMap<ToolName, VecDeque<Instant>>> {
+        self.tool_calls
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn remembered_rules(&self) -> std::sync::MutexGuard<'_, Vec<PolicyRule>> {
+        lock_rules(&self.remembered)
+    }
+
     /// Create a permission callback from a closure
     pub fn callback<F, Fut>(f: F) -> CanUseToolCallback
     where
@@ -108,6 +447,88 @@ impl PermissionManager {
     }
 }
 
+/// Wrap `callback` so that it remembers its own `AllowAlways` and
+/// `DenyAlways` answers and returns plain results
+///
+/// For callbacks composed behind checks that must see every call, such as
+/// [`BashGuard::callback_then`]: the remembered answers stand in for
+/// `callback` alone instead of skipping the checks in front of it.
+pub(crate) fn remembering(callback: CanUseToolCallback) -> CanUseToolCallback {
+    let remembered = Arc::new(Mutex::new(Vec::new()));
+    Arc::new(move |tool_name, tool_input, context| {
+        if let Some(result) = recall(&remembered, tool_name.as_str(), &tool_input) {
+            return Box::pin(async move { Ok(result) });
+        }
+        let remembered = Arc::clone(&remembered);
+        let answer = callback(tool_name.clone(), tool_input, context);
+        Box::pin(async move { Ok(remember(&remembered, &tool_name, answer.await?)) })
+    })
+}
+
+/// Remembered answer covering a call
+fn recall(
+    remembered: &Mutex<Vec<PolicyRule>>,
+    tool_name: &str,
+    tool_input: &serde_json::Value,
+) -> Option<PermissionResult> {
+    let rules = lock_rules(remembered);
+    let rule = rules
+        .iter()
+        .find(|rule| rule.matches(tool_name, tool_input))?;
+    Some(match rule.action {
+        PolicyAction::Deny => deny(rule.message.clone().unwrap_or_default()),
+        PolicyAction::Allow | PolicyAction::Ask => allow(),
+    })
+}
+
+/// Record an `AllowAlways` or `DenyAlways` answer, returning the plain
+/// result sent to the CLI
+fn remember(
+    remembered: &Mutex<Vec<PolicyRule>>,
+    tool_name: &ToolName,
+    result: PermissionResult,
+) -> PermissionResult {
+    let (action, message, input, plain) = match result {
+        PermissionResult::AllowAlways { input } => (PolicyAction::Allow, None, input, allow()),
+        PermissionResult::DenyAlways { message, input } => (
+            PolicyAction::Deny,
+            Some(message.clone()),
+            input,
+            deny(message),
+        ),
+        result => return result,
+    };
+    lock_rules(remembered).push(PolicyRule {
+        tool: tool_name.as_str().to_string(),
+        input,
+        action,
+        message,
+    });
+    plain
+}
+
+fn lock_rules(rules: &Mutex<Vec<PolicyRule>>) -> std::sync::MutexGuard<'_, Vec<PolicyRule>> {
+    rules
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Plain allow result
+const fn allow() -> PermissionResult {
+    PermissionResult::Allow(PermissionResultAllow {
+        updated_input: None,
+        updated_permissions: None,
+    })
+}
+
+/// Deny result giving `message`, without interrupting
+const fn deny(message: String) -> PermissionResult {
+    PermissionResult::Deny(PermissionResultDeny {
+        message,
+        interrupt: false,
+    })
+}
+
 impl Default for PermissionManager {
     fn default() -> Self {
         Self::new()
@@ -119,6 +540,9 @@ pub struct PermissionManagerBuilder {
     callback: Option<CanUseToolCallback>,
     allowed_tools: Option<Vec<ToolName>>,
     disallowed_tools: Vec<ToolName>,
+    policy: Option<PermissionPolicy>,
+    sandbox: Option<PathSandbox>,
+    rate_limits: HashMap<ToolName, (usize, Duration)>,
 }
 
 impl PermissionManagerBuilder {
@@ -129,9 +553,24 @@ impl PermissionManagerBuilder {
             callback: None,
             allowed_tools: None,
             disallowed_tools: Vec::new(),
+            policy: None,
+            sandbox: None,
+            rate_limits: HashMap::new(),
         }
     }
 
+    /// Set the tool lists and callback of `preset`
+    ///
+    /// Replaces the callback and tool lists set so far; call it first and
+    /// adjust the preset with later calls.
+    #[must_use]
+    pub fn preset(mut self, preset: Preset) -> Self {
+        self.allowed_tools = preset.allowed_tools();
+        self.disallowed_tools = preset.disallowed_tools();
+        self.callback = Some(preset.list_callback());
+        self
+    }
+
     /// Set the permission callback
     #[must_use]
     pub fn callback(mut self, callback: CanUseToolCallback) -> Self {
@@ -153,6 +592,27 @@ impl PermissionManagerBuilder {
         self
     }
 
+    /// Set the policy tried before the callback
+    #[must_use]
+    pub fn policy(mut self, policy: PermissionPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Set the directories tool input may refer to
+    #[must_use]
+    pub fn sandbox(mut self, sandbox: PathSandbox) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Allow at most `max_calls` uses of `tool` in any `window`
+    #[must_use]
+    pub fn rate_limit(mut self, tool: ToolName, max_calls: usize, window: Duration) -> Self {
+        self.rate_limits.insert(tool, (max_calls, window));
+        self
+    }
+
     /// Build the permission manager
     #[must_use]
     pub fn build(self) -> PermissionManager {
@@ -160,6 +620,13 @@ impl PermissionManagerBuilder {
             callback: self.callback,
             allowed_tools: self.allowed_tools,
             disallowed_tools: self.disallowed_tools,
+            policy: self.policy,
+            sandbox: self.sandbox,
+            remembered: Mutex::default(),
+            grants: Mutex::default(),
+            rate_limits: self.rate_limits,
+            tool_calls: Mutex::default(),
+            telemetry: Telemetry::new(),
         }
     }
 }
@@ -0,0 +1,193 @@
+//! Declarative permission policies
+//!
+//! A [`PermissionPolicy`] is an ordered list of rules matching a tool and,
+//! optionally, fields of its input. The first matching rule decides; `ask`
+//! defers to the permission callback. Policies can be kept in a JSON or
+//! (with the `toml` feature) TOML file:
+//!
+//! ```toml
+//! default = "ask"
+//!
+//! [[rules]]
+//! tool = "Read|Glob|Grep"
+//! action = "allow"
+//!
+//! [[rules]]
+//! tool = "Bash"
+//! input = { command = "rm *" }
+//! action = "deny"
+//! message = "No deletions"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::glob::segment_matches;
+use crate::error::{ClaudeError, Result};
+
+/// What a policy does with a tool call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum PolicyAction {
+    /// Allow without asking
+    Allow,
+    /// Deny without asking
+    Deny,
+    /// Defer to the permission callback
+    #[default]
+    Ask,
+}
+
+/// One rule of a [`PermissionPolicy`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PolicyRule {
+    /// Tool name, `|`-separated alternatives, or `*` for every tool
+    pub tool: String,
+    /// Patterns that input fields must match, by field name
+    ///
+    /// `*` matches any run of characters and `?` any one character. A field
+    /// that is missing or not a string does not match.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub input: BTreeMap<String, String>,
+    /// Action taken when the rule matches
+    pub action: PolicyAction,
+    /// Reason given when the rule denies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl PolicyRule {
+    /// Whether the rule applies to a call of `tool_name` with `tool_input`
+    #[must_use]
+    pub fn matches(&self, tool_name: &str, tool_input: &serde_json::Value) -> bool {
+        let tool_matches =
+            self.tool == "*" || self.tool.split('|').any(|tool| tool.trim() == tool_name);
+        tool_matches
+            && self.input.iter().all(|(field, pattern)| {
+                tool_input
+                    .get(field)
+                    .and_then(|value| value.as_str())
+                    .is_some_and(|value| segment_matches(pattern, value))
+            })
+    }
+}
+
+/// Ordered permission rules with a default action
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PermissionPolicy {
+    /// Action when no rule matches (default: ask)
+    #[serde(default)]
+    pub default: PolicyAction,
+    /// Rules in the order they are tried
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+impl PermissionPolicy {
+    /// Load and validate a policy from a `.json` or `.toml` file
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read, has another extension, does
+    /// not parse, or fails [`validate`](Self::validate)
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let policy = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&text),
+            Some("toml") => Self::from_toml(&text),
+            _ => Err(ClaudeError::invalid_config(
+                "Permission policy files must end in .json or .toml",
+            )),
+        };
+        policy.map_err(|e| {
+            ClaudeError::invalid_config(format!(
+                "Invalid permission policy {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Parse and validate a policy from JSON
+    ///
+    /// # Errors
+    /// Returns error if the JSON does not parse or the policy is invalid
+    pub fn from_json(text: &str) -> Result<Self> {
+        let policy: Self =
+            serde_json::from_str(text).map_err(|e| ClaudeError::invalid_config(e.to_string()))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Parse and validate a policy from TOML
+    ///
+    /// # Errors
+    /// Returns error if the TOML does not parse or the policy is invalid, or
+    /// if the crate was built without the `toml` feature
+    pub fn from_toml(text: &str) -> Result<Self> {
+        #[cfg(feature = "toml")]
+        {
+            let policy: Self =
+                toml::from_str(text).map_err(|e| ClaudeError::invalid_config(e.to_string()))?;
+            policy.validate()?;
+            Ok(policy)
+        }
+        #[cfg(not(feature = "toml"))]
+        {
+            let _ = text;
+            Err(ClaudeError::invalid_config(
+                "TOML permission policies need the `toml` feature",
+            ))
+        }
+    }
+
+    /// Check every rule, naming the first invalid one
+    ///
+    /// # Errors
+    /// Returns error for a rule with an empty tool name or alternative, or an
+    /// empty input field name
+    pub fn validate(&self) -> Result<()> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            let problem = if rule.tool.split('|').any(|tool| tool.trim().is_empty()) {
+                Some("empty tool name".to_string())
+            } else if rule.input.keys().any(String::is_empty) {
+                Some("empty input field name".to_string())
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                return Err(ClaudeError::invalid_config(format!(
+                    "rule {} (tool \"{}\"): {problem}",
+                    index + 1,
+                    rule.tool
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// First rule matching a call, if any
+    #[must_use]
+    pub fn matching_rule(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<&PolicyRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool_name, tool_input))
+    }
+
+    /// Action for a call: that of the first matching rule, or the default
+    #[must_use]
+    pub fn evaluate(&self, tool_name: &str, tool_input: &serde_json::Value) -> PolicyAction {
+        self.matching_rule(tool_name, tool_input)
+            .map_or(self.default, |rule| rule.action)
+    }
+}
@@ -0,0 +1,130 @@
+//! Ready-made permission configurations
+//!
+//! A [`Preset`] fills in a [`PermissionManagerBuilder`](super::PermissionManagerBuilder)'s
+//! tool lists and callback; later builder calls adjust it:
+//!
+//! ```
+//! use kodegen_claude_agent::permissions::{PermissionManagerBuilder, Preset};
+//!
+//! let manager = PermissionManagerBuilder::new().preset(Preset::ReadOnly).build();
+//! ```
+
+use std::sync::Arc;
+
+use super::BashGuard;
+use crate::types::identifiers::ToolName;
+use crate::types::permissions::{
+    CanUseToolCallback, PermissionResult, PermissionResultAllow, PermissionResultDeny,
+};
+
+/// Tools that only read, allowed by [`Preset::ReadOnly`]
+pub const READ_ONLY_TOOLS: [&str; 8] = [
+    "Read",
+    "Glob",
+    "Grep",
+    "LS",
+    "NotebookRead",
+    "WebFetch",
+    "WebSearch",
+    "TodoWrite",
+];
+
+/// Tools that change files or run commands, denied by [`Preset::ReadOnly`]
+pub const WRITE_TOOLS: [&str; 6] = [
+    "Write",
+    "Edit",
+    "MultiEdit",
+    "NotebookEdit",
+    "Bash",
+    "KillShell",
+];
+
+/// Executables [`Preset::Dev`] never lets `Bash` run
+pub const DEV_DENIED_COMMANDS: [&str; 7] =
+    ["sudo", "su", "doas", "shutdown", "reboot", "mkfs", "dd"];
+
+/// Ready-made permission configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// Only the [`READ_ONLY_TOOLS`]; everything else is denied
+    ReadOnly,
+    /// Every tool, with `Bash` guarded against privilege escalation,
+    /// recursive forced deletion (`-r`/`-R`/`--recursive` with
+    /// `-f`/`--force`), force pushes (`--force`, `-f`,
+    /// `--force-with-lease`, `+refspec`), and command substitution
+    Dev,
+    /// Every tool and command, without asking
+    Unrestricted,
+}
+
+impl Preset {
+    /// Tools the preset allows (None = all)
+    #[must_use]
+    pub fn allowed_tools(self) -> Option<Vec<ToolName>> {
+        match self {
+            Self::ReadOnly => Some(READ_ONLY_TOOLS.into_iter().map(ToolName::new).collect()),
+            Self::Dev | Self::Unrestricted => None,
+        }
+    }
+
+    /// Tools the preset denies
+    #[must_use]
+    pub fn disallowed_tools(self) -> Vec<ToolName> {
+        match self {
+            Self::ReadOnly => WRITE_TOOLS.into_iter().map(ToolName::new).collect(),
+            Self::Dev | Self::Unrestricted => Vec::new(),
+        }
+    }
+
+    /// Callback enforcing the preset on its own
+    ///
+    /// For callers that do not apply the preset's tool lists, such as
+    /// [`ClaudeAgentOptionsBuilder::can_use_tool`](crate::types::options::ClaudeAgentOptionsBuilder::can_use_tool):
+    /// [`ReadOnly`](Self::ReadOnly) denies every tool outside the
+    /// [`READ_ONLY_TOOLS`] here.
+    #[must_use]
+    pub fn callback(self) -> CanUseToolCallback {
+        match self {
+            Self::ReadOnly => Arc::new(|tool_name, _tool_input, _context| {
+                let result = if READ_ONLY_TOOLS.contains(&tool_name.as_str()) {
+                    allow()
+                } else {
+                    PermissionResult::Deny(PermissionResultDeny {
+                        message: format!("Tool {} is not read-only", tool_name.as_str()),
+                        interrupt: false,
+                    })
+                };
+                Box::pin(async move { Ok(result) })
+            }),
+            Self::Dev | Self::Unrestricted => self.list_callback(),
+        }
+    }
+
+    /// Callback deciding what the tool lists let through
+    ///
+    /// A [`PermissionManagerBuilder`](super::PermissionManagerBuilder)
+    /// applies the lists, which later builder calls may widen.
+    pub(super) fn list_callback(self) -> CanUseToolCallback {
+        match self {
+            Self::Dev => BashGuard::new()
+                .deny(DEV_DENIED_COMMANDS)
+                .deny_flags("rm", &[&["-r", "-R", "--recursive"], &["-f", "--force"]])
+                .deny_flag("git", "--force")
+                .deny_flag("git push", "-f")
+                .deny_flag("git push", "--force-with-lease")
+                .deny_arg_prefix("git push", "+")
+                .callback(),
+            Self::ReadOnly | Self::Unrestricted => {
+                Arc::new(|_tool_name, _tool_input, _context| Box::pin(async { Ok(allow()) }))
+            }
+        }
+    }
+}
+
+/// Answer allowing a call unchanged
+fn allow() -> PermissionResult {
+    PermissionResult::Allow(PermissionResultAllow {
+        updated_input: None,
+        updated_permissions: None,
+    })
+}
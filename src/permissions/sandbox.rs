@@ -0,0 +1,144 @@
+//! Path sandbox for tool input
+//!
+//! A [`PathSandbox`] denies tool calls whose `file_path`, `notebook_path` or
+//! `path` field, or any path-like argument of a Bash `command`, resolves
+//! outside the session directory and its additional directories. Paths are
+//! resolved with symlinks followed, so a link inside the sandbox pointing out
+//! of it does not escape. Words of a command are checked heuristically; the
+//! sandbox is a guard against mistakes, not a substitute for OS isolation.
+
+use std::path::{Component, Path, PathBuf};
+
+use super::shell::{is_assignment, parse};
+
+/// Input fields holding a path
+const PATH_FIELDS: [&str; 3] = ["file_path", "notebook_path", "path"];
+
+/// Directories tool input may refer to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSandbox {
+    /// Directory relative paths resolve against
+    base: PathBuf,
+    /// Resolved allowed directories, `base` first
+    roots: Vec<PathBuf>,
+}
+
+impl PathSandbox {
+    /// Sandbox allowing `base` and `add_dirs`
+    ///
+    /// Relative `add_dirs` resolve against `base`, as the CLI's `--add-dir`
+    /// does for its working directory.
+    pub fn new<I, P>(base: impl Into<PathBuf>, add_dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let base = resolve(&base.into());
+        let roots = std::iter::once(base.clone())
+            .chain(
+                add_dirs
+                    .into_iter()
+                    .map(|dir| resolve(&base.join(dir.into()))),
+            )
+            .collect();
+        Self { base, roots }
+    }
+
+    /// Resolved allowed directories, the base directory first
+    #[must_use]
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Whether `path`, resolved against the base directory, is inside the
+    /// sandbox
+    #[must_use]
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        let path = resolve(&self.base.join(expand_home(path.as_ref())));
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    /// First path in `tool_input` that resolves outside the sandbox
+    #[must_use]
+    pub fn violation(&self, tool_input: &serde_json::Value) -> Option<PathBuf> {
+        let fields = PATH_FIELDS
+            .iter()
+            .filter_map(|field| tool_input.get(*field).and_then(|v| v.as_str()));
+        let words = tool_input
+            .get("command")
+            .and_then(|v| v.as_str())
+            .into_iter()
+            .flat_map(command_paths);
+        fields
+            .map(str::to_string)
+            .chain(words)
+            .map(|path| resolve(&self.base.join(expand_home(Path::new(&path)))))
+            .find(|path| !self.roots.iter().any(|root| path.starts_with(root)))
+    }
+}
+
+/// Argument and redirection words of a shell command that look like paths
+///
+/// The executable of each command is not checked, nor is a redirection to a
+/// device such as `2>/dev/null`. A command with unbalanced quotes does not
+/// run, so it names no paths.
+fn command_paths(command: &str) -> Vec<String> {
+    let Ok(parsed) = parse(command) else {
+        return Vec::new();
+    };
+    let arguments = parsed.commands.into_iter().flat_map(|words| {
+        // Leading `NAME=value` assignments are arguments too
+        let executable = words.iter().position(|word| !is_assignment(&word.text));
+        words
+            .into_iter()
+            .enumerate()
+            .filter(move |(index, _)| Some(*index) != executable)
+            .map(|(_, word)| match word.text.split_once('=') {
+                // `--out=/tmp/x` names a path after the `=`
+                Some((_, value)) => value.to_string(),
+                None => word.text,
+            })
+    });
+    let redirects = parsed
+        .redirects
+        .into_iter()
+        .map(|word| word.text)
+        .filter(|word| !word.starts_with("/dev/"));
+    arguments
+        .chain(redirects)
+        .filter(|word| {
+            !word.contains("://") && (word.contains('/') || word.starts_with('~') || word == "..")
+        })
+        .collect()
+}
+
+/// Replace a leading `~` with the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Resolve `.`, `..` and symlinks of the existing part of `path`
+///
+/// Each existing prefix is canonicalized before the next component is
+/// applied, so `..` after a symlink leaves its target, as the OS would.
+fn resolve(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => {
+                resolved.push(other);
+                if let Ok(real) = std::fs::canonicalize(&resolved) {
+                    resolved = real;
+                }
+            }
+        }
+    }
+    resolved
+}
@@ -0,0 +1,202 @@
+//! Shell word splitting
+//!
+//! Splits a command line into the words of its simple commands the way a
+//! POSIX shell does, honouring quotes and escapes, for the checks of
+//! [`BashGuard`](super::BashGuard) and [`PathSandbox`](super::PathSandbox).
+
+/// A command line split into simple commands
+pub(super) struct Parsed {
+    /// Words of each simple command of the pipelines and lists
+    pub commands: Vec<Vec<Word>>,
+    /// Redirection targets, such as `out.txt` of `> out.txt`
+    pub redirects: Vec<Word>,
+    /// Whether command or process substitution occurs outside single quotes
+    pub substitution: bool,
+}
+
+/// A word of a simple command
+#[derive(Debug, Clone)]
+pub(super) struct Word {
+    /// The word with quotes and escapes removed
+    pub text: String,
+    /// Whether a `$` expansion outside single quotes makes up part of it,
+    /// so its value is only known when the shell runs
+    pub expanded: bool,
+}
+
+/// Split `command` into the words of its simple commands
+///
+/// Redirection targets are kept apart, so `> out.txt` is not read as an
+/// argument.
+///
+/// # Errors
+/// Returns the reason if the command has unbalanced quotes
+pub(super) fn parse(command: &str) -> std::result::Result<Parsed, String> {
+    #[derive(PartialEq)]
+    enum Quote {
+        None,
+        Single,
+        Double,
+    }
+
+    let mut words = Words::default();
+    let mut substitution = false;
+    let mut quote = Quote::None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Quote::Single => {
+                if c == '\'' {
+                    quote = Quote::None;
+                } else {
+                    words.push(c);
+                }
+            }
+            Quote::Double => match c {
+                '"' => quote = Quote::None,
+                '\\' => {
+                    if let Some(next) = chars.next() {
+                        words.push(next);
+                    }
+                }
+                '`' => substitution = true,
+                '$' if chars.peek() == Some(&'(') => substitution = true,
+                '$' => {
+                    words.expanded |= chars.peek().is_some_and(|&next| expands(next, false));
+                    words.push(c);
+                }
+                _ => words.push(c),
+            },
+            Quote::None => match c {
+                '\'' => {
+                    quote = Quote::Single;
+                    words.started = true;
+                }
+                '"' => {
+                    quote = Quote::Double;
+                    words.started = true;
+                }
+                '\\' => {
+                    if let Some(next) = chars.next() {
+                        words.push(next);
+                    }
+                }
+                '`' => substitution = true,
+                '$' if chars.peek() == Some(&'(') => substitution = true,
+                '$' => {
+                    words.expanded |= chars.peek().is_some_and(|&next| expands(next, true));
+                    words.push(c);
+                }
+                '<' | '>' => {
+                    if chars.peek() == Some(&'(') {
+                        substitution = true;
+                    }
+                    // `2>&1` duplicates a descriptor; neither number is a word
+                    if chars.peek() == Some(&'&') {
+                        chars.next();
+                    }
+                    if words.word.chars().all(|c| c.is_ascii_digit()) {
+                        words.word.clear();
+                        words.started = false;
+                        words.expanded = false;
+                    }
+                    words.finish();
+                    words.redirect_next = true;
+                }
+                '|' | '&' | ';' | '\n' | '(' | ')' => {
+                    words.finish();
+                    words.redirect_next = false;
+                    words.commands.push(Vec::new());
+                }
+                c if c.is_whitespace() => {
+                    words.finish();
+                }
+                c => words.push(c),
+            },
+        }
+    }
+    if quote != Quote::None {
+        return Err("Command has unbalanced quotes".to_string());
+    }
+    words.finish();
+    words.commands.retain(|command| !command.is_empty());
+    Ok(Parsed {
+        commands: words.commands,
+        redirects: words.redirects,
+        substitution,
+    })
+}
+
+/// Words collected by [`parse`]
+struct Words {
+    /// Words of each simple command so far
+    commands: Vec<Vec<Word>>,
+    /// Redirection targets so far
+    redirects: Vec<Word>,
+    /// Word being read
+    word: String,
+    /// Whether `word` has started, so that `""` is kept as an empty word
+    started: bool,
+    /// Whether `word` contains a `$` expansion
+    expanded: bool,
+    /// Whether the next word is a redirection target
+    redirect_next: bool,
+}
+
+impl Default for Words {
+    fn default() -> Self {
+        Self {
+            commands: vec![Vec::new()],
+            redirects: Vec::new(),
+            word: String::new(),
+            started: false,
+            expanded: false,
+            redirect_next: false,
+        }
+    }
+}
+
+impl Words {
+    fn push(&mut self, c: char) {
+        self.word.push(c);
+        self.started = true;
+    }
+
+    /// End the current word, adding it to the current command or the
+    /// redirection targets
+    fn finish(&mut self) {
+        if self.started {
+            let word = Word {
+                text: std::mem::take(&mut self.word),
+                expanded: self.expanded,
+            };
+            if std::mem::take(&mut self.redirect_next) {
+                self.redirects.push(word);
+            } else if let Some(command) = self.commands.last_mut() {
+                command.push(word);
+            }
+        }
+        self.word.clear();
+        self.started = false;
+        self.expanded = false;
+    }
+}
+
+/// Whether `$` followed by `next` starts an expansion: a parameter
+/// (`$name`, `${...}`, `$1`, `$@`), or unquoted, an ANSI-C or locale
+/// string (`$'...'`, `$"..."`)
+fn expands(next: char, unquoted: bool) -> bool {
+    next.is_ascii_alphanumeric()
+        || matches!(next, '_' | '{' | '@' | '*' | '#' | '?' | '-' | '$' | '!')
+        || (unquoted && matches!(next, '\'' | '"'))
+}
+
+/// Whether `word` is a `NAME=value` assignment
+pub(super) fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
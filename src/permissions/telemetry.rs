@@ -0,0 +1,146 @@
+//! Permission telemetry
+//!
+//! Every request a [`PermissionManager`](super::PermissionManager) decides is
+//! announced as a [`PermissionEvent`] and counted in [`PermissionStats`], so
+//! an embedding server can chart how often, for which tools, and by which
+//! check calls are allowed or denied.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::types::permissions::PermissionResult;
+
+/// Capacity of the event channel; slow subscribers skip older events
+const TELEMETRY_CHANNEL_CAPACITY: usize = 256;
+
+/// Check that decided a permission request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DecisionSource {
+    /// The tool is in the disallowed list
+    DisallowedTools,
+    /// The tool is missing from the allowed list
+    AllowedTools,
+    /// The input refers to a path outside the sandbox
+    Sandbox,
+    /// The tool reached its rate limit
+    RateLimit,
+    /// A time-limited grant
+    Grant,
+    /// The permission policy
+    Policy,
+    /// An `AllowAlways` or `DenyAlways` answer given earlier
+    Remembered,
+    /// The permission callback
+    Callback,
+    /// Nothing objected, so the call was allowed
+    Default,
+}
+
+/// One decided permission request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionEvent {
+    /// Tool the request was for
+    pub tool_name: String,
+    /// Whether the call was allowed
+    pub allowed: bool,
+    /// Check that decided
+    pub source: DecisionSource,
+    /// Reason given for a denial
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// When the request was decided
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Decisions about one tool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolDecisions {
+    /// Calls allowed
+    pub allowed: u64,
+    /// Calls denied
+    pub denied: u64,
+}
+
+/// Decisions counted since the manager was created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionStats {
+    /// Calls allowed
+    pub allowed: u64,
+    /// Calls denied
+    pub denied: u64,
+    /// Decisions by tool
+    pub by_tool: HashMap<String, ToolDecisions>,
+    /// Denials by the check that denied
+    pub denied_by: HashMap<DecisionSource, u64>,
+}
+
+/// Emitter and counters of a manager's decisions
+pub(crate) struct Telemetry {
+    tx: broadcast::Sender<PermissionEvent>,
+    stats: Mutex<PermissionStats>,
+}
+
+impl Telemetry {
+    /// Create an emitter with no decisions
+    pub(crate) fn new() -> Self {
+        let (tx, _) = broadcast::channel(TELEMETRY_CHANNEL_CAPACITY);
+        Self {
+            tx,
+            stats: Mutex::default(),
+        }
+    }
+
+    /// Count a decision and announce it to subscribers
+    pub(crate) fn record(
+        &self,
+        tool_name: &str,
+        result: &PermissionResult,
+        source: DecisionSource,
+    ) {
+        let reason = match result {
+            PermissionResult::Deny(deny) => Some(deny.message.clone()),
+            _ => None,
+        };
+        let allowed = reason.is_none();
+        {
+            let mut stats = self.stats();
+            let tool = stats.by_tool.entry(tool_name.to_string()).or_default();
+            if allowed {
+                tool.allowed += 1;
+                stats.allowed += 1;
+            } else {
+                tool.denied += 1;
+                stats.denied += 1;
+                *stats.denied_by.entry(source).or_default() += 1;
+            }
+        }
+        // No subscribers is fine
+        let _ = self.tx.send(PermissionEvent {
+            tool_name: tool_name.to_string(),
+            allowed,
+            source,
+            reason,
+            timestamp: Utc::now(),
+        });
+    }
+
+    /// Subscribe to decisions made from now on
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<PermissionEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Decisions counted so far
+    pub(crate) fn snapshot(&self) -> PermissionStats {
+        self.stats().clone()
+    }
+
+    fn stats(&self) -> MutexGuard<'_, PermissionStats> {
+        self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,41 @@
+//! Commonly used types, for glob import
+//!
+//! ```no_run
+//! use kodegen_claude_agent::prelude::*;
+//!
+//! # async fn example() -> Result<()> {
+//! let options = ClaudeAgentOptions::builder()
+//!     .max_turns(5)
+//!     .permission_mode(PermissionMode::AcceptEdits)
+//!     .build();
+//!
+//! let mut client = ClaudeSDKClient::new(options, None).await?;
+//! client.send_message("Hello").await?;
+//! while let Some(message) = client.next_message().await {
+//!     if let Message::Result { .. } = message? {
+//!         break;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "client")]
+pub use crate::client::ClaudeSDKClient;
+pub use crate::error::{ClaudeError, Result};
+pub use crate::hooks::{HookManager, HookMatcherBuilder};
+#[cfg(feature = "manager")]
+pub use crate::manager::{AgentManager, SpawnSessionRequest};
+pub use crate::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
+#[cfg(feature = "client")]
+pub use crate::query::query;
+pub use crate::transport::Transport;
+pub use crate::types::agent::SystemPrompt;
+pub use crate::types::hooks::{HookContext, HookEvent, HookMatcher, HookOutput};
+pub use crate::types::messages::{ContentBlock, Message};
+pub use crate::types::options::{ClaudeAgentOptions, ClaudeAgentOptionsBuilder};
+pub use crate::types::permissions::{
+    PermissionMode, PermissionResult, PermissionResultAllow, PermissionResultDeny,
+    ToolPermissionContext,
+};
+pub use crate::types::transport::TransportConfig;
@@ -1,13 +1,15 @@
 //! Simple query function for one-shot interactions
 
-use futures::Stream;
+use futures::{Stream, StreamExt};
 
 use crate::Transport;
-use crate::error::Result;
+use crate::client::ClaudeSDKClient;
+use crate::error::{ClaudeError, Result};
 use crate::message::parse_message;
 use crate::transport::{PromptInput, SubprocessTransport};
-use crate::types::messages::Message;
-use crate::types::options::ClaudeAgentOptions;
+use crate::types::identifiers::SessionId;
+use crate::types::messages::{ContentBlock, Message};
+use crate::types::options::{ClaudeAgentOptions, RetryPolicy};
 
 /// One-shot query function for simple interactions with Claude Code
 ///
@@ -35,6 +37,25 @@ use crate::types::options::ClaudeAgentOptions;
 /// - When you need interrupt capabilities
 /// - Long-running sessions with state
 ///
+/// # Progress
+/// A callback set with
+/// [`ClaudeAgentOptionsBuilder::on_progress`](crate::ClaudeAgentOptionsBuilder::on_progress)
+/// sees every message before the result as it is streamed.
+///
+/// # SDK tools
+/// In-process tools added with
+/// [`ClaudeAgentOptionsBuilder::sdk_mcp_server`](crate::ClaudeAgentOptionsBuilder::sdk_mcp_server)
+/// are offered to Claude and called in this process: the query then runs
+/// on a [`ClaudeSDKClient`] that answers the CLI's tool calls, and its
+/// stream ends with the first result. Retry policies do not apply.
+///
+/// # Retries
+/// With a [`RetryPolicy`](crate::RetryPolicy) set through
+/// [`ClaudeAgentOptionsBuilder::retry`](crate::ClaudeAgentOptionsBuilder::retry),
+/// a run whose CLI fails to connect or fails before producing any output is
+/// started again after a backoff. The first message is then awaited before
+/// `query` returns.
+///
 /// # Arguments
 /// * `prompt` - The prompt to send to Claude (string)
 /// * `options` - Optional configuration (defaults to `ClaudeAgentOptions::default()` if None)
@@ -94,22 +115,64 @@ pub async fn query(
     options: Option<ClaudeAgentOptions>,
 ) -> Result<impl Stream<Item = Result<Message>>> {
     let options = options.unwrap_or_default();
-    let prompt_input = PromptInput::from(prompt.into());
+    let prompt = prompt.into();
+    let policy = options.retry.unwrap_or_else(|| RetryPolicy::new(1));
+    let on_progress = options.on_progress.clone();
 
-    let mut transport = SubprocessTransport::new(prompt_input, options, None)?;
-    transport.connect().await?;
+    // In-process SDK tools are called over the control protocol of an
+    // interactive session
+    if !options.sdk_mcp_servers.is_empty() {
+        let stream = query_with_sdk_tools(prompt, options).await?;
+        return Ok(stream.left_stream());
+    }
 
-    // Get message receiver from transport
-    let mut msg_receiver = transport.read_messages();
+    let mut attempt = 1;
+    let (transport, mut msg_receiver, mut first) = loop {
+        let retry = |error: &ClaudeError| policy.should_retry(attempt, error);
+        let mut transport =
+            SubprocessTransport::new(PromptInput::from(prompt.clone()), options.clone(), None)?;
+        match transport.connect().await {
+            Ok(()) => {
+                // Get message receiver from transport
+                let mut msg_receiver = transport.read_messages();
+                if attempt >= policy.max_attempts {
+                    break (transport, msg_receiver, None);
+                }
+                // The run can still be retried until it has produced output
+                let first = msg_receiver.recv().await;
+                match first {
+                    Some(Err(ref e)) if retry(e) => {
+                        log::warn!("Query attempt {attempt} failed: {e}");
+                        let _ = transport.close().await;
+                    }
+                    first => break (transport, msg_receiver, first),
+                }
+            }
+            Err(e) if retry(&e) => log::warn!("Query attempt {attempt} failed to connect: {e}"),
+            Err(e) => return Err(e),
+        }
+        tokio::time::sleep(policy.backoff(attempt - 1)).await;
+        attempt += 1;
+    };
 
     // Create stream that parses messages
     // We need to move transport into the stream to keep it alive
     let message_stream = async_stream::stream! {
-        while let Some(result) = msg_receiver.recv().await {
+        while let Some(result) = match first.take() {
+            Some(result) => Some(result),
+            None => msg_receiver.recv().await,
+        } {
             match result {
                 Ok(value) => {
                     match parse_message(value) {
-                        Ok(msg) => yield Ok(msg),
+                        Ok(msg) => {
+                            if let Some(ref on_progress) = on_progress
+                                && !matches!(msg, Message::Result { .. })
+                            {
+                                on_progress(&msg);
+                            }
+                            yield Ok(msg)
+                        }
                         Err(e) => yield Err(e),
                     }
                 }
@@ -120,5 +183,437 @@ pub async fn query(
         drop(transport);
     };
 
+    Ok(message_stream.right_stream())
+}
+
+/// Run a one-shot query on a client, which answers the CLI's calls to the
+/// in-process SDK MCP servers in `options`
+async fn query_with_sdk_tools(
+    prompt: String,
+    options: ClaudeAgentOptions,
+) -> Result<impl Stream<Item = Result<Message>>> {
+    let on_progress = options.on_progress.clone();
+    let mut client = ClaudeSDKClient::new(options, None).await?;
+    if let Err(e) = client.send_message(prompt).await {
+        let _ = client.close().await;
+        return Err(e);
+    }
+
+    Ok(async_stream::stream! {
+        while let Some(message) = client.next_message().await {
+            let done = matches!(message, Ok(Message::Result { .. }));
+            if let (Some(on_progress), Ok(msg)) = (&on_progress, &message)
+                && !done
+            {
+                on_progress(msg);
+            }
+            yield message;
+            if done {
+                break;
+            }
+        }
+        if let Err(e) = client.close().await {
+            log::warn!("Failed to close query client: {e}");
+        }
+    })
+}
+
+/// One-shot query whose prompt may be a named template
+///
+/// Resolves `prompt` with `prompt_manager`, usually the prompt tools'
+/// `PromptManager` (rendering a
+/// [`PromptInput::Template`](crate::types::PromptInput::Template) with its
+/// parameters, as the spawn tools do) and runs [`query()`] with the result.
+///
+/// # Errors
+/// Returns `ClaudeError::PromptTemplateError` if the template cannot be
+/// rendered, or any error of [`query()`]
+#[cfg(feature = "tools")]
+pub async fn query_prompt(
+    prompt: &crate::types::PromptInput,
+    prompt_manager: &impl crate::types::PromptRenderer,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<impl Stream<Item = Result<Message>>> {
+    let prompt = prompt.resolve(prompt_manager).await?;
+    query(prompt, options).await
+}
+
+/// Run many one-shot queries, at most `max_concurrency` at a time
+///
+/// Each prompt gets its own CLI process, started with a copy of `options`.
+/// The returned stream yields `(index, messages)` as each query completes,
+/// in completion order; `index` is the prompt's position in `prompts`. A
+/// query that fails yields its first error instead of its messages, without
+/// affecting the others. A `max_concurrency` of 0 is treated as 1.
+///
+/// # Example
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use kodegen_claude_agent::query_batch;
+///
+/// # async fn example() {
+/// let prompts = ["Summarize lib.rs", "Summarize main.rs", "Summarize query.rs"];
+/// let mut results = Box::pin(query_batch(prompts, None, 2));
+/// while let Some((index, messages)) = results.next().await {
+///     match messages {
+///         Ok(messages) => log::info!("{}: {} messages", prompts[index], messages.len()),
+///         Err(e) => log::error!("{}: {e}", prompts[index]),
+///     }
+/// }
+/// # }
+/// ```
+pub fn query_batch<I>(
+    prompts: I,
+    options: Option<ClaudeAgentOptions>,
+    max_concurrency: usize,
+) -> impl Stream<Item = (usize, Result<Vec<Message>>)>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    use futures::TryStreamExt;
+
+    let options = options.unwrap_or_default();
+    futures::stream::iter(prompts.into_iter().enumerate())
+        .map(move |(index, prompt)| {
+            let query = query(prompt.into(), Some(options.clone()));
+            async move {
+                let messages = match query.await {
+                    Ok(stream) => stream.try_collect().await,
+                    Err(e) => Err(e),
+                };
+                (index, messages)
+            }
+        })
+        .buffer_unordered(max_concurrency.max(1))
+}
+
+/// One-shot query over a caller-provided transport
+///
+/// Like [`query()`], but talks to whatever `transport` reaches: a CLI over
+/// HTTP or SSH, a recorded session, or a
+/// [`MockTransport`](crate::transport::mock::MockTransport) in tests. The
+/// transport is connected, the prompt is written as a user message and the
+/// input is ended; the stream ends with the first `Message::Result`, after
+/// which the transport is closed.
+///
+/// The transport should carry the CLI's `stream-json` input and output
+/// formats. Retry policies do not apply, as a used transport cannot be
+/// started again.
+///
+/// # Errors
+/// Returns error if the transport fails to connect or the prompt cannot be
+/// written
+///
+/// # Example
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use kodegen_claude_agent::query_with_transport;
+/// use kodegen_claude_agent::transport::mock::{self, MockTransport};
+///
+/// # async fn example() -> kodegen_claude_agent::Result<()> {
+/// let transport = MockTransport::new()
+///     .reply(vec![mock::assistant_text("4"), mock::result("session-1", 1)]);
+///
+/// let mut stream = Box::pin(query_with_transport("What is 2 + 2?", transport).await?);
+/// while let Some(message) = stream.next().await {
+///     log::info!("{:?}", message?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query_with_transport<T>(
+    prompt: impl Into<String>,
+    mut transport: T,
+) -> Result<impl Stream<Item = Result<Message>>>
+where
+    T: Transport + 'static,
+{
+    transport.connect().await?;
+    let mut msg_receiver = transport.read_messages();
+
+    // Send the prompt in the format the CLI expects, then end the input
+    let message = serde_json::json!({
+        "type": "user",
+        "message": {
+            "role": "user",
+            "content": prompt.into()
+        }
+    });
+    transport
+        .write(&format!("{}\n", serde_json::to_string(&message)?))
+        .await?;
+    transport.end_input().await?;
+
+    let message_stream = async_stream::stream! {
+        while let Some(result) = msg_receiver.recv().await {
+            let message = result.and_then(parse_message);
+            let done = matches!(message, Ok(Message::Result { .. }));
+            yield message;
+            if done {
+                break;
+            }
+        }
+        if let Err(e) = transport.close().await {
+            log::warn!("Failed to close query transport: {e}");
+        }
+    };
+
     Ok(message_stream)
 }
+
+/// One-shot query returning only the final answer
+///
+/// Runs [`query()`] to completion and returns the result message's text, or
+/// the concatenated assistant text if the result carries none.
+///
+/// # Errors
+/// Returns error if the query fails or the CLI's output ends without a
+/// result
+///
+/// # Example
+///
+/// ```no_run
+/// use kodegen_claude_agent::query_text;
+///
+/// # async fn example() -> kodegen_claude_agent::Result<()> {
+/// let answer = query_text("What is the capital of France?", None).await?;
+/// log::info!("{answer}");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query_text(
+    prompt: impl Into<String>,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<String> {
+    final_text(query(prompt, options).await?).await
+}
+
+/// One-shot query for code without an async runtime
+///
+/// Runs [`query_text`] on a runtime of its own. Meant for CLIs and build
+/// scripts; async code should use [`query()`] or [`query_text`] instead.
+///
+/// # Panics
+/// Panics if called from within an async runtime
+///
+/// # Errors
+/// Returns error if the runtime cannot be started, the query fails, or the
+/// CLI's output ends without a result
+///
+/// # Example
+///
+/// ```no_run
+/// use kodegen_claude_agent::query_blocking;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let answer = query_blocking("What is 2 + 2?", None)?;
+///     println!("{answer}");
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "blocking")]
+pub fn query_blocking(
+    prompt: impl Into<String>,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(query_text(prompt, options))
+}
+
+/// Continue an earlier conversation with a one-shot query
+///
+/// Resumes the session `session_id` (clearing `continue_conversation`, which
+/// would pick the most recent session instead) and runs [`query_text`].
+/// Returns the session ID to continue from next, as reported by the
+/// result, together with the answer; with `fork_session` set it is a new
+/// session branched off the old one.
+///
+/// # Errors
+/// Returns error if the query fails or the CLI's output ends without a
+/// result
+///
+/// # Example
+///
+/// ```no_run
+/// use kodegen_claude_agent::{query_continue, query_text};
+///
+/// # async fn example(first_session: &str) -> kodegen_claude_agent::Result<()> {
+/// let (session, answer) =
+///     query_continue(first_session, "Now make it shorter", None).await?;
+/// log::info!("{answer}");
+/// let (_, answer) = query_continue(session, "And in French", None).await?;
+/// log::info!("{answer}");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query_continue(
+    session_id: impl Into<SessionId>,
+    prompt: impl Into<String>,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<(SessionId, String)> {
+    let mut options = options.unwrap_or_default();
+    options.resume = Some(session_id.into());
+    options.continue_conversation = false;
+    final_answer(query(prompt, Some(options)).await?).await
+}
+
+/// Final answer of a query's message stream
+///
+/// The result message's text, or the concatenated assistant text if the
+/// result carries none.
+async fn final_text(stream: impl Stream<Item = Result<Message>>) -> Result<String> {
+    final_answer(stream).await.map(|(_, text)| text)
+}
+
+/// Session ID and final answer of a query's message stream
+async fn final_answer(stream: impl Stream<Item = Result<Message>>) -> Result<(SessionId, String)> {
+    let mut stream = Box::pin(stream);
+    let mut text = String::new();
+    while let Some(message) = stream.next().await {
+        match message? {
+            Message::Assistant { message, .. } => {
+                for block in message.content {
+                    if let ContentBlock::Text { text: segment } = block {
+                        text.push_str(&segment);
+                    }
+                }
+            }
+            Message::Result {
+                session_id, result, ..
+            } => return Ok((session_id, result.unwrap_or(text))),
+            _ => {}
+        }
+    }
+    Err(ClaudeError::transport("Query ended without a result"))
+}
+
+/// Attempts `query_json` makes before giving up on malformed output
+#[cfg(feature = "schema")]
+const STRUCTURED_OUTPUT_ATTEMPTS: u32 = 3;
+
+/// One-shot query whose answer is parsed into `T`
+///
+/// Asks Claude to reply with only a JSON value matching `T`'s JSON Schema and
+/// parses the final result with [`parse_structured_output`]. When the reply
+/// does not parse, the conversation is resumed with the parse error and the
+/// request to try again, up to three attempts in total.
+///
+/// # Errors
+/// Returns error if a query fails, or `ClaudeError::MessageParse` (carrying
+/// the last reply) if no attempt produced a valid value
+///
+/// # Example
+///
+/// ```no_run
+/// use kodegen_claude_agent::query_json;
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct Capital {
+///     country: String,
+///     city: String,
+/// }
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let capital: Capital = query_json("What is the capital of France?", None).await?;
+/// log::info!("{} -> {}", capital.country, capital.city);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "schema")]
+pub async fn query_json<T>(
+    prompt: impl Into<String>,
+    options: Option<ClaudeAgentOptions>,
+) -> Result<T>
+where
+    T: serde::de::DeserializeOwned + schemars::JsonSchema,
+{
+    use futures::StreamExt;
+
+    let mut options = options.unwrap_or_default();
+    let schema = serde_json::to_string_pretty(&schemars::schema_for!(T))?;
+    let mut prompt = format!(
+        "{}\n\nReply with only a JSON value matching this JSON Schema, \
+         without any other text:\n\n{schema}",
+        prompt.into()
+    );
+
+    let mut attempt = 1;
+    loop {
+        let mut stream = Box::pin(query(prompt, Some(options.clone())).await?);
+        let mut last = None;
+        while let Some(message) = stream.next().await {
+            if let Message::Result {
+                session_id, result, ..
+            } = message?
+            {
+                last = Some((session_id, result.unwrap_or_default()));
+            }
+        }
+        let (session_id, output) =
+            last.ok_or_else(|| ClaudeError::transport("Query ended without a result"))?;
+
+        let error = match parse_structured_output(&output) {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        if attempt == STRUCTURED_OUTPUT_ATTEMPTS {
+            return Err(ClaudeError::message_parse(
+                format!("No valid structured output after {attempt} attempts: {error}"),
+                Some(serde_json::Value::String(output)),
+            ));
+        }
+
+        // Continue the same conversation so Claude can correct its reply
+        attempt += 1;
+        options.resume = Some(session_id);
+        prompt = format!(
+            "Your reply could not be parsed: {error}\n\nReply again with only a JSON \
+             value matching this JSON Schema, without any other text:\n\n{schema}"
+        );
+    }
+}
+
+/// Parse a JSON value of type `T` out of Claude's reply
+///
+/// Accepts the value on its own, inside a Markdown code fence, or surrounded
+/// by prose (the outermost `{...}` or `[...]` is used).
+///
+/// # Errors
+/// Returns `ClaudeError::JsonDecode` if no JSON value of type `T` is found
+pub fn parse_structured_output<T: serde::de::DeserializeOwned>(output: &str) -> Result<T> {
+    let trimmed = output.trim();
+    let error = match serde_json::from_str(trimmed) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let fenced = trimmed.split("```").nth(1).map(|block| {
+        // Drop the fence's language tag
+        block.split_once('\n').map_or(block, |(tag, rest)| {
+            if tag.trim().chars().all(char::is_alphanumeric) {
+                rest
+            } else {
+                block
+            }
+        })
+    });
+    let spans = ['{', '['].into_iter().filter_map(|open| {
+        let close = if open == '{' { '}' } else { ']' };
+        let start = trimmed.find(open)?;
+        let end = trimmed.rfind(close)?;
+        trimmed.get(start..=end)
+    });
+
+    fenced
+        .into_iter()
+        .chain(spans)
+        .find_map(|candidate| serde_json::from_str(candidate.trim()).ok())
+        .ok_or(ClaudeError::JsonDecode(error))
+}
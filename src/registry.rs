@@ -6,6 +6,7 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use crate::manager::AgentManager;
+use crate::types::agent::SessionGrade;
 use kodegen_mcp_schema::claude_agent::ClaudeAgentSummary;
 
 // Maps (connection_id, agent_id) to session UUID
@@ -81,6 +82,39 @@ impl AgentRegistry {
         &self.manager
     }
 
+    /// Interrupt an agent's current turn and redirect it with a new prompt
+    ///
+    /// Returns the session ID the prompt was delivered to.
+    pub async fn interrupt_and_send(
+        &self,
+        connection_id: &str,
+        agent_id: u32,
+        prompt: &str,
+    ) -> Result<String> {
+        let session_id = self.get_session_id(connection_id, agent_id).await?;
+        self.manager
+            .interrupt_and_send(&session_id, prompt)
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(session_id)
+    }
+
+    /// Grade an agent's final result against a rubric
+    ///
+    /// The grade is stored on the agent's session and reported by session listings.
+    pub async fn grade_output(
+        &self,
+        connection_id: &str,
+        agent_id: u32,
+        rubric: &str,
+    ) -> Result<SessionGrade> {
+        let session_id = self.get_session_id(connection_id, agent_id).await?;
+        self.manager
+            .grade_output(&session_id, rubric)
+            .await
+            .map_err(|e| anyhow!("{}", e))
+    }
+
     /// Cleanup all agents for a connection (called on connection drop)
     pub async fn cleanup_connection(&self, connection_id: &str) -> usize {
         let mut agents = self.agents.lock().await;
@@ -0,0 +1,79 @@
+//! JSON Schemas for the public JSON types
+//!
+//! Non-Rust consumers of the HTTP and MCP surface can generate clients from
+//! these schemas or validate payloads against them. The
+//! `kodegen-claude-agent-schema` binary writes them to disk.
+//!
+//! # Example
+//!
+//! ```
+//! let schemas = kodegen_claude_agent::schema::schemas();
+//! let message = &schemas["SerializedMessage"];
+//! assert!(message.get("properties").is_some());
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use schemars::{JsonSchema, Schema, schema_for};
+
+use crate::capabilities::Capabilities;
+use crate::control::protocol::ControlMessage;
+use crate::types::agent::{
+    AgentInfo, GetOutputResponse, ListSessionsResponse, SerializedMessage, SessionHealth,
+    TerminateResponse,
+};
+
+/// Schema of one type, titled with its name
+fn schema<T: JsonSchema>() -> (String, Schema) {
+    (T::schema_name().into_owned(), schema_for!(T))
+}
+
+/// Schemas of every public JSON type, keyed by type name
+///
+/// With the `tools` feature this includes the output of the `claude_agent`
+/// MCP tool.
+#[must_use]
+pub fn schemas() -> BTreeMap<String, Schema> {
+    #[allow(unused_mut)]
+    let mut schemas: BTreeMap<String, Schema> = [
+        schema::<SerializedMessage>(),
+        schema::<GetOutputResponse>(),
+        schema::<AgentInfo>(),
+        schema::<ListSessionsResponse>(),
+        schema::<TerminateResponse>(),
+        schema::<SessionHealth>(),
+        schema::<ControlMessage>(),
+        schema::<Capabilities>(),
+    ]
+    .into_iter()
+    .collect();
+
+    #[cfg(feature = "tools")]
+    {
+        use kodegen_mcp_schema::ToolArgs;
+        use kodegen_mcp_schema::claude_agent::ClaudeAgentArgs;
+        schemas.extend([schema::<<ClaudeAgentArgs as ToolArgs>::Output>()]);
+    }
+
+    schemas
+}
+
+/// Write each schema to `<dir>/<TypeName>.schema.json`
+///
+/// Creates `dir` if needed and returns the paths written, in name order.
+///
+/// # Errors
+/// Returns an error if the directory or a file cannot be written
+pub fn write_schemas(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    for (name, schema) in schemas() {
+        let path = dir.join(format!("{name}.schema.json"));
+        let json = serde_json::to_string_pretty(&schema).map_err(std::io::Error::other)?;
+        std::fs::write(&path, json + "\n")?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -1,25 +1,126 @@
 //! Unified Claude agent tool - Elite Registry Pattern
 
 use crate::manager::SpawnSessionRequest;
+use crate::permissions::PermissionPolicy;
 use crate::registry::AgentRegistry;
+use crate::types::hooks::{HookEvent, HookMatcher};
+use crate::types::permissions::PermissionMode;
 use kodegen_mcp_schema::claude_agent::{
     ClaudeAgentAction, ClaudeAgentArgs, ClaudeAgentOutput, ClaudeAgentPrompts,
     CLAUDE_AGENT,
 };
 use kodegen_mcp_schema::{McpError, Tool, ToolExecutionContext, ToolResponse};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+/// Tool description, ending with the capabilities of this build
+static DESCRIPTION: LazyLock<String> = LazyLock::new(|| {
+    format!(
+        "Unified Claude agent interface with action-based dispatch (SPAWN/SEND/READ/LIST/KILL). \
+         Each connection gets independent agent numbering (agent:0, agent:1, agent:2). \
+         Supports timeout with background continuation.\n\n\
+         **Actions:**\n\
+         • SPAWN: Create new agent session with initial prompt\n\
+         • SEND: Send additional prompt to existing agent\n\
+         • READ: Read current agent output\n\
+         • LIST: List all agents for this connection\n\
+         • KILL: Terminate agent and cleanup\n\n\
+         **Server:** {}",
+        crate::capabilities()
+    )
+});
 
 /// Unified MCP tool for Claude agent lifecycle management
 #[derive(Clone)]
 pub struct ClaudeAgentTool {
     registry: Arc<AgentRegistry>,
+    /// Hooks given to every agent spawned through the tool
+    hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
+    /// Permission mode every agent spawned through the tool starts in
+    permission_mode: Option<PermissionMode>,
+    /// Permission rules applied to every agent spawned through the tool
+    permission_policy: Option<PermissionPolicy>,
 }
 
 impl ClaudeAgentTool {
     /// Create a new unified claude_agent tool with agent registry
     #[must_use]
     pub fn new(registry: Arc<AgentRegistry>) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            hooks: None,
+            permission_mode: None,
+            permission_policy: None,
+        }
+    }
+
+    /// Run `hooks` in every agent spawned through the tool
+    ///
+    /// Hook callbacks cannot travel in the tool's JSON arguments, so audit
+    /// and guard hooks for MCP-spawned agents are set here by the server.
+    #[must_use]
+    pub fn with_hooks(mut self, hooks: HashMap<HookEvent, Vec<HookMatcher>>) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// Start every agent spawned through the tool in `mode`
+    #[must_use]
+    pub const fn with_permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.permission_mode = Some(mode);
+        self
+    }
+
+    /// Apply `policy` to the tool calls of every agent spawned through the
+    /// tool
+    ///
+    /// Load it with [`PermissionPolicy::from_path`] to keep the rules for
+    /// MCP-spawned agents in a JSON or TOML document. With no callback to
+    /// defer to, calls the policy would `ask` about are denied.
+    ///
+    /// The SPAWN action cannot take a policy per call: its arguments are
+    /// `ClaudeAgentArgs` from `kodegen_mcp_schema`, which this crate does not
+    /// define, so the server sets the policy here.
+    #[must_use]
+    pub fn with_permission_policy(mut self, policy: PermissionPolicy) -> Self {
+        self.permission_policy = Some(policy);
+        self
+    }
+
+    /// Output of the EVENTS action: the agent's operational events (spawn,
+    /// restarts, skipped output, limits, termination) as formatted JSON
+    ///
+    /// READ keeps returning the transcript alone. `ClaudeAgentAction` is
+    /// defined by `kodegen_mcp_schema`, so `execute` dispatches EVENTS here
+    /// once the schema has the variant; until then servers call it directly.
+    ///
+    /// # Errors
+    /// Returns error if the agent is unknown to this connection
+    pub async fn events(
+        &self,
+        connection_id: &str,
+        agent: u32,
+    ) -> Result<ClaudeAgentOutput, McpError> {
+        let session_id = self.registry.get_session_id(connection_id, agent).await
+            .map_err(McpError::Other)?;
+
+        let info = self.registry.manager().get_session_info(&session_id).await
+            .map_err(|e| McpError::Other(e.into()))?;
+
+        let events = self.registry.manager().events(&session_id).await
+            .map_err(|e| McpError::Other(e.into()))?;
+
+        Ok(ClaudeAgentOutput {
+            agent,
+            action: "EVENTS".to_string(),
+            session_id: Some(session_id),
+            output: serde_json::to_string_pretty(&events).unwrap_or_else(|_| "[]".to_string()),
+            message_count: Some(info.message_count),
+            working: Some(info.working),
+            completed: info.is_complete,
+            exit_code: if info.is_complete { Some(0) } else { None },
+            agents: None,
+        })
     }
 }
 
@@ -32,15 +133,7 @@ impl Tool for ClaudeAgentTool {
     }
 
     fn description() -> &'static str {
-        "Unified Claude agent interface with action-based dispatch (SPAWN/SEND/READ/LIST/KILL). \
-         Each connection gets independent agent numbering (agent:0, agent:1, agent:2). \
-         Supports timeout with background continuation.\n\n\
-         **Actions:**\n\
-         • SPAWN: Create new agent session with initial prompt\n\
-         • SEND: Send additional prompt to existing agent\n\
-         • READ: Read current agent output\n\
-         • LIST: List all agents for this connection\n\
-         • KILL: Terminate agent and cleanup"
+        &DESCRIPTION
     }
 
     fn read_only() -> bool {
@@ -142,6 +235,10 @@ impl Tool for ClaudeAgentTool {
                     cwd: args.cwd.clone(),
                     add_dirs: args.add_dirs.clone(),
                     label: format!("agent:{}", args.agent),
+                    hooks: self.hooks.clone(),
+                    permission_mode: self.permission_mode,
+                    permission_policy: self.permission_policy.clone(),
+                    ..Default::default()
                 };
 
                 // Spawn the agent
@@ -0,0 +1,108 @@
+//! Type-erased transport
+//!
+//! `Transport` uses `impl Future` return types, which keeps implementations
+//! allocation-free but makes the trait unusable as a trait object. This module
+//! provides [`BoxedTransport`], which erases the concrete transport type so the
+//! client can choose a transport at runtime.
+
+use futures::future::BoxFuture;
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+
+use super::{MetricsRecorder, Transport};
+
+/// Object-safe mirror of [`Transport`]
+trait DynTransport: Send + Sync {
+    fn connect(&mut self) -> BoxFuture<'_, Result<()>>;
+    fn write<'a>(&'a mut self, data: &'a str) -> BoxFuture<'a, Result<()>>;
+    fn flush(&mut self) -> BoxFuture<'_, Result<()>>;
+    fn end_input(&mut self) -> BoxFuture<'_, Result<()>>;
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>>;
+    fn is_ready(&self) -> bool;
+    fn metrics(&self) -> Option<MetricsRecorder>;
+    fn close(&mut self) -> BoxFuture<'_, Result<()>>;
+}
+
+impl<T: Transport> DynTransport for T {
+    fn connect(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(Transport::connect(self))
+    }
+
+    fn write<'a>(&'a mut self, data: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(Transport::write(self, data))
+    }
+
+    fn flush(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(Transport::flush(self))
+    }
+
+    fn end_input(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(Transport::end_input(self))
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
+        Transport::read_messages(self)
+    }
+
+    fn is_ready(&self) -> bool {
+        Transport::is_ready(self)
+    }
+
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        Transport::metrics(self)
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(Transport::close(self))
+    }
+}
+
+/// Heap-allocated transport of any concrete type
+pub struct BoxedTransport {
+    inner: Box<dyn DynTransport>,
+}
+
+impl BoxedTransport {
+    /// Erase the type of a transport
+    #[must_use]
+    pub fn new<T: Transport + 'static>(transport: T) -> Self {
+        Self {
+            inner: Box::new(transport),
+        }
+    }
+}
+
+impl Transport for BoxedTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.inner.end_input().await
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
+        self.inner.read_messages()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        self.inner.metrics()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+}
@@ -0,0 +1,79 @@
+//! Container transport for running the CLI inside Docker or Podman
+//!
+//! This module provides a transport that starts the Claude Code CLI in a
+//! throwaway container with the working directory mounted, so agents run fully
+//! sandboxed while keeping the same stream-json semantics as
+//! `SubprocessTransport`.
+
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+use crate::transport::{MetricsRecorder, Transport};
+use crate::types::options::ClaudeAgentOptions;
+use crate::types::transport::ContainerTransportConfig;
+
+use super::subprocess::{Launcher, PromptInput, SubprocessTransport};
+
+/// Transport that runs the CLI inside a container
+pub struct ContainerTransport {
+    inner: SubprocessTransport,
+}
+
+impl ContainerTransport {
+    /// Create a new container transport
+    ///
+    /// # Arguments
+    /// * `prompt` - The prompt input (string or stream)
+    /// * `options` - Configuration options (`cwd` and `add_dirs` are mounted)
+    /// * `config` - Image, runtime, mounts and network configuration
+    #[must_use]
+    pub fn new(
+        prompt: PromptInput,
+        options: ClaudeAgentOptions,
+        config: ContainerTransportConfig,
+    ) -> Self {
+        let cli_path = config.cli_path.clone().into();
+        Self {
+            inner: SubprocessTransport::with_launcher(
+                prompt,
+                options,
+                cli_path,
+                Launcher::Container(config),
+            ),
+        }
+    }
+}
+
+impl Transport for ContainerTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.inner.end_input().await
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
+        self.inner.read_messages()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        self.inner.metrics()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+}
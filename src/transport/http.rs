@@ -0,0 +1,282 @@
+//! HTTP transport for hosted Claude Code CLI endpoints
+//!
+//! This module provides a transport that talks to a CLI running behind an HTTP
+//! endpoint instead of a local subprocess. Writes are sent as `POST` requests
+//! and messages are read from a server-sent event (SSE) stream, using the same
+//! stream-json message format as the subprocess transport.
+
+use futures::StreamExt;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::Transport;
+use crate::error::{ClaudeError, Result};
+use crate::types::transport::HttpTransportConfig;
+
+/// Default maximum size of a single SSE event (1MB)
+const DEFAULT_MAX_EVENT_SIZE: usize = 1024 * 1024;
+
+/// HTTP transport for a hosted Claude Code CLI
+pub struct HttpTransport {
+    config: HttpTransportConfig,
+    client: Option<reqwest::Client>,
+    input_closed: bool,
+    max_event_size: usize,
+    reader_task: Option<JoinHandle<()>>,
+}
+
+impl HttpTransport {
+    /// Create a new HTTP transport
+    ///
+    /// # Arguments
+    /// * `config` - Endpoint and header configuration
+    /// * `max_event_size` - Maximum size of a single SSE event (default: 1MB)
+    #[must_use]
+    pub fn new(config: HttpTransportConfig, max_event_size: Option<usize>) -> Self {
+        Self {
+            config,
+            client: None,
+            input_closed: false,
+            max_event_size: max_event_size.unwrap_or(DEFAULT_MAX_EVENT_SIZE),
+            reader_task: None,
+        }
+    }
+
+    /// Build the URL for an endpoint path
+    fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.config.endpoint.trim_end_matches('/'))
+    }
+
+    /// Convert configured headers into a reqwest header map
+    fn default_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.config.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| ClaudeError::invalid_config(format!("Invalid header name: {e}")))?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                ClaudeError::invalid_config(format!("Invalid value for header {name}: {e}"))
+            })?;
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+}
+
+impl Transport for HttpTransport {
+    async fn connect(&mut self) -> Result<()> {
+        if self.client.is_some() {
+            return Ok(());
+        }
+
+        reqwest::Url::parse(&self.config.endpoint).map_err(|e| {
+            ClaudeError::invalid_config(format!(
+                "Invalid HTTP endpoint '{}': {e}",
+                self.config.endpoint
+            ))
+        })?;
+
+        let client = reqwest::Client::builder()
+            .default_headers(self.default_headers()?)
+            .build()
+            .map_err(|e| ClaudeError::connection(format!("Failed to build HTTP client: {e}")))?;
+
+        self.client = Some(client);
+        self.input_closed = false;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        if self.input_closed {
+            return Err(ClaudeError::transport("Input stream has been closed"));
+        }
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| ClaudeError::transport("Transport is not ready for writing"))?;
+
+        let response = client
+            .post(self.url("messages"))
+            .header(CONTENT_TYPE, "application/json")
+            .body(data.trim_end().to_string())
+            .send()
+            .await
+            .map_err(|e| ClaudeError::transport(format!("Failed to POST message: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(ClaudeError::transport(format!(
+                "Endpoint rejected message with status {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.input_closed = true;
+        Ok(())
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let Some(client) = self.client.clone() else {
+            let _ = tx.send(Err(ClaudeError::connection(
+                "Not connected - call connect() first",
+            )));
+            return rx;
+        };
+        let url = self.url("events");
+        let max_event_size = self.max_event_size;
+
+        let task = tokio::spawn(async move {
+            let response = match client
+                .get(&url)
+                .header(ACCEPT, "text/event-stream")
+                .send()
+                .await
+            {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    let _ = tx.send(Err(ClaudeError::connection(format!(
+                        "Event stream returned status {}",
+                        response.status()
+                    ))));
+                    return;
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(ClaudeError::connection(format!(
+                        "Failed to open event stream: {e}"
+                    ))));
+                    return;
+                }
+            };
+
+            let mut stream = response.bytes_stream();
+            let mut parser = SseParser::new(max_event_size);
+
+            while let Some(chunk) = stream.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        let _ = tx.send(Err(ClaudeError::transport(format!(
+                            "Event stream error: {e}"
+                        ))));
+                        return;
+                    }
+                };
+
+                for event in parser.feed(&chunk) {
+                    let message = event.and_then(|data| {
+                        serde_json::from_str::<serde_json::Value>(&data).map_err(ClaudeError::from)
+                    });
+                    if tx.send(message).is_err() {
+                        // Receiver dropped, stop reading
+                        return;
+                    }
+                }
+            }
+        });
+
+        self.reader_task = Some(task);
+        rx
+    }
+
+    fn is_ready(&self) -> bool {
+        self.client.is_some()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if let Some(task) = self.reader_task.take() {
+            task.abort();
+        }
+        self.client = None;
+        self.input_closed = true;
+        Ok(())
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        if let Some(task) = self.reader_task.take() {
+            task.abort();
+        }
+    }
+}
+
+// ============================================================================
+// SSE Parsing
+// ============================================================================
+
+/// Incremental parser for `text/event-stream` bodies
+///
+/// Only `data:` fields are used; each completed event yields the joined data
+/// lines. Comments, `event:`, `id:` and `retry:` fields are ignored.
+struct SseParser {
+    line: Vec<u8>,
+    data: String,
+    max_event_size: usize,
+    overflowed: bool,
+}
+
+impl SseParser {
+    fn new(max_event_size: usize) -> Self {
+        Self {
+            line: Vec::new(),
+            data: String::new(),
+            max_event_size,
+            overflowed: false,
+        }
+    }
+
+    /// Feed a chunk of the response body, returning any completed events
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Result<String>> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if byte != b'\n' {
+                self.line.push(byte);
+                if self.line.len() > self.max_event_size {
+                    self.overflowed = true;
+                    self.line.clear();
+                }
+                continue;
+            }
+
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(&line);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+
+            if line.is_empty() {
+                if let Some(event) = self.finish_event() {
+                    events.push(event);
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                if !self.data.is_empty() {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                if self.data.len() > self.max_event_size {
+                    self.overflowed = true;
+                    self.data.clear();
+                }
+            }
+        }
+        events
+    }
+
+    /// Complete the current event at a blank line
+    fn finish_event(&mut self) -> Option<Result<String>> {
+        if std::mem::take(&mut self.overflowed) {
+            self.data.clear();
+            return Some(Err(ClaudeError::json_decode(format!(
+                "SSE event exceeded maximum size of {} bytes",
+                self.max_event_size
+            ))));
+        }
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(Ok(std::mem::take(&mut self.data)))
+    }
+}
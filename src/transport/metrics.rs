@@ -0,0 +1,134 @@
+//! Transport-level traffic metrics
+//!
+//! Counters for what crossed the wire, independent of how the messages were
+//! interpreted. Useful for telling a stuck agent (no traffic) from a busy one
+//! (traffic but no results) or a broken one (unparseable output).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Point-in-time view of a transport's traffic
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TransportMetrics {
+    /// JSON messages read from the CLI
+    pub messages_read: u64,
+    /// Payloads written to the CLI
+    pub messages_written: u64,
+    /// Bytes read from the CLI, including lines that never parsed
+    pub bytes_read: u64,
+    /// Bytes written to the CLI
+    pub bytes_written: u64,
+    /// Output discarded because it could not be parsed as JSON
+    pub parse_errors: u64,
+    /// Invalid UTF-8 sequences in the CLI's output, replaced with U+FFFD
+    pub invalid_utf8: u64,
+    /// When anything was last read or written
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Time from the most recent write to the first message read after it
+    pub last_response_latency: Option<Duration>,
+}
+
+/// Timing state guarded together
+#[derive(Default)]
+struct Timing {
+    last_activity: Option<DateTime<Utc>>,
+    awaiting_since: Option<Instant>,
+    last_response_latency: Option<Duration>,
+}
+
+/// Shared recorder a transport updates as traffic flows
+///
+/// Cheap to clone; every clone updates and observes the same counters, so a
+/// recorder obtained through [`Transport::metrics`](super::Transport::metrics)
+/// stays live for the lifetime of the transport.
+#[derive(Clone, Default)]
+pub struct MetricsRecorder {
+    messages_read: Arc<AtomicU64>,
+    messages_written: Arc<AtomicU64>,
+    bytes_read: Arc<AtomicU64>,
+    bytes_written: Arc<AtomicU64>,
+    parse_errors: Arc<AtomicU64>,
+    invalid_utf8: Arc<AtomicU64>,
+    timing: Arc<Mutex<Timing>>,
+}
+
+impl MetricsRecorder {
+    /// Create a recorder with all counters at zero
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record raw bytes read, whether or not they formed a message
+    pub fn record_bytes_read(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.timing().last_activity = Some(Utc::now());
+    }
+
+    /// Record a parsed message
+    pub fn record_message_read(&self) {
+        self.messages_read.fetch_add(1, Ordering::Relaxed);
+        let mut timing = self.timing();
+        timing.last_activity = Some(Utc::now());
+        if let Some(since) = timing.awaiting_since.take() {
+            timing.last_response_latency = Some(since.elapsed());
+        }
+    }
+
+    /// Record a payload written to the CLI
+    pub fn record_write(&self, bytes: usize) {
+        self.messages_written.fetch_add(1, Ordering::Relaxed);
+        self.bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        let mut timing = self.timing();
+        timing.last_activity = Some(Utc::now());
+        // Latency is measured from the first write the CLI has not answered yet
+        timing.awaiting_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Record output discarded because it could not be parsed
+    pub fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record invalid UTF-8 sequences replaced in the CLI's output
+    pub fn record_invalid_utf8(&self, sequences: usize) {
+        self.invalid_utf8
+            .fetch_add(sequences as u64, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of the current counters
+    #[must_use]
+    pub fn snapshot(&self) -> TransportMetrics {
+        let timing = self.timing();
+        TransportMetrics {
+            messages_read: self.messages_read.load(Ordering::Relaxed),
+            messages_written: self.messages_written.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            invalid_utf8: self.invalid_utf8.load(Ordering::Relaxed),
+            last_activity: timing.last_activity,
+            last_response_latency: timing.last_response_latency,
+        }
+    }
+
+    fn timing(&self) -> std::sync::MutexGuard<'_, Timing> {
+        self.timing
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl std::fmt::Debug for MetricsRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricsRecorder")
+            .field("snapshot", &self.snapshot())
+            .finish()
+    }
+}
@@ -0,0 +1,274 @@
+//! Scripted in-memory transport for tests
+//!
+//! This module provides [`MockTransport`], which plays back a scripted sequence
+//! of JSON messages instead of talking to the Claude Code CLI, and records every
+//! payload written to it. Use it with `ClaudeSDKClient::with_transport` to test
+//! clients, hooks and permission callbacks deterministically.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kodegen_claude_agent::transport::mock::{self, MockTransport};
+//! use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
+//!
+//! # async fn example() -> kodegen_claude_agent::Result<()> {
+//! let transport = MockTransport::new()
+//!     .reply(vec![mock::assistant_text("Hi!"), mock::result("session-1", 1)]);
+//! let handle = transport.handle();
+//!
+//! let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport).await?;
+//! client.send_message("Hello").await?;
+//!
+//! assert_eq!(handle.written_json()[0]["type"], "user");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use crate::error::{ClaudeError, Result};
+
+use super::Transport;
+
+/// Shared state between a [`MockTransport`] and its [`MockHandle`]s
+#[derive(Default)]
+struct MockState {
+    /// Messages waiting for `read_messages` to be called
+    pending: Vec<Result<Value>>,
+    /// Message batches played back after each write, in order
+    replies: VecDeque<Vec<Result<Value>>>,
+    /// Everything written to the transport
+    written: Vec<String>,
+    /// Sender feeding the stream returned by `read_messages`
+    sender: Option<mpsc::UnboundedSender<Result<Value>>>,
+    /// End the stream once every reply has been played back
+    finish_after_replies: bool,
+    connected: bool,
+    input_ended: bool,
+    closed: bool,
+}
+
+impl MockState {
+    /// Deliver a message now, or queue it until `read_messages` is called
+    fn emit(&mut self, message: Result<Value>) {
+        match self.sender {
+            Some(ref sender) => {
+                let _ = sender.send(message);
+            }
+            None => self.pending.push(message),
+        }
+    }
+
+    /// End the stream if the script is exhausted and that was requested
+    fn maybe_finish(&mut self) {
+        if self.finish_after_replies && self.replies.is_empty() && self.pending.is_empty() {
+            self.sender = None;
+        }
+    }
+}
+
+/// Transport that plays back scripted messages and records writes
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    /// Create a mock transport with an empty script
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState::default())),
+        }
+    }
+
+    /// Emit a message as soon as the stream is read
+    #[must_use]
+    pub fn message(self, message: Value) -> Self {
+        lock(&self.state).pending.push(Ok(message));
+        self
+    }
+
+    /// Emit an error as soon as the stream is read
+    #[must_use]
+    pub fn error(self, error: ClaudeError) -> Self {
+        lock(&self.state).pending.push(Err(error));
+        self
+    }
+
+    /// Emit a batch of messages after the next unanswered write
+    ///
+    /// Each call scripts the response to one write: the first `reply` answers
+    /// the first write, the second answers the second, and so on.
+    #[must_use]
+    pub fn reply(self, messages: Vec<Value>) -> Self {
+        lock(&self.state)
+            .replies
+            .push_back(messages.into_iter().map(Ok).collect());
+        self
+    }
+
+    /// End the message stream once every scripted message has been emitted
+    #[must_use]
+    pub fn finish_after_replies(self) -> Self {
+        lock(&self.state).finish_after_replies = true;
+        self
+    }
+
+    /// Get a handle for inspecting and driving the transport after it is moved
+    #[must_use]
+    pub fn handle(&self) -> MockHandle {
+        MockHandle {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for MockTransport {
+    async fn connect(&mut self) -> Result<()> {
+        lock(&self.state).connected = true;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        let mut state = lock(&self.state);
+        if !state.connected || state.closed {
+            return Err(ClaudeError::transport("Transport is not ready for writing"));
+        }
+        if state.input_ended {
+            return Err(ClaudeError::transport("stdin not available"));
+        }
+
+        state.written.push(data.trim_end().to_string());
+        if let Some(batch) = state.replies.pop_front() {
+            for message in batch {
+                state.emit(message);
+            }
+        }
+        state.maybe_finish();
+        Ok(())
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        lock(&self.state).input_ended = true;
+        Ok(())
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<Value>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut state = lock(&self.state);
+        for message in state.pending.drain(..) {
+            let _ = tx.send(message);
+        }
+        if !state.closed {
+            state.sender = Some(tx);
+        }
+        state.maybe_finish();
+        rx
+    }
+
+    fn is_ready(&self) -> bool {
+        let state = lock(&self.state);
+        state.connected && !state.closed
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        let mut state = lock(&self.state);
+        state.closed = true;
+        state.sender = None;
+        Ok(())
+    }
+}
+
+/// Handle for inspecting and driving a [`MockTransport`]
+#[derive(Clone)]
+pub struct MockHandle {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockHandle {
+    /// Raw payloads written to the transport, without trailing newlines
+    #[must_use]
+    pub fn written(&self) -> Vec<String> {
+        lock(&self.state).written.clone()
+    }
+
+    /// Written payloads parsed as JSON (payloads that are not JSON are skipped)
+    #[must_use]
+    pub fn written_json(&self) -> Vec<Value> {
+        lock(&self.state)
+            .written
+            .iter()
+            .filter_map(|payload| serde_json::from_str(payload).ok())
+            .collect()
+    }
+
+    /// Emit a message immediately, outside the script
+    pub fn push(&self, message: Value) {
+        lock(&self.state).emit(Ok(message));
+    }
+
+    /// Emit an error immediately, outside the script
+    pub fn push_error(&self, error: ClaudeError) {
+        lock(&self.state).emit(Err(error));
+    }
+
+    /// End the message stream, as if the CLI exited
+    pub fn finish(&self) {
+        lock(&self.state).sender = None;
+    }
+
+    /// Whether `end_input` was called
+    #[must_use]
+    pub fn input_ended(&self) -> bool {
+        lock(&self.state).input_ended
+    }
+
+    /// Whether `close` was called
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        lock(&self.state).closed
+    }
+}
+
+/// Lock the shared state, recovering from a poisoned lock
+fn lock(state: &Mutex<MockState>) -> MutexGuard<'_, MockState> {
+    state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Build an assistant message containing a single text block
+#[must_use]
+pub fn assistant_text(text: &str) -> Value {
+    json!({
+        "type": "assistant",
+        "message": {
+            "model": "mock",
+            "content": [{ "type": "text", "text": text }],
+        },
+    })
+}
+
+/// Build a successful result message ending a turn
+#[must_use]
+pub fn result(session_id: &str, num_turns: u32) -> Value {
+    json!({
+        "type": "result",
+        "subtype": "success",
+        "duration_ms": 0,
+        "duration_api_ms": 0,
+        "is_error": false,
+        "num_turns": num_turns,
+        "session_id": session_id,
+    })
+}
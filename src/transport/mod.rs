@@ -3,12 +3,23 @@
 //! This module provides the transport abstraction and implementations for
 //! communicating with the Claude Code CLI process.
 
+pub mod boxed;
+pub mod container;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod metrics;
+pub mod mock;
+pub mod pool;
+pub mod record;
+pub mod ssh;
 pub mod subprocess;
 
 use tokio::sync::mpsc;
 
 use crate::error::Result;
 
+pub use metrics::{MetricsRecorder, TransportMetrics};
+
 /// Transport trait for communicating with Claude Code
 ///
 /// This trait defines the interface for sending and receiving messages
@@ -29,6 +40,17 @@ pub trait Transport: Send + Sync {
     /// Returns error if write fails or transport is not ready
     fn write(&mut self, data: &str) -> impl std::future::Future<Output = Result<()>> + Send;
 
+    /// Wait until every earlier write has been delivered
+    ///
+    /// Transports that deliver each write before returning from
+    /// [`write`](Self::write) need not override this.
+    ///
+    /// # Errors
+    /// Returns error if the transport can no longer deliver writes
+    fn flush(&mut self) -> impl std::future::Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+
     /// End the input stream (close stdin)
     ///
     /// # Errors
@@ -45,6 +67,11 @@ pub trait Transport: Send + Sync {
     /// Check if transport is ready for communication
     fn is_ready(&self) -> bool;
 
+    /// Shared recorder of this transport's traffic, if it collects metrics
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        None
+    }
+
     /// Close the transport and clean up resources
     ///
     /// # Errors
@@ -52,4 +79,12 @@ pub trait Transport: Send + Sync {
     fn close(&mut self) -> impl std::future::Future<Output = Result<()>> + Send;
 }
 
+pub use boxed::BoxedTransport;
+pub use container::ContainerTransport;
+#[cfg(feature = "http")]
+pub use http::HttpTransport;
+pub use mock::MockTransport;
+pub use pool::TransportPool;
+pub use record::{RecordingTransport, ReplayTransport, TransportFixture};
+pub use ssh::SshTransport;
 pub use subprocess::{PromptInput, SubprocessTransport};
@@ -0,0 +1,195 @@
+//! Pool of pre-started CLI processes
+//!
+//! Starting the Claude Code CLI dominates the latency of short-lived
+//! sessions. A [`TransportPool`] keeps a number of CLI processes started and
+//! waiting for input, hands them out as connected [`SubprocessTransport`]s
+//! and starts replacements in the background.
+//!
+//! A warm process was started with the pool's options, so it can only serve
+//! sessions that would start the same process; [`TransportPool::matches`]
+//! compares the command line, environment overrides, working directory and
+//! pre-spawn hook. Options that only affect the SDK side (hooks, permission
+//! callbacks) may differ freely.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::error::Result;
+use crate::types::options::ClaudeAgentOptions;
+
+use super::Transport;
+use super::subprocess::{LaunchKey, PromptInput, SubprocessTransport, launch_key};
+
+/// Pool of connected, idle subprocess transports
+///
+/// Cloning the pool is cheap; clones share the same processes.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() -> kodegen_claude_agent::Result<()> {
+/// use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
+/// use kodegen_claude_agent::transport::TransportPool;
+///
+/// let options = ClaudeAgentOptions::builder().max_turns(3).build();
+/// let pool = TransportPool::new(options.clone(), None, 4)?;
+/// pool.warm().await?;
+///
+/// let transport = pool.acquire().await?;
+/// let client = ClaudeSDKClient::with_transport(options, transport).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TransportPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    options: ClaudeAgentOptions,
+    cli_path: PathBuf,
+    key: LaunchKey,
+    size: usize,
+    idle: Mutex<VecDeque<SubprocessTransport>>,
+}
+
+impl TransportPool {
+    /// Create a pool keeping `size` CLI processes started with `options`
+    ///
+    /// No process is started until [`warm`](Self::warm) or
+    /// [`acquire`](Self::acquire) is called.
+    ///
+    /// # Errors
+    /// Returns error if neither `cli_path` nor `options.cli_path()` is set
+    /// and the CLI cannot be found
+    pub fn new(
+        options: ClaudeAgentOptions,
+        cli_path: Option<PathBuf>,
+        size: usize,
+    ) -> Result<Self> {
+        let cli_path = match cli_path.or_else(|| options.cli_path.clone()) {
+            Some(path) => path,
+            None => SubprocessTransport::find_cli()?,
+        };
+        let key = launch_key(&cli_path, &options);
+
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                options,
+                cli_path,
+                key,
+                size,
+                idle: Mutex::new(VecDeque::with_capacity(size)),
+            }),
+        })
+    }
+
+    /// Start processes until `size` are idle
+    ///
+    /// # Errors
+    /// Returns error if a process cannot be started
+    pub async fn warm(&self) -> Result<()> {
+        self.inner.refill().await
+    }
+
+    /// Take a connected transport, starting one if none is idle
+    ///
+    /// Idle processes that have exited are discarded. Replacements are
+    /// started in the background.
+    ///
+    /// # Errors
+    /// Returns error if no process is idle and a new one cannot be started
+    pub async fn acquire(&self) -> Result<SubprocessTransport> {
+        let warm = {
+            let mut idle = self.inner.idle.lock().await;
+            let mut warm = None;
+            while let Some(mut transport) = idle.pop_front() {
+                if !transport.has_exited() {
+                    warm = Some(transport);
+                    break;
+                }
+            }
+            warm
+        };
+
+        let inner = Arc::clone(&self.inner);
+        tokio::spawn(async move {
+            if let Err(e) = inner.refill().await {
+                log::warn!("Failed to refill transport pool: {e}");
+            }
+        });
+
+        match warm {
+            Some(transport) => Ok(transport),
+            None => self.inner.start().await,
+        }
+    }
+
+    /// Whether sessions with `options` can use this pool's processes
+    #[must_use]
+    pub fn matches(&self, options: &ClaudeAgentOptions) -> bool {
+        let cli_path = options.cli_path.as_deref().unwrap_or(&self.inner.cli_path);
+        launch_key(cli_path, options) == self.inner.key
+    }
+
+    /// Number of processes kept ready
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.inner.size
+    }
+
+    /// Number of processes currently idle
+    pub async fn idle_count(&self) -> usize {
+        self.inner.idle.lock().await.len()
+    }
+
+    /// Stop all idle processes
+    ///
+    /// The pool stays usable; later calls start processes again.
+    pub async fn close(&self) {
+        let idle: Vec<_> = self.inner.idle.lock().await.drain(..).collect();
+        for mut transport in idle {
+            let _ = transport.close().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for TransportPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportPool")
+            .field("cli_path", &self.inner.cli_path)
+            .field("size", &self.inner.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PoolInner {
+    /// Start and connect one transport
+    async fn start(&self) -> Result<SubprocessTransport> {
+        let mut transport = SubprocessTransport::new(
+            PromptInput::Stream,
+            self.options.clone(),
+            Some(self.cli_path.clone()),
+        )?;
+        transport.connect().await?;
+        Ok(transport)
+    }
+
+    /// Start processes until `size` are idle
+    async fn refill(&self) -> Result<()> {
+        loop {
+            if self.idle.lock().await.len() >= self.size {
+                return Ok(());
+            }
+            // Start outside the lock so acquire() is never blocked on a spawn
+            let transport = self.start().await?;
+            let mut idle = self.idle.lock().await;
+            if idle.len() >= self.size {
+                return Ok(());
+            }
+            idle.push_back(transport);
+        }
+    }
+}
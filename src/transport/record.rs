@@ -0,0 +1,348 @@
+//! Record/replay transports for integration fixtures
+//!
+//! [`RecordingTransport`] wraps any transport and appends every payload written
+//! to the CLI and every message read from it to a fixture file. [`ReplayTransport`]
+//! serves a recorded fixture back, so a real CLI run can be turned into a
+//! regression test that needs no CLI binary.
+//!
+//! # Fixture format
+//!
+//! Fixtures are JSON Lines. Each line is one event, in the order it happened:
+//!
+//! ```text
+//! {"direction":"sent","data":"{\"type\":\"user\",...}"}
+//! {"direction":"received","message":{"type":"assistant",...}}
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kodegen_claude_agent::transport::record::{RecordingTransport, ReplayTransport};
+//! use kodegen_claude_agent::transport::{PromptInput, SubprocessTransport};
+//! use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
+//!
+//! # async fn example() -> kodegen_claude_agent::Result<()> {
+//! // Record a real run
+//! let cli = SubprocessTransport::new(PromptInput::Stream, ClaudeAgentOptions::default(), None)?;
+//! let transport = RecordingTransport::create(cli, "tests/fixtures/hello.jsonl")?;
+//! let client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport).await?;
+//!
+//! // Later, replay it without the CLI
+//! let transport = ReplayTransport::from_path("tests/fixtures/hello.jsonl")?;
+//! let client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::error::{ClaudeError, Result};
+
+use super::{MetricsRecorder, Transport};
+use super::mock::{MockHandle, MockTransport};
+
+// ============================================================================
+// FIXTURES
+// ============================================================================
+
+/// One recorded exchange with the CLI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "direction", rename_all = "snake_case")]
+pub enum FixtureEvent {
+    /// Payload written to the CLI's stdin (without the trailing newline)
+    Sent {
+        /// Raw payload
+        data: String,
+    },
+    /// Message read from the CLI's stdout
+    Received {
+        /// Parsed message
+        message: Value,
+    },
+}
+
+/// Recorded stdin/stdout exchange with the CLI
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransportFixture {
+    /// Events in the order they happened
+    pub events: Vec<FixtureEvent>,
+}
+
+impl TransportFixture {
+    /// Load a fixture from a JSON Lines file
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be read or a line is not a valid event
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(serde_json::from_str(&line)?);
+        }
+        Ok(Self { events })
+    }
+
+    /// Write the fixture to a JSON Lines file
+    ///
+    /// # Errors
+    /// Returns error if the file cannot be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut out = String::new();
+        for event in &self.events {
+            out.push_str(&serde_json::to_string(event)?);
+            out.push('\n');
+        }
+        std::fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// Payloads written to the CLI, in order
+    #[must_use]
+    pub fn sent(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                FixtureEvent::Sent { data } => Some(data.as_str()),
+                FixtureEvent::Received { .. } => None,
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// RECORDING
+// ============================================================================
+
+/// Transport wrapper that records the exchange with the wrapped transport
+///
+/// Events are appended to the fixture file as they happen, so a run that
+/// crashes still leaves a usable fixture behind.
+pub struct RecordingTransport<T: Transport> {
+    inner: T,
+    sink: Arc<Mutex<File>>,
+}
+
+impl<T: Transport> RecordingTransport<T> {
+    /// Wrap a transport, recording to a new (or truncated) fixture file
+    ///
+    /// # Errors
+    /// Returns error if the fixture file cannot be created
+    pub fn create(inner: T, path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            inner,
+            sink: Arc::new(Mutex::new(File::create(path)?)),
+        })
+    }
+}
+
+/// Append one event to the fixture file
+fn record(sink: &Mutex<File>, event: &FixtureEvent) {
+    let line = match serde_json::to_string(event) {
+        Ok(line) => line,
+        Err(e) => {
+            log::warn!("Failed to serialize fixture event: {e}");
+            return;
+        }
+    };
+    let mut file = sink
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Err(e) = writeln!(file, "{line}") {
+        log::warn!("Failed to write fixture event: {e}");
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        // Record first so the payload precedes any reply to it in the fixture
+        record(
+            &self.sink,
+            &FixtureEvent::Sent {
+                data: data.trim_end().to_string(),
+            },
+        );
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.inner.end_input().await
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<Value>> {
+        let mut inner_rx = self.inner.read_messages();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sink = Arc::clone(&self.sink);
+
+        tokio::spawn(async move {
+            while let Some(result) = inner_rx.recv().await {
+                if let Ok(ref message) = result {
+                    record(
+                        &sink,
+                        &FixtureEvent::Received {
+                            message: message.clone(),
+                        },
+                    );
+                }
+                if tx.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        rx
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        self.inner.metrics()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+}
+
+// ============================================================================
+// REPLAY
+// ============================================================================
+
+/// Transport that serves a recorded fixture back
+///
+/// Messages received before the first recorded write are emitted as soon as
+/// the stream is read; the messages following each recorded write are emitted
+/// after the corresponding write to this transport. The stream ends once the
+/// fixture is exhausted.
+///
+/// Payloads are not compared against the recording by default, since control
+/// requests carry generated ids. Use [`ReplayTransport::strict`] to require
+/// every write to match the recorded payload exactly.
+pub struct ReplayTransport {
+    inner: MockTransport,
+    expected: VecDeque<String>,
+    strict: bool,
+}
+
+impl ReplayTransport {
+    /// Create a replay transport for a fixture
+    #[must_use]
+    pub fn new(fixture: TransportFixture) -> Self {
+        let mut inner = MockTransport::new();
+        let mut expected = VecDeque::new();
+        let mut batch: Option<Vec<Value>> = None;
+
+        for event in fixture.events {
+            match event {
+                FixtureEvent::Sent { data } => {
+                    if let Some(messages) = batch.replace(Vec::new()) {
+                        inner = inner.reply(messages);
+                    }
+                    expected.push_back(data);
+                }
+                FixtureEvent::Received { message } => match batch {
+                    Some(ref mut messages) => messages.push(message),
+                    None => inner = inner.message(message),
+                },
+            }
+        }
+        if let Some(messages) = batch {
+            inner = inner.reply(messages);
+        }
+
+        Self {
+            inner: inner.finish_after_replies(),
+            expected,
+            strict: false,
+        }
+    }
+
+    /// Load a fixture file and create a replay transport for it
+    ///
+    /// # Errors
+    /// Returns error if the fixture cannot be loaded
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(TransportFixture::load(path)?))
+    }
+
+    /// Require every write to match the recorded payload
+    #[must_use]
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Get a handle for inspecting what was written during the replay
+    #[must_use]
+    pub fn handle(&self) -> MockHandle {
+        self.inner.handle()
+    }
+}
+
+impl Transport for ReplayTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        let expected = self.expected.pop_front();
+        if self.strict {
+            let actual = data.trim_end();
+            match expected {
+                Some(ref expected) if expected == actual => {}
+                Some(expected) => {
+                    return Err(ClaudeError::transport(format!(
+                        "Replay mismatch: expected {expected}, got {actual}"
+                    )));
+                }
+                None => {
+                    return Err(ClaudeError::transport(format!(
+                        "Replay exhausted: unexpected write {actual}"
+                    )));
+                }
+            }
+        }
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.inner.end_input().await
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<Value>> {
+        self.inner.read_messages()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+}
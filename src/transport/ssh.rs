@@ -0,0 +1,78 @@
+//! SSH transport for running the CLI on a remote host
+//!
+//! This module provides a transport that starts the Claude Code CLI on another
+//! machine through the local `ssh` client and pipes stdin/stdout over the
+//! connection, with the same stream-json semantics as `SubprocessTransport`.
+
+use tokio::sync::mpsc;
+
+use crate::error::Result;
+use crate::transport::{MetricsRecorder, Transport};
+use crate::types::options::ClaudeAgentOptions;
+use crate::types::transport::SshTransportConfig;
+
+use super::subprocess::{Launcher, PromptInput, SubprocessTransport};
+
+/// Transport that runs the CLI on a remote host over ssh
+pub struct SshTransport {
+    inner: SubprocessTransport,
+}
+
+impl SshTransport {
+    /// Create a new ssh transport
+    ///
+    /// # Arguments
+    /// * `prompt` - The prompt input (string or stream)
+    /// * `options` - Configuration options (`cwd` and `env` apply remotely)
+    /// * `config` - Remote host and ssh client configuration
+    #[must_use]
+    pub fn new(
+        prompt: PromptInput,
+        options: ClaudeAgentOptions,
+        config: SshTransportConfig,
+    ) -> Self {
+        let cli_path = config.remote_cli_path.clone().into();
+        Self {
+            inner: SubprocessTransport::with_launcher(
+                prompt,
+                options,
+                cli_path,
+                Launcher::Ssh(config),
+            ),
+        }
+    }
+}
+
+impl Transport for SshTransport {
+    async fn connect(&mut self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&mut self, data: &str) -> Result<()> {
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn end_input(&mut self) -> Result<()> {
+        self.inner.end_input().await
+    }
+
+    fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
+        self.inner.read_messages()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        self.inner.metrics()
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.inner.close().await
+    }
+}
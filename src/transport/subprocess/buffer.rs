@@ -0,0 +1,71 @@
+//! Pooled byte buffers for the stdout reader
+//!
+//! Each reader needs a line buffer and a buffer accumulating the JSON value
+//! in progress. Both grow to the size of the largest message seen, so instead
+//! of allocating them per reader (and per CLI restart) they are taken from a
+//! process-wide pool and returned when the reader finishes. A manager
+//! streaming many short-lived sessions then reuses the same few allocations.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Most idle buffers kept in the pool
+const MAX_POOLED: usize = 32;
+
+/// Capacity a buffer is shrunk to before returning to the pool
+///
+/// One huge message should not pin megabytes per pooled buffer for the rest
+/// of the process.
+const MAX_RETAINED_CAPACITY: usize = 64 * 1024;
+
+/// Initial capacity of freshly allocated buffers
+const INITIAL_CAPACITY: usize = 8 * 1024;
+
+static POOL: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+
+fn pool() -> MutexGuard<'static, Vec<Vec<u8>>> {
+    POOL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// An empty byte buffer, returned to the pool on drop
+#[derive(Debug)]
+pub(super) struct PooledBuffer {
+    buf: Vec<u8>,
+}
+
+impl PooledBuffer {
+    /// Take a buffer from the pool, allocating one if the pool is empty
+    pub(super) fn take() -> Self {
+        let buf = pool()
+            .pop()
+            .unwrap_or_else(|| Vec::with_capacity(INITIAL_CAPACITY));
+        Self { buf }
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buf
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        buf.shrink_to(MAX_RETAINED_CAPACITY);
+
+        let mut pool = pool();
+        if pool.len() < MAX_POOLED {
+            pool.push(buf);
+        }
+    }
+}
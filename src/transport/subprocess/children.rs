@@ -0,0 +1,182 @@
+//! Registry of running CLI processes
+//!
+//! Every CLI process this crate starts is recorded here until its handle is
+//! dropped (which kills it). A process may carry an owner: the manager
+//! session it was started for. A handle can outlive its session, e.g. when
+//! an `AgentManager` is dropped without `shutdown` and the session tasks
+//! keep running; the manager's orphan reaper finds such processes here and
+//! kills them.
+
+// Only the manager (orphan reaper, memory tracking) reads the registry
+#![cfg_attr(not(feature = "manager"), allow(dead_code))]
+
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError, Weak};
+use tokio::process::Child;
+
+use super::spill::SpilledArgs;
+
+/// Session a CLI process was started for
+///
+/// The session holds the strong side of `lease` for as long as it exists;
+/// once every strong reference is gone the process is an orphan.
+#[derive(Debug, Clone)]
+pub(crate) struct ProcessOwner {
+    /// ID of the owning session
+    pub session: String,
+    /// Liveness of the owning session
+    pub lease: Weak<()>,
+}
+
+impl ProcessOwner {
+    /// Whether the owning session no longer exists
+    pub fn is_gone(&self) -> bool {
+        self.lease.strong_count() == 0
+    }
+}
+
+/// A registered CLI process
+#[derive(Debug, Clone)]
+pub(crate) struct TrackedProcess {
+    /// OS process ID
+    pub pid: u32,
+    /// Session the process belongs to, if any
+    pub owner: Option<ProcessOwner>,
+}
+
+static CHILDREN: Mutex<Option<HashMap<u32, TrackedProcess>>> = Mutex::new(None);
+
+fn children() -> MutexGuard<'static, Option<HashMap<u32, TrackedProcess>>> {
+    CHILDREN.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A child process handle that stays registered while it is alive
+///
+/// Dereferences to the tokio [`Child`]; dropping it unregisters the process
+/// and removes the temporary files holding its spilled arguments.
+#[derive(Debug)]
+pub(super) struct TrackedChild {
+    child: Child,
+    pid: Option<u32>,
+    /// Arguments the process reads from files (removed after the process)
+    files: SpilledArgs,
+}
+
+impl TrackedChild {
+    /// Register `child`, started for `owner`
+    pub(super) fn new(child: Child, owner: Option<ProcessOwner>) -> Self {
+        let pid = child.id();
+        if let Some(pid) = pid {
+            children().get_or_insert_default().insert(
+                pid,
+                TrackedProcess { pid, owner },
+            );
+        }
+        Self {
+            child,
+            pid,
+            files: SpilledArgs::default(),
+        }
+    }
+
+    /// Keep the files holding the process's spilled arguments until the
+    /// handle is dropped
+    pub(super) fn keep_files(mut self, files: SpilledArgs) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Record a new owner for the process
+    pub(super) fn set_owner(&self, owner: Option<ProcessOwner>) {
+        if let Some(pid) = self.pid
+            && let Some(process) = children().as_mut().and_then(|c| c.get_mut(&pid))
+        {
+            process.owner = owner;
+        }
+    }
+}
+
+impl Deref for TrackedChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for TrackedChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid
+            && let Some(children) = children().as_mut()
+        {
+            children.remove(&pid);
+        }
+    }
+}
+
+/// Registered processes whose owning session no longer exists
+pub(crate) fn orphans() -> Vec<TrackedProcess> {
+    children().as_ref().map_or_else(Vec::new, |children| {
+        children
+            .values()
+            .filter(|p| p.owner.as_ref().is_some_and(ProcessOwner::is_gone))
+            .cloned()
+            .collect()
+    })
+}
+
+/// PIDs of the registered processes started for `session`
+pub(crate) fn session_pids(session: &str) -> Vec<u32> {
+    children().as_ref().map_or_else(Vec::new, |children| {
+        children
+            .values()
+            .filter(|p| p.owner.as_ref().is_some_and(|o| o.session == session))
+            .map(|p| p.pid)
+            .collect()
+    })
+}
+
+/// Kill those of `pids` that are still registered
+///
+/// Returns the PIDs that were killed. A process stays registered until its
+/// handle observes the exit and is dropped. The registered PIDs are
+/// collected first and killed after the registry lock is released, so
+/// processes starting or exiting meanwhile are not held up. Blocks while the
+/// kill commands run.
+pub(crate) fn kill_tracked(pids: impl IntoIterator<Item = u32>) -> Vec<u32> {
+    let registered: Vec<u32> = {
+        let children = children();
+        pids.into_iter()
+            .filter(|pid| children.as_ref().is_some_and(|c| c.contains_key(pid)))
+            .collect()
+    };
+    registered
+        .into_iter()
+        .filter(|pid| kill_pid(*pid))
+        .collect()
+}
+
+/// Send SIGKILL (or terminate the process tree on Windows)
+fn kill_pid(pid: u32) -> bool {
+    let pid = pid.to_string();
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = std::process::Command::new("taskkill");
+        cmd.args(["/F", "/T", "/PID", &pid]);
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("kill");
+        cmd.args(["-KILL", &pid]);
+        cmd
+    };
+    cmd.stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
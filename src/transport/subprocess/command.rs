@@ -1,7 +1,7 @@
 //! CLI command building logic for subprocess transport
 
 use std::collections::HashMap;
-use tokio::process::Command;
+use std::ffi::{OsStr, OsString};
 
 use crate::types::agent::SystemPrompt;
 use crate::types::mcp::{McpServerConfig, McpServers};
@@ -10,30 +10,32 @@ use crate::types::permissions::{PermissionMode, SettingSource};
 
 use super::config::{ALLOWED_EXTRA_FLAGS, PromptInput};
 
+/// Arguments collected in order, quoted only once the command is built
+#[derive(Default)]
+struct ArgList(Vec<OsString>);
+
+impl ArgList {
+    fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.0.push(arg.as_ref().to_owned());
+        self
+    }
+}
+
 /// Command builder for Claude CLI
 pub struct CommandBuilder<'a> {
-    cli_path: &'a std::path::Path,
     prompt: &'a PromptInput,
     options: &'a ClaudeAgentOptions,
 }
 
 impl<'a> CommandBuilder<'a> {
     /// Create a new command builder
-    pub fn new(
-        cli_path: &'a std::path::Path,
-        prompt: &'a PromptInput,
-        options: &'a ClaudeAgentOptions,
-    ) -> Self {
-        Self {
-            cli_path,
-            prompt,
-            options,
-        }
+    pub fn new(prompt: &'a PromptInput, options: &'a ClaudeAgentOptions) -> Self {
+        Self { prompt, options }
     }
 
-    /// Build the complete CLI command with all arguments
-    pub fn build(&self) -> Command {
-        let mut cmd = Command::new(self.cli_path);
+    /// Arguments of the CLI command, before any platform quoting
+    pub fn args(&self) -> Vec<OsString> {
+        let mut cmd = ArgList::default();
 
         // Base arguments
         cmd.arg("--print")
@@ -71,11 +73,11 @@ impl<'a> CommandBuilder<'a> {
             }
         }
 
-        cmd
+        cmd.0
     }
 
     /// Add tool-related arguments
-    fn add_tool_args(&self, cmd: &mut Command) {
+    fn add_tool_args(&self, cmd: &mut ArgList) {
         if !self.options.allowed_tools.is_empty() {
             let tools: Vec<String> = self
                 .options
@@ -98,7 +100,7 @@ impl<'a> CommandBuilder<'a> {
     }
 
     /// Add configuration arguments (model, max turns, permissions)
-    fn add_configuration_args(&self, cmd: &mut Command) {
+    fn add_configuration_args(&self, cmd: &mut ArgList) {
         if let Some(max_turns) = self.options.max_turns {
             cmd.arg("--max-turns").arg(max_turns.to_string());
         }
@@ -123,7 +125,7 @@ impl<'a> CommandBuilder<'a> {
     }
 
     /// Add session-related arguments
-    fn add_session_args(&self, cmd: &mut Command) {
+    fn add_session_args(&self, cmd: &mut ArgList) {
         if self.options.continue_conversation {
             cmd.arg("--continue");
         }
@@ -155,18 +157,19 @@ impl<'a> CommandBuilder<'a> {
     }
 
     /// Add MCP server configuration
-    fn add_mcp_args(&self, cmd: &mut Command) {
+    ///
+    /// In-process SDK servers are merged into the configured servers; with a
+    /// configuration file they are passed as a second `--mcp-config` value.
+    fn add_mcp_args(&self, cmd: &mut ArgList) {
+        let mut config_map = HashMap::new();
+        for (name, server) in &self.options.sdk_mcp_servers {
+            config_map.insert(name.clone(), serialize_mcp_config(&server.config()));
+        }
+
         match &self.options.mcp_servers {
             McpServers::Dict(servers) => {
-                if !servers.is_empty() {
-                    let mut config_map = HashMap::new();
-                    for (name, config) in servers {
-                        config_map.insert(name.clone(), serialize_mcp_config(config));
-                    }
-                    let config_json = serde_json::json!({
-                        "mcpServers": config_map
-                    });
-                    cmd.arg("--mcp-config").arg(config_json.to_string());
+                for (name, config) in servers {
+                    config_map.insert(name.clone(), serialize_mcp_config(config));
                 }
             }
             McpServers::Path(path) => {
@@ -174,10 +177,20 @@ impl<'a> CommandBuilder<'a> {
             }
             McpServers::None => {}
         }
+
+        if !config_map.is_empty() {
+            let config_json = serde_json::json!({
+                "mcpServers": config_map
+            });
+            if !matches!(self.options.mcp_servers, McpServers::Path(_)) {
+                cmd.arg("--mcp-config");
+            }
+            cmd.arg(config_json.to_string());
+        }
     }
 
     /// Add setting sources and extra arguments
-    fn add_extra_args(&self, cmd: &mut Command) {
+    fn add_extra_args(&self, cmd: &mut ArgList) {
         if let Some(ref sources) = self.options.setting_sources {
             let sources_str: Vec<&str> = sources
                 .iter()
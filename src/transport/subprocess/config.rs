@@ -1,8 +1,19 @@
 //! Configuration constants and types for subprocess transport
 
+use std::time::Duration;
+
 /// Default maximum buffer size for JSON messages (1MB)
 pub const DEFAULT_MAX_BUFFER_SIZE: usize = 1024 * 1024;
 
+/// Default amount of recent stderr output kept for diagnostics (64KB)
+pub const DEFAULT_MAX_STDERR_SIZE: usize = 64 * 1024;
+
+/// Default longest wait for a line of CLI output (30 seconds)
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time `close` waits for the CLI to exit (5 seconds)
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
 /// Dangerous environment variables that should not be passed to subprocess
 ///
 /// These variables can affect how the subprocess loads and executes code,
@@ -0,0 +1,169 @@
+//! Locating the Claude Code CLI and starting it on each platform
+//!
+//! On Windows npm installs the CLI as `claude.cmd` / `claude.ps1` script
+//! shims, which cannot be started directly, and environment variable names
+//! are case-insensitive. Both are handled here so the rest of the transport
+//! can stay platform-neutral.
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::error::Result;
+
+use super::config::DANGEROUS_ENV_VARS;
+use super::quoting;
+
+/// Fallback CLI locations searched when `claude` is not on `PATH`
+///
+/// `var` looks up environment variables; `windows` selects the Windows
+/// layout (`%USERPROFILE%`, `%APPDATA%`, `%LOCALAPPDATA%`).
+pub(super) fn search_locations(
+    windows: bool,
+    var: impl Fn(&str) -> Option<String>,
+) -> Vec<PathBuf> {
+    if windows {
+        let home = PathBuf::from(var("USERPROFILE").unwrap_or_else(|| String::from(r"C:\")));
+        let mut locations = vec![home.join(r".local\bin\claude.exe")];
+        if let Some(appdata) = var("APPDATA") {
+            let npm = PathBuf::from(appdata).join("npm");
+            locations.push(npm.join("claude.cmd"));
+            locations.push(npm.join("claude.ps1"));
+        }
+        if let Some(local) = var("LOCALAPPDATA") {
+            let local = PathBuf::from(local);
+            locations.push(local.join(r"Programs\claude\claude.exe"));
+            locations.push(local.join(r"Yarn\bin\claude.cmd"));
+        }
+        locations.push(home.join(r"node_modules\.bin\claude.cmd"));
+        return locations;
+    }
+
+    let home = PathBuf::from(var("HOME").unwrap_or_else(|| String::from("/root")));
+    vec![
+        home.join(".npm-global/bin/claude"),
+        PathBuf::from("/usr/local/bin/claude"),
+        home.join(".local/bin/claude"),
+        home.join("node_modules/.bin/claude"),
+        home.join(".yarn/bin/claude"),
+    ]
+}
+
+/// Kind of script shim a CLI path points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shim {
+    /// Batch file, run through `cmd.exe`
+    Batch,
+    /// PowerShell script, run through `powershell.exe`
+    PowerShell,
+}
+
+/// Shim kind of a Windows CLI path, if it is not directly executable
+fn shim_kind(cli_path: &Path) -> Option<Shim> {
+    if !cfg!(windows) {
+        return None;
+    }
+    let extension = cli_path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "cmd" | "bat" => Some(Shim::Batch),
+        "ps1" => Some(Shim::PowerShell),
+        _ => None,
+    }
+}
+
+/// Command starting the CLI at `cli_path` with `args`, through its
+/// interpreter for shims
+///
+/// # Errors
+/// Returns `ClaudeError::InvalidConfig` if an argument cannot pass through a
+/// `.cmd` shim (see [`quoting::cmd_shim`])
+pub(super) fn cli_command(cli_path: &Path, args: &[OsString]) -> Result<Command> {
+    match shim_kind(cli_path) {
+        Some(Shim::Batch) => {
+            // cmd.exe does not parse its command line with the MSVC rules,
+            // so the line is quoted here and passed on verbatim
+            let line = format!("\"{}\"", quoting::batch_command_line(cli_path, args)?);
+            let mut cmd = Command::new("cmd.exe");
+            cmd.args(["/d", "/s", "/c"]);
+            #[cfg(windows)]
+            cmd.raw_arg(line);
+            #[cfg(not(windows))]
+            cmd.arg(line);
+            Ok(cmd)
+        }
+        Some(Shim::PowerShell) => {
+            let mut cmd = Command::new("powershell.exe");
+            cmd.args([
+                "-NoLogo",
+                "-NoProfile",
+                "-NonInteractive",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+            ])
+            .arg(cli_path)
+            .args(args);
+            Ok(cmd)
+        }
+        None => {
+            let mut cmd = Command::new(cli_path);
+            cmd.args(args);
+            Ok(cmd)
+        }
+    }
+}
+
+/// Whether a variable may not be overridden through the `env` option
+///
+/// Names compare case-insensitively on Windows, where `Path` and `PATH` are
+/// the same variable.
+pub(super) fn is_dangerous_env_var(key: &str) -> bool {
+    if cfg!(windows) {
+        DANGEROUS_ENV_VARS
+            .iter()
+            .any(|dangerous| dangerous.eq_ignore_ascii_case(key))
+    } else {
+        DANGEROUS_ENV_VARS.contains(&key)
+    }
+}
+
+/// Set a variable in a process environment map
+///
+/// On Windows any entry differing only in case is replaced, so the child
+/// does not receive two spellings of one variable.
+pub(super) fn set_env_var(env: &mut HashMap<String, String>, key: &str, value: String) {
+    if cfg!(windows) {
+        env.retain(|existing, _| !existing.eq_ignore_ascii_case(key));
+    }
+    env.insert(key.to_string(), value);
+}
+
+/// Prepend a script shim's directory to `PATH`
+///
+/// npm shims look for `node` and sibling scripts next to themselves or on
+/// `PATH`; a CLI found in a fallback location is usually not on `PATH`.
+/// Entries are split and joined with the platform's separator.
+pub(super) fn add_shim_dir_to_path(env: &mut HashMap<String, String>, cli_path: &Path) {
+    let Some(dir) = cli_path.parent().filter(|_| shim_kind(cli_path).is_some()) else {
+        return;
+    };
+
+    let key = env
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case("PATH"))
+        .cloned()
+        .unwrap_or_else(|| String::from("PATH"));
+    let current = env.get(&key).map(OsString::from).unwrap_or_default();
+
+    let mut entries: Vec<PathBuf> = env::split_paths(&current).collect();
+    if entries.iter().any(|entry| entry == dir) {
+        return;
+    }
+    entries.insert(0, dir.to_path_buf());
+
+    if let Ok(joined) = env::join_paths(entries) {
+        env.insert(key, joined.to_string_lossy().into_owned());
+    }
+}
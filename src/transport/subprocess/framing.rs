@@ -0,0 +1,161 @@
+//! Incremental framing of the CLI's JSON output
+//!
+//! The CLI writes one JSON object per line, but large messages may arrive
+//! pretty-printed over many lines. Rather than re-parsing the accumulated
+//! text after every line, the framer tracks string and nesting state as bytes
+//! arrive and parses each value exactly once, when its closing bracket is
+//! seen. Ingestion is linear in the size of the output.
+//!
+//! Tools may print binary or otherwise invalid UTF-8, which the CLI can pass
+//! on inside JSON strings. Such bytes are replaced with U+FFFD rather than
+//! failing the whole message, and the framer counts the replacements.
+
+use super::buffer::PooledBuffer;
+
+/// Longest excerpt of non-JSON output quoted in errors
+const NOISE_EXCERPT_LEN: usize = 100;
+
+/// Outcome of a completed frame
+#[derive(Debug)]
+pub(super) enum Frame {
+    /// A complete JSON value
+    Message(serde_json::Value),
+    /// A complete frame that is not valid JSON
+    Invalid(serde_json::Error),
+    /// Output outside any JSON value; the rest of its line is discarded
+    Noise(String),
+    /// The value in progress exceeded the size limit and was discarded
+    Overflow,
+}
+
+/// Splits raw CLI output into JSON values
+pub(super) struct JsonFramer {
+    frame: PooledBuffer,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    max_size: usize,
+    /// Invalid UTF-8 sequences replaced since the last `take_invalid_utf8`
+    invalid_utf8: usize,
+}
+
+impl JsonFramer {
+    /// Create a framer that discards values larger than `max_size` bytes
+    pub(super) fn new(max_size: usize) -> Self {
+        Self {
+            frame: PooledBuffer::take(),
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            max_size,
+            invalid_utf8: 0,
+        }
+    }
+
+    /// Feed one line of output (with or without its newline)
+    ///
+    /// Returns the frames the line completed, in order; a value spanning
+    /// several lines is returned with the line that closes it.
+    pub(super) fn push(&mut self, line: &[u8]) -> Vec<Frame> {
+        let mut frames = Vec::new();
+
+        for (i, &byte) in line.iter().enumerate() {
+            if self.depth == 0 {
+                match byte {
+                    b'{' | b'[' => {
+                        self.frame.push(byte);
+                        self.depth = 1;
+                    }
+                    byte if byte.is_ascii_whitespace() => {}
+                    _ => {
+                        frames.push(Frame::Noise(noise_excerpt(&line[i..])));
+                        return frames;
+                    }
+                }
+                continue;
+            }
+
+            self.frame.push(byte);
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+            } else {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => {
+                        self.depth -= 1;
+                        if self.depth == 0 {
+                            frames.push(self.finish());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if self.frame.len() > self.max_size {
+                self.reset();
+                frames.push(Frame::Overflow);
+                return frames;
+            }
+        }
+
+        frames
+    }
+
+    /// Number of invalid UTF-8 sequences replaced since the last call
+    pub(super) fn take_invalid_utf8(&mut self) -> usize {
+        std::mem::take(&mut self.invalid_utf8)
+    }
+
+    /// Parse the completed frame and start a new one
+    fn finish(&mut self) -> Frame {
+        let frame = match serde_json::from_slice(&self.frame) {
+            Ok(value) => Frame::Message(value),
+            Err(e) => match invalid_utf8_sequences(&self.frame) {
+                0 => Frame::Invalid(e),
+                invalid => {
+                    self.invalid_utf8 += invalid;
+                    match serde_json::from_str(&String::from_utf8_lossy(&self.frame)) {
+                        Ok(value) => Frame::Message(value),
+                        Err(e) => Frame::Invalid(e),
+                    }
+                }
+            },
+        };
+        self.reset();
+        frame
+    }
+
+    fn reset(&mut self) {
+        self.frame.clear();
+        self.depth = 0;
+        self.in_string = false;
+        self.escaped = false;
+    }
+}
+
+/// Number of invalid UTF-8 sequences in `bytes`
+///
+/// Each sequence becomes one U+FFFD in a lossy conversion.
+pub(super) fn invalid_utf8_sequences(bytes: &[u8]) -> usize {
+    bytes
+        .utf8_chunks()
+        .filter(|chunk| !chunk.invalid().is_empty())
+        .count()
+}
+
+/// Start of a non-JSON line, for error messages
+fn noise_excerpt(rest: &[u8]) -> String {
+    let text = String::from_utf8_lossy(rest);
+    let text = text.trim_end();
+    match text.char_indices().nth(NOISE_EXCERPT_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
@@ -0,0 +1,179 @@
+//! Launchers that run the CLI somewhere other than the local host
+//!
+//! A launcher rewrites the command produced by `CommandBuilder` so the CLI is
+//! started through another program (e.g. `ssh` or `docker`) while keeping stdin/stdout
+//! piped back to the transport. The stream-json protocol is unchanged.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::types::options::ClaudeAgentOptions;
+use crate::types::transport::{ContainerTransportConfig, SshTransportConfig};
+
+use super::quoting;
+
+/// How the CLI process is started
+#[derive(Debug, Clone)]
+pub(crate) enum Launcher {
+    /// Run the CLI on a remote host via the local ssh client
+    Ssh(SshTransportConfig),
+    /// Run the CLI inside a container via docker/podman
+    Container(ContainerTransportConfig),
+}
+
+impl Launcher {
+    /// Wrap a local CLI command so it runs through this launcher
+    ///
+    /// # Arguments
+    /// * `cli` - Command built for the CLI (program and arguments are reused)
+    /// * `cli_env` - Environment variables for the CLI process itself
+    /// * `options` - Options the command was built from (`cwd`, `add_dirs`)
+    pub(crate) fn wrap(
+        &self,
+        cli: &Command,
+        cli_env: &HashMap<String, String>,
+        options: &ClaudeAgentOptions,
+    ) -> Command {
+        match self {
+            Self::Ssh(config) => wrap_ssh(config, cli, cli_env, options.cwd.as_deref()),
+            Self::Container(config) => wrap_container(config, cli, cli_env, options),
+        }
+    }
+
+    /// Bytes to write to the wrapped command's stdin before the CLI's input
+    ///
+    /// The ssh launcher sends the CLI's environment this way, so values
+    /// never appear on the local command line.
+    pub(crate) fn preamble(&self, cli_env: &HashMap<String, String>) -> Option<Vec<u8>> {
+        match self {
+            Self::Ssh(_) if !cli_env.is_empty() => {
+                let mut env: Vec<_> = cli_env.iter().collect();
+                env.sort();
+                let script: String = env
+                    .into_iter()
+                    .map(|(key, value)| {
+                        format!("export {}\n", quoting::posix(&format!("{key}={value}")))
+                    })
+                    .collect();
+                Some(format!("{}\n{script}", script.len()).into_bytes())
+            }
+            Self::Ssh(_) | Self::Container(_) => None,
+        }
+    }
+}
+
+/// Build `ssh [options] [user@]host -- <remote shell command>`
+fn wrap_ssh(
+    config: &SshTransportConfig,
+    cli: &Command,
+    cli_env: &HashMap<String, String>,
+    cwd: Option<&Path>,
+) -> Command {
+    let mut cmd = Command::new(&config.ssh_path);
+
+    // Never prompt: a password prompt would hang the transport
+    cmd.arg("-o").arg("BatchMode=yes").arg("-T");
+    if let Some(port) = config.port {
+        cmd.arg("-p").arg(port.to_string());
+    }
+    if let Some(ref identity) = config.identity_file {
+        cmd.arg("-i").arg(identity);
+    }
+    cmd.args(&config.ssh_args);
+
+    let destination = match config.user {
+        Some(ref user) => format!("{user}@{}", config.host),
+        None => config.host.clone(),
+    };
+    cmd.arg(destination).arg("--");
+
+    // ssh hands the remote command to the remote user's shell, so every part
+    // must be quoted for a POSIX shell
+    let mut remote = String::new();
+    if let Some(cwd) = cwd {
+        remote.push_str("cd ");
+        remote.push_str(&quoting::posix(&cwd.to_string_lossy()));
+        remote.push_str(" && ");
+    }
+    // The environment arrives on stdin (see `Launcher::preamble`): its
+    // length, then that many bytes of `export` lines. `read` and `dd` take
+    // exactly those bytes, leaving the rest of stdin to the CLI
+    if !cli_env.is_empty() {
+        remote.push_str("read -r n && eval \"$(dd bs=1 count=\"$n\" 2>/dev/null)\" && ");
+    }
+    remote.push_str("exec ");
+    remote.push_str(&quoting::posix(&config.remote_cli_path));
+
+    let std_cmd = cli.as_std();
+    for arg in std_cmd.get_args() {
+        remote.push(' ');
+        remote.push_str(&quoting::posix(&arg.to_string_lossy()));
+    }
+    cmd.arg(remote);
+
+    cmd
+}
+
+/// Build `<runtime> run --rm -i [options] <image> <cli> <args>`
+fn wrap_container(
+    config: &ContainerTransportConfig,
+    cli: &Command,
+    cli_env: &HashMap<String, String>,
+    options: &ClaudeAgentOptions,
+) -> Command {
+    let mut cmd = Command::new(&config.runtime);
+    cmd.arg("run").arg("--rm").arg("-i");
+
+    if let Some(ref network) = config.network {
+        cmd.arg("--network").arg(network);
+    }
+
+    // The working directory and extra directories are mounted at the same
+    // path so paths in CLI arguments stay valid inside the container
+    let mut mounted: Vec<&Path> = Vec::new();
+    if let Some(ref cwd) = options.cwd {
+        cmd.arg("-v").arg(bind_spec(cwd, cwd, false));
+        cmd.arg("-w").arg(cwd);
+        mounted.push(cwd);
+    }
+    for dir in &options.add_dirs {
+        if !mounted.contains(&dir.as_path()) {
+            cmd.arg("-v").arg(bind_spec(dir, dir, false));
+            mounted.push(dir);
+        }
+    }
+    for mount in &config.mounts {
+        cmd.arg("-v").arg(bind_spec(
+            &mount.host_path,
+            &mount.container_path,
+            mount.read_only,
+        ));
+    }
+
+    // Pass variables by name only; the runtime reads the values from its own
+    // environment so they never appear on the command line
+    let mut env: Vec<_> = cli_env.keys().collect();
+    env.sort();
+    for key in env {
+        cmd.arg("-e").arg(key);
+    }
+
+    cmd.args(&config.run_args);
+    cmd.arg(&config.image).arg(&config.cli_path);
+    cmd.args(cli.as_std().get_args());
+
+    cmd
+}
+
+/// Format a `-v` bind mount specification
+fn bind_spec(host: &Path, container: &Path, read_only: bool) -> OsString {
+    let mut spec = host.as_os_str().to_owned();
+    spec.push(":");
+    spec.push(container.as_os_str());
+    if read_only {
+        spec.push(":ro");
+    }
+    spec
+}
@@ -1,52 +1,179 @@
 //! Lifecycle management for subprocess transport (connect, close)
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{ChildStdin, ChildStdout};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
 use crate::VERSION;
 use crate::error::{ClaudeError, Result};
+use crate::transport::MetricsRecorder;
+use crate::types::options::ClaudeAgentOptions;
+use crate::types::transport::Priority;
 
+use super::children::TrackedChild;
 use super::command::CommandBuilder;
-use super::config::{DANGEROUS_ENV_VARS, PromptInput};
+use super::config::PromptInput;
+use super::discovery::{add_shim_dir_to_path, cli_command, is_dangerous_env_var, set_env_var};
+use super::launcher::Launcher;
+use super::priority::apply as apply_priority;
+use super::spill::{MAX_COMMAND_LINE, SpilledArgs, command_line_len, spill_long_args, too_long};
+use super::stderr::StderrBuffer;
 use super::transport::SubprocessTransport;
+use super::writer::{Restarts, StdinWriter};
 
-impl SubprocessTransport {
-    /// Connect to the subprocess transport
+/// Extra time allowed for a restarted CLI to start, beyond the backoff delays
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Everything needed to start (or restart) the CLI process
+#[derive(Clone)]
+pub(super) struct ProcessSpec {
+    pub cli_path: PathBuf,
+    pub options: ClaudeAgentOptions,
+    pub launcher: Option<Launcher>,
+    pub stderr: StderrBuffer,
+    pub metrics: MetricsRecorder,
+}
+
+/// Identity of the local CLI process a set of options starts
+///
+/// Options with equal keys start interchangeable processes: the same
+/// command line, environment overrides, working directory, pre-spawn
+/// hook (compared by identity) and scheduling priority. Options that only affect the SDK side
+/// (hooks, callbacks, timeouts) are not part of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LaunchKey {
+    command: Vec<OsString>,
+    env: BTreeMap<String, String>,
+    cwd: Option<PathBuf>,
+    /// Address of the pre-spawn hook, which may rewrite the command
+    pre_spawn: Option<usize>,
+    /// Scheduling priority the process runs at
+    priority: Option<Priority>,
+}
+
+/// Launch key of a streaming-mode CLI started locally from `cli_path`
+pub(crate) fn launch_key(cli_path: &Path, options: &ClaudeAgentOptions) -> LaunchKey {
+    let command = std::iter::once(cli_path.as_os_str().to_owned())
+        .chain(CommandBuilder::new(&PromptInput::Stream, options).args())
+        .collect();
+
+    LaunchKey {
+        command,
+        env: options
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        cwd: options.cwd.clone(),
+        pre_spawn: options
+            .pre_spawn
+            .as_ref()
+            .map(|hook| Arc::as_ptr(hook).cast::<()>().addr()),
+        priority: options.process_priority,
+    }
+}
+
+/// Handles of a freshly started CLI process
+pub(super) struct SpawnedProcess {
+    pub child: TrackedChild,
+    pub stdin: ChildStdin,
+    pub stdout: ChildStdout,
+    pub stderr_task: JoinHandle<()>,
+    /// Blocking task applying the process priority
+    pub priority: Option<JoinHandle<()>>,
+    /// Launcher input still to be written ahead of the CLI's
+    pub preamble: Option<Vec<u8>>,
+}
+
+impl SpawnedProcess {
+    /// Wait until the process runs at its configured priority
+    pub async fn priority_applied(&mut self) {
+        if let Some(task) = self.priority.take() {
+            let _ = task.await;
+        }
+    }
+
+    /// Write the launcher's preamble to stdin, before any CLI input
     ///
-    /// This method spawns the Claude Code CLI process and sets up stdio pipes.
+    /// # Errors
+    /// Returns error if the launched process closed its stdin
+    pub async fn send_preamble(&mut self) -> Result<()> {
+        if let Some(preamble) = self.preamble.take() {
+            self.stdin.write_all(&preamble).await?;
+            self.stdin.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+impl ProcessSpec {
+    /// Spawn the CLI process with piped stdio
     ///
     /// # Errors
     /// Returns error if process spawning fails or stdio handles cannot be obtained
-    pub(super) async fn connect_impl(&mut self) -> Result<()> {
-        if self.process.is_some() {
-            return Ok(());
-        }
+    pub(super) fn spawn(&self, prompt: &PromptInput) -> Result<SpawnedProcess> {
+        let mut args = CommandBuilder::new(prompt, &self.options).args();
 
-        let builder = CommandBuilder::new(&self.cli_path, &self.prompt, &self.options);
-        let mut cmd = builder.build();
+        // Files on this host are not visible to a CLI started elsewhere
+        let spilled = if self.launcher.is_none() {
+            spill_long_args(&self.cli_path, &mut args)?
+        } else {
+            SpilledArgs::default()
+        };
+        let command_line_len = command_line_len(&self.cli_path, &args);
+        let mut cmd = cli_command(&self.cli_path, &args)?;
 
         // Set up environment - filter dangerous variables
         let mut process_env = env::vars().collect::<HashMap<_, _>>();
 
         // Only add user-provided env vars that are not in the dangerous list
         for (key, value) in &self.options.env {
-            if !DANGEROUS_ENV_VARS.contains(&key.as_str()) {
-                process_env.insert(key.clone(), value.clone());
+            if !is_dangerous_env_var(key) {
+                set_env_var(&mut process_env, key, value.clone());
             }
         }
 
-        process_env.insert("CLAUDE_CODE_ENTRYPOINT".to_string(), "sdk-rust".to_string());
-        process_env.insert("CLAUDE_AGENT_SDK_VERSION".to_string(), VERSION.to_string());
+        set_env_var(&mut process_env, "CLAUDE_CODE_ENTRYPOINT", "sdk-rust".to_string());
+        set_env_var(&mut process_env, "CLAUDE_AGENT_SDK_VERSION", VERSION.to_string());
 
-        if let Some(ref cwd) = self.cwd {
-            process_env.insert("PWD".to_string(), cwd.to_string_lossy().to_string());
-            cmd.current_dir(cwd);
+        let mut preamble = None;
+        if let Some(ref launcher) = self.launcher {
+            // The CLI runs elsewhere: forward only SDK-controlled variables and
+            // apply the working directory on the far side
+            let mut cli_env: HashMap<String, String> = self
+                .options
+                .env
+                .iter()
+                .filter(|(key, _)| !is_dangerous_env_var(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            cli_env.insert("CLAUDE_CODE_ENTRYPOINT".to_string(), "sdk-rust".to_string());
+            cli_env.insert("CLAUDE_AGENT_SDK_VERSION".to_string(), VERSION.to_string());
+            cmd = launcher.wrap(&cmd, &cli_env, &self.options);
+            preamble = launcher.preamble(&cli_env);
+        } else {
+            add_shim_dir_to_path(&mut process_env, &self.cli_path);
+            if let Some(ref cwd) = self.options.cwd {
+                set_env_var(&mut process_env, "PWD", cwd.to_string_lossy().to_string());
+                cmd.current_dir(cwd);
+            }
         }
 
         cmd.envs(process_env);
 
+        if let Some(ref hook) = self.options.pre_spawn {
+            hook(cmd.as_std_mut())?;
+        }
+
         // Set up stdio
         // IMPORTANT: We pipe stderr instead of inheriting to prevent the child process
         // from manipulating the parent terminal state. Inheriting stderr gives the child
@@ -55,9 +182,13 @@ impl SubprocessTransport {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped()); // Pipe stderr to prevent terminal manipulation
 
+        // Never leave the CLI running if its owner is dropped or aborted
+        cmd.kill_on_drop(true);
+
         // Spawn process
         let mut child = cmd.spawn().map_err(|e| {
-            if let Some(ref cwd) = self.cwd
+            if let Some(ref cwd) = self.options.cwd
+                && self.launcher.is_none()
                 && !cwd.exists()
             {
                 #[cfg(debug_assertions)]
@@ -68,9 +199,20 @@ impl SubprocessTransport {
                 #[cfg(not(debug_assertions))]
                 return ClaudeError::connection("Working directory does not exist".to_string());
             }
+            if command_line_len > MAX_COMMAND_LINE {
+                return too_long(command_line_len, &e);
+            }
             ClaudeError::connection(format!("Failed to start Claude Code: {e}"))
         })?;
 
+        // The priority tools are external commands; keep them off the runtime
+        let priority = match (self.launcher.is_none(), self.options.process_priority, child.id()) {
+            (true, Some(priority), Some(pid)) => Some(tokio::task::spawn_blocking(move || {
+                apply_priority(pid, priority);
+            })),
+            _ => None,
+        };
+
         // Get stdin, stdout, and stderr
         let stdin = child
             .stdin
@@ -87,37 +229,102 @@ impl SubprocessTransport {
             .take()
             .ok_or_else(|| ClaudeError::connection("Failed to get stderr handle"))?;
 
-        // Spawn task to consume stderr to prevent blocking
-        // We forward it to parent stderr for visibility
-        let stderr_task = tokio::spawn(async move {
-            use tokio::io::AsyncReadExt;
-            let mut stderr = stderr;
-            let mut buffer = vec![0u8; 4096];
-
-            loop {
-                match stderr.read(&mut buffer).await {
-                    Ok(0) | Err(_) => break, // EOF
-                    Ok(n) => {
-                        // Forward stderr to parent's stderr
-                        let _ = std::io::Write::write_all(&mut std::io::stderr(), &buffer[..n]);
-                    }
-                }
-            }
-        });
+        // Drain stderr so the CLI never blocks on a full pipe
+        let stderr_task = self.stderr.spawn_drain(
+            stderr,
+            self.options.stderr_callback.clone(),
+            self.metrics.clone(),
+        );
+
+        Ok(SpawnedProcess {
+            child: TrackedChild::new(child, self.options.process_owner.clone()).keep_files(spilled),
+            stdin,
+            stdout,
+            stderr_task,
+            priority,
+            preamble,
+        })
+    }
+}
+
+impl SubprocessTransport {
+    /// Specification used to start the CLI process
+    pub(super) fn process_spec(&self) -> ProcessSpec {
+        ProcessSpec {
+            cli_path: self.cli_path.clone(),
+            options: self.options.clone(),
+            launcher: self.launcher.clone(),
+            stderr: self.stderr.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+
+    /// Record the manager session the CLI process now belongs to
+    ///
+    /// Applies to the running process and to any restarted one.
+    #[cfg(feature = "manager")]
+    pub(crate) fn set_process_owner(&mut self, owner: Option<super::ProcessOwner>) {
+        if let Some(ref child) = self.process {
+            child.set_owner(owner.clone());
+        }
+        self.options.process_owner = owner;
+    }
+
+    /// Whether the CLI process has exited (or was never started)
+    pub(crate) fn has_exited(&mut self) -> bool {
+        self.process
+            .as_mut()
+            .is_none_or(|child| !matches!(child.try_wait(), Ok(None)))
+    }
+
+    /// Connect to the subprocess transport
+    ///
+    /// This method spawns the Claude Code CLI process and sets up stdio pipes.
+    ///
+    /// # Errors
+    /// Returns error if process spawning fails or stdio handles cannot be obtained
+    pub(super) async fn connect_impl(&mut self) -> Result<()> {
+        if self.process.is_some() {
+            return Ok(());
+        }
+
+        let mut spawned = self.process_spec().spawn(&self.prompt)?;
+        spawned.priority_applied().await;
+        spawned.send_preamble().await?;
 
         // Store handles
-        self.stdin = Some(stdin);
-        self.stdout = Some(tokio::io::BufReader::new(stdout));
-        self.process = Some(child);
-        self.stderr_task = Some(stderr_task);
+        self.stdout = Some(tokio::io::BufReader::new(spawned.stdout));
+        self.process = Some(spawned.child);
+        self.stderr_task = Some(spawned.stderr_task);
         self.ready.store(true, Ordering::SeqCst);
 
-        // For string mode, close stdin immediately
-        if matches!(self.prompt, PromptInput::String(_))
-            && let Some(mut stdin) = self.stdin.take()
-        {
-            use tokio::io::AsyncWriteExt;
-            let _ = stdin.shutdown().await;
+        match (&self.prompt, self.options.reconnect) {
+            // For string mode, close stdin immediately
+            (PromptInput::String(_), _) => {
+                let mut stdin = spawned.stdin;
+                let _ = stdin.shutdown().await;
+            }
+            // Restarted CLIs hand their stdin to the writer through the reader task
+            (PromptInput::Stream, Some(policy)) => {
+                let (stdin_tx, rx) = mpsc::unbounded_channel();
+                let budget = (0..policy.max_retries)
+                    .map(|attempt| policy.backoff(attempt))
+                    .sum::<Duration>()
+                    + RESTART_GRACE_PERIOD;
+                self.stdin_tx = Some(stdin_tx);
+                self.writer = Some(StdinWriter::spawn(
+                    spawned.stdin,
+                    Some(Restarts { rx, budget }),
+                    self.metrics.clone(),
+                ));
+            }
+            (PromptInput::Stream, None) => {
+                self.writer = Some(StdinWriter::spawn(
+                    spawned.stdin,
+                    None,
+                    self.metrics.clone(),
+                ));
+            }
         }
 
         Ok(())
@@ -130,17 +337,24 @@ impl SubprocessTransport {
     pub(super) async fn close_impl(&mut self) -> Result<()> {
         self.ready.store(false, Ordering::SeqCst);
 
-        // Close stdin to signal the process to exit gracefully
-        if let Some(mut stdin) = self.stdin.take() {
-            use tokio::io::AsyncWriteExt;
-            let _ = stdin.shutdown().await;
+        // Deliver queued writes, then close stdin to signal the process to
+        // exit gracefully
+        self.stdin_tx = None;
+        if let Some(writer) = self.writer.take()
+            && let Err(e) = writer.finish(Some(self.shutdown_grace)).await
+        {
+            log::debug!("Closing CLI stdin: {e}");
         }
 
-        // Abort reader and stderr tasks first to prevent race conditions
-        if let Some(task) = self.reader_task.take() {
+        // The reader task owns the process once messages are being read; let it
+        // observe the exit, and abort it (killing the process) if it takes too long
+        if let Some(mut task) = self.reader_task.take()
+            && tokio::time::timeout(self.shutdown_grace, &mut task)
+                .await
+                .is_err()
+        {
             task.abort();
-            // Give the task a moment to clean up
-            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let _ = task.await;
         }
         if let Some(task) = self.stderr_task.take() {
             task.abort();
@@ -150,10 +364,8 @@ impl SubprocessTransport {
 
         // Try to wait for the process to exit gracefully first
         if let Some(mut child) = self.process.take() {
-            // Give the process a configurable timeout to exit gracefully
-            let timeout_duration = std::time::Duration::from_secs(5);
-
-            match tokio::time::timeout(timeout_duration, child.wait()).await {
+            // Give the process the shutdown grace period to exit gracefully
+            match tokio::time::timeout(self.shutdown_grace, child.wait()).await {
                 Ok(Ok(_status)) => {
                     // Process exited gracefully
                 }
@@ -173,10 +385,9 @@ impl SubprocessTransport {
 
     /// Handle Drop cleanup
     pub(super) fn drop_impl(&mut self) {
-        // Close stdin if still open to signal graceful shutdown
-        if let Some(stdin) = self.stdin.take() {
-            // Drop will close it
-            drop(stdin);
+        // Stop the writer; dropping its stdin signals graceful shutdown
+        if let Some(writer) = self.writer.take() {
+            writer.abort();
         }
 
         // Abort reader task if running
@@ -3,12 +3,29 @@
 //! This module provides a transport implementation that spawns the Claude Code CLI
 //! as a subprocess and communicates with it via stdin/stdout.
 
+mod buffer;
+mod children;
 mod command;
 mod config;
+mod discovery;
+mod framing;
+mod launcher;
 mod lifecycle;
+mod priority;
+pub mod quoting;
 mod reader;
+mod spill;
+mod stderr;
 mod transport;
+mod writer;
 
 // Re-export public types
 pub use config::PromptInput;
+pub(crate) use children::ProcessOwner;
+pub(crate) use config::DEFAULT_MAX_BUFFER_SIZE;
+#[cfg(feature = "manager")]
+pub(crate) use children::{kill_tracked, orphans, session_pids};
+pub(crate) use launcher::Launcher;
+pub(crate) use lifecycle::{LaunchKey, launch_key};
+pub(crate) use spill::MAX_COMMAND_LINE;
 pub use transport::SubprocessTransport;
@@ -0,0 +1,86 @@
+//! Scheduling priority of local CLI processes
+//!
+//! Priority is applied to the running process with the platform's tools
+//! (`renice`/`ionice`, or PowerShell on Windows) rather than by wrapping the
+//! command, so the CLI keeps its PID and starts the same way everywhere.
+//! Failures are logged and otherwise ignored: a CLI at the wrong priority is
+//! still a working CLI.
+
+use std::process::{Command, Stdio};
+
+use crate::types::transport::Priority;
+
+/// Apply `priority` to process `pid`
+///
+/// Blocks while the priority tools run, so call it from a blocking task.
+pub(super) fn apply(pid: u32, priority: Priority) {
+    if priority == Priority::Normal {
+        return;
+    }
+
+    for mut cmd in commands(pid, priority) {
+        let program = cmd.get_program().to_string_lossy().into_owned();
+        match cmd.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                log::warn!(
+                    "Could not set priority {priority:?} of CLI process {pid}: {program} exited with {status}"
+                );
+            }
+            Err(e) => {
+                log::warn!(
+                    "Could not set priority {priority:?} of CLI process {pid}: {program}: {e}"
+                );
+            }
+        }
+    }
+}
+
+/// Commands setting CPU (and, on Linux, I/O) priority
+#[cfg(unix)]
+fn commands(pid: u32, priority: Priority) -> Vec<Command> {
+    let pid = pid.to_string();
+    let mut renice = Command::new("renice");
+    renice.args(["-n", &priority.nice().to_string(), "-p", &pid]);
+    let mut commands = vec![renice];
+
+    if cfg!(target_os = "linux") {
+        let class = match priority {
+            Priority::Low => Some(["-c", "2", "-n", "7"].as_slice()),
+            Priority::Idle => Some(["-c", "3"].as_slice()),
+            _ => None,
+        };
+        if let Some(class) = class {
+            let mut ionice = Command::new("ionice");
+            ionice.args(class).args(["-p", &pid]);
+            commands.push(ionice);
+        }
+    }
+    commands
+}
+
+/// Command setting the process priority class
+#[cfg(windows)]
+fn commands(pid: u32, priority: Priority) -> Vec<Command> {
+    let class = match priority.nice() {
+        ..=-10 => "High",
+        -9..=-1 => "AboveNormal",
+        0 => "Normal",
+        1..=14 => "BelowNormal",
+        _ => "Idle",
+    };
+    let mut powershell = Command::new("powershell");
+    powershell.args([
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        &format!("(Get-Process -Id {pid}).PriorityClass = '{class}'"),
+    ]);
+    vec![powershell]
+}
+
+/// No priority control on other platforms
+#[cfg(not(any(unix, windows)))]
+fn commands(_pid: u32, _priority: Priority) -> Vec<Command> {
+    Vec::new()
+}
@@ -0,0 +1,115 @@
+//! Quoting of CLI arguments for the command lines that carry them
+//!
+//! The CLI receives JSON blobs (`--mcp-config`, `--agents`), multi-line system
+//! prompts and arbitrary user text as arguments. Each hop that turns a
+//! command line back into arguments has its own rules:
+//!
+//! - a POSIX shell (the remote side of an ssh launcher): [`posix`]
+//! - a Windows program parsing its command line with the MSVC rules
+//!   (`CommandLineToArgvW`): [`windows`]; the standard library applies the
+//!   same rules to ordinary Windows commands
+//! - `cmd.exe` running an npm `.cmd` shim, which re-parses the line twice
+//!   before the MSVC rules apply: [`cmd_shim`] and [`batch_command_line`]
+//!
+//! `cmd.exe` ends a command at a line break, so an argument containing one
+//! cannot reach a `.cmd` shim; such arguments are rejected rather than
+//! silently truncated. PowerShell `.ps1` shims receive their arguments with
+//! the MSVC rules, but Windows PowerShell 5.1 may drop embedded double
+//! quotes when the shim starts Node; prefer the native `claude.exe` there.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use crate::error::{ClaudeError, Result};
+
+/// Characters `cmd.exe` interprets, escaped with `^` in shim command lines
+const CMD_META_CHARS: &[char] = &[
+    '(', ')', '[', ']', '%', '!', '^', '"', '`', '<', '>', '&', '|', ';', ',', ' ', '*', '?',
+];
+
+/// Quote a string as one word for a POSIX shell
+#[must_use]
+pub fn posix(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quote an argument for the MSVC command-line rules
+///
+/// Arguments without whitespace or double quotes are returned unchanged.
+#[must_use]
+pub fn windows(value: &str) -> String {
+    msvc_quote(value, false)
+}
+
+/// Quote and escape an argument passed through `cmd.exe` to a `.cmd` shim
+///
+/// The argument is always quoted for the MSVC rules, then every `cmd.exe`
+/// metacharacter (the quotes included) is escaped with `^` twice: once for
+/// `cmd.exe /c` and once for the shim re-expanding `%*`.
+///
+/// # Errors
+/// Returns `ClaudeError::InvalidConfig` if the argument contains a line
+/// break or NUL, which `cmd.exe` cannot pass on
+pub fn cmd_shim(value: &str) -> Result<String> {
+    if value.contains(['\n', '\r', '\0']) {
+        return Err(ClaudeError::invalid_config(
+            "Argument contains a line break, which cannot be passed through a .cmd shim; \
+             use the native claude executable instead",
+        ));
+    }
+    Ok(escape_cmd_meta(&escape_cmd_meta(&msvc_quote(value, true))))
+}
+
+/// Command line for `cmd.exe /d /s /c "<line>"` running the shim at `shim`
+///
+/// # Errors
+/// Returns `ClaudeError::InvalidConfig` if an argument cannot pass through
+/// `cmd.exe` (see [`cmd_shim`])
+pub fn batch_command_line(shim: &Path, args: &[OsString]) -> Result<String> {
+    let mut line = escape_cmd_meta(&shim.to_string_lossy());
+    for arg in args {
+        line.push(' ');
+        line.push_str(&cmd_shim(&arg.to_string_lossy())?);
+    }
+    Ok(line)
+}
+
+/// Quote `value` for the MSVC rules, always when `force` is set
+fn msvc_quote(value: &str, force: bool) -> String {
+    let needs_quotes = force || value.is_empty() || value.contains([' ', '\t', '\n', '\x0b', '"']);
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in value.chars() {
+        if c == '\\' {
+            backslashes += 1;
+        } else {
+            if c == '"' {
+                // Double the preceding backslashes and escape the quote
+                quoted.extend(std::iter::repeat_n('\\', backslashes + 1));
+            }
+            backslashes = 0;
+        }
+        quoted.push(c);
+    }
+    // Backslashes before the closing quote must be doubled
+    quoted.extend(std::iter::repeat_n('\\', backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Escape every `cmd.exe` metacharacter with `^`
+fn escape_cmd_meta(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if CMD_META_CHARS.contains(&c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
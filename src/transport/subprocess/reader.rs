@@ -1,17 +1,95 @@
 //! Message reading logic for subprocess transport
 
 use std::sync::Arc;
-use tokio::io::AsyncBufReadExt;
-use tokio::sync::{Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{ChildStdin, ChildStdout};
+use tokio::sync::mpsc;
 
 use crate::error::{ClaudeError, Result};
+use crate::transport::MetricsRecorder;
+use crate::types::transport::ReconnectPolicy;
 
+use super::buffer::PooledBuffer;
+use super::config::PromptInput;
+use super::framing::{Frame, JsonFramer};
+use super::lifecycle::{ProcessSpec, SpawnedProcess};
 use super::transport::SubprocessTransport;
 
+/// Longest wait for a failed CLI's remaining stderr output
+const STDERR_SETTLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Why reading from a CLI process stopped
+enum ReadEnd {
+    /// stdout reached EOF or failed; the process may have exited
+    Closed,
+    /// Reading stopped for a reason a restart would not fix
+    Stopped,
+}
+
+/// State needed by the reader task to restart a crashed CLI
+struct Restarter {
+    policy: ReconnectPolicy,
+    spec: ProcessSpec,
+    ready: Arc<AtomicBool>,
+    stdin_tx: mpsc::UnboundedSender<ChildStdin>,
+}
+
+impl Restarter {
+    /// Restart the CLI, resuming the session if its ID is known
+    ///
+    /// Backs off exponentially between attempts; `attempt` counts failed
+    /// restarts since the last message was received.
+    async fn restart(
+        &self,
+        session_id: Option<&str>,
+        attempt: &mut u32,
+    ) -> Option<Result<SpawnedProcess>> {
+        let mut spec = self.spec.clone();
+        if let Some(id) = session_id {
+            spec.options.resume = Some(id.into());
+            spec.options.continue_conversation = false;
+            spec.options.fork_session = false;
+        }
+
+        let mut last_error = None;
+        while *attempt < self.policy.max_retries {
+            tokio::time::sleep(self.policy.backoff(*attempt)).await;
+            *attempt += 1;
+
+            // close() or end_input() ran while we were backing off
+            if !self.ready.load(Ordering::SeqCst) || self.stdin_tx.is_closed() {
+                return None;
+            }
+
+            match spec.spawn(&PromptInput::Stream) {
+                Ok(mut spawned) => {
+                    spawned.priority_applied().await;
+                    match spawned.send_preamble().await {
+                        Ok(()) => return Some(Ok(spawned)),
+                        Err(e) => {
+                            log::warn!("CLI restart attempt {attempt} failed: {e}");
+                            last_error = Some(e);
+                        }
+                    }
+                }
+                Err(e) => {
+                    log::warn!("CLI restart attempt {attempt} failed: {e}");
+                    last_error = Some(e);
+                }
+            }
+        }
+        last_error.map(Err)
+    }
+}
+
 impl SubprocessTransport {
     /// Read messages from the subprocess output
     ///
     /// This method spawns a background task to read JSON messages from stdout.
+    /// With a reconnect policy, the task restarts the CLI if it exits with a
+    /// failure and hands the new stdin back to the transport.
     ///
     /// # Returns
     /// A receiver that yields parsed JSON values or errors
@@ -22,8 +100,21 @@ impl SubprocessTransport {
 
         // Take ownership of stdout and process
         let stdout = self.stdout.take();
-        let process = Arc::new(Mutex::new(self.process.take()));
+        let mut process = self.process.take();
         let max_buffer_size = self.max_buffer_size;
+        let read_timeout = self.read_timeout;
+        let metrics = self.metrics.clone();
+        let stderr = self.stderr.clone();
+
+        let restarter = match (self.options.reconnect, self.stdin_tx.take()) {
+            (Some(policy), Some(stdin_tx)) => Some(Restarter {
+                policy,
+                spec: self.process_spec(),
+                ready: Arc::clone(&self.ready),
+                stdin_tx,
+            }),
+            _ => None,
+        };
 
         // Spawn background task to read messages
         let task = tokio::spawn(async move {
@@ -33,83 +124,67 @@ impl SubprocessTransport {
                 )));
                 return;
             };
-            let mut json_buffer = String::new();
+            let mut session_id: Option<String> = None;
+            let mut attempt = 0;
 
             loop {
-                let mut line = String::new();
-
-                // Add timeout to read_line to prevent hanging
-                match tokio::time::timeout(
-                    std::time::Duration::from_secs(30),
-                    stdout.read_line(&mut line),
+                let end = read_stream(
+                    &mut stdout,
+                    &tx,
+                    max_buffer_size,
+                    read_timeout,
+                    &metrics,
+                    &mut session_id,
+                    &mut attempt,
                 )
-                .await
-                {
-                    Ok(Ok(0)) => break, // EOF
-                    Ok(Ok(_)) => {
-                        let line = line.trim();
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        // Accumulate partial JSON until we can parse it
-                        json_buffer.push_str(line);
-
-                        if json_buffer.len() > max_buffer_size {
-                            let _ = tx.send(Err(ClaudeError::JsonDecode(
-                                serde_json::Error::io(std::io::Error::new(
-                                    std::io::ErrorKind::InvalidData,
-                                    format!(
-                                        "JSON message exceeded maximum buffer size of {max_buffer_size} bytes"
-                                    ),
-                                )),
-                            )));
-                            json_buffer.clear();
-                            continue;
-                        }
+                .await;
 
-                        // Try to parse JSON
-                        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&json_buffer) {
-                            json_buffer.clear();
-                            if tx.send(Ok(data)).is_err() {
-                                // Receiver dropped, stop reading
-                                break;
-                            }
-                        }
-                        // Otherwise not complete yet, continue accumulating
-                        // The timeout on read_line will handle incomplete JSON timeouts
-                    }
-                    Ok(Err(e)) => {
+                // Check process exit code
+                let Some(mut child) = process.take() else {
+                    return;
+                };
+                let status = match child.wait().await {
+                    Ok(status) => status,
+                    Err(e) => {
                         let _ = tx.send(Err(ClaudeError::Io(e)));
-                        break;
-                    }
-                    Err(_) => {
-                        let _ = tx.send(Err(ClaudeError::timeout("Read operation timed out")));
-                        break;
+                        return;
                     }
+                };
+                if status.success() {
+                    return;
                 }
-            }
 
-            // Check process exit code
-            if let Ok(mut process_guard) = process.try_lock()
-                && let Some(mut child) = process_guard.take()
-            {
-                match child.wait().await {
-                    Ok(status) => {
-                        if !status.success()
-                            && let Some(code) = status.code()
-                        {
-                            let _ = tx.send(Err(ClaudeError::process(
-                                "Command failed",
-                                code,
-                                Some("Check stderr output for details".to_string()),
-                            )));
+                if matches!(end, ReadEnd::Closed)
+                    && let Some(ref restarter) = restarter
+                {
+                    log::warn!("CLI exited unexpectedly ({status}), restarting");
+                    match restarter.restart(session_id.as_deref(), &mut attempt).await {
+                        Some(Ok(spawned)) => {
+                            if restarter.stdin_tx.send(spawned.stdin).is_err() {
+                                return;
+                            }
+                            stdout = BufReader::new(spawned.stdout);
+                            process = Some(spawned.child);
+                            continue;
                         }
+                        Some(Err(e)) => {
+                            let _ = tx.send(Err(e));
+                            return;
+                        }
+                        None => return,
                     }
-                    Err(e) => {
-                        let _ = tx.send(Err(ClaudeError::Io(e)));
-                    }
                 }
+
+                if let Some(code) = status.code() {
+                    stderr.settle(STDERR_SETTLE_TIMEOUT).await;
+                    let output = stderr.contents();
+                    let _ = tx.send(Err(ClaudeError::process(
+                        "Command failed",
+                        code,
+                        (!output.is_empty()).then_some(output),
+                    )));
+                }
+                return;
             }
         });
 
@@ -119,3 +194,77 @@ impl SubprocessTransport {
         rx
     }
 }
+
+/// Forward JSON messages from one CLI process until its output ends
+///
+/// Records the session ID seen in messages (used to resume after a restart)
+/// and resets the restart attempt counter whenever a message arrives.
+async fn read_stream(
+    stdout: &mut BufReader<ChildStdout>,
+    tx: &mpsc::UnboundedSender<Result<serde_json::Value>>,
+    max_buffer_size: usize,
+    read_timeout: Duration,
+    metrics: &MetricsRecorder,
+    session_id: &mut Option<String>,
+    attempt: &mut u32,
+) -> ReadEnd {
+    let mut framer = JsonFramer::new(max_buffer_size);
+    let mut line = PooledBuffer::take();
+
+    loop {
+        line.clear();
+
+        // Add timeout to read_until to prevent hanging
+        match tokio::time::timeout(read_timeout, stdout.read_until(b'\n', &mut line)).await {
+            Ok(Ok(0)) => return ReadEnd::Closed, // EOF
+            Ok(Ok(bytes)) => {
+                metrics.record_bytes_read(bytes);
+
+                // Values spanning several lines are completed by a later line;
+                // the timeout on read_until handles values that never complete
+                let frames = framer.push(&line);
+                metrics.record_invalid_utf8(framer.take_invalid_utf8());
+                for frame in frames {
+                    let item = match frame {
+                        Frame::Message(data) => {
+                            metrics.record_message_read();
+                            if let Some(id) = data.get("session_id").and_then(|v| v.as_str()) {
+                                *session_id = Some(id.to_string());
+                            }
+                            *attempt = 0;
+                            Ok(data)
+                        }
+                        Frame::Invalid(e) => {
+                            metrics.record_parse_error();
+                            Err(ClaudeError::JsonDecode(e))
+                        }
+                        Frame::Noise(text) => {
+                            metrics.record_parse_error();
+                            Err(ClaudeError::json_decode(format!(
+                                "Unexpected non-JSON output from CLI: {text}"
+                            )))
+                        }
+                        Frame::Overflow => {
+                            metrics.record_parse_error();
+                            Err(ClaudeError::json_decode(format!(
+                                "JSON message exceeded maximum buffer size of {max_buffer_size} bytes"
+                            )))
+                        }
+                    };
+                    if tx.send(item).is_err() {
+                        // Receiver dropped, stop reading
+                        return ReadEnd::Stopped;
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                let _ = tx.send(Err(ClaudeError::Io(e)));
+                return ReadEnd::Closed;
+            }
+            Err(_) => {
+                let _ = tx.send(Err(ClaudeError::timeout("Read operation timed out")));
+                return ReadEnd::Stopped;
+            }
+        }
+    }
+}
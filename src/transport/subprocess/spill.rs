@@ -0,0 +1,177 @@
+//! Spilling of oversized CLI arguments to temporary files
+//!
+//! Large `--agents` or `--mcp-config` JSON and long system prompts can push
+//! the command line past what the OS accepts (about 8 KB through `cmd.exe`,
+//! 128 KB for a single argument on Linux). Before the CLI is started, such
+//! values are written to temporary files and passed by path where the CLI
+//! accepts one. The files are removed once the process handle is dropped.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{ClaudeError, Result};
+
+/// Longest command line (in bytes) started without spilling arguments
+#[cfg(windows)]
+pub(crate) const MAX_COMMAND_LINE: usize = 8_000;
+
+/// Longest command line (in bytes) started without spilling arguments
+#[cfg(not(windows))]
+pub(crate) const MAX_COMMAND_LINE: usize = 100_000;
+
+/// Counter keeping file names unique within this process
+static NEXT_FILE: AtomicU64 = AtomicU64::new(0);
+
+/// Temporary files holding spilled arguments, removed on drop
+#[derive(Debug, Default)]
+pub(super) struct SpilledArgs {
+    files: Vec<PathBuf>,
+}
+
+impl Drop for SpilledArgs {
+    fn drop(&mut self) {
+        for path in &self.files {
+            if let Err(e) = std::fs::remove_file(path) {
+                log::debug!("Removing spilled argument file {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+/// How a spilled value is passed to the CLI
+#[derive(Debug, Clone, Copy)]
+enum Spill {
+    /// The flag accepts a path in place of the value
+    Path,
+    /// The flag accepts `@<path>` in place of the value
+    AtPath,
+    /// A sibling flag takes the path
+    Flag(&'static str),
+}
+
+/// A value that may be spilled: the indices of its flag and value
+struct Candidate {
+    flag: usize,
+    value: usize,
+    spill: Spill,
+}
+
+/// Length of the command line running `cli_path` with `args`
+pub(super) fn command_line_len(cli_path: &Path, args: &[OsString]) -> usize {
+    args.iter().map(|arg| arg.len() + 1).sum::<usize>() + cli_path.as_os_str().len()
+}
+
+/// Move the largest spillable values to files until the command line fits
+///
+/// Values are spilled largest first, and only while the command line is
+/// over [`MAX_COMMAND_LINE`]. The command line may still be too long if the
+/// remaining arguments (e.g. the prompt) are large.
+///
+/// # Errors
+/// Returns `ClaudeError::Io` if a temporary file cannot be written
+pub(super) fn spill_long_args(cli_path: &Path, args: &mut [OsString]) -> Result<SpilledArgs> {
+    let mut spilled = SpilledArgs::default();
+    let mut candidates = candidates(args);
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(args[candidate.value].len()));
+
+    for candidate in candidates {
+        if command_line_len(cli_path, args) <= MAX_COMMAND_LINE {
+            break;
+        }
+
+        let path = write_temp_file(&args[candidate.value])?;
+        let reference = match candidate.spill {
+            Spill::Path => path.clone().into_os_string(),
+            Spill::AtPath => {
+                let mut reference = OsString::from("@");
+                reference.push(&path);
+                reference
+            }
+            Spill::Flag(flag) => {
+                args[candidate.flag] = flag.into();
+                path.clone().into_os_string()
+            }
+        };
+        log::debug!(
+            "Passing {} ({} bytes) through {}",
+            args[candidate.flag].to_string_lossy(),
+            args[candidate.value].len(),
+            path.display()
+        );
+        args[candidate.value] = reference;
+        spilled.files.push(path);
+    }
+
+    Ok(spilled)
+}
+
+/// Error for a CLI that could not start with a command line of `len` bytes
+pub(super) fn too_long(len: usize, error: &std::io::Error) -> ClaudeError {
+    ClaudeError::connection(format!(
+        "Failed to start Claude Code: {error} (the command line is {len} bytes even after \
+         moving large values to files; send long prompts in streaming mode instead)"
+    ))
+}
+
+/// Values the CLI can also read from a file
+///
+/// Scanning stops at `--`, after which only the prompt follows.
+fn candidates(args: &[OsString]) -> Vec<Candidate> {
+    let is_json = |arg: &OsString| {
+        let arg = arg.to_string_lossy();
+        arg.starts_with('{') || arg.starts_with('[')
+    };
+
+    let mut candidates = Vec::new();
+    for (flag, arg) in args.iter().enumerate() {
+        let value = flag + 1;
+        let spill = match arg.to_str() {
+            Some("--") => break,
+            Some("--system-prompt") => Spill::Flag("--system-prompt-file"),
+            Some("--agents") => Spill::AtPath,
+            Some("--settings") if args.get(value).is_some_and(is_json) => Spill::Path,
+            Some("--mcp-config") => {
+                // A configuration file may be followed by the SDK servers' JSON
+                candidates.extend(
+                    (value..args.len())
+                        .take_while(|&i| !args[i].to_string_lossy().starts_with("--"))
+                        .filter(|&i| is_json(&args[i]))
+                        .map(|value| Candidate {
+                            flag,
+                            value,
+                            spill: Spill::Path,
+                        }),
+                );
+                continue;
+            }
+            _ => continue,
+        };
+        if value < args.len() {
+            candidates.push(Candidate { flag, value, spill });
+        }
+    }
+    candidates
+}
+
+/// Write `value` to a new temporary file readable only by this user
+fn write_temp_file(value: &OsString) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "claude-agent-{}-{}.arg",
+        std::process::id(),
+        NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(&path)?;
+    if let Err(e) = file.write_all(value.to_string_lossy().as_bytes()) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e.into());
+    }
+    Ok(path)
+}
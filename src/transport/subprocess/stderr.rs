@@ -0,0 +1,118 @@
+//! Capture of the CLI's stderr
+//!
+//! Stderr is always piped (inheriting it lets the CLI manipulate the parent
+//! terminal). Each line is handed to the `stderr_callback` option, or
+//! forwarded to the parent's stderr when no callback is set, and the most
+//! recent output is kept in a bounded buffer so process failures can report it.
+//! Output is read as bytes; invalid UTF-8 is replaced with U+FFFD (and
+//! counted in the transport metrics) wherever it is turned into text.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::ChildStderr;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::transport::MetricsRecorder;
+use crate::types::options::StderrCallback;
+
+use super::framing::invalid_utf8_sequences;
+
+/// Most recent stderr output of the CLI, bounded to a byte budget
+///
+/// Shared between the transport and the task draining stderr; output of
+/// restarted processes is appended to the same buffer.
+#[derive(Clone)]
+pub(super) struct StderrBuffer {
+    bytes: Arc<Mutex<VecDeque<u8>>>,
+    capacity: usize,
+    /// Set while a process's stderr is being drained, cleared at EOF
+    draining: Arc<watch::Sender<bool>>,
+}
+
+impl StderrBuffer {
+    /// Create an empty buffer keeping at most `capacity` bytes
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            bytes: Arc::new(Mutex::new(VecDeque::new())),
+            capacity,
+            draining: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Append output, dropping the oldest bytes beyond the capacity
+    fn push(&self, data: &[u8]) {
+        let mut bytes = self
+            .bytes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let skip = data.len().saturating_sub(self.capacity);
+        bytes.extend(&data[skip..]);
+        let excess = bytes.len().saturating_sub(self.capacity);
+        bytes.drain(..excess);
+    }
+
+    /// Captured output, starting at a line boundary once older output was dropped
+    pub(super) fn contents(&self) -> String {
+        let bytes = self
+            .bytes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let (front, back) = bytes.as_slices();
+        let mut text = String::from_utf8_lossy(&[front, back].concat()).into_owned();
+        if bytes.len() == self.capacity
+            && let Some(newline) = text.find('\n')
+        {
+            text.drain(..=newline);
+        }
+        text.trim_end().to_string()
+    }
+
+    /// Wait (up to `timeout`) for stderr of the current process to reach EOF
+    ///
+    /// A process's last words are often written just before it exits, so
+    /// callers reporting a failure wait for them before reading the buffer.
+    pub(super) async fn settle(&self, timeout: Duration) {
+        let mut draining = self.draining.subscribe();
+        let _ = tokio::time::timeout(timeout, draining.wait_for(|draining| !draining)).await;
+    }
+
+    /// Drain a process's stderr into this buffer on a background task
+    pub(super) fn spawn_drain(
+        &self,
+        stderr: ChildStderr,
+        callback: Option<StderrCallback>,
+        metrics: MetricsRecorder,
+    ) -> JoinHandle<()> {
+        let buffer = self.clone();
+        buffer.draining.send_replace(true);
+
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stderr);
+            let mut line = Vec::new();
+
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line).await {
+                    Ok(0) | Err(_) => break, // EOF
+                    Ok(_) => {
+                        metrics.record_invalid_utf8(invalid_utf8_sequences(&line));
+                        buffer.push(&line);
+                        match callback {
+                            Some(ref callback) => {
+                                callback(String::from_utf8_lossy(&line).trim_end());
+                            }
+                            None => {
+                                let _ = std::io::Write::write_all(&mut std::io::stderr(), &line);
+                            }
+                        }
+                    }
+                }
+            }
+
+            buffer.draining.send_replace(false);
+        })
+    }
+}
@@ -4,30 +4,51 @@ use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::io::{AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, ChildStdout};
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio::process::{ChildStdin, ChildStdout};
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use crate::Transport;
+use crate::transport::MetricsRecorder;
 use crate::error::{ClaudeError, Result};
 use crate::types::options::ClaudeAgentOptions;
 
-use super::config::{DEFAULT_MAX_BUFFER_SIZE, PromptInput};
+use super::children::TrackedChild;
+use super::config::{
+    DEFAULT_MAX_BUFFER_SIZE, DEFAULT_MAX_STDERR_SIZE, DEFAULT_READ_TIMEOUT,
+    DEFAULT_SHUTDOWN_GRACE, PromptInput,
+};
+use super::discovery::search_locations;
+use super::launcher::Launcher;
+use super::stderr::StderrBuffer;
+use super::writer::StdinWriter;
 
 /// Subprocess transport for Claude Code CLI
 pub struct SubprocessTransport {
     pub(super) prompt: PromptInput,
     pub(super) options: ClaudeAgentOptions,
     pub(super) cli_path: PathBuf,
-    pub(super) cwd: Option<PathBuf>,
-    pub(super) process: Option<Child>,
-    pub(super) stdin: Option<ChildStdin>,
+    pub(super) process: Option<TrackedChild>,
+    /// Queue of writes to the CLI's stdin (streaming mode, until input ends)
+    pub(super) writer: Option<StdinWriter>,
     pub(super) stdout: Option<BufReader<ChildStdout>>,
     pub(super) ready: Arc<AtomicBool>,
     pub(super) max_buffer_size: usize,
+    pub(super) read_timeout: Duration,
+    pub(super) write_timeout: Option<Duration>,
+    pub(super) shutdown_grace: Duration,
     pub(super) reader_task: Option<JoinHandle<()>>,
     pub(super) stderr_task: Option<JoinHandle<()>>,
+    pub(super) launcher: Option<Launcher>,
+    /// Sender of restarted CLIs' stdin to the writer, for the reader task
+    /// (reconnect policy only)
+    pub(super) stdin_tx: Option<mpsc::UnboundedSender<ChildStdin>>,
+    /// Traffic counters, shared with the reader task
+    pub(super) metrics: MetricsRecorder,
+    /// Recent stderr output of the CLI
+    pub(super) stderr: StderrBuffer,
 }
 
 impl SubprocessTransport {
@@ -36,7 +57,8 @@ impl SubprocessTransport {
     /// # Arguments
     /// * `prompt` - The prompt input (string or stream)
     /// * `options` - Configuration options
-    /// * `cli_path` - Optional path to Claude Code CLI (will search if None)
+    /// * `cli_path` - Optional path to Claude Code CLI (falls back to
+    ///   `options.cli_path()`, then searches)
     ///
     /// # Errors
     /// Returns error if CLI cannot be found
@@ -45,32 +67,86 @@ impl SubprocessTransport {
         options: ClaudeAgentOptions,
         cli_path: Option<PathBuf>,
     ) -> Result<Self> {
-        let cli_path = if let Some(path) = cli_path {
-            path
-        } else {
-            Self::find_cli()?
+        let cli_path = match cli_path.or_else(|| options.cli_path.clone()) {
+            Some(path) => path,
+            None => Self::find_cli()?,
         };
 
-        let cwd = options.cwd.clone();
         let max_buffer_size = options.max_buffer_size.unwrap_or(DEFAULT_MAX_BUFFER_SIZE);
+        let read_timeout = options.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT);
+        let write_timeout = options.write_timeout;
+        let shutdown_grace = options.shutdown_grace.unwrap_or(DEFAULT_SHUTDOWN_GRACE);
+        let stderr = StderrBuffer::new(options.max_stderr_size.unwrap_or(DEFAULT_MAX_STDERR_SIZE));
 
         Ok(Self {
             prompt,
             options,
             cli_path,
-            cwd,
             process: None,
-            stdin: None,
+            writer: None,
             stdout: None,
             ready: Arc::new(AtomicBool::new(false)),
             max_buffer_size,
+            read_timeout,
+            write_timeout,
+            shutdown_grace,
             reader_task: None,
             stderr_task: None,
+            launcher: None,
+            stdin_tx: None,
+            metrics: MetricsRecorder::new(),
+            stderr,
         })
     }
 
+    /// Create a transport whose CLI is started through a launcher
+    ///
+    /// `cli_path` is interpreted by the launcher (e.g. a path on a remote host)
+    /// and is not searched for locally.
+    pub(crate) fn with_launcher(
+        prompt: PromptInput,
+        options: ClaudeAgentOptions,
+        cli_path: PathBuf,
+        launcher: Launcher,
+    ) -> Self {
+        let max_buffer_size = options.max_buffer_size.unwrap_or(DEFAULT_MAX_BUFFER_SIZE);
+        let read_timeout = options.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT);
+        let write_timeout = options.write_timeout;
+        let shutdown_grace = options.shutdown_grace.unwrap_or(DEFAULT_SHUTDOWN_GRACE);
+        let stderr = StderrBuffer::new(options.max_stderr_size.unwrap_or(DEFAULT_MAX_STDERR_SIZE));
+
+        Self {
+            prompt,
+            options,
+            cli_path,
+            process: None,
+            writer: None,
+            stdout: None,
+            ready: Arc::new(AtomicBool::new(false)),
+            max_buffer_size,
+            read_timeout,
+            write_timeout,
+            shutdown_grace,
+            reader_task: None,
+            stderr_task: None,
+            launcher: Some(launcher),
+            stdin_tx: None,
+            metrics: MetricsRecorder::new(),
+            stderr,
+        }
+    }
+
+    /// Most recent stderr output of the CLI (bounded by `max_stderr_size`)
+    #[must_use]
+    pub fn stderr_output(&self) -> String {
+        self.stderr.contents()
+    }
+
     /// Find Claude Code CLI binary
     ///
+    /// Searches `PATH` (honouring `PATHEXT` on Windows, so `claude.cmd` npm
+    /// shims are found), then [`cli_search_locations`](Self::cli_search_locations).
+    ///
     /// # Errors
     /// Returns error if CLI cannot be found in PATH or common locations
     pub fn find_cli() -> Result<PathBuf> {
@@ -80,22 +156,20 @@ impl SubprocessTransport {
         }
 
         // Manual search in common locations
-        let home = env::var("HOME").unwrap_or_else(|_| String::from("/root"));
-        let locations = vec![
-            PathBuf::from(home.clone()).join(".npm-global/bin/claude"),
-            PathBuf::from("/usr/local/bin/claude"),
-            PathBuf::from(home.clone()).join(".local/bin/claude"),
-            PathBuf::from(home.clone()).join("node_modules/.bin/claude"),
-            PathBuf::from(home).join(".yarn/bin/claude"),
-        ];
-
-        for path in locations {
-            if path.exists() && path.is_file() {
-                return Ok(path);
-            }
-        }
+        Self::cli_search_locations()
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(ClaudeError::cli_not_found)
+    }
 
-        Err(ClaudeError::cli_not_found())
+    /// Locations searched by [`find_cli`](Self::find_cli) when `claude` is
+    /// not on `PATH`, in search order
+    ///
+    /// On Windows these are the native installer's `%USERPROFILE%\.local\bin`,
+    /// npm's `%APPDATA%\npm` and locations under `%LOCALAPPDATA%`.
+    #[must_use]
+    pub fn cli_search_locations() -> Vec<PathBuf> {
+        search_locations(cfg!(windows), |name| env::var(name).ok())
     }
 }
 
@@ -109,32 +183,27 @@ impl Transport for SubprocessTransport {
             return Err(ClaudeError::transport("Transport is not ready for writing"));
         }
 
-        let stdin = self
-            .stdin
-            .as_mut()
+        let writer = self
+            .writer
+            .as_ref()
             .ok_or_else(|| ClaudeError::transport("stdin not available"))?;
+        writer.write(data, self.write_timeout).await
+    }
 
-        stdin
-            .write_all(data.as_bytes())
-            .await
-            .map_err(|e| ClaudeError::transport(format!("Failed to write to stdin: {e}")))?;
-
-        stdin
-            .flush()
-            .await
-            .map_err(|e| ClaudeError::transport(format!("Failed to flush stdin: {e}")))?;
-
-        Ok(())
+    async fn flush(&mut self) -> Result<()> {
+        match self.writer {
+            Some(ref writer) => writer.flush().await,
+            None => Ok(()),
+        }
     }
 
     async fn end_input(&mut self) -> Result<()> {
-        if let Some(mut stdin) = self.stdin.take() {
-            stdin
-                .shutdown()
-                .await
-                .map_err(|e| ClaudeError::transport(format!("Failed to close stdin: {e}")))?;
+        // Queued writes are delivered first; no restarts once input has ended
+        self.stdin_tx = None;
+        match self.writer.take() {
+            Some(writer) => writer.finish(self.write_timeout).await,
+            None => Ok(()),
         }
-        Ok(())
     }
 
     fn read_messages(&mut self) -> mpsc::UnboundedReceiver<Result<serde_json::Value>> {
@@ -145,6 +214,10 @@ impl Transport for SubprocessTransport {
         self.ready.load(Ordering::SeqCst)
     }
 
+    fn metrics(&self) -> Option<MetricsRecorder> {
+        Some(self.metrics.clone())
+    }
+
     async fn close(&mut self) -> Result<()> {
         self.close_impl().await
     }
@@ -0,0 +1,202 @@
+//! Ordered stdin write queue
+//!
+//! All writes to the CLI go through one task that owns its stdin. A write is
+//! queued whole and delivered whole, in order, even if the caller stops
+//! waiting for it (a write timeout or a cancelled future), so the CLI never
+//! sees a truncated message. Ending the input drains the queue before stdin
+//! is closed.
+
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::ChildStdin;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::error::{ClaudeError, Result};
+use crate::transport::MetricsRecorder;
+
+/// Replacement stdin handles from CLI restarts
+pub(super) struct Restarts {
+    /// Receives the stdin of each restarted CLI process
+    pub rx: mpsc::UnboundedReceiver<ChildStdin>,
+    /// Longest wait for a restart after a failed write
+    pub budget: Duration,
+}
+
+/// Operation queued for the writer task
+enum WriteOp {
+    /// Write (and flush) one chunk of data
+    Data {
+        data: String,
+        done: oneshot::Sender<Result<()>>,
+    },
+    /// Report once every earlier operation has completed
+    Flush(oneshot::Sender<Result<()>>),
+    /// Deliver everything queued, then close stdin
+    Finish(oneshot::Sender<Result<()>>),
+}
+
+/// Handle to the task writing to the CLI's stdin
+pub(super) struct StdinWriter {
+    ops: mpsc::UnboundedSender<WriteOp>,
+    task: JoinHandle<()>,
+}
+
+impl StdinWriter {
+    /// Start the writer task for `stdin`
+    pub(super) fn spawn(
+        stdin: ChildStdin,
+        restarts: Option<Restarts>,
+        metrics: MetricsRecorder,
+    ) -> Self {
+        let (ops, ops_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run(stdin, ops_rx, restarts, metrics));
+        Self { ops, task }
+    }
+
+    /// Queue `data` and wait until it has been written
+    ///
+    /// With a timeout, gives up waiting after it; the data stays queued and
+    /// is still delivered in order.
+    pub(super) async fn write(&self, data: &str, timeout: Option<Duration>) -> Result<()> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(WriteOp::Data {
+            data: data.to_string(),
+            done,
+        })?;
+
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, done_rx)
+                .await
+                .map_err(|_| ClaudeError::timeout("Write operation timed out"))?,
+            None => done_rx.await,
+        }
+        .unwrap_or_else(|_| Err(stopped()))
+    }
+
+    /// Wait until everything queued so far has been written
+    pub(super) async fn flush(&self) -> Result<()> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(WriteOp::Flush(done))?;
+        done_rx.await.unwrap_or_else(|_| Err(stopped()))
+    }
+
+    /// Deliver everything queued, then close stdin
+    ///
+    /// With a timeout, gives up after it, stopping the writer with data
+    /// still queued.
+    pub(super) async fn finish(self, timeout: Option<Duration>) -> Result<()> {
+        let (done, done_rx) = oneshot::channel();
+        self.send(WriteOp::Finish(done))?;
+
+        let Some(timeout) = timeout else {
+            return done_rx.await.unwrap_or_else(|_| Err(stopped()));
+        };
+        match tokio::time::timeout(timeout, done_rx).await {
+            Ok(result) => result.unwrap_or_else(|_| Err(stopped())),
+            Err(_) => {
+                self.task.abort();
+                Err(ClaudeError::timeout(
+                    "Timed out delivering queued writes before closing stdin",
+                ))
+            }
+        }
+    }
+
+    /// Stop the writer immediately, discarding queued data
+    pub(super) fn abort(&self) {
+        self.task.abort();
+    }
+
+    fn send(&self, op: WriteOp) -> Result<()> {
+        self.ops.send(op).map_err(|_| stopped())
+    }
+}
+
+/// Error for operations on a writer that is no longer running
+fn stopped() -> ClaudeError {
+    ClaudeError::transport("stdin not available")
+}
+
+/// Writer task: performs queued operations in order
+async fn run(
+    stdin: ChildStdin,
+    mut ops: mpsc::UnboundedReceiver<WriteOp>,
+    mut restarts: Option<Restarts>,
+    metrics: MetricsRecorder,
+) {
+    let mut stdin = Some(stdin);
+
+    while let Some(op) = ops.recv().await {
+        match op {
+            WriteOp::Data { data, done } => {
+                let result = deliver(&mut stdin, &data, restarts.as_mut()).await;
+                if result.is_ok() {
+                    metrics.record_write(data.len());
+                }
+                let _ = done.send(result);
+            }
+            WriteOp::Flush(done) => {
+                // Every earlier write has completed (and flushed) by now
+                let _ = done.send(Ok(()));
+            }
+            WriteOp::Finish(done) => {
+                // No restarts once input has ended
+                drop(restarts.take());
+                let result = match stdin.take() {
+                    Some(mut stdin) => stdin
+                        .shutdown()
+                        .await
+                        .map_err(|e| ClaudeError::transport(format!("Failed to close stdin: {e}"))),
+                    None => Ok(()),
+                };
+                let _ = done.send(result);
+                return;
+            }
+        }
+    }
+}
+
+/// Write one chunk, retrying once on the stdin of a restarted CLI
+async fn deliver(
+    stdin: &mut Option<ChildStdin>,
+    data: &str,
+    restarts: Option<&mut Restarts>,
+) -> Result<()> {
+    let Some(restarts) = restarts else {
+        return write_all(stdin.as_mut(), data).await;
+    };
+
+    // Switch to the newest restarted process, if any
+    while let Ok(restarted) = restarts.rx.try_recv() {
+        *stdin = Some(restarted);
+    }
+
+    match write_all(stdin.as_mut(), data).await {
+        Err(e) => {
+            // The CLI may have crashed; wait for the restarted process
+            match tokio::time::timeout(restarts.budget, restarts.rx.recv()).await {
+                Ok(Some(restarted)) => {
+                    let stdin = stdin.insert(restarted);
+                    write_all(Some(stdin), data).await
+                }
+                _ => Err(e),
+            }
+        }
+        ok => ok,
+    }
+}
+
+async fn write_all(stdin: Option<&mut ChildStdin>, data: &str) -> Result<()> {
+    let stdin = stdin.ok_or_else(stopped)?;
+
+    stdin
+        .write_all(data.as_bytes())
+        .await
+        .map_err(|e| ClaudeError::transport(format!("Failed to write to stdin: {e}")))?;
+
+    stdin
+        .flush()
+        .await
+        .map_err(|e| ClaudeError::transport(format!("Failed to flush stdin: {e}")))
+}
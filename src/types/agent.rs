@@ -73,12 +73,16 @@ pub struct AgentDefinition {
 // ============================================================================
 
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::transport::TransportMetrics;
 
 /// Serialized message stored in agent session circular buffer
 ///
 /// Flattens Message enum variants into storable JSON format for efficient
 /// retrieval and pagination.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SerializedMessage {
     /// Message type: "assistant", "user", "system_<subtype>", "result", "`stream_event`"
     pub message_type: String,
@@ -92,10 +96,19 @@ pub struct SerializedMessage {
 
     /// When this message was received by session manager
     pub timestamp: DateTime<Utc>,
+
+    /// Hash of the preceding transcript entry (the genesis hash for the first)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+
+    /// Hash chaining this entry to `prev_hash`, see `manager::verify_transcript`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
 }
 
 /// Response from `get_output` (paginated agent message output)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GetOutputResponse {
     /// Unique identifier for the agent session
     pub session_id: String,
@@ -130,6 +143,7 @@ pub struct GetOutputResponse {
 
 /// Response from `terminate_session`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TerminateResponse {
     /// Unique identifier for the terminated session
     pub session_id: String,
@@ -144,10 +158,31 @@ pub struct TerminateResponse {
 
     /// Session runtime in milliseconds
     pub runtime_ms: u64,
+
+    /// Hash of the last transcript entry when the session was archived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_head: Option<String>,
+
+    /// Why the manager stopped the session on its own (None if it did not)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<TerminationReason>,
+}
+
+/// Why the manager stopped a session without being asked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum TerminationReason {
+    /// The session wrote more to its working directory than its disk quota allows
+    DiskQuotaExceeded,
+    /// The session's CLI process grew past its memory cap
+    MemoryLimitExceeded,
 }
 
 /// Agent session info for `list_sessions` response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgentInfo {
     /// Unique identifier for the agent session
     pub session_id: String,
@@ -179,10 +214,53 @@ pub struct AgentInfo {
 
     /// When session completed (None if still active)
     pub completion_time: Option<DateTime<Utc>>,
+
+    /// Score from `grade_output` (None if never graded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade: Option<SessionGrade>,
+
+    /// Hash of the latest transcript entry (None before the first message)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_head: Option<String>,
+
+    /// Bytes written to the working directory (None without a disk quota)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_usage_bytes: Option<u64>,
+
+    /// Latest RSS of the CLI process in bytes (None unless memory is
+    /// tracked and the session is active)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+
+    /// Highest RSS of the CLI process in bytes (None unless memory is tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+
+    /// Why the manager stopped the session on its own (None if it did not)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub termination_reason: Option<TerminationReason>,
+}
+
+/// Score assigned to a session's final result by a grading agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionGrade {
+    /// Score from 0 (fails the rubric) to 100 (fully satisfies it)
+    pub score: u8,
+
+    /// Grader's short justification for the score
+    pub rationale: String,
+
+    /// Rubric the result was graded against
+    pub rubric: String,
+
+    /// When the grade was assigned
+    pub graded_at: DateTime<Utc>,
 }
 
 /// Response from `list_sessions`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ListSessionsResponse {
     /// All sessions (active + completed if requested)
     /// Sorted by: working=true first, then by runtime (newest first)
@@ -194,3 +272,212 @@ pub struct ListSessionsResponse {
     /// Count of completed sessions (`is_complete=true`)
     pub total_completed: usize,
 }
+
+/// Control channel health for an active session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionHealth {
+    /// Unique identifier for the agent session
+    pub session_id: String,
+
+    /// Round-trip latency of the most recent answered ping in milliseconds
+    pub last_rtt_ms: Option<u64>,
+
+    /// Milliseconds since the CLI last produced any output
+    pub idle_ms: u64,
+
+    /// Pings in a row that went unanswered while the CLI was silent
+    pub consecutive_failures: u32,
+
+    /// Total pings sent
+    pub pings_sent: u64,
+
+    /// Total pings answered
+    pub pings_answered: u64,
+
+    /// TRUE if the CLI is silent and not answering pings
+    pub wedged: bool,
+
+    /// Traffic counters of the session's transport, if it collects them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<TransportMetrics>,
+
+    /// Latest RSS of the CLI process in bytes, if memory is tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+}
+
+/// Severity of a session event, as OpenTelemetry severity text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "UPPERCASE")]
+#[non_exhaustive]
+pub enum EventSeverity {
+    /// Normal lifecycle event
+    Info,
+    /// Something the session recovered from or that needs attention
+    Warn,
+    /// A failure that stopped or disrupted the session
+    Error,
+}
+
+/// Operational event the manager recorded for a session
+///
+/// Separate from the transcript: events describe what happened to the agent
+/// (spawn, restarts, truncation, limits) rather than what it said.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionEvent {
+    /// When the event was recorded
+    pub timestamp: DateTime<Utc>,
+
+    /// How serious the event is
+    pub severity: EventSeverity,
+
+    /// Dotted event name, e.g. `session.spawned`
+    pub name: String,
+
+    /// Human-readable description
+    pub body: String,
+
+    /// Structured details of the event
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub attributes: serde_json::Value,
+}
+
+// ============================================================================
+// SESSION GROUP TYPES
+// ============================================================================
+
+/// Related agent sessions working on one task
+///
+/// Members share a spending budget and a set of labels. With `share_context`
+/// enabled, each new member's initial prompt is prefixed with a digest of the
+/// final results its siblings have produced so far.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionGroup {
+    /// Unique identifier for the group
+    pub group_id: String,
+
+    /// Human-readable label, also used for members spawned without one
+    pub label: String,
+
+    /// Labels shared by every member (e.g. ticket or task identifiers)
+    pub labels: Vec<String>,
+
+    /// Member session IDs in spawn order
+    pub members: Vec<String>,
+
+    /// Shared spending limit in USD (unlimited when `None`)
+    pub budget_usd: Option<f64>,
+
+    /// Prefix new members' prompts with a digest of siblings' final results
+    pub share_context: bool,
+
+    /// When the group was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl SessionGroup {
+    /// Create an empty group with a fresh ID
+    #[must_use]
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            group_id: uuid::Uuid::new_v4().to_string(),
+            label: label.into(),
+            labels: Vec::new(),
+            members: Vec::new(),
+            budget_usd: None,
+            share_context: false,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Add a label shared by every member
+    #[must_use]
+    pub fn shared_label(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+
+    /// Set the shared spending limit in USD
+    #[must_use]
+    pub const fn budget_usd(mut self, budget: f64) -> Self {
+        self.budget_usd = Some(budget);
+        self
+    }
+
+    /// Enable or disable sharing siblings' final results with new members
+    #[must_use]
+    pub const fn share_context(mut self, enabled: bool) -> Self {
+        self.share_context = enabled;
+        self
+    }
+}
+
+/// Spend of a session group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupCost {
+    /// Unique identifier for the group
+    pub group_id: String,
+
+    /// Total cost of all members in USD
+    pub total_cost_usd: f64,
+
+    /// Shared spending limit in USD (unlimited when `None`)
+    pub budget_usd: Option<f64>,
+
+    /// Budget left in USD (`None` when unlimited)
+    pub remaining_usd: Option<f64>,
+
+    /// Cost of each member in USD, keyed by session ID
+    pub sessions: HashMap<String, f64>,
+}
+
+/// Outcome of one member of a swarm stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmMemberReport {
+    /// Session ID of the member
+    pub session_id: String,
+
+    /// Label of the member
+    pub label: String,
+
+    /// Final result text (`None` if the member failed)
+    pub result: Option<String>,
+
+    /// Why the member produced no result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of one stage of a swarm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmStageReport {
+    /// Name of the stage
+    pub name: String,
+
+    /// Members in spawn order
+    pub members: Vec<SwarmMemberReport>,
+}
+
+/// Outcome of a swarm run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwarmReport {
+    /// Group the swarm's sessions belong to
+    pub group_id: String,
+
+    /// Stages in the order they ran
+    pub stages: Vec<SwarmStageReport>,
+
+    /// Spend of the whole swarm
+    pub cost: GroupCost,
+}
+
+impl SwarmReport {
+    /// Result of the last member of the last stage (e.g. the reviewer)
+    #[must_use]
+    pub fn final_result(&self) -> Option<&str> {
+        self.stages.last()?.members.last()?.result.as_deref()
+    }
+}
@@ -0,0 +1,143 @@
+//! Typed deltas of partial-message streaming
+//!
+//! With `include_partial_messages` enabled, the CLI forwards the model's raw
+//! stream events as [`Message::StreamEvent`]. A [`DeltaDecoder`] turns them
+//! into [`Delta`]s carrying just the new text, thinking or tool input, so a
+//! UI can render output token by token.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::messages::Message;
+
+/// New text of a text block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextDelta {
+    /// Index of the content block in the assistant message
+    pub index: usize,
+    /// Text to append
+    pub text: String,
+}
+
+/// New reasoning of a thinking block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingDelta {
+    /// Index of the content block in the assistant message
+    pub index: usize,
+    /// Thinking text to append
+    pub thinking: String,
+}
+
+/// New input of a tool call
+///
+/// The first delta of a tool call is sent when the block starts and has an
+/// empty `partial_json`. Concatenating the `partial_json` of every delta of
+/// one block gives the tool's JSON input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolUseDelta {
+    /// Index of the content block in the assistant message
+    pub index: usize,
+    /// Tool use ID
+    pub id: String,
+    /// Tool name
+    pub name: String,
+    /// JSON fragment to append to the tool input
+    pub partial_json: String,
+}
+
+/// Incremental output of the model
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Delta {
+    /// Text output
+    Text(TextDelta),
+    /// Extended thinking output
+    Thinking(ThinkingDelta),
+    /// Tool call input
+    ToolUse(ToolUseDelta),
+}
+
+/// Decodes the stream events of one conversation into [`Delta`]s
+///
+/// Remembers the tool calls started so far, so input deltas carry the
+/// tool's ID and name.
+#[derive(Debug, Clone, Default)]
+pub struct DeltaDecoder {
+    /// Tool calls by content block index: (ID, name)
+    tools: HashMap<usize, (String, String)>,
+}
+
+impl DeltaDecoder {
+    /// Create a decoder
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delta carried by `message`, if it is a stream event with one
+    pub fn decode_message(&mut self, message: &Message) -> Option<Delta> {
+        match message {
+            Message::StreamEvent { event, .. } => self.decode(event),
+            _ => None,
+        }
+    }
+
+    /// Delta carried by a raw stream event
+    ///
+    /// Returns `None` for events without new content (message start and
+    /// stop, block stop, signatures).
+    pub fn decode(&mut self, event: &Value) -> Option<Delta> {
+        let kind = event["type"].as_str()?;
+        if kind == "message_start" {
+            // Block indices restart with each message
+            self.tools.clear();
+            return None;
+        }
+
+        let index = usize::try_from(event["index"].as_u64()?).ok()?;
+        match kind {
+            "content_block_start" => {
+                let block = &event["content_block"];
+                if block["type"] != "tool_use" {
+                    return None;
+                }
+                let id = block["id"].as_str().unwrap_or_default().to_string();
+                let name = block["name"].as_str().unwrap_or_default().to_string();
+                self.tools.insert(index, (id.clone(), name.clone()));
+                Some(Delta::ToolUse(ToolUseDelta {
+                    index,
+                    id,
+                    name,
+                    partial_json: String::new(),
+                }))
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str()? {
+                    "text_delta" => Some(Delta::Text(TextDelta {
+                        index,
+                        text: delta["text"].as_str()?.to_string(),
+                    })),
+                    "thinking_delta" => Some(Delta::Thinking(ThinkingDelta {
+                        index,
+                        thinking: delta["thinking"].as_str()?.to_string(),
+                    })),
+                    "input_json_delta" => {
+                        let (id, name) = self.tools.get(&index).cloned().unwrap_or_default();
+                        Some(Delta::ToolUse(ToolUseDelta {
+                            index,
+                            id,
+                            name,
+                            partial_json: delta["partial_json"].as_str()?.to_string(),
+                        }))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -8,7 +8,7 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use crate::error::Result;
+use crate::error::{ClaudeError, Result};
 
 // ============================================================================
 // Hook Types
@@ -16,6 +16,7 @@ use crate::error::Result;
 
 /// Hook event types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum HookEvent {
     /// Before a tool is used
     PreToolUse,
@@ -29,12 +30,98 @@ pub enum HookEvent {
     SubagentStop,
     /// Before compacting the conversation
     PreCompact,
+    /// When a session starts or resumes
+    SessionStart,
+}
+
+/// Input of a `PreToolUse` hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreToolUsePayload {
+    /// Tool about to run
+    #[serde(alias = "toolName")]
+    pub tool_name: String,
+    /// Input the tool will receive
+    #[serde(default, alias = "toolInput")]
+    pub tool_input: serde_json::Value,
+}
+
+/// Input of a `PostToolUse` hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostToolUsePayload {
+    /// Tool that ran
+    #[serde(alias = "toolName")]
+    pub tool_name: String,
+    /// Input the tool received
+    #[serde(default, alias = "toolInput")]
+    pub tool_input: serde_json::Value,
+    /// Output the tool produced
+    #[serde(default, alias = "toolResponse")]
+    pub tool_response: serde_json::Value,
+}
+
+/// Input of a `UserPromptSubmit` hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserPromptSubmitPayload {
+    /// Prompt the user submitted
+    pub prompt: String,
+}
+
+/// Typed hook input
+///
+/// Events without a payload struct, and inputs that do not match the one
+/// for their event, are kept as [`Raw`](Self::Raw).
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookPayload {
+    /// Input of a `PreToolUse` hook
+    PreToolUse(PreToolUsePayload),
+    /// Input of a `PostToolUse` hook
+    PostToolUse(PostToolUsePayload),
+    /// Input of a `UserPromptSubmit` hook
+    UserPromptSubmit(UserPromptSubmitPayload),
+    /// Untyped input
+    Raw(serde_json::Value),
+}
+
+impl HookPayload {
+    /// Parse the input of a hook for `event`
+    #[must_use]
+    pub fn parse(event: HookEvent, data: serde_json::Value) -> Self {
+        let typed = match event {
+            HookEvent::PreToolUse => serde_json::from_value(data.clone()).map(Self::PreToolUse),
+            HookEvent::PostToolUse => serde_json::from_value(data.clone()).map(Self::PostToolUse),
+            HookEvent::UserPromptSubmit => {
+                serde_json::from_value(data.clone()).map(Self::UserPromptSubmit)
+            }
+            _ => return Self::Raw(data),
+        };
+        typed.unwrap_or(Self::Raw(data))
+    }
+
+    /// Parse a hook input, taking the event from its `hook_event_name` field
+    #[must_use]
+    pub fn from_value(data: serde_json::Value) -> Self {
+        let event = data
+            .get("hook_event_name")
+            .or_else(|| data.get("hookEventName"))
+            .cloned()
+            .and_then(|name| serde_json::from_value::<HookEvent>(name).ok());
+        match event {
+            Some(event) => Self::parse(event, data),
+            None => Self::Raw(data),
+        }
+    }
 }
 
 /// Hook decision
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum HookDecision {
+    /// Approve the action
+    ///
+    /// For `PreToolUse` the CLI skips its permission prompt and runs the
+    /// tool. Under [`HookMergeStrategy::BlockWins`] a block from another
+    /// callback still overrides it.
+    Approve,
     /// Block the action
     Block,
 }
@@ -51,6 +138,90 @@ pub struct HookOutput {
     /// Hook-specific output data
     #[serde(skip_serializing_if = "Option::is_none", rename = "hookSpecificOutput")]
     pub hook_specific_output: Option<serde_json::Value>,
+    /// Replacement tool input (`PreToolUse` only)
+    #[serde(skip_serializing_if = "Option::is_none", rename = "updatedInput")]
+    pub updated_input: Option<serde_json::Value>,
+    /// Context added to the conversation (`UserPromptSubmit` and `SessionStart` only)
+    ///
+    /// The CLI adds it as a system message before the turn, e.g. the current
+    /// git status.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "additionalContext")]
+    pub additional_context: Option<String>,
+}
+
+impl HookOutput {
+    /// Hook response sent to the CLI for `event`
+    ///
+    /// The event-specific fields, [`updated_input`](Self::updated_input) for
+    /// `PreToolUse` and [`additional_context`](Self::additional_context) for
+    /// `UserPromptSubmit` and `SessionStart`, are moved into
+    /// `hookSpecificOutput`, where the CLI reads them.
+    #[must_use]
+    pub fn to_response(&self, event: HookEvent) -> serde_json::Value {
+        let mut response = serde_json::to_value(self).unwrap_or_default();
+        let field = match event {
+            HookEvent::PreToolUse => "updatedInput",
+            HookEvent::UserPromptSubmit | HookEvent::SessionStart => "additionalContext",
+            _ => return response,
+        };
+        let Some(fields) = response.as_object_mut() else {
+            return response;
+        };
+        if let Some(value) = fields.remove(field) {
+            let specific = fields
+                .entry("hookSpecificOutput")
+                .or_insert_with(|| serde_json::json!({"hookEventName": event}));
+            if let Some(specific) = specific.as_object_mut() {
+                specific.insert(field.to_string(), value);
+            }
+        }
+        response
+    }
+}
+
+/// Response used when a hook callback fails
+#[derive(Debug, Clone)]
+pub enum HookErrorPolicy {
+    /// Let the action proceed (fail open)
+    Allow,
+    /// Block the action (fail closed)
+    Block,
+    /// Respond with a fixed output
+    DefaultOutput(HookOutput),
+}
+
+impl HookErrorPolicy {
+    /// Output standing in for a callback that failed with `error`
+    #[must_use]
+    pub fn output(&self, error: &ClaudeError) -> HookOutput {
+        match self {
+            Self::Allow => HookOutput::default(),
+            Self::Block => HookOutput {
+                decision: Some(HookDecision::Block),
+                system_message: Some(format!("Hook failed: {error}")),
+                ..HookOutput::default()
+            },
+            Self::DefaultOutput(output) => output.clone(),
+        }
+    }
+}
+
+/// How the outputs of several hook callbacks combine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HookMergeStrategy {
+    /// A block from any callback wins; other fields take the latest value
+    ///
+    /// Callbacks after a block still run, so audit hooks see every call,
+    /// but their outputs are ignored.
+    #[default]
+    BlockWins,
+    /// The first callback to decide settles the outcome
+    ///
+    /// Callbacks after it are not run (serial) or ignored (parallel).
+    FirstDecision,
+    /// Every output is kept: a block from any callback wins and system
+    /// messages are joined, one per line
+    Collect,
 }
 
 /// Context for hook callbacks
@@ -73,17 +244,29 @@ pub type HookCallback = Arc<
 /// Hook matcher configuration
 #[derive(Clone)]
 pub struct HookMatcher {
+    /// Name for enabling, disabling, or removing the matcher at runtime
+    pub name: Option<String>,
     /// Matcher pattern (e.g., tool name like "Bash" or pattern like "Write|Edit")
     pub matcher: Option<String>,
     /// List of hook callbacks
     pub hooks: Vec<HookCallback>,
+    /// Response used when a callback fails (the action is blocked when `None`)
+    pub on_error: Option<HookErrorPolicy>,
+    /// How long a callback's output is reused for identical inputs (not cached when `None`)
+    pub cache_ttl: Option<std::time::Duration>,
+    /// Whether decisions are only logged, never enforced
+    pub shadow: bool,
 }
 
 impl std::fmt::Debug for HookMatcher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HookMatcher")
+            .field("name", &self.name)
             .field("matcher", &self.matcher)
             .field("hooks", &format!("[{} callbacks]", self.hooks.len()))
+            .field("on_error", &self.on_error)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("shadow", &self.shadow)
             .finish()
     }
 }
@@ -3,6 +3,8 @@
 //! This module contains newtype wrappers that provide type safety by wrapping
 //! primitive types (like String) into distinct types.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -11,6 +13,7 @@ use serde::{Deserialize, Serialize};
 
 /// Session ID newtype for type safety
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(transparent)]
 pub struct SessionId(String);
 
@@ -47,6 +50,7 @@ impl From<&str> for SessionId {
 
 /// Tool name newtype
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(transparent)]
 pub struct ToolName(String);
 
@@ -77,6 +81,7 @@ impl From<&str> for ToolName {
 
 /// Request ID newtype for control protocol
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(transparent)]
 pub struct RequestId(String);
 
@@ -104,3 +109,83 @@ impl From<&str> for RequestId {
         Self(s.to_string())
     }
 }
+
+// ============================================================================
+// Identifier Generation
+// ============================================================================
+
+/// Source of session and control request identifiers
+///
+/// Inject a [`SequentialIdGenerator`] (through `AgentManager::with_id_generator`
+/// or the `id_generator` option) to get stable identifiers for snapshot tests
+/// and replays.
+pub trait IdGenerator: std::fmt::Debug + Send + Sync {
+    /// Identifier for a new agent session
+    fn session_id(&self) -> SessionId;
+
+    /// Identifier for a new control protocol request
+    fn request_id(&self) -> RequestId;
+}
+
+/// Default generator: random UUIDv4 session IDs and counted request IDs
+/// (`req-1`, `req-2`, ...)
+#[derive(Debug, Default)]
+pub struct RandomIdGenerator {
+    requests: AtomicU64,
+}
+
+impl RandomIdGenerator {
+    /// Create a generator whose request counter starts at 1
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdGenerator for RandomIdGenerator {
+    fn session_id(&self) -> SessionId {
+        SessionId::new(uuid::Uuid::new_v4().to_string())
+    }
+
+    fn request_id(&self) -> RequestId {
+        let id = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        RequestId::new(format!("req-{id}"))
+    }
+}
+
+/// Deterministic generator: `<prefix>session-1`, `<prefix>req-1`, ...
+#[derive(Debug, Default)]
+pub struct SequentialIdGenerator {
+    prefix: String,
+    sessions: AtomicU64,
+    requests: AtomicU64,
+}
+
+impl SequentialIdGenerator {
+    /// Create a generator with no prefix, counting from 1
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a generator whose identifiers start with `prefix`
+    #[must_use]
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn session_id(&self) -> SessionId {
+        let id = self.sessions.fetch_add(1, Ordering::SeqCst) + 1;
+        SessionId::new(format!("{}session-{id}", self.prefix))
+    }
+
+    fn request_id(&self) -> RequestId {
+        let id = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        RequestId::new(format!("{}req-{id}", self.prefix))
+    }
+}
@@ -3,7 +3,10 @@
 //! This module contains types for representing messages, content blocks,
 //! and various message formats used in conversations with Claude.
 
+use std::path::Path;
+
 use super::identifiers::SessionId;
+use crate::error::{ClaudeError, Result};
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -23,6 +26,7 @@ pub enum ContentValue {
 /// Content block types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum ContentBlock {
     /// Text content block
     Text {
@@ -45,6 +49,11 @@ pub enum ContentBlock {
         /// Tool input parameters
         input: serde_json::Value,
     },
+    /// Image content block
+    Image {
+        /// Where the image data comes from
+        source: ImageSource,
+    },
     /// Tool execution result
     ToolResult {
         /// ID of the tool use this is a result for
@@ -58,6 +67,98 @@ pub enum ContentBlock {
     },
 }
 
+impl ContentBlock {
+    /// Text block
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// Tool result block answering the tool use `tool_use_id`
+    #[must_use]
+    pub fn tool_result(
+        tool_use_id: impl Into<String>,
+        content: impl Into<String>,
+        is_error: bool,
+    ) -> Self {
+        Self::ToolResult {
+            tool_use_id: tool_use_id.into(),
+            content: Some(ContentValue::String(content.into())),
+            is_error: Some(is_error),
+        }
+    }
+
+    /// Image block from base64-encoded data
+    #[must_use]
+    pub fn image(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self::Image {
+            source: ImageSource::Base64 {
+                media_type: media_type.into(),
+                data: data.into(),
+            },
+        }
+    }
+
+    /// Image block from raw image bytes, base64-encoding them
+    #[must_use]
+    pub fn image_bytes(media_type: impl Into<String>, bytes: &[u8]) -> Self {
+        use base64::Engine;
+        Self::image(
+            media_type,
+            base64::engine::general_purpose::STANDARD.encode(bytes),
+        )
+    }
+
+    /// Image block from an image file
+    ///
+    /// The media type is taken from the file extension (`png`, `jpg`/`jpeg`,
+    /// `gif` or `webp`).
+    ///
+    /// # Errors
+    /// Returns `ClaudeError::InvalidConfig` for other extensions, or an I/O
+    /// error if the file cannot be read
+    pub fn image_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let media_type = image_media_type(path).ok_or_else(|| unsupported_image(path))?;
+        Ok(Self::image_bytes(media_type, &std::fs::read(path)?))
+    }
+}
+
+/// Source of an image content block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ImageSource {
+    /// Image data embedded in the message
+    Base64 {
+        /// MIME type, e.g. `image/png`
+        media_type: String,
+        /// Base64-encoded image data
+        data: String,
+    },
+}
+
+/// MIME type of an image file, from its extension
+#[must_use]
+pub fn image_media_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Error for an image file of a type Claude does not accept
+pub(crate) fn unsupported_image(path: &Path) -> ClaudeError {
+    ClaudeError::invalid_config(format!(
+        "Unsupported image type (expected png, jpeg, gif or webp): {}",
+        path.display()
+    ))
+}
+
 /// User message content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserMessageContent {
@@ -90,6 +191,7 @@ pub struct AssistantMessageContent {
 /// Message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum Message {
     /// User message
     User {
@@ -158,3 +260,130 @@ pub enum Message {
         parent_tool_use_id: Option<String>,
     },
 }
+
+impl Message {
+    /// The compaction this message reports, if it is a `compact_boundary`
+    /// system message
+    #[must_use]
+    pub fn compact_boundary(&self) -> Option<CompactBoundary> {
+        match self {
+            Self::System { subtype, data } if subtype == "compact_boundary" => {
+                let metadata = &data["compact_metadata"];
+                Some(CompactBoundary {
+                    trigger: match metadata["trigger"].as_str() {
+                        Some("auto") => CompactTrigger::Auto,
+                        _ => CompactTrigger::Manual,
+                    },
+                    pre_tokens: metadata["pre_tokens"].as_u64(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What started a compaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactTrigger {
+    /// Requested, e.g. with `ClaudeSDKClient::compact`
+    Manual,
+    /// Started by the CLI as the context filled up
+    Auto,
+}
+
+/// Point where the CLI replaced the conversation so far with a summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactBoundary {
+    /// What started the compaction
+    pub trigger: CompactTrigger,
+    /// Tokens in the context before compaction, if reported
+    pub pre_tokens: Option<u64>,
+}
+
+/// Everything the CLI produced for one turn, up to and including its result
+#[derive(Debug, Clone)]
+pub struct TurnOutcome {
+    /// Content blocks of every assistant message in the turn, in order
+    pub assistant_blocks: Vec<ContentBlock>,
+    /// The `Message::Result` that ended the turn
+    pub result: Message,
+    /// Token usage reported by the result message
+    pub usage: Option<serde_json::Value>,
+}
+
+impl TurnOutcome {
+    /// Concatenated text of the turn's assistant text blocks
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.assistant_blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the result message reports an error
+    #[must_use]
+    pub const fn is_error(&self) -> bool {
+        matches!(self.result, Message::Result { is_error: true, .. })
+    }
+}
+
+/// Usage and cost accumulated over the results of a session
+///
+/// Token counts are summed over every result message. The CLI reports the
+/// session's running cost with each result, so `total_cost_usd` is the most
+/// recently reported value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    /// Session cost in USD, as last reported by the CLI
+    pub total_cost_usd: f64,
+    /// Input tokens not read from or written to the prompt cache
+    pub input_tokens: u64,
+    /// Output tokens
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache
+    pub cache_creation_input_tokens: u64,
+    /// Input tokens read from the prompt cache
+    pub cache_read_input_tokens: u64,
+    /// Number of result messages counted
+    pub results: u32,
+}
+
+impl UsageTotals {
+    /// All input and output tokens, cached or not
+    #[must_use]
+    pub const fn total_tokens(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+
+    /// Add the usage and cost of `message` if it is a result
+    pub fn record(&mut self, message: &Message) {
+        let Message::Result {
+            total_cost_usd,
+            usage,
+            ..
+        } = message
+        else {
+            return;
+        };
+
+        self.results += 1;
+        if let Some(cost) = total_cost_usd {
+            self.total_cost_usd = *cost;
+        }
+        if let Some(usage) = usage {
+            let tokens = |field: &str| usage[field].as_u64().unwrap_or(0);
+            self.input_tokens += tokens("input_tokens");
+            self.output_tokens += tokens("output_tokens");
+            self.cache_creation_input_tokens += tokens("cache_creation_input_tokens");
+            self.cache_read_input_tokens += tokens("cache_read_input_tokens");
+        }
+    }
+}
@@ -8,11 +8,15 @@
 //! - [`hooks`] - Hook system types and callbacks
 //! - [`mcp`] - MCP server configuration
 //! - [`messages`] - Message and content block types
+//! - [`delta`] - Typed deltas of partial-message streaming
 //! - [`agent`] - Agent definitions and system prompts
 //! - [`options`] - Main configuration options
+//! - [`role`] - Built-in agent role templates
+//! - [`transport`] - Transport selection configuration
 //! - [`prompt_input`] - Prompt input types supporting both plain strings and templates
 
 pub mod agent;
+pub mod delta;
 pub mod hooks;
 pub mod identifiers;
 pub mod mcp;
@@ -22,20 +26,33 @@ pub mod permissions;
 /// Prompt input types for Claude agents
 ///
 /// Supports both plain string prompts and template-based prompts with parameters.
+#[cfg(feature = "tools")]
 pub mod prompt_input;
+pub mod role;
+pub mod transport;
 
 // Re-export commonly used types
-pub use identifiers::{RequestId, SessionId, ToolName};
+pub use identifiers::{
+    IdGenerator, RandomIdGenerator, RequestId, SequentialIdGenerator, SessionId, ToolName,
+};
 pub use permissions::{
     CanUseToolCallback, PermissionBehavior, PermissionMode, PermissionRequest, PermissionResult,
     PermissionResultAllow, PermissionResultDeny, PermissionRuleValue, PermissionUpdate,
     PermissionUpdateDestination, SettingSource, ToolPermissionContext,
 };
+pub use role::AgentRole;
+pub use transport::{
+    ContainerMount, ContainerTransportConfig, HttpTransportConfig, Priority, ReconnectPolicy,
+    SshTransportConfig, TransportConfig,
+};
 
 // Re-export session management types from agent module
 pub use agent::{
-    AgentInfo, GetOutputResponse, ListSessionsResponse, SerializedMessage, TerminateResponse,
+    AgentInfo, EventSeverity, GetOutputResponse, GroupCost, ListSessionsResponse,
+    SerializedMessage, SessionEvent, SessionGrade, SessionGroup, SessionHealth,
+    SwarmMemberReport, SwarmReport, SwarmStageReport, TerminateResponse, TerminationReason,
 };
 
 // Re-export prompt input types
-pub use prompt_input::{PromptInput, PromptTemplateInput};
+#[cfg(feature = "tools")]
+pub use prompt_input::{PromptInput, PromptRenderer, PromptTemplateInput};
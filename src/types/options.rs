@@ -4,67 +4,142 @@
 //! including a builder pattern for easy configuration.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use super::agent::{AgentDefinition, SystemPrompt};
-use super::hooks::{HookEvent, HookMatcher};
-use super::identifiers::{SessionId, ToolName};
+use crate::error::ClaudeError;
+use crate::mcp::SdkMcpServer;
+use crate::permissions::PermissionPolicy;
+use crate::transport::subprocess::ProcessOwner;
+use super::hooks::{HookEvent, HookMatcher, HookMergeStrategy, HookOutput};
+use super::identifiers::{IdGenerator, SessionId, ToolName};
+use super::messages::Message;
 use super::mcp::{McpServerConfig, McpServers};
 use super::permissions::{CanUseToolCallback, PermissionMode, SettingSource};
+use super::transport::{Priority, ReconnectPolicy, TransportConfig};
+
+/// Largest turn limit accepted by [`ClaudeAgentOptionsBuilder::max_turns`]
+pub(crate) const MAX_TURNS: u32 = 1000;
+
+/// Callback receiving each line the CLI writes to stderr
+pub type StderrCallback = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Callback receiving each message of a `query()` before its result
+pub type ProgressCallback = Arc<dyn Fn(&Message) + Send + Sync>;
+
+/// Hook run on the fully built CLI command just before it is spawned
+///
+/// The hook may change the program, arguments and environment, or return an
+/// error to refuse the launch; that error is returned from `connect`.
+pub type PreSpawnHook =
+    Arc<dyn Fn(&mut std::process::Command) -> crate::error::Result<()> + Send + Sync>;
 
 // ============================================================================
 // Claude Agent Options
 // ============================================================================
 
 /// Main options for Claude Agent SDK
+///
+/// Options are constructed with [`ClaudeAgentOptions::builder`] and read through
+/// accessor methods, so new CLI settings can be added without breaking callers.
 #[derive(Clone, Default)]
 pub struct ClaudeAgentOptions {
     /// List of tools that Claude is allowed to use
-    pub allowed_tools: Vec<ToolName>,
+    pub(crate) allowed_tools: Vec<ToolName>,
     /// System prompt configuration
-    pub system_prompt: Option<SystemPrompt>,
+    pub(crate) system_prompt: Option<SystemPrompt>,
     /// MCP server configurations
-    pub mcp_servers: McpServers,
+    pub(crate) mcp_servers: McpServers,
+    /// In-process MCP servers, keyed by name
+    pub(crate) sdk_mcp_servers: HashMap<String, SdkMcpServer>,
     /// Permission mode for tool execution
-    pub permission_mode: Option<PermissionMode>,
+    pub(crate) permission_mode: Option<PermissionMode>,
     /// Whether to continue from the previous conversation
-    pub continue_conversation: bool,
+    pub(crate) continue_conversation: bool,
     /// Session ID to resume from
-    pub resume: Option<SessionId>,
+    pub(crate) resume: Option<SessionId>,
     /// Maximum number of turns before stopping
-    pub max_turns: Option<u32>,
+    pub(crate) max_turns: Option<u32>,
     /// List of tools that Claude is not allowed to use
-    pub disallowed_tools: Vec<ToolName>,
+    pub(crate) disallowed_tools: Vec<ToolName>,
     /// AI model to use
-    pub model: Option<String>,
+    pub(crate) model: Option<String>,
     /// Tool name to use for permission prompts
-    pub permission_prompt_tool_name: Option<String>,
+    pub(crate) permission_prompt_tool_name: Option<String>,
     /// Working directory for the CLI process
-    pub cwd: Option<PathBuf>,
+    pub(crate) cwd: Option<PathBuf>,
     /// Path to settings file
-    pub settings: Option<PathBuf>,
+    pub(crate) settings: Option<PathBuf>,
     /// Additional directories to add to the context
-    pub add_dirs: Vec<PathBuf>,
+    pub(crate) add_dirs: Vec<PathBuf>,
     /// Environment variables for the CLI process
-    pub env: HashMap<String, String>,
+    pub(crate) env: HashMap<String, String>,
     /// Extra CLI arguments to pass
-    pub extra_args: HashMap<String, Option<String>>,
+    pub(crate) extra_args: HashMap<String, Option<String>>,
     /// Maximum buffer size for JSON messages (default: 1MB)
-    pub max_buffer_size: Option<usize>,
+    pub(crate) max_buffer_size: Option<usize>,
     /// Callback for tool permission checks
-    pub can_use_tool: Option<CanUseToolCallback>,
+    pub(crate) can_use_tool: Option<CanUseToolCallback>,
+    /// Declarative permission rules tried before `can_use_tool`
+    pub(crate) permission_policy: Option<PermissionPolicy>,
+    /// Whether tool input may only refer to paths inside `cwd` and `add_dirs`
+    pub(crate) sandbox_paths: bool,
+    /// Most calls allowed per window, by tool
+    pub(crate) tool_rate_limits: HashMap<ToolName, (usize, Duration)>,
     /// Hook configurations
-    pub hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
+    pub(crate) hooks: Option<HashMap<HookEvent, Vec<HookMatcher>>>,
+    /// Longest a single hook callback may run (unbounded when `None`)
+    pub(crate) hook_timeout: Option<Duration>,
+    /// Output used for a hook callback that times out (default: empty output)
+    pub(crate) hook_timeout_output: Option<HookOutput>,
+    /// Whether hook callbacks matching an event run concurrently
+    pub(crate) parallel_hooks: bool,
+    /// How the outputs of several hook callbacks combine (default: block wins)
+    pub(crate) hook_merge_strategy: HookMergeStrategy,
     /// User identifier
-    pub user: Option<String>,
+    pub(crate) user: Option<String>,
     /// Whether to include partial messages in stream
-    pub include_partial_messages: bool,
+    pub(crate) include_partial_messages: bool,
     /// Whether to fork the session when resuming
-    pub fork_session: bool,
+    pub(crate) fork_session: bool,
     /// Custom agent definitions
-    pub agents: Option<HashMap<String, AgentDefinition>>,
+    pub(crate) agents: Option<HashMap<String, AgentDefinition>>,
     /// Setting sources to load
-    pub setting_sources: Option<Vec<SettingSource>>,
+    pub(crate) setting_sources: Option<Vec<SettingSource>>,
+    /// Interval between control channel health pings (disabled when `None`)
+    pub(crate) health_check_interval: Option<Duration>,
+    /// Messages kept in the client's conversation history (disabled when `None`)
+    pub(crate) history_limit: Option<usize>,
+    /// Transport used to reach the CLI (default: local subprocess)
+    pub(crate) transport: TransportConfig,
+    /// Restart policy for unexpected CLI exits (disabled when `None`)
+    pub(crate) reconnect: Option<ReconnectPolicy>,
+    /// Retry policy for `query()` runs that fail to start (disabled when `None`)
+    pub(crate) retry: Option<RetryPolicy>,
+    /// Callback for the messages of a `query()` before its result
+    pub(crate) on_progress: Option<ProgressCallback>,
+    /// Longest wait for a line of CLI output (default: 30s)
+    pub(crate) read_timeout: Option<Duration>,
+    /// Longest wait for a write to the CLI's stdin (unbounded when `None`)
+    pub(crate) write_timeout: Option<Duration>,
+    /// How long `close` waits for the CLI to exit before killing it (default: 5s)
+    pub(crate) shutdown_grace: Option<Duration>,
+    /// Callback for CLI stderr lines (forwarded to the parent's stderr when `None`)
+    pub(crate) stderr_callback: Option<StderrCallback>,
+    /// Recent stderr output kept for error reports (default: 64KB)
+    pub(crate) max_stderr_size: Option<usize>,
+    /// Source of control request IDs (default: `req-1`, `req-2`, ...)
+    pub(crate) id_generator: Option<Arc<dyn IdGenerator>>,
+    /// Path of the local Claude Code CLI (searched for when `None`)
+    pub(crate) cli_path: Option<PathBuf>,
+    /// Hook inspecting or rewriting the CLI command before spawn
+    pub(crate) pre_spawn: Option<PreSpawnHook>,
+    /// Scheduling priority of the local CLI process (unchanged when `None`)
+    pub(crate) process_priority: Option<Priority>,
+    /// Manager session the CLI process is started for (set by the manager)
+    pub(crate) process_owner: Option<ProcessOwner>,
 }
 
 impl ClaudeAgentOptions {
@@ -73,6 +148,288 @@ impl ClaudeAgentOptions {
     pub fn builder() -> ClaudeAgentOptionsBuilder {
         ClaudeAgentOptionsBuilder::default()
     }
+
+    /// Turn these options back into a builder to derive modified options
+    #[must_use]
+    pub fn into_builder(self) -> ClaudeAgentOptionsBuilder {
+        ClaudeAgentOptionsBuilder { options: self }
+    }
+
+    /// Tools that Claude is allowed to use (empty = no restriction)
+    #[must_use]
+    pub fn allowed_tools(&self) -> &[ToolName] {
+        &self.allowed_tools
+    }
+
+    /// System prompt configuration
+    #[must_use]
+    pub const fn system_prompt(&self) -> Option<&SystemPrompt> {
+        self.system_prompt.as_ref()
+    }
+
+    /// MCP server configurations
+    #[must_use]
+    pub const fn mcp_servers(&self) -> &McpServers {
+        &self.mcp_servers
+    }
+
+    /// In-process MCP servers, keyed by name
+    #[must_use]
+    pub const fn sdk_mcp_servers(&self) -> &HashMap<String, SdkMcpServer> {
+        &self.sdk_mcp_servers
+    }
+
+    /// Permission mode for tool execution
+    #[must_use]
+    pub const fn permission_mode(&self) -> Option<PermissionMode> {
+        self.permission_mode
+    }
+
+    /// Whether to continue from the previous conversation
+    #[must_use]
+    pub const fn continue_conversation(&self) -> bool {
+        self.continue_conversation
+    }
+
+    /// Session ID to resume from
+    #[must_use]
+    pub const fn resume(&self) -> Option<&SessionId> {
+        self.resume.as_ref()
+    }
+
+    /// Maximum number of turns before stopping
+    #[must_use]
+    pub const fn max_turns(&self) -> Option<u32> {
+        self.max_turns
+    }
+
+    /// Tools that Claude is not allowed to use
+    #[must_use]
+    pub fn disallowed_tools(&self) -> &[ToolName] {
+        &self.disallowed_tools
+    }
+
+    /// AI model to use
+    #[must_use]
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Tool name to use for permission prompts
+    #[must_use]
+    pub fn permission_prompt_tool_name(&self) -> Option<&str> {
+        self.permission_prompt_tool_name.as_deref()
+    }
+
+    /// Working directory for the CLI process
+    #[must_use]
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// Path to settings file
+    #[must_use]
+    pub fn settings(&self) -> Option<&Path> {
+        self.settings.as_deref()
+    }
+
+    /// Additional directories to add to the context
+    #[must_use]
+    pub fn add_dirs(&self) -> &[PathBuf] {
+        &self.add_dirs
+    }
+
+    /// Environment variables for the CLI process
+    #[must_use]
+    pub const fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Extra CLI arguments to pass
+    #[must_use]
+    pub const fn extra_args(&self) -> &HashMap<String, Option<String>> {
+        &self.extra_args
+    }
+
+    /// Maximum buffer size for JSON messages
+    #[must_use]
+    pub const fn max_buffer_size(&self) -> Option<usize> {
+        self.max_buffer_size
+    }
+
+    /// Callback for tool permission checks
+    #[must_use]
+    pub const fn can_use_tool(&self) -> Option<&CanUseToolCallback> {
+        self.can_use_tool.as_ref()
+    }
+
+    /// Declarative permission rules tried before `can_use_tool`
+    #[must_use]
+    pub const fn permission_policy(&self) -> Option<&PermissionPolicy> {
+        self.permission_policy.as_ref()
+    }
+
+    /// Whether tool input may only refer to paths inside `cwd` and `add_dirs`
+    #[must_use]
+    pub const fn sandbox_paths(&self) -> bool {
+        self.sandbox_paths
+    }
+
+    /// Most calls allowed per window, by tool
+    #[must_use]
+    pub const fn tool_rate_limits(&self) -> &HashMap<ToolName, (usize, Duration)> {
+        &self.tool_rate_limits
+    }
+
+    /// Hook configurations
+    #[must_use]
+    pub const fn hooks(&self) -> Option<&HashMap<HookEvent, Vec<HookMatcher>>> {
+        self.hooks.as_ref()
+    }
+
+    /// Longest a single hook callback may run
+    #[must_use]
+    pub const fn hook_timeout(&self) -> Option<Duration> {
+        self.hook_timeout
+    }
+
+    /// Output used for a hook callback that times out
+    #[must_use]
+    pub const fn hook_timeout_output(&self) -> Option<&HookOutput> {
+        self.hook_timeout_output.as_ref()
+    }
+
+    /// Whether hook callbacks matching an event run concurrently
+    #[must_use]
+    pub const fn parallel_hooks(&self) -> bool {
+        self.parallel_hooks
+    }
+
+    /// How the outputs of several hook callbacks combine
+    #[must_use]
+    pub const fn hook_merge_strategy(&self) -> HookMergeStrategy {
+        self.hook_merge_strategy
+    }
+
+    /// User identifier
+    #[must_use]
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Whether to include partial messages in stream
+    #[must_use]
+    pub const fn include_partial_messages(&self) -> bool {
+        self.include_partial_messages
+    }
+
+    /// Whether to fork the session when resuming
+    #[must_use]
+    pub const fn fork_session(&self) -> bool {
+        self.fork_session
+    }
+
+    /// Custom agent definitions
+    #[must_use]
+    pub const fn agents(&self) -> Option<&HashMap<String, AgentDefinition>> {
+        self.agents.as_ref()
+    }
+
+    /// Setting sources to load
+    #[must_use]
+    pub fn setting_sources(&self) -> Option<&[SettingSource]> {
+        self.setting_sources.as_deref()
+    }
+
+    /// Interval between control channel health pings
+    #[must_use]
+    pub const fn health_check_interval(&self) -> Option<Duration> {
+        self.health_check_interval
+    }
+
+    /// Messages kept in the client's conversation history
+    #[must_use]
+    pub const fn history_limit(&self) -> Option<usize> {
+        self.history_limit
+    }
+
+    /// Transport used to reach the CLI
+    #[must_use]
+    pub const fn transport(&self) -> &TransportConfig {
+        &self.transport
+    }
+
+    /// Restart policy for unexpected CLI exits
+    #[must_use]
+    pub const fn reconnect(&self) -> Option<ReconnectPolicy> {
+        self.reconnect
+    }
+
+    /// Retry policy for `query()` runs that fail to start
+    #[must_use]
+    pub const fn retry(&self) -> Option<RetryPolicy> {
+        self.retry
+    }
+
+    /// Callback for the messages of a `query()` before its result
+    #[must_use]
+    pub const fn on_progress(&self) -> Option<&ProgressCallback> {
+        self.on_progress.as_ref()
+    }
+
+    /// Longest wait for a line of CLI output
+    #[must_use]
+    pub const fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+
+    /// Longest wait for a write to the CLI's stdin
+    #[must_use]
+    pub const fn write_timeout(&self) -> Option<Duration> {
+        self.write_timeout
+    }
+
+    /// How long `close` waits for the CLI to exit before killing it
+    #[must_use]
+    pub const fn shutdown_grace(&self) -> Option<Duration> {
+        self.shutdown_grace
+    }
+
+    /// Callback for CLI stderr lines
+    #[must_use]
+    pub const fn stderr_callback(&self) -> Option<&StderrCallback> {
+        self.stderr_callback.as_ref()
+    }
+
+    /// Recent stderr output kept for error reports
+    #[must_use]
+    pub const fn max_stderr_size(&self) -> Option<usize> {
+        self.max_stderr_size
+    }
+
+    /// Source of control request IDs
+    #[must_use]
+    pub const fn id_generator(&self) -> Option<&Arc<dyn IdGenerator>> {
+        self.id_generator.as_ref()
+    }
+
+    /// Path of the local Claude Code CLI
+    #[must_use]
+    pub fn cli_path(&self) -> Option<&Path> {
+        self.cli_path.as_deref()
+    }
+
+    /// Hook run on the CLI command before spawn
+    #[must_use]
+    pub const fn pre_spawn(&self) -> Option<&PreSpawnHook> {
+        self.pre_spawn.as_ref()
+    }
+
+    /// Scheduling priority of the local CLI process
+    #[must_use]
+    pub const fn process_priority(&self) -> Option<Priority> {
+        self.process_priority
+    }
 }
 
 impl std::fmt::Debug for ClaudeAgentOptions {
@@ -81,6 +438,11 @@ impl std::fmt::Debug for ClaudeAgentOptions {
             .field("allowed_tools", &self.allowed_tools)
             .field("system_prompt", &self.system_prompt)
             .field("mcp_servers", &self.mcp_servers)
+            .field("sdk_mcp_servers", &{
+                let mut names: Vec<&String> = self.sdk_mcp_servers.keys().collect();
+                names.sort();
+                names
+            })
             .field("permission_mode", &self.permission_mode)
             .field("continue_conversation", &self.continue_conversation)
             .field("resume", &self.resume)
@@ -101,6 +463,9 @@ impl std::fmt::Debug for ClaudeAgentOptions {
                 "can_use_tool",
                 &self.can_use_tool.as_ref().map(|_| "<callback>"),
             )
+            .field("permission_policy", &self.permission_policy)
+            .field("sandbox_paths", &self.sandbox_paths)
+            .field("tool_rate_limits", &self.tool_rate_limits)
             .field(
                 "hooks",
                 &self
@@ -108,15 +473,132 @@ impl std::fmt::Debug for ClaudeAgentOptions {
                     .as_ref()
                     .map(|h| format!("[{} hook types]", h.len())),
             )
+            .field("hook_timeout", &self.hook_timeout)
+            .field("hook_timeout_output", &self.hook_timeout_output)
+            .field("parallel_hooks", &self.parallel_hooks)
+            .field("hook_merge_strategy", &self.hook_merge_strategy)
             .field("user", &self.user)
             .field("include_partial_messages", &self.include_partial_messages)
             .field("fork_session", &self.fork_session)
             .field("agents", &self.agents)
             .field("setting_sources", &self.setting_sources)
+            .field("health_check_interval", &self.health_check_interval)
+            .field("history_limit", &self.history_limit)
+            .field("transport", &self.transport)
+            .field("reconnect", &self.reconnect)
+            .field("retry", &self.retry)
+            .field("on_progress", &self.on_progress.as_ref().map(|_| "<callback>"))
+            .field("read_timeout", &self.read_timeout)
+            .field("write_timeout", &self.write_timeout)
+            .field("shutdown_grace", &self.shutdown_grace)
+            .field(
+                "stderr_callback",
+                &self.stderr_callback.as_ref().map(|_| "<callback>"),
+            )
+            .field("max_stderr_size", &self.max_stderr_size)
+            .field("id_generator", &self.id_generator)
+            .field("cli_path", &self.cli_path)
+            .field("pre_spawn", &self.pre_spawn.as_ref().map(|_| "<hook>"))
+            .field("process_priority", &self.process_priority)
+            .field("process_owner", &self.process_owner)
             .finish()
     }
 }
 
+// ============================================================================
+// Retry Policy
+// ============================================================================
+
+/// Policy for retrying `query()` when the CLI fails to start
+///
+/// A run is retried when connecting fails or the first thing the CLI's
+/// output yields is an error the policy classifies as retryable (by default
+/// [`ClaudeError::is_transient`]). Once a message has been streamed the
+/// run is not retried, so no output is ever repeated. Created with
+/// [`RetryPolicy::new`] or `Default`.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each failed attempt
+    pub multiplier: f64,
+    /// Whether an error is worth another attempt
+    pub retry_on: fn(&ClaudeError) -> bool,
+}
+
+impl RetryPolicy {
+    /// Create a policy making up to `max_attempts` attempts with default backoff
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Set the delay before the first retry
+    #[must_use]
+    pub const fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound for the delay between attempts
+    #[must_use]
+    pub const fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay grows by after each failed attempt
+    #[must_use]
+    pub const fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Set which errors are retried
+    #[must_use]
+    pub const fn retry_on(mut self, retry_on: fn(&ClaudeError) -> bool) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// Whether the attempt that failed with `error` should be followed by
+    /// another (`attempt` is 1-based)
+    #[must_use]
+    pub fn should_retry(&self, attempt: u32, error: &ClaudeError) -> bool {
+        attempt < self.max_attempts && (self.retry_on)(error)
+    }
+
+    /// Delay before the given retry (0-based)
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(retry.min(i32::MAX as u32) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            retry_on: ClaudeError::is_transient,
+        }
+    }
+}
+
 // ============================================================================
 // Builder for ClaudeAgentOptions
 // ============================================================================
@@ -129,6 +611,11 @@ pub struct ClaudeAgentOptionsBuilder {
 
 impl ClaudeAgentOptionsBuilder {
     /// Set allowed tools
+    ///
+    /// An empty list (the default) places no restriction, as for the CLI.
+    /// Earlier releases denied every tool when the list was empty and a
+    /// [`can_use_tool`](Self::can_use_tool) callback was set; list the tools
+    /// explicitly to restrict a session with a callback.
     #[must_use]
     pub fn allowed_tools(mut self, tools: Vec<impl Into<ToolName>>) -> Self {
         self.options.allowed_tools = tools.into_iter().map(std::convert::Into::into).collect();
@@ -156,6 +643,18 @@ impl ClaudeAgentOptionsBuilder {
         self
     }
 
+    /// Add an in-process MCP server, replacing any with the same name
+    ///
+    /// The server is announced to the CLI alongside `mcp_servers`; its tools
+    /// still need to be allowed like any other MCP tool.
+    #[must_use]
+    pub fn sdk_mcp_server(mut self, server: SdkMcpServer) -> Self {
+        self.options
+            .sdk_mcp_servers
+            .insert(server.name().to_string(), server);
+        self
+    }
+
     /// Set permission mode
     #[must_use]
     pub const fn permission_mode(mut self, mode: PermissionMode) -> Self {
@@ -163,21 +662,62 @@ impl ClaudeAgentOptionsBuilder {
         self
     }
 
+    /// Continue from the previous conversation
+    #[must_use]
+    pub const fn continue_conversation(mut self, enabled: bool) -> Self {
+        self.options.continue_conversation = enabled;
+        self
+    }
+
+    /// Set the session ID to resume from
+    #[must_use]
+    pub fn resume(mut self, session_id: impl Into<SessionId>) -> Self {
+        self.options.resume = Some(session_id.into());
+        self
+    }
+
     /// Set max turns
     ///
     /// # Panics
     /// Panics if turns exceeds 1000
     #[must_use]
     pub fn max_turns(mut self, turns: u32) -> Self {
-        const MAX_ALLOWED_TURNS: u32 = 1000;
         assert!(
-            turns <= MAX_ALLOWED_TURNS,
-            "max_turns {turns} exceeds maximum allowed: {MAX_ALLOWED_TURNS}"
+            turns <= MAX_TURNS,
+            "max_turns {turns} exceeds maximum allowed: {MAX_TURNS}"
         );
         self.options.max_turns = Some(turns);
         self
     }
 
+    /// Set disallowed tools
+    #[must_use]
+    pub fn disallowed_tools(mut self, tools: Vec<impl Into<ToolName>>) -> Self {
+        self.options.disallowed_tools = tools.into_iter().map(std::convert::Into::into).collect();
+        self
+    }
+
+    /// Add a disallowed tool
+    #[must_use]
+    pub fn add_disallowed_tool(mut self, tool: impl Into<ToolName>) -> Self {
+        self.options.disallowed_tools.push(tool.into());
+        self
+    }
+
+    /// Set the AI model
+    #[must_use]
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.options.model = Some(model.into());
+        self
+    }
+
+    /// Set the tool name used for permission prompts
+    #[must_use]
+    pub fn permission_prompt_tool_name(mut self, name: impl Into<String>) -> Self {
+        self.options.permission_prompt_tool_name = Some(name.into());
+        self
+    }
+
     /// Set working directory
     #[must_use]
     pub fn cwd(mut self, path: impl Into<PathBuf>) -> Self {
@@ -185,6 +725,62 @@ impl ClaudeAgentOptionsBuilder {
         self
     }
 
+    /// Set the settings file
+    #[must_use]
+    pub fn settings(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.settings = Some(path.into());
+        self
+    }
+
+    /// Set additional context directories
+    #[must_use]
+    pub fn add_dirs(mut self, dirs: Vec<impl Into<PathBuf>>) -> Self {
+        self.options.add_dirs = dirs.into_iter().map(std::convert::Into::into).collect();
+        self
+    }
+
+    /// Add an additional context directory
+    #[must_use]
+    pub fn add_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.add_dirs.push(dir.into());
+        self
+    }
+
+    /// Set environment variables for the CLI process
+    #[must_use]
+    pub fn env(mut self, env: HashMap<String, String>) -> Self {
+        self.options.env = env;
+        self
+    }
+
+    /// Add an environment variable for the CLI process
+    #[must_use]
+    pub fn add_env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.env.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set extra CLI arguments
+    #[must_use]
+    pub fn extra_args(mut self, args: HashMap<String, Option<String>>) -> Self {
+        self.options.extra_args = args;
+        self
+    }
+
+    /// Add an extra CLI argument (`--flag` or `--flag value`)
+    #[must_use]
+    pub fn extra_arg(mut self, flag: impl Into<String>, value: Option<String>) -> Self {
+        self.options.extra_args.insert(flag.into(), value);
+        self
+    }
+
+    /// Set the maximum buffer size for JSON messages
+    #[must_use]
+    pub const fn max_buffer_size(mut self, size: usize) -> Self {
+        self.options.max_buffer_size = Some(size);
+        self
+    }
+
     /// Set `can_use_tool` callback
     #[must_use]
     pub fn can_use_tool(mut self, callback: CanUseToolCallback) -> Self {
@@ -192,6 +788,43 @@ impl ClaudeAgentOptionsBuilder {
         self
     }
 
+    /// Set declarative permission rules tried before `can_use_tool`
+    ///
+    /// See [`PermissionPolicy::from_path`] for loading them from a file.
+    #[must_use]
+    pub fn permission_policy(mut self, policy: PermissionPolicy) -> Self {
+        self.options.permission_policy = Some(policy);
+        self
+    }
+
+    /// Deny tool calls referring to paths outside `cwd` and `add_dirs`
+    ///
+    /// Checks the `file_path`, `notebook_path` and `path` input fields and
+    /// path-like words of Bash commands, following symlinks. See
+    /// [`PathSandbox`](crate::permissions::PathSandbox).
+    #[must_use]
+    pub const fn sandbox_paths(mut self, enabled: bool) -> Self {
+        self.options.sandbox_paths = enabled;
+        self
+    }
+
+    /// Allow at most `max_calls` uses of `tool` in any `window`
+    ///
+    /// Further calls are denied with a message naming the limit, e.g. to stop
+    /// a runaway loop of `Bash` or `WebFetch` calls.
+    #[must_use]
+    pub fn tool_rate_limit(
+        mut self,
+        tool: impl Into<ToolName>,
+        max_calls: usize,
+        window: Duration,
+    ) -> Self {
+        self.options
+            .tool_rate_limits
+            .insert(tool.into(), (max_calls, window));
+        self
+    }
+
     /// Set hooks
     #[must_use]
     pub fn hooks(mut self, hooks: HashMap<HookEvent, Vec<HookMatcher>>) -> Self {
@@ -199,6 +832,237 @@ impl ClaudeAgentOptionsBuilder {
         self
     }
 
+    /// Set the longest a single hook callback may run
+    ///
+    /// A callback still running after `timeout` is abandoned and the
+    /// [`hook_timeout_output`](Self::hook_timeout_output) is used in its place,
+    /// so a hung hook cannot stall the CLI.
+    #[must_use]
+    pub const fn hook_timeout(mut self, timeout: Duration) -> Self {
+        self.options.hook_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the output used for a hook callback that times out
+    #[must_use]
+    pub fn hook_timeout_output(mut self, output: HookOutput) -> Self {
+        self.options.hook_timeout_output = Some(output);
+        self
+    }
+
+    /// Run hook callbacks matching an event concurrently
+    ///
+    /// See [`HookManager::set_parallel`](crate::hooks::HookManager::set_parallel).
+    #[must_use]
+    pub const fn parallel_hooks(mut self, parallel: bool) -> Self {
+        self.options.parallel_hooks = parallel;
+        self
+    }
+
+    /// Set how the outputs of several hook callbacks combine
+    #[must_use]
+    pub const fn hook_merge_strategy(mut self, strategy: HookMergeStrategy) -> Self {
+        self.options.hook_merge_strategy = strategy;
+        self
+    }
+
+    /// Set the user identifier
+    #[must_use]
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.options.user = Some(user.into());
+        self
+    }
+
+    /// Include partial messages in the stream
+    #[must_use]
+    pub const fn include_partial_messages(mut self, enabled: bool) -> Self {
+        self.options.include_partial_messages = enabled;
+        self
+    }
+
+    /// Fork the session when resuming
+    #[must_use]
+    pub const fn fork_session(mut self, enabled: bool) -> Self {
+        self.options.fork_session = enabled;
+        self
+    }
+
+    /// Set custom agent definitions
+    #[must_use]
+    pub fn agents(mut self, agents: HashMap<String, AgentDefinition>) -> Self {
+        self.options.agents = Some(agents);
+        self
+    }
+
+    /// Set the setting sources to load
+    #[must_use]
+    pub fn setting_sources(mut self, sources: Vec<SettingSource>) -> Self {
+        self.options.setting_sources = Some(sources);
+        self
+    }
+
+    /// Enable periodic control channel health pings
+    #[must_use]
+    pub const fn health_check_interval(mut self, interval: Duration) -> Self {
+        self.options.health_check_interval = Some(interval);
+        self
+    }
+
+    /// Keep the latest `limit` messages sent and received in the client
+    ///
+    /// The history is read with `ClaudeSDKClient::history`; partial-message
+    /// stream events are not kept.
+    #[must_use]
+    pub const fn history_limit(mut self, limit: usize) -> Self {
+        self.options.history_limit = Some(limit);
+        self
+    }
+
+    /// Set the transport used to reach the CLI
+    #[must_use]
+    pub fn transport(mut self, transport: TransportConfig) -> Self {
+        self.options.transport = transport;
+        self
+    }
+
+    /// Restart the CLI with `--resume` if it exits unexpectedly
+    #[must_use]
+    pub const fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.options.reconnect = Some(policy);
+        self
+    }
+
+    /// Retry `query()` runs that fail before producing any output
+    #[must_use]
+    pub const fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.options.retry = Some(policy);
+        self
+    }
+
+    /// Call `callback` with every message of a `query()` before its result
+    ///
+    /// Lets callers of the one-shot functions that only return the final
+    /// answer, such as `query_text`, show tool use and text as it arrives.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use kodegen_claude_agent::{ClaudeAgentOptions, Message};
+    ///
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .on_progress(Arc::new(|message: &Message| {
+    ///         if let Message::Assistant { .. } = message {
+    ///             eprint!(".");
+    ///         }
+    ///     }))
+    ///     .build();
+    /// assert!(options.on_progress().is_some());
+    /// ```
+    #[must_use]
+    pub fn on_progress(mut self, callback: ProgressCallback) -> Self {
+        self.options.on_progress = Some(callback);
+        self
+    }
+
+    /// Set the longest wait for a line of CLI output
+    ///
+    /// The message stream ends with a timeout error when the CLI stays silent
+    /// for longer than this.
+    #[must_use]
+    pub const fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.options.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the longest wait for a write to the CLI's stdin
+    #[must_use]
+    pub const fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.options.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Set how long `close` waits for the CLI to exit before killing it
+    #[must_use]
+    pub const fn shutdown_grace(mut self, grace: Duration) -> Self {
+        self.options.shutdown_grace = Some(grace);
+        self
+    }
+
+    /// Receive each line the CLI writes to stderr instead of forwarding it
+    #[must_use]
+    pub fn stderr_callback(mut self, callback: StderrCallback) -> Self {
+        self.options.stderr_callback = Some(callback);
+        self
+    }
+
+    /// Set how much recent stderr output is kept for error reports
+    #[must_use]
+    pub const fn max_stderr_size(mut self, size: usize) -> Self {
+        self.options.max_stderr_size = Some(size);
+        self
+    }
+
+    /// Generate control request IDs with `ids`, e.g. a
+    /// [`SequentialIdGenerator`](super::identifiers::SequentialIdGenerator)
+    /// for stable IDs in tests
+    #[must_use]
+    pub fn id_generator(mut self, ids: Arc<dyn IdGenerator>) -> Self {
+        self.options.id_generator = Some(ids);
+        self
+    }
+
+    /// Start this Claude Code CLI instead of searching `PATH` for `claude`
+    ///
+    /// Applies to the local subprocess transport; a path passed to
+    /// [`ClaudeSDKClient::new`](crate::ClaudeSDKClient::new) takes precedence.
+    #[must_use]
+    pub fn cli_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.cli_path = Some(path.into());
+        self
+    }
+
+    /// Inspect, rewrite or veto the CLI command before it is spawned
+    ///
+    /// The hook sees the command exactly as it will run: program, arguments
+    /// and environment, after any ssh or container wrapping. Use it to inject
+    /// credentials from a secrets manager or to enforce policy on CLI flags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError};
+    ///
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .pre_spawn(Arc::new(|cmd| {
+    ///         if cmd.get_args().any(|arg| arg == "--dangerously-skip-permissions") {
+    ///             return Err(ClaudeError::invalid_config("Flag not allowed by policy"));
+    ///         }
+    ///         cmd.env("ANTHROPIC_API_KEY", "from-vault");
+    ///         Ok(())
+    ///     }))
+    ///     .build();
+    /// assert!(options.pre_spawn().is_some());
+    /// ```
+    #[must_use]
+    pub fn pre_spawn(mut self, hook: PreSpawnHook) -> Self {
+        self.options.pre_spawn = Some(hook);
+        self
+    }
+
+    /// Run the local CLI process at a lower (or higher) scheduling priority
+    ///
+    /// Keeps large batches of background agents from starving interactive
+    /// work on a shared machine. Not applied to CLIs started over ssh or in
+    /// a container.
+    #[must_use]
+    pub const fn process_priority(mut self, priority: Priority) -> Self {
+        self.options.process_priority = Some(priority);
+        self
+    }
+
     /// Build the options
     #[must_use]
     pub fn build(self) -> ClaudeAgentOptions {
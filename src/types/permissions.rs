@@ -4,6 +4,7 @@
 //! permission rules, permission requests, and permission results.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -17,6 +18,7 @@ use crate::error::Result;
 
 /// Permission modes for tool execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum PermissionMode {
     /// Default mode - CLI prompts for dangerous tools
@@ -43,6 +45,7 @@ pub enum SettingSource {
 
 /// Permission update destination
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub enum PermissionUpdateDestination {
     /// Save to user settings
@@ -57,6 +60,7 @@ pub enum PermissionUpdateDestination {
 
 /// Permission behavior
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum PermissionBehavior {
     /// Allow the action
@@ -68,24 +72,35 @@ pub enum PermissionBehavior {
 }
 
 /// Permission rule value
+///
+/// Serialized in camelCase like the CLI and the other agent SDKs; the
+/// snake_case names are still accepted when parsing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
 pub struct PermissionRuleValue {
     /// Name of the tool
+    #[serde(alias = "tool_name")]
     pub tool_name: String,
     /// Optional rule content
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "rule_content")]
     pub rule_content: Option<String>,
 }
 
 /// Permission update configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum PermissionUpdate {
     /// Add permission rules
     AddRules {
         /// Rules to add
         #[serde(skip_serializing_if = "Option::is_none")]
         rules: Option<Vec<PermissionRuleValue>>,
+        /// Behavior the rules apply
+        #[serde(skip_serializing_if = "Option::is_none")]
+        behavior: Option<PermissionBehavior>,
         /// Where to save the rules
         #[serde(skip_serializing_if = "Option::is_none")]
         destination: Option<PermissionUpdateDestination>,
@@ -95,6 +110,9 @@ pub enum PermissionUpdate {
         /// New rules
         #[serde(skip_serializing_if = "Option::is_none")]
         rules: Option<Vec<PermissionRuleValue>>,
+        /// Behavior the rules apply
+        #[serde(skip_serializing_if = "Option::is_none")]
+        behavior: Option<PermissionBehavior>,
         /// Where to save the rules
         #[serde(skip_serializing_if = "Option::is_none")]
         destination: Option<PermissionUpdateDestination>,
@@ -104,6 +122,9 @@ pub enum PermissionUpdate {
         /// Rules to remove
         #[serde(skip_serializing_if = "Option::is_none")]
         rules: Option<Vec<PermissionRuleValue>>,
+        /// Behavior the rules apply
+        #[serde(skip_serializing_if = "Option::is_none")]
+        behavior: Option<PermissionBehavior>,
         /// Where to remove from
         #[serde(skip_serializing_if = "Option::is_none")]
         destination: Option<PermissionUpdateDestination>,
@@ -138,6 +159,7 @@ pub enum PermissionUpdate {
 
 /// Context for tool permission callbacks
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ToolPermissionContext {
     /// Permission suggestions from CLI
     pub suggestions: Vec<PermissionUpdate>,
@@ -145,6 +167,7 @@ pub struct ToolPermissionContext {
 
 /// Permission request from CLI
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PermissionRequest {
     /// Tool name being requested
     pub tool_name: ToolName,
@@ -156,6 +179,7 @@ pub struct PermissionRequest {
 
 /// Permission result for allowing tool use
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PermissionResultAllow {
     /// Modified input for the tool
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -167,6 +191,7 @@ pub struct PermissionResultAllow {
 
 /// Permission result for denying tool use
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PermissionResultDeny {
     /// Reason for denying
     pub message: String,
@@ -176,13 +201,43 @@ pub struct PermissionResultDeny {
 }
 
 /// Permission result enum
+///
+/// Marked `#[non_exhaustive]` since `AllowAlways` and `DenyAlways` were
+/// added: matches need a wildcard arm, so later answer kinds do not break
+/// callers again.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "lowercase")]
+#[non_exhaustive]
 pub enum PermissionResult {
     /// Allow the tool use
     Allow(PermissionResultAllow),
     /// Deny the tool use
     Deny(PermissionResultDeny),
+    /// Allow the tool use and, for the rest of the session, later uses it covers
+    ///
+    /// [`PermissionManager`](crate::permissions::PermissionManager) remembers
+    /// the answer and sends the CLI a plain allow.
+    #[serde(rename = "allowAlways")]
+    AllowAlways {
+        /// Patterns later inputs must match to be covered, by field, with `*`
+        /// and `?` wildcards (every use of the tool when empty)
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        input: BTreeMap<String, String>,
+    },
+    /// Deny the tool use and, for the rest of the session, later uses it covers
+    ///
+    /// [`PermissionManager`](crate::permissions::PermissionManager) remembers
+    /// the answer and sends the CLI a plain deny.
+    #[serde(rename = "denyAlways")]
+    DenyAlways {
+        /// Reason for denying
+        message: String,
+        /// Patterns later inputs must match to be covered, by field, with `*`
+        /// and `?` wildcards (every use of the tool when empty)
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        input: BTreeMap<String, String>,
+    },
 }
 
 /// Callback type for tool permission checks
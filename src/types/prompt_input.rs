@@ -32,20 +32,45 @@ pub struct PromptTemplateInput {
     pub parameters: HashMap<String, TemplateParamValue>,
 }
 
+/// Renders named prompt templates
+///
+/// Implemented by the prompt tools' `PromptManager`; other implementations
+/// can serve templates from elsewhere, e.g. fixed strings in tests.
+pub trait PromptRenderer: Sync {
+    /// Render template `name` with `parameters`, or describe why it failed
+    fn render(
+        &self,
+        name: &str,
+        parameters: HashMap<String, TemplateParamValue>,
+    ) -> impl Future<Output = Result<String, String>> + Send;
+}
+
+impl PromptRenderer for kodegen_tools_prompt::PromptManager {
+    async fn render(
+        &self,
+        name: &str,
+        parameters: HashMap<String, TemplateParamValue>,
+    ) -> Result<String, String> {
+        self.render_prompt(name, Some(parameters))
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
 impl PromptInput {
     /// Convert to plain string, resolving templates if needed
     pub async fn resolve(
         &self,
-        prompt_manager: &kodegen_tools_prompt::PromptManager,
+        prompt_manager: &impl PromptRenderer,
     ) -> Result<String, crate::error::ClaudeError> {
         match self {
             PromptInput::String(s) => Ok(s.clone()),
             PromptInput::Template(template) => prompt_manager
-                .render_prompt(&template.name, Some(template.parameters.clone()))
+                .render(&template.name, template.parameters.clone())
                 .await
-                .map_err(|e| crate::error::ClaudeError::PromptTemplateError {
+                .map_err(|message| crate::error::ClaudeError::PromptTemplateError {
                     template: template.name.clone(),
-                    message: e.to_string(),
+                    message,
                 }),
         }
     }
@@ -0,0 +1,135 @@
+//! Built-in agent role templates
+//!
+//! This module contains ready-made roles for common agent tasks. Each role pairs
+//! a system prompt with a tool allowlist and a default turn budget, so callers
+//! can spawn a focused agent by name instead of hand-writing its configuration.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ClaudeError;
+
+// ============================================================================
+// Role Templates
+// ============================================================================
+
+const CODE_REVIEWER_PROMPT: &str = "You are a meticulous code reviewer. Read the \
+changes and the code around them, then report correctness bugs, security issues, \
+unclear naming and missing tests. Cite file paths and line numbers, order findings \
+by severity, and do not modify any files.";
+
+const TEST_WRITER_PROMPT: &str = "You are a test engineer. Write focused tests for \
+the requested code that follow the project's existing test layout and helpers. \
+Cover edge cases and failure paths, run the tests, and fix only the tests you \
+wrote until they pass.";
+
+const BUG_TRIAGER_PROMPT: &str = "You are a bug triager. Reproduce the reported \
+problem, narrow it down to the responsible code, and summarize the root cause, \
+affected components, severity and a suggested fix. Do not change any files.";
+
+const DOC_WRITER_PROMPT: &str = "You are a technical writer. Write or update \
+documentation for the requested code so it matches the current behavior. Keep \
+the project's existing tone and format, include short usage examples, and do not \
+change any code.";
+
+/// Built-in agent role
+///
+/// Roles are selected by their kebab-case name (e.g. `"code-reviewer"`) and
+/// provide defaults for the system prompt, allowed tools and max turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentRole {
+    /// Reviews code and reports findings without editing
+    CodeReviewer,
+    /// Writes and runs tests
+    TestWriter,
+    /// Reproduces and diagnoses bug reports without editing
+    BugTriager,
+    /// Writes and updates documentation
+    DocWriter,
+}
+
+impl AgentRole {
+    /// All built-in roles
+    pub const ALL: [Self; 4] = [
+        Self::CodeReviewer,
+        Self::TestWriter,
+        Self::BugTriager,
+        Self::DocWriter,
+    ];
+
+    /// Name used to select this role
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::CodeReviewer => "code-reviewer",
+            Self::TestWriter => "test-writer",
+            Self::BugTriager => "bug-triager",
+            Self::DocWriter => "doc-writer",
+        }
+    }
+
+    /// System prompt for this role
+    #[must_use]
+    pub const fn system_prompt(self) -> &'static str {
+        match self {
+            Self::CodeReviewer => CODE_REVIEWER_PROMPT,
+            Self::TestWriter => TEST_WRITER_PROMPT,
+            Self::BugTriager => BUG_TRIAGER_PROMPT,
+            Self::DocWriter => DOC_WRITER_PROMPT,
+        }
+    }
+
+    /// Tools this role is allowed to use
+    #[must_use]
+    pub const fn allowed_tools(self) -> &'static [&'static str] {
+        match self {
+            Self::CodeReviewer => &[
+                "Read",
+                "Grep",
+                "Glob",
+                "Bash(git diff:*)",
+                "Bash(git log:*)",
+            ],
+            Self::TestWriter => &["Read", "Grep", "Glob", "Edit", "Write", "Bash"],
+            Self::BugTriager => &["Read", "Grep", "Glob", "Bash"],
+            Self::DocWriter => &["Read", "Grep", "Glob", "Edit", "Write"],
+        }
+    }
+
+    /// Default maximum number of turns for this role
+    #[must_use]
+    pub const fn default_max_turns(self) -> u32 {
+        match self {
+            Self::CodeReviewer => 15,
+            Self::TestWriter => 30,
+            Self::BugTriager => 20,
+            Self::DocWriter => 20,
+        }
+    }
+}
+
+impl fmt::Display for AgentRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for AgentRole {
+    type Err = ClaudeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|role| role.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|role| role.name()).collect();
+                ClaudeError::invalid_agent_config(format!(
+                    "Unknown agent role '{s}' (expected one of: {})",
+                    names.join(", ")
+                ))
+            })
+    }
+}
@@ -0,0 +1,388 @@
+//! Transport selection types
+//!
+//! This module contains the configuration used to choose how `ClaudeSDKClient`
+//! reaches the Claude Code CLI. These types are always available so options can
+//! be built regardless of enabled features; selecting a transport whose feature
+//! is disabled fails when the client is created.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// ============================================================================
+// Transport Selection
+// ============================================================================
+
+/// Transport used to communicate with the Claude Code CLI
+#[derive(Debug, Clone, Default)]
+pub enum TransportConfig {
+    /// Spawn the CLI locally and communicate over stdin/stdout
+    #[default]
+    Subprocess,
+    /// Connect to a hosted CLI endpoint over HTTP (requires the `http` feature)
+    Http(HttpTransportConfig),
+    /// Spawn the CLI on a remote host over ssh
+    Ssh(SshTransportConfig),
+    /// Spawn the CLI inside a Docker/Podman container
+    Container(ContainerTransportConfig),
+}
+
+// ============================================================================
+// HTTP Transport Configuration
+// ============================================================================
+
+/// Configuration for connecting to a hosted CLI endpoint
+///
+/// Messages are written with `POST {endpoint}/messages` and read from a
+/// server-sent event stream at `GET {endpoint}/events`, one JSON message per
+/// event. Created with [`HttpTransportConfig::new`] and
+/// [`header`](Self::header).
+#[derive(Clone)]
+#[non_exhaustive]
+pub struct HttpTransportConfig {
+    /// Base URL of the hosted CLI endpoint
+    pub endpoint: String,
+    /// Extra headers sent with every request (e.g. authorization)
+    pub headers: HashMap<String, String>,
+}
+
+impl HttpTransportConfig {
+    /// Create a configuration for the given base URL
+    #[must_use]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            headers: HashMap::new(),
+        }
+    }
+
+    /// Add a header sent with every request
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl std::fmt::Debug for HttpTransportConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Header values commonly carry credentials, so only names are shown
+        let mut header_names: Vec<&String> = self.headers.keys().collect();
+        header_names.sort();
+        f.debug_struct("HttpTransportConfig")
+            .field("endpoint", &self.endpoint)
+            .field("headers", &header_names)
+            .finish()
+    }
+}
+
+// ============================================================================
+// SSH Transport Configuration
+// ============================================================================
+
+/// Configuration for running the CLI on a remote host over ssh
+///
+/// The local `ssh` client is used, so host aliases, agents and keys from
+/// `~/.ssh/config` apply. `cwd` and `env` from `ClaudeAgentOptions` are
+/// applied on the remote side; `env` values are sent over the session's
+/// stdin, so they never appear in the local process list. Created with
+/// [`SshTransportConfig::new`] and the setters below.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SshTransportConfig {
+    /// Remote host name or `~/.ssh/config` alias
+    pub host: String,
+    /// Remote user (defaults to the ssh client's choice)
+    pub user: Option<String>,
+    /// Remote port (defaults to the ssh client's choice)
+    pub port: Option<u16>,
+    /// Private key to authenticate with
+    pub identity_file: Option<PathBuf>,
+    /// Path of the claude CLI on the remote host (default: `claude`)
+    pub remote_cli_path: String,
+    /// Additional arguments passed to ssh before the host (e.g. `-o` options)
+    pub ssh_args: Vec<String>,
+    /// Local ssh client binary (default: `ssh`)
+    pub ssh_path: PathBuf,
+}
+
+impl SshTransportConfig {
+    /// Create a configuration for the given host with defaults
+    #[must_use]
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            user: None,
+            port: None,
+            identity_file: None,
+            remote_cli_path: "claude".to_string(),
+            ssh_args: Vec::new(),
+            ssh_path: PathBuf::from("ssh"),
+        }
+    }
+
+    /// Set the remote user
+    #[must_use]
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Set the remote port
+    #[must_use]
+    pub const fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the private key to authenticate with
+    #[must_use]
+    pub fn identity_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity_file = Some(path.into());
+        self
+    }
+
+    /// Set the path of the claude CLI on the remote host
+    #[must_use]
+    pub fn remote_cli_path(mut self, path: impl Into<String>) -> Self {
+        self.remote_cli_path = path.into();
+        self
+    }
+
+    /// Add an argument passed to ssh before the host
+    #[must_use]
+    pub fn ssh_arg(mut self, arg: impl Into<String>) -> Self {
+        self.ssh_args.push(arg.into());
+        self
+    }
+
+    /// Set the local ssh client binary
+    #[must_use]
+    pub fn ssh_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ssh_path = path.into();
+        self
+    }
+}
+
+// ============================================================================
+// Container Transport Configuration
+// ============================================================================
+
+/// Configuration for running the CLI inside a container
+///
+/// The container is started with `<runtime> run --rm -i` and removed when the
+/// transport closes. `cwd` and `add_dirs` from `ClaudeAgentOptions` are
+/// bind-mounted at the same paths, and `env` is forwarded by name. Created
+/// with [`ContainerTransportConfig::new`] and the setters below.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ContainerTransportConfig {
+    /// Image containing the claude CLI
+    pub image: String,
+    /// Container runtime binary (default: `docker`; `podman` is compatible)
+    pub runtime: PathBuf,
+    /// Additional bind mounts
+    pub mounts: Vec<ContainerMount>,
+    /// Network mode (e.g. `none`, `host`, or a network name)
+    pub network: Option<String>,
+    /// Path of the claude CLI inside the image (default: `claude`)
+    pub cli_path: String,
+    /// Additional arguments passed to `run` before the image
+    pub run_args: Vec<String>,
+}
+
+impl ContainerTransportConfig {
+    /// Create a configuration for the given image with defaults
+    #[must_use]
+    pub fn new(image: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+            runtime: PathBuf::from("docker"),
+            mounts: Vec::new(),
+            network: None,
+            cli_path: "claude".to_string(),
+            run_args: Vec::new(),
+        }
+    }
+
+    /// Set the container runtime binary
+    #[must_use]
+    pub fn runtime(mut self, runtime: impl Into<PathBuf>) -> Self {
+        self.runtime = runtime.into();
+        self
+    }
+
+    /// Add a bind mount
+    #[must_use]
+    pub fn mount(mut self, mount: ContainerMount) -> Self {
+        self.mounts.push(mount);
+        self
+    }
+
+    /// Set the network mode
+    #[must_use]
+    pub fn network(mut self, network: impl Into<String>) -> Self {
+        self.network = Some(network.into());
+        self
+    }
+
+    /// Set the path of the claude CLI inside the image
+    #[must_use]
+    pub fn cli_path(mut self, path: impl Into<String>) -> Self {
+        self.cli_path = path.into();
+        self
+    }
+
+    /// Add an argument passed to `run` before the image
+    #[must_use]
+    pub fn run_arg(mut self, arg: impl Into<String>) -> Self {
+        self.run_args.push(arg.into());
+        self
+    }
+}
+
+/// Bind mount from the host into the container
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContainerMount {
+    /// Path on the host
+    pub host_path: PathBuf,
+    /// Path inside the container
+    pub container_path: PathBuf,
+    /// Mount read-only
+    pub read_only: bool,
+}
+
+impl ContainerMount {
+    /// Create a read-write mount
+    #[must_use]
+    pub fn new(host_path: impl Into<PathBuf>, container_path: impl Into<PathBuf>) -> Self {
+        Self {
+            host_path: host_path.into(),
+            container_path: container_path.into(),
+            read_only: false,
+        }
+    }
+
+    /// Create a read-only mount
+    #[must_use]
+    pub fn read_only(host_path: impl Into<PathBuf>, container_path: impl Into<PathBuf>) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(host_path, container_path)
+        }
+    }
+}
+
+// ============================================================================
+// Reconnect Policy
+// ============================================================================
+
+/// Policy for restarting the CLI after it exits unexpectedly
+///
+/// When the CLI process dies mid-session, the subprocess-based transports
+/// restart it with `--resume <session_id>` after an exponential backoff, so the
+/// conversation continues without the caller reconnecting. Created with
+/// [`ReconnectPolicy::new`] or `Default`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ReconnectPolicy {
+    /// Restart attempts before giving up (reset after a successful restart)
+    pub max_retries: u32,
+    /// Delay before the first restart attempt
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each failed attempt
+    pub multiplier: f64,
+}
+
+impl ReconnectPolicy {
+    /// Create a policy with the given retry budget and default backoff
+    #[must_use]
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Set the delay before the first restart attempt
+    #[must_use]
+    pub const fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound for the delay between attempts
+    #[must_use]
+    pub const fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay grows by after each failed attempt
+    #[must_use]
+    pub const fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Delay before the given attempt (0-based)
+    #[must_use]
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(attempt.min(i32::MAX as u32) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+// ============================================================================
+// Process Priority
+// ============================================================================
+
+/// Scheduling priority of a local CLI process
+///
+/// Applied right after the process starts: `renice` (and `ionice` on Linux)
+/// on Unix, the process priority class on Windows. Lowering priority always
+/// works; raising it above normal usually needs elevated privileges, and a
+/// priority that cannot be applied is logged and otherwise ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Priority {
+    /// Leave the CLI at the default priority
+    Normal,
+    /// Below normal: nice 10, best-effort I/O at the lowest level
+    Low,
+    /// Only run when the machine is otherwise idle: nice 19, idle I/O class
+    Idle,
+    /// Explicit Unix nice value (-20 to 19); mapped to the nearest priority
+    /// class on Windows
+    Nice(i8),
+}
+
+impl Priority {
+    /// Unix nice value for this priority
+    #[must_use]
+    pub fn nice(self) -> i8 {
+        match self {
+            Self::Normal => 0,
+            Self::Low => 10,
+            Self::Idle => 19,
+            Self::Nice(nice) => nice.clamp(-20, 19),
+        }
+    }
+}
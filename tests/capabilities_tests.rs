@@ -0,0 +1,42 @@
+//! Capabilities tests - mirrors src/capabilities.rs
+
+use kodegen_claude_agent::capabilities;
+use kodegen_claude_agent::control::ProtocolHandler;
+
+#[test]
+fn test_capabilities_report_compiled_features() {
+    let caps = capabilities();
+
+    assert_eq!(caps.version, kodegen_claude_agent::VERSION);
+    assert_eq!(caps.has_feature("client"), cfg!(feature = "client"));
+    assert_eq!(caps.has_feature("http"), cfg!(feature = "http"));
+    assert_eq!(caps.has_feature("server"), cfg!(feature = "server"));
+    assert_eq!(caps.has_feature("blocking"), cfg!(feature = "blocking"));
+    assert_eq!(caps.has_feature("toml"), cfg!(feature = "toml"));
+    assert!(!caps.has_feature("otel"));
+    assert_eq!(caps.tool_actions.is_empty(), !cfg!(feature = "tools"));
+}
+
+#[test]
+fn test_capabilities_match_protocol_and_limits() {
+    let caps = capabilities();
+
+    let init = ProtocolHandler::new().create_init_request();
+    assert_eq!(caps.protocol_versions, [init.protocol_version.as_str()]);
+    for method in ["interrupt", "set_permission_mode", "set_model"] {
+        assert!(caps.control_methods.contains(&method), "missing {method}");
+    }
+    assert_eq!(caps.limits.max_turns, 1000);
+    assert!(caps.limits.default_max_buffer_size > 0);
+}
+
+#[test]
+fn test_capabilities_serialize() {
+    let caps = capabilities();
+    let json = serde_json::to_value(&caps).unwrap();
+
+    assert_eq!(json["version"], kodegen_claude_agent::VERSION);
+    assert!(json["features"].is_array());
+    assert_eq!(json["limits"]["max_turns"], 1000);
+    assert!(caps.to_string().contains(kodegen_claude_agent::VERSION));
+}
@@ -4,9 +4,1190 @@
 
 use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient};
 
+#[cfg(unix)]
+use crate::common::Fixture;
+
 #[tokio::test]
 async fn test_client_creation() {
     let options = ClaudeAgentOptions::default();
     let result = ClaudeSDKClient::new(options, None).await;
     assert!(result.is_ok() || result.is_err()); // Will succeed if CLI is available
 }
+
+#[cfg(not(feature = "http"))]
+#[tokio::test]
+async fn test_http_transport_requires_feature() {
+    use kodegen_claude_agent::{ClaudeError, HttpTransportConfig, TransportConfig};
+
+    let options = ClaudeAgentOptions::builder()
+        .transport(TransportConfig::Http(HttpTransportConfig::new(
+            "http://127.0.0.1:1",
+        )))
+        .build();
+    let result = ClaudeSDKClient::new(options, None).await;
+    assert!(matches!(result, Err(ClaudeError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_close_releases_transport() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use kodegen_claude_agent::Transport;
+    use tokio::sync::mpsc;
+
+    /// Transport whose message stream never ends and which reports its drop
+    struct IdleTransport {
+        dropped: Arc<AtomicBool>,
+        message_tx: Option<mpsc::UnboundedSender<kodegen_claude_agent::Result<serde_json::Value>>>,
+    }
+
+    impl Transport for IdleTransport {
+        async fn connect(&mut self) -> kodegen_claude_agent::Result<()> {
+            Ok(())
+        }
+
+        async fn write(&mut self, _data: &str) -> kodegen_claude_agent::Result<()> {
+            Ok(())
+        }
+
+        async fn end_input(&mut self) -> kodegen_claude_agent::Result<()> {
+            Ok(())
+        }
+
+        fn read_messages(
+            &mut self,
+        ) -> mpsc::UnboundedReceiver<kodegen_claude_agent::Result<serde_json::Value>> {
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.message_tx = Some(tx);
+            rx
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        async fn close(&mut self) -> kodegen_claude_agent::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Drop for IdleTransport {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let transport = IdleTransport {
+        dropped: Arc::clone(&dropped),
+        message_tx: None,
+    };
+    let options = ClaudeAgentOptions::builder()
+        .health_check_interval(std::time::Duration::from_secs(60))
+        .build();
+
+    let mut client = ClaudeSDKClient::with_transport(options, transport)
+        .await
+        .unwrap();
+    tokio::task::yield_now().await;
+
+    client.close().await.unwrap();
+    client.close().await.unwrap();
+    drop(client);
+
+    // No background task may still hold the transport
+    assert!(dropped.load(Ordering::SeqCst));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_close_stops_cli_process() {
+    use kodegen_claude_agent::Message;
+
+    // Fake CLI: report its pid, then wait for stdin to close
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "echo \"{\\\"type\\\":\\\"system\\\",\\\"subtype\\\":\\\"init\\\",\\\"pid\\\":$$}\"\nwhile read -r line; do :; done\n",
+    );
+
+    let mut client = ClaudeSDKClient::new(ClaudeAgentOptions::default(), Some(cli))
+        .await
+        .unwrap();
+    let pid = match client.next_message().await.unwrap().unwrap() {
+        Message::System { data, .. } => data["pid"].as_u64().unwrap(),
+        other => panic!("unexpected message: {other:?}"),
+    };
+    let proc_path = std::path::PathBuf::from(format!("/proc/{pid}"));
+    assert!(proc_path.exists());
+
+    client.close().await.unwrap();
+
+    assert!(
+        !proc_path.exists(),
+        "CLI process {pid} still exists after close"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_transport_metrics() {
+    use kodegen_claude_agent::Message;
+
+    // Fake CLI: answer the first line with a result
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "read -r line\necho \"$RESULT\"\nwhile read -r line; do :; done\n",
+    );
+
+    let mut client = ClaudeSDKClient::new(ClaudeAgentOptions::default(), Some(cli))
+        .await
+        .unwrap();
+    let idle = client.transport_metrics().unwrap();
+    assert_eq!(idle.messages_written, 0);
+    assert!(idle.last_activity.is_none());
+
+    client.send_message("Hello").await.unwrap();
+    match client.next_message().await.unwrap().unwrap() {
+        Message::Result { .. } => {}
+        other => panic!("unexpected message: {other:?}"),
+    }
+
+    let metrics = client.transport_metrics().unwrap();
+    assert_eq!(metrics.messages_written, 1);
+    assert!(metrics.bytes_written > "Hello".len() as u64);
+    assert_eq!(metrics.messages_read, 1);
+    assert!(metrics.bytes_read > 0);
+    assert!(metrics.last_activity.is_some());
+    assert!(metrics.last_response_latency.is_some());
+    assert_eq!(client.metrics_recorder().unwrap().snapshot(), metrics);
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sdk_mcp_server_answers_cli_messages() {
+    use kodegen_claude_agent::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
+    use kodegen_claude_agent::transport::mock::MockTransport;
+    use serde_json::json;
+
+    let server = SdkMcpServer::new("greeter").tool(SdkMcpTool::new(
+        "greet",
+        "Greet someone",
+        json!({"type": "object"}),
+        |input| {
+            Box::pin(async move {
+                Ok(ToolResult::text(format!(
+                    "Hello, {}!",
+                    input["name"].as_str().unwrap_or_default()
+                )))
+            })
+        },
+    ));
+    let options = ClaudeAgentOptions::builder().sdk_mcp_server(server).build();
+    assert!(options.sdk_mcp_servers().contains_key("greeter"));
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(options, transport)
+        .await
+        .unwrap();
+
+    handle.push(json!({
+        "type": "response",
+        "status": "mcp_message",
+        "id": "mcp-1",
+        "server_name": "greeter",
+        "message": {
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": {"name": "greet", "arguments": {"name": "Ada"}}
+        }
+    }));
+
+    let mut written = Vec::new();
+    for _ in 0..100 {
+        written = handle.written_json();
+        if !written.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0]["method"], "mcp_response");
+    assert_eq!(written[0]["params"]["request_id"], "mcp-1");
+    let response = &written[0]["params"]["response"];
+    assert_eq!(response["id"], 7);
+    assert_eq!(response["result"]["content"][0]["text"], "Hello, Ada!");
+
+    client.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_receive_response_collects_one_turn() {
+    use kodegen_claude_agent::{ClaudeError, ContentBlock, Message};
+
+    // Fake CLI: answer one line with a system message, two assistant
+    // messages and a result, then exit
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "read -r line\n\
+         echo '{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"s1\"}'\n\
+         echo '{\"type\":\"assistant\",\"message\":{\"model\":\"m\",\"content\":[{\"type\":\"text\",\"text\":\"Hello\"}]}}'\n\
+         echo '{\"type\":\"assistant\",\"message\":{\"model\":\"m\",\"content\":[{\"type\":\"tool_use\",\"id\":\"t1\",\"name\":\"Read\",\"input\":{}},{\"type\":\"text\",\"text\":\", world\"}]}}'\n\
+         echo '{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"s1\",\"usage\":{\"output_tokens\":5}}'\n",
+    );
+
+    let mut client = ClaudeSDKClient::new(ClaudeAgentOptions::default(), Some(cli))
+        .await
+        .unwrap();
+    client.send_message("Hi").await.unwrap();
+
+    let outcome = client.receive_response().await.unwrap();
+    assert_eq!(outcome.assistant_blocks.len(), 3);
+    assert!(matches!(
+        &outcome.assistant_blocks[1],
+        ContentBlock::ToolUse { name, .. } if name == "Read"
+    ));
+    assert_eq!(outcome.text(), "Hello, world");
+    assert!(!outcome.is_error());
+    assert!(matches!(
+        outcome.result,
+        Message::Result { num_turns: 1, .. }
+    ));
+    assert_eq!(outcome.usage.unwrap()["output_tokens"], 5);
+
+    // The CLI has exited: no further result will arrive
+    let next = client.receive_response().await;
+    assert!(matches!(next, Err(ClaudeError::Transport(_))), "{next:?}");
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_send_content_writes_block_message() {
+    use kodegen_claude_agent::transport::mock::MockTransport;
+    use kodegen_claude_agent::{ClaudeError, ContentBlock, ContentValue};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .send_content(vec![
+            ContentBlock::ToolResult {
+                tool_use_id: "toolu_1".to_string(),
+                content: Some(ContentValue::String("42".to_string())),
+                is_error: None,
+            },
+            ContentBlock::Text {
+                text: "Now explain the result".to_string(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let written = handle.written_json();
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0]["type"], "user");
+    assert_eq!(written[0]["message"]["role"], "user");
+    let content = &written[0]["message"]["content"];
+    assert_eq!(content[0]["type"], "tool_result");
+    assert_eq!(content[0]["tool_use_id"], "toolu_1");
+    assert_eq!(content[0]["content"], "42");
+    assert_eq!(content[1]["type"], "text");
+    assert_eq!(content[1]["text"], "Now explain the result");
+
+    let empty = client.send_content(Vec::new()).await;
+    assert!(matches!(empty, Err(ClaudeError::InvalidConfig(_))));
+    assert_eq!(handle.written_json().len(), 1);
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_send_tool_result() {
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .send_tool_result("toolu_1", "file not found", true)
+        .await
+        .unwrap();
+    client
+        .sender()
+        .send_tool_result("toolu_2", "42", false)
+        .await
+        .unwrap();
+
+    let written = handle.written_json();
+    assert_eq!(written.len(), 2);
+    assert_eq!(written[0]["type"], "user");
+    let block = &written[0]["message"]["content"][0];
+    assert_eq!(block["type"], "tool_result");
+    assert_eq!(block["tool_use_id"], "toolu_1");
+    assert_eq!(block["content"], "file not found");
+    assert_eq!(block["is_error"], true);
+    let block = &written[1]["message"]["content"][0];
+    assert_eq!(block["tool_use_id"], "toolu_2");
+    assert_eq!(block["content"], "42");
+    assert_eq!(block["is_error"], false);
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_send_message_with_images() {
+    use base64::Engine;
+    use kodegen_claude_agent::transport::mock::MockTransport;
+    use kodegen_claude_agent::{ClaudeError, ContentBlock, ImageSource};
+
+    let dir = tempfile::tempdir().unwrap();
+    let screenshot = dir.path().join("screenshot.PNG");
+    std::fs::write(&screenshot, b"\x89PNG fake image").unwrap();
+    let notes = dir.path().join("notes.txt");
+    std::fs::write(&notes, "not an image").unwrap();
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .send_message_with_images("What is wrong in this screenshot?", &[&screenshot])
+        .await
+        .unwrap();
+
+    let written = handle.written_json();
+    assert_eq!(written.len(), 1);
+    let content = &written[0]["message"]["content"];
+    assert_eq!(content[0]["type"], "image");
+    assert_eq!(content[0]["source"]["type"], "base64");
+    assert_eq!(content[0]["source"]["media_type"], "image/png");
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(content[0]["source"]["data"].as_str().unwrap())
+        .unwrap();
+    assert_eq!(data, b"\x89PNG fake image");
+    assert_eq!(content[1]["type"], "text");
+    assert_eq!(content[1]["text"], "What is wrong in this screenshot?");
+
+    // Image blocks round-trip through the message types
+    let block: ContentBlock = serde_json::from_value(content[0].clone()).unwrap();
+    assert!(matches!(
+        block,
+        ContentBlock::Image {
+            source: ImageSource::Base64 { ref media_type, .. }
+        } if media_type == "image/png"
+    ));
+
+    let unsupported = client
+        .send_message_with_images("Read this", &[&notes])
+        .await;
+    assert!(matches!(unsupported, Err(ClaudeError::InvalidConfig(_))));
+    let missing = client
+        .send_message_with_images("Read this", &[dir.path().join("missing.png")])
+        .await;
+    assert!(matches!(missing, Err(ClaudeError::Io(_))));
+    assert_eq!(handle.written_json().len(), 1);
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_permission_mode_sends_control_request() {
+    use kodegen_claude_agent::transport::mock::MockTransport;
+    use kodegen_claude_agent::types::permissions::PermissionMode;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .set_permission_mode(PermissionMode::AcceptEdits)
+        .await
+        .unwrap();
+
+    // Control requests are written by the client's writer task
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(2);
+    let request = loop {
+        if let Some(request) = handle
+            .written_json()
+            .into_iter()
+            .find(|message| message["method"] == "set_permission_mode")
+        {
+            break request;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "request not written"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+    assert_eq!(request["type"], "request");
+    assert_eq!(request["params"]["mode"], "acceptEdits");
+    assert!(request["params"]["id"].is_string());
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_set_model_sends_control_request() {
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client.set_model("claude-haiku-4-5").await.unwrap();
+
+    // Control requests are written by the client's writer task
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(2);
+    let request = loop {
+        if let Some(request) = handle
+            .written_json()
+            .into_iter()
+            .find(|message| message["method"] == "set_model")
+        {
+            break request;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "request not written"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+    assert_eq!(request["type"], "request");
+    assert_eq!(request["params"]["model"], "claude-haiku-4-5");
+    assert!(request["params"]["id"].is_string());
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_for_result_interrupts_on_timeout() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::ClaudeError;
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    // The CLI answers the interrupt with the end of the interrupted turn
+    let transport =
+        MockTransport::new().reply(vec![mock::assistant_text("partial"), mock::result("s1", 1)]);
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    let err = client
+        .wait_for_result(Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::Timeout(_)), "{err:?}");
+
+    // The interrupt is written by the client's writer task
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    while !handle
+        .written_json()
+        .iter()
+        .any(|request| request["method"] == "interrupt")
+    {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "interrupt not written"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // The interrupted turn's result was consumed; the next read gets the next turn
+    handle.push(mock::assistant_text("answer"));
+    handle.push(mock::result("s1", 2));
+    let outcome = client
+        .wait_for_result(Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(outcome.text(), "answer");
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_session_id_is_captured_from_init() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    assert_eq!(client.session_id(), None);
+
+    handle.push(serde_json::json!({
+        "type": "system",
+        "subtype": "init",
+        "session_id": "session-1"
+    }));
+    client.next_message().await.unwrap().unwrap();
+    assert_eq!(client.session_id().unwrap().as_str(), "session-1");
+
+    // A forked session reports its new ID with the turn's result
+    handle.push(mock::result("session-2", 1));
+    client
+        .wait_for_result(Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(client.session_id().unwrap().as_str(), "session-2");
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_usage_accumulates_over_results() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    assert_eq!(client.usage().results, 0);
+
+    for (cost, input, cached) in [(0.01, 100, 0), (0.03, 20, 400)] {
+        let mut result = mock::result("s1", 1);
+        result["total_cost_usd"] = serde_json::json!(cost);
+        result["usage"] = serde_json::json!({
+            "input_tokens": input,
+            "output_tokens": 10,
+            "cache_read_input_tokens": cached,
+        });
+        handle.push(result);
+        client
+            .wait_for_result(Duration::from_secs(2))
+            .await
+            .unwrap();
+    }
+
+    let usage = client.usage();
+    assert_eq!(usage.results, 2);
+    assert!((usage.total_cost_usd - 0.03).abs() < f64::EPSILON);
+    assert_eq!(usage.input_tokens, 120);
+    assert_eq!(usage.output_tokens, 20);
+    assert_eq!(usage.cache_read_input_tokens, 400);
+    assert_eq!(usage.total_tokens(), 540);
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_deltas_ends_with_turn() {
+    use futures::StreamExt;
+
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+    use kodegen_claude_agent::{Delta, TextDelta};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    for text in ["Hel", "lo"] {
+        handle.push(serde_json::json!({
+            "type": "stream_event",
+            "uuid": "u1",
+            "session_id": "s1",
+            "event": {
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": {"type": "text_delta", "text": text}
+            }
+        }));
+    }
+    handle.push(mock::assistant_text("Hello"));
+    handle.push(mock::result("s1", 1));
+    handle.push(mock::assistant_text("next turn"));
+
+    let deltas: Vec<Delta> = client.stream_deltas().map(Result::unwrap).collect().await;
+    assert_eq!(
+        deltas,
+        [
+            Delta::Text(TextDelta {
+                index: 0,
+                text: "Hel".to_string()
+            }),
+            Delta::Text(TextDelta {
+                index: 0,
+                text: "lo".to_string()
+            }),
+        ]
+    );
+
+    // The next turn's messages are left in the stream
+    assert!(client.next_message().await.unwrap().is_ok());
+
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_hooks_added_and_removed_on_live_client() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::hooks::{HookManager, HookMatcherBuilder};
+    use kodegen_claude_agent::transport::mock::{MockHandle, MockTransport};
+    use kodegen_claude_agent::{HookDecision, HookEvent, HookOutput};
+
+    /// Send a PreToolUse hook for Bash and return the SDK's response
+    async fn pre_tool_use(handle: &MockHandle, hook_id: &str) -> serde_json::Value {
+        handle.push(serde_json::json!({
+            "type": "response",
+            "status": "hook",
+            "id": hook_id,
+            "event": "PreToolUse",
+            "event_data": {"tool_name": "Bash"}
+        }));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        loop {
+            if let Some(request) = handle
+                .written_json()
+                .into_iter()
+                .find(|request| request["params"]["hook_id"] == hook_id)
+            {
+                return request["params"]["response"].clone();
+            }
+            assert!(tokio::time::Instant::now() < deadline, "no hook response");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    let block = HookManager::callback(|_event_data, _tool_name, _context| async {
+        Ok(HookOutput {
+            decision: Some(HookDecision::Block),
+            ..Default::default()
+        })
+    });
+    let id = client
+        .add_hook(
+            HookEvent::PreToolUse,
+            HookMatcherBuilder::new(Some("Bash"))
+                .add_hook(block)
+                .build(),
+        )
+        .await;
+    assert_eq!(pre_tool_use(&handle, "h1").await["decision"], "block");
+
+    assert!(client.remove_hook(id).await);
+    assert!(pre_tool_use(&handle, "h2").await.get("decision").is_none());
+    assert!(!client.remove_hook(id).await);
+}
+
+#[tokio::test]
+async fn test_permission_callback_swapped_mid_session() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::permissions::PermissionManager;
+    use kodegen_claude_agent::transport::mock::{MockHandle, MockTransport};
+    use kodegen_claude_agent::{PermissionResult, PermissionResultAllow, PermissionResultDeny};
+
+    /// Ask permission to run Bash and return the SDK's result
+    async fn ask(handle: &MockHandle, id: &str) -> serde_json::Value {
+        handle.push(serde_json::json!({
+            "type": "response",
+            "status": "permission",
+            "id": id,
+            "request": {
+                "tool_name": "Bash",
+                "tool_input": {"command": "ls"},
+                "context": {"suggestions": []}
+            }
+        }));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        loop {
+            if let Some(request) = handle
+                .written_json()
+                .into_iter()
+                .find(|request| request["params"]["request_id"] == id)
+            {
+                return request["params"]["result"].clone();
+            }
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "no permission response"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    let allow = PermissionManager::callback(|_tool_name, _tool_input, _context| async {
+        Ok(PermissionResult::Allow(PermissionResultAllow {
+            updated_input: None,
+            updated_permissions: None,
+        }))
+    });
+    let deny = PermissionManager::callback(|_tool_name, _tool_input, _context| async {
+        Ok(PermissionResult::Deny(PermissionResultDeny {
+            message: "User is away".to_string(),
+            interrupt: false,
+        }))
+    });
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let options = ClaudeAgentOptions::builder().can_use_tool(allow).build();
+    let client = ClaudeSDKClient::with_transport(options, transport)
+        .await
+        .unwrap();
+    assert_eq!(ask(&handle, "p1").await["type"], "allow");
+
+    client.set_can_use_tool(deny).await;
+    let result = ask(&handle, "p2").await;
+    assert_eq!(result["type"], "deny");
+    assert_eq!(result["message"], "User is away");
+}
+
+#[tokio::test]
+async fn test_lifecycle_events_report_exit_and_close() {
+    use kodegen_claude_agent::ClaudeError;
+    use kodegen_claude_agent::client::LifecycleEvent;
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    let mut events = client.lifecycle_events();
+    assert_eq!(events.recv().await, Some(LifecycleEvent::Connected));
+
+    handle.push_error(ClaudeError::process("Command failed", 3, None));
+    assert_eq!(
+        events.recv().await,
+        Some(LifecycleEvent::ProcessExited { code: Some(3) })
+    );
+    assert!(client.next_message().await.unwrap().is_err());
+
+    client.close().await.unwrap();
+    assert_eq!(events.recv().await, Some(LifecycleEvent::Closed));
+
+    // A late subscription replays everything
+    let mut late = client.lifecycle_events();
+    assert_eq!(late.recv().await, Some(LifecycleEvent::Connected));
+    drop(client);
+    assert!(matches!(
+        late.recv().await,
+        Some(LifecycleEvent::ProcessExited { .. })
+    ));
+    assert_eq!(late.recv().await, Some(LifecycleEvent::Closed));
+    assert_eq!(late.recv().await, None);
+}
+
+#[tokio::test]
+async fn test_lifecycle_event_when_output_ends() {
+    use kodegen_claude_agent::client::LifecycleEvent;
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    let mut events = client.lifecycle_events();
+    // Wait for the reader to start before ending the stream
+    handle.push(mock::assistant_text("Bye"));
+    client.next_message().await.unwrap().unwrap();
+    handle.finish();
+
+    assert_eq!(events.recv().await, Some(LifecycleEvent::Connected));
+    assert_eq!(
+        events.recv().await,
+        Some(LifecycleEvent::ProcessExited { code: None })
+    );
+}
+
+#[tokio::test]
+async fn test_history_keeps_latest_messages() {
+    use kodegen_claude_agent::Message;
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new()
+        .reply(vec![mock::assistant_text("One"), mock::result("s1", 1)])
+        .reply(vec![mock::assistant_text("Two"), mock::result("s1", 2)]);
+    let options = ClaudeAgentOptions::builder().history_limit(4).build();
+    let mut client = ClaudeSDKClient::with_transport(options, transport)
+        .await
+        .unwrap();
+    assert!(client.history().is_empty());
+
+    client.send_message("First").await.unwrap();
+    client.receive_response().await.unwrap();
+    let history = client.history();
+    assert_eq!(history.len(), 3);
+    assert!(matches!(history[0], Message::User { .. }));
+    assert!(matches!(history[2], Message::Result { .. }));
+
+    client.send_message("Second").await.unwrap();
+    client.receive_response().await.unwrap();
+    let history = client.history();
+    assert_eq!(history.len(), 4);
+    // The first user message and answer were dropped
+    assert!(matches!(history[0], Message::Result { num_turns: 1, .. }));
+    assert!(matches!(history[3], Message::Result { num_turns: 2, .. }));
+}
+
+#[tokio::test]
+async fn test_history_disabled_by_default() {
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport =
+        MockTransport::new().reply(vec![mock::assistant_text("Hi"), mock::result("s1", 1)]);
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Hello").await.unwrap();
+    client.receive_response().await.unwrap();
+    assert!(client.history().is_empty());
+}
+
+#[tokio::test]
+async fn test_compact_and_rewind_send_control_requests() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::ClaudeError;
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client.compact().await.unwrap();
+    client.rewind(2).await.unwrap();
+    assert!(matches!(
+        client.rewind(0).await,
+        Err(ClaudeError::InvalidConfig(_))
+    ));
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    let written = loop {
+        let written = handle.written_json();
+        if written.len() == 2 {
+            break written;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "requests not written"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(written[0]["method"], "compact");
+    assert_eq!(written[1]["method"], "rewind");
+    assert_eq!(written[1]["params"]["turns"], 2);
+}
+
+#[tokio::test]
+async fn test_update_permissions_sends_control_request() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::mock::MockTransport;
+    use kodegen_claude_agent::types::permissions::{
+        PermissionBehavior, PermissionRuleValue, PermissionUpdateDestination,
+    };
+    use kodegen_claude_agent::{ClaudeError, PermissionUpdate};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        client.update_permissions(vec![]).await,
+        Err(ClaudeError::InvalidConfig(_))
+    ));
+    client
+        .update_permissions(vec![PermissionUpdate::AddRules {
+            rules: Some(vec![PermissionRuleValue {
+                tool_name: "Bash".to_string(),
+                rule_content: Some("git status".to_string()),
+            }]),
+            behavior: Some(PermissionBehavior::Allow),
+            destination: Some(PermissionUpdateDestination::Session),
+        }])
+        .await
+        .unwrap();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    let written = loop {
+        let written = handle.written_json();
+        if !written.is_empty() {
+            break written;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "request not written"
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    assert_eq!(written[0]["method"], "update_permissions");
+    let update = &written[0]["params"]["updates"][0];
+    assert_eq!(update["type"], "addRules");
+    assert_eq!(update["rules"][0]["toolName"], "Bash");
+    assert_eq!(update["destination"], "session");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_fork_resumes_session_with_fork_flag() {
+    use std::time::Duration;
+
+    let fixture = Fixture::new();
+    let args = fixture.path("args");
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "echo \"$@\" >> {}\necho '{{\"type\":\"system\",\"subtype\":\"init\",\"session_id\":\"s1\"}}'\nwhile read -r line; do :; done\n",
+            args.display()
+        ),
+    );
+
+    let options = ClaudeAgentOptions::builder().model("sonnet").build();
+    let mut client = ClaudeSDKClient::new(options, Some(cli)).await.unwrap();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while client.session_id().is_none() {
+        assert!(tokio::time::Instant::now() < deadline, "no session ID");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut fork = client.fork().await.unwrap();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    let invocations = loop {
+        let invocations = std::fs::read_to_string(&args).unwrap_or_default();
+        if invocations.lines().count() == 2 {
+            break invocations;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "fork not started");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    };
+    let forked = invocations.lines().nth(1).unwrap();
+    assert!(forked.contains("--resume s1"), "{forked}");
+    assert!(forked.contains("--fork-session"), "{forked}");
+    assert!(forked.contains("--model sonnet"), "{forked}");
+    assert!(
+        !invocations
+            .lines()
+            .next()
+            .unwrap()
+            .contains("--fork-session")
+    );
+
+    fork.close().await.unwrap();
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fork_needs_session_id() {
+    use kodegen_claude_agent::ClaudeError;
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let client =
+        ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), MockTransport::new())
+            .await
+            .unwrap();
+    assert!(matches!(
+        client.fork().await,
+        Err(ClaudeError::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_split_sends_from_other_tasks() {
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new()
+        .reply(vec![mock::assistant_text("One"), mock::result("s1", 1)])
+        .reply(vec![mock::assistant_text("Two"), mock::result("s1", 2)]);
+    let handle = transport.handle();
+    let client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    let (sender, mut receiver) = client.split();
+
+    let first = sender.clone();
+    tokio::spawn(async move { first.send_message("First").await })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receiver.receive_response().await.unwrap().text(), "One");
+
+    tokio::spawn(async move { sender.send_message("Second").await })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receiver.receive_response().await.unwrap().text(), "Two");
+    assert_eq!(handle.written_json()[1]["message"]["content"], "Second");
+
+    let mut client = receiver.into_inner();
+    client.close().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_split_sender_fails_after_close() {
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let client =
+        ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), MockTransport::new())
+            .await
+            .unwrap();
+    let (sender, mut receiver) = client.split();
+    receiver.close().await.unwrap();
+    assert!(sender.send_message("Hello").await.is_err());
+    assert!(sender.interrupt().await.is_err());
+}
+
+#[tokio::test]
+async fn test_deadline_interrupts_runaway_turn() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::ClaudeError;
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .send_message_with_deadline("Loop forever", Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert!(matches!(
+        client.next_message().await,
+        Some(Err(ClaudeError::Timeout(_)))
+    ));
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+    while !handle
+        .written_json()
+        .iter()
+        .any(|request| request["method"] == "interrupt")
+    {
+        assert!(tokio::time::Instant::now() < deadline, "no interrupt");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_deadline_disarmed_by_result() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport =
+        MockTransport::new().reply(vec![mock::assistant_text("Done"), mock::result("s1", 1)]);
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+
+    client
+        .send_message_with_deadline("Quick", Duration::from_millis(50))
+        .await
+        .unwrap();
+    assert_eq!(client.receive_response().await.unwrap().text(), "Done");
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(handle.written_json().len(), 1);
+    let next = tokio::time::timeout(Duration::from_millis(20), client.next_message()).await;
+    assert!(next.is_err(), "unexpected message: {next:?}");
+}
+
+#[tokio::test]
+async fn test_close_gracefully_waits_for_result() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::Message;
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Write the report").await.unwrap();
+
+    let sender = client.sender();
+    let finisher = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // No new messages once closing has begun
+        let refused = sender.send_message("One more thing").await.is_err();
+        handle.push(mock::assistant_text("Report written"));
+        handle.push(mock::result("s1", 1));
+        (refused, handle)
+    });
+
+    client
+        .close_gracefully(Duration::from_secs(5))
+        .await
+        .unwrap();
+    let (refused, handle) = finisher.await.unwrap();
+    assert!(refused);
+    assert!(handle.is_closed());
+    assert_eq!(handle.written_json().len(), 1);
+
+    // The turn's output is still readable after closing
+    let mut messages = Vec::new();
+    while let Some(message) = client.next_message().await {
+        messages.push(message.unwrap());
+    }
+    assert!(matches!(messages.last(), Some(Message::Result { .. })));
+}
+
+#[tokio::test]
+async fn test_close_gracefully_times_out() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::mock::MockTransport;
+
+    let transport = MockTransport::new();
+    let handle = transport.handle();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Loop forever").await.unwrap();
+
+    tokio::time::timeout(
+        Duration::from_secs(2),
+        client.close_gracefully(Duration::from_millis(50)),
+    )
+    .await
+    .expect("close_gracefully ignored its timeout")
+    .unwrap();
+    assert!(handle.is_closed());
+}
@@ -1,3 +1,4 @@
 //! Client tests - mirrors src/client/
 
+mod common;
 mod client;
@@ -0,0 +1,49 @@
+//! Helpers shared by the integration test crates
+//!
+//! Each crate that needs them declares `mod common;` and uses a subset, so
+//! unused items are expected.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+/// A successful `result` line for session `s1`, as the CLI writes it
+pub const RESULT: &str = r#"{"type":"result","subtype":"success","duration_ms":1,"duration_api_ms":1,"is_error":false,"num_turns":1,"session_id":"s1"}"#;
+
+/// Fake CLIs and container runtimes standing in for the real CLI
+///
+/// Scripts are written to a temporary directory that is removed when the
+/// fixture is dropped, so keep it alive while sessions use them.
+pub struct Fixture {
+    dir: tempfile::TempDir,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Self {
+            dir: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    /// The fixture's directory
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Path of `name` in the fixture's directory
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.path().join(name)
+    }
+
+    /// Write the executable `/bin/sh` script `name` running `body`
+    ///
+    /// `body` can echo `"$RESULT"` for a [`RESULT`] line.
+    #[cfg(unix)]
+    pub fn script(&self, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = self.path(name);
+        std::fs::write(&script, format!("#!/bin/sh\nRESULT='{RESULT}'\n{body}")).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+}
@@ -0,0 +1,3 @@
+//! Context module tests
+
+pub mod test_context_pack;
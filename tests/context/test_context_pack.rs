@@ -0,0 +1,130 @@
+//! Unit tests for `ContextPack`
+//!
+//! Tests file selection, budgets and rendering
+
+use kodegen_claude_agent::context::ContextPack;
+use std::fs;
+
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+    fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("src/lib.rs"), "pub mod nested;\n").unwrap();
+    fs::write(dir.path().join("src/nested/mod.rs"), "pub fn f() {}\n").unwrap();
+    fs::write(dir.path().join("src/notes.md"), "# Notes\n").unwrap();
+    fs::write(dir.path().join("src/blob.rs"), [0u8, 1, 2, 3]).unwrap();
+    fs::write(dir.path().join("target/debug/out.rs"), "ignored\n").unwrap();
+    dir
+}
+
+#[test]
+fn test_globs_select_files() {
+    let dir = fixture();
+    let pack = ContextPack::builder(dir.path())
+        .include("src/**/*.rs")
+        .include("Cargo.toml")
+        .build()
+        .unwrap();
+
+    let paths: Vec<&str> = pack.files.iter().map(|f| f.path.as_str()).collect();
+    // Binary files are skipped; target/ is never walked
+    assert_eq!(paths, vec!["Cargo.toml", "src/lib.rs", "src/nested/mod.rs"]);
+    assert!(pack.omitted.is_empty());
+
+    let rendered = pack.render();
+    assert!(rendered.contains("## File tree"));
+    assert!(rendered.contains("### src/nested/mod.rs"));
+    assert!(!rendered.contains("notes.md"));
+}
+
+#[test]
+fn test_basename_patterns_and_exclude() {
+    let dir = fixture();
+    let pack = ContextPack::builder(dir.path())
+        .include("*.rs")
+        .exclude("src/nested/**")
+        .include_tree(false)
+        .build()
+        .unwrap();
+
+    let paths: Vec<&str> = pack.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["src/lib.rs"]);
+    assert!(pack.tree.is_none());
+}
+
+#[test]
+fn test_budgets_truncate_and_omit() {
+    let dir = tempfile::tempdir().unwrap();
+    let big: String = (0..400).map(|i| format!("line {i}\n")).collect();
+    fs::write(dir.path().join("a.txt"), &big).unwrap();
+    fs::write(dir.path().join("b.txt"), &big).unwrap();
+
+    let pack = ContextPack::builder(dir.path())
+        .max_file_bytes(1024)
+        .max_tokens(400)
+        .include_tree(false)
+        .build()
+        .unwrap();
+
+    assert_eq!(pack.files.len(), 1);
+    let file = &pack.files[0];
+    assert!(file.truncated);
+    assert!(file.content.len() <= 1024);
+    assert!(file.content.ends_with('\n'));
+    assert_eq!(pack.omitted, vec!["b.txt".to_string()]);
+    assert!(pack.render().contains("[truncated: showing"));
+}
+
+#[test]
+fn test_prepend_and_write_to_dir() {
+    let dir = fixture();
+    let pack = ContextPack::builder(dir.path())
+        .include("Cargo.toml")
+        .build()
+        .unwrap();
+
+    let prompt = pack.prepend_to("Summarize the crate.");
+    assert!(prompt.starts_with("# Repository context"));
+    assert!(prompt.ends_with("Summarize the crate."));
+
+    let out = tempfile::tempdir().unwrap();
+    let path = pack.write_to_dir(out.path()).unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), pack.render());
+}
+
+#[test]
+fn test_invalid_root() {
+    let result = ContextPack::builder("/definitely/not/a/dir").build();
+    assert!(result.is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_entries_are_omitted() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = fixture();
+    let locked_dir = dir.path().join("src/nested");
+    let locked_file = dir.path().join("src/notes.md");
+    for path in [&locked_dir, &locked_file] {
+        fs::set_permissions(path, fs::Permissions::from_mode(0o000)).unwrap();
+    }
+    // Permissions do not restrict privileged users
+    if fs::File::open(&locked_file).is_ok() {
+        return;
+    }
+
+    let pack = ContextPack::builder(dir.path()).build().unwrap();
+
+    let paths: Vec<&str> = pack.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["Cargo.toml", "src/lib.rs"]);
+    assert_eq!(pack.omitted, ["src/nested/", "src/notes.md"]);
+    assert!(pack.render().contains("- src/nested/\n"));
+
+    fs::set_permissions(&locked_dir, fs::Permissions::from_mode(0o755)).unwrap();
+}
@@ -0,0 +1,3 @@
+//! Context tests - mirrors src/context/
+
+mod context;
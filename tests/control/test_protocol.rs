@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use kodegen_claude_agent::control::protocol::{
     ClientCapabilities, ControlMessage, ControlRequest, ControlResponse, InitResponse,
     ProtocolHandler, ServerCapabilities,
@@ -6,6 +8,7 @@ use kodegen_claude_agent::{
     HookEvent, PermissionRequest, PermissionResult, PermissionResultAllow, RequestId, ToolName,
     ToolPermissionContext,
 };
+use kodegen_claude_agent::types::SequentialIdGenerator;
 
 #[test]
 fn test_request_id_generation() {
@@ -15,6 +18,18 @@ fn test_request_id_generation() {
     assert_ne!(id1, id2);
 }
 
+#[test]
+fn test_injected_id_generator() {
+    let mut handler = ProtocolHandler::new();
+    handler.set_id_generator(Arc::new(SequentialIdGenerator::with_prefix("t-")));
+
+    match handler.create_interrupt_request() {
+        ControlRequest::Interrupt { id } => assert_eq!(id.as_str(), "t-req-1"),
+        other => panic!("unexpected request: {other:?}"),
+    }
+    assert_eq!(handler.next_id().as_str(), "t-req-2");
+}
+
 #[test]
 fn test_init_request_creation() {
     let handler = ProtocolHandler::new();
@@ -257,3 +272,49 @@ fn test_get_request_id() {
     };
     assert_eq!(ProtocolHandler::get_request_id(&perm_resp).as_str(), "id4");
 }
+
+#[test]
+fn test_ping_request_serialization() {
+    let handler = ProtocolHandler::new();
+    let request = handler.create_ping_request();
+    let message = ControlMessage::Request(request);
+
+    let serialized = handler.serialize_message(&message).unwrap();
+    let value: serde_json::Value = serde_json::from_str(serialized.trim()).unwrap();
+    assert_eq!(value["type"], "request");
+    assert_eq!(value["method"], "ping");
+
+    match handler.deserialize_message(serialized.trim()).unwrap() {
+        ControlMessage::Request(ControlRequest::Ping { id }) => {
+            assert!(id.as_str().starts_with("req-"));
+        }
+        _ => panic!("Expected Ping request"),
+    }
+}
+
+#[tokio::test]
+async fn test_ping_response_and_cancel() {
+    let handler = ProtocolHandler::new();
+    handler.set_initialized(true);
+
+    // An answered ping resolves its pending receiver
+    let ping = handler.create_ping_request();
+    let id = ProtocolHandler::get_request_id(&ping);
+    let response_rx = handler.send_request(ping).await.unwrap();
+    handler
+        .handle_response(ControlResponse::Success {
+            id: id.clone(),
+            data: None,
+        })
+        .await
+        .unwrap();
+    assert!(response_rx.await.is_ok());
+    assert!(!handler.cancel_request(&id).await);
+
+    // A timed-out ping can be cancelled exactly once
+    let ping = handler.create_ping_request();
+    let id = ProtocolHandler::get_request_id(&ping);
+    let _response_rx = handler.send_request(ping).await.unwrap();
+    assert!(handler.cancel_request(&id).await);
+    assert!(!handler.cancel_request(&id).await);
+}
@@ -1,3 +1,4 @@
 //! Hooks module tests
 
+pub mod test_builtin;
 pub mod test_hooks;
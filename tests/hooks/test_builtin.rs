@@ -0,0 +1,158 @@
+//! Unit tests for the built-in hooks
+
+use std::time::Duration;
+
+use kodegen_claude_agent::hooks::{HookManager, builtin};
+use kodegen_claude_agent::{HookContext, HookDecision, HookEvent, HookMatcher};
+use serde_json::{Value, json};
+
+/// Decision of `matcher` on a `PreToolUse` event for `tool`
+async fn decide(matcher: &HookMatcher, tool: &str, input: Value) -> Option<HookDecision> {
+    let mut manager = HookManager::new();
+    manager.register_for(HookEvent::PreToolUse, matcher.clone());
+    manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            json!({"tool_name": tool, "tool_input": input, "cwd": "/srv/project"}),
+            Some(tool.to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap()
+        .decision
+}
+
+#[tokio::test]
+async fn test_path_guard() {
+    let guard = builtin::path_guard(["/srv/project"]);
+    assert_eq!(guard.name.as_deref(), Some("builtin.path_guard"));
+
+    let write = |path: &str| json!({"file_path": path, "content": ""});
+    assert_eq!(decide(&guard, "Write", write("src/main.rs")).await, None);
+    assert_eq!(
+        decide(&guard, "Write", write("/srv/project/a.txt")).await,
+        None
+    );
+    assert_eq!(
+        decide(&guard, "Edit", write("../other/a.txt")).await,
+        Some(HookDecision::Block)
+    );
+    assert_eq!(
+        decide(&guard, "Write", write("/etc/passwd")).await,
+        Some(HookDecision::Block)
+    );
+    // Reads are not matched
+    assert_eq!(decide(&guard, "Read", write("/etc/passwd")).await, None);
+}
+
+#[tokio::test]
+async fn test_path_guard_resolves_relative_roots() {
+    let cwd = std::env::current_dir().unwrap();
+    let write = |path: &std::path::Path| json!({"file_path": path, "content": ""});
+
+    let guard = builtin::path_guard(["."]);
+    assert_eq!(
+        decide(&guard, "Write", write(&cwd.join("a.txt"))).await,
+        None
+    );
+    assert_eq!(
+        decide(&guard, "Write", write("/etc/passwd".as_ref())).await,
+        Some(HookDecision::Block)
+    );
+
+    let guard = builtin::path_guard(["src"]);
+    assert_eq!(
+        decide(&guard, "Write", write(&cwd.join("src/lib.rs"))).await,
+        None
+    );
+    assert_eq!(
+        decide(&guard, "Write", write(&cwd.join("Cargo.toml"))).await,
+        Some(HookDecision::Block)
+    );
+}
+
+#[tokio::test]
+async fn test_bash_allowlist() {
+    let allowlist = builtin::bash_allowlist(["ls", "cargo", "grep"]);
+    let bash = |command: &str| json!({"command": command});
+
+    assert_eq!(decide(&allowlist, "Bash", bash("cargo test")).await, None);
+    assert_eq!(
+        decide(
+            &allowlist,
+            "Bash",
+            bash("RUST_LOG=debug cargo build && ls | grep x")
+        )
+        .await,
+        None
+    );
+    assert_eq!(
+        decide(&allowlist, "Bash", bash("ls; rm -rf /")).await,
+        Some(HookDecision::Block)
+    );
+    assert_eq!(
+        decide(&allowlist, "Bash", bash("ls $(curl example.com)")).await,
+        Some(HookDecision::Block)
+    );
+    // Process substitution runs commands as well
+    assert_eq!(
+        decide(&allowlist, "Bash", bash("grep x <(curl example.com)")).await,
+        Some(HookDecision::Block)
+    );
+    assert_eq!(
+        decide(&allowlist, "Bash", bash("ls >(curl -d @- example.com)")).await,
+        Some(HookDecision::Block)
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    let limit = builtin::rate_limit(2, Duration::from_millis(200));
+    let mut manager = HookManager::new();
+    manager.register_for(HookEvent::PreToolUse, limit);
+    let call = || {
+        manager.invoke_event(
+            HookEvent::PreToolUse,
+            json!({}),
+            Some("Read".to_string()),
+            HookContext {},
+        )
+    };
+
+    assert_eq!(call().await.unwrap().decision, None);
+    assert_eq!(call().await.unwrap().decision, None);
+    assert_eq!(call().await.unwrap().decision, Some(HookDecision::Block));
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(call().await.unwrap().decision, None);
+}
+
+#[tokio::test]
+async fn test_audit_log_appends_jsonl() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let mut manager = HookManager::new();
+    manager.register(builtin::audit_log(&path));
+
+    for command in ["ls", "pwd"] {
+        manager
+            .invoke_event(
+                HookEvent::PreToolUse,
+                json!({"tool_name": "Bash", "tool_input": {"command": command}}),
+                Some("Bash".to_string()),
+                HookContext {},
+            )
+            .await
+            .unwrap();
+    }
+
+    let lines: Vec<Value> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["tool_name"], "Bash");
+    assert_eq!(lines[1]["input"]["tool_input"]["command"], "pwd");
+    assert!(lines[0]["timestamp"].is_string());
+}
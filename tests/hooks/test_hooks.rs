@@ -64,3 +64,660 @@ fn test_matcher_pattern() {
         Some("Bash".to_string()).as_ref()
     ));
 }
+
+/// Decision of the hooks for `event` on a Bash call
+async fn decision(
+    manager: &HookManager,
+    event: kodegen_claude_agent::HookEvent,
+) -> Option<kodegen_claude_agent::HookDecision> {
+    manager
+        .invoke_event(
+            event,
+            serde_json::json!({}),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap()
+        .decision
+}
+
+#[tokio::test]
+async fn test_hooks_registered_for_an_event() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+
+    let block = HookManager::callback(|_event_data, _tool_name, _context| async {
+        Ok(HookOutput {
+            decision: Some(HookDecision::Block),
+            ..Default::default()
+        })
+    });
+    let mut manager = HookManager::new();
+    let id = manager.register_for(
+        HookEvent::PreToolUse,
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(block)
+            .build(),
+    );
+    assert_eq!(manager.len(), 1);
+
+    assert_eq!(
+        decision(&manager, HookEvent::PreToolUse).await,
+        Some(HookDecision::Block)
+    );
+    assert_eq!(decision(&manager, HookEvent::PostToolUse).await, None);
+
+    assert!(manager.remove(id));
+    assert!(!manager.remove(id));
+    assert!(manager.is_empty());
+    assert_eq!(decision(&manager, HookEvent::PreToolUse).await, None);
+}
+
+#[test]
+fn test_payload_parses_tool_events() {
+    use kodegen_claude_agent::{HookEvent, HookPayload};
+
+    let payload = HookPayload::parse(
+        HookEvent::PreToolUse,
+        serde_json::json!({"tool_name": "Bash", "tool_input": {"command": "ls"}}),
+    );
+    let HookPayload::PreToolUse(pre) = payload else {
+        panic!("expected PreToolUse, got {payload:?}");
+    };
+    assert_eq!(pre.tool_name, "Bash");
+    assert_eq!(pre.tool_input["command"], "ls");
+
+    let payload = HookPayload::from_value(serde_json::json!({
+        "hook_event_name": "PostToolUse",
+        "tool_name": "Read",
+        "tool_input": {},
+        "tool_response": "contents",
+    }));
+    let HookPayload::PostToolUse(post) = payload else {
+        panic!("expected PostToolUse, got {payload:?}");
+    };
+    assert_eq!(post.tool_name, "Read");
+    assert_eq!(post.tool_response, "contents");
+}
+
+#[test]
+fn test_payload_falls_back_to_raw() {
+    use kodegen_claude_agent::{HookEvent, HookPayload};
+
+    let data = serde_json::json!({"unexpected": true});
+    assert_eq!(
+        HookPayload::parse(HookEvent::PreToolUse, data.clone()),
+        HookPayload::Raw(data.clone())
+    );
+    assert_eq!(
+        HookPayload::parse(HookEvent::Stop, data.clone()),
+        HookPayload::Raw(data.clone())
+    );
+    assert_eq!(
+        HookPayload::from_value(data.clone()),
+        HookPayload::Raw(data)
+    );
+}
+
+#[tokio::test]
+async fn test_typed_callback_receives_payload() {
+    use kodegen_claude_agent::{HookDecision, HookEvent, HookPayload};
+
+    let mut manager = HookManager::new();
+    let hook = HookManager::typed_callback(|payload, _tool_name, _context| async move {
+        let blocked = matches!(
+            payload,
+            HookPayload::PreToolUse(ref pre) if pre.tool_input["command"] == "rm -rf /"
+        );
+        Ok(HookOutput {
+            decision: blocked.then_some(HookDecision::Block),
+            ..HookOutput::default()
+        })
+    });
+    manager.register(HookMatcherBuilder::new(Some("Bash")).add_hook(hook).build());
+
+    let output = manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "tool_name": "Bash",
+                "tool_input": {"command": "rm -rf /"},
+            }),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+    assert_eq!(output.decision, Some(HookDecision::Block));
+}
+
+#[tokio::test]
+async fn test_hook_timeout_uses_timeout_output() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+    use std::time::Duration;
+
+    let mut manager = HookManager::new();
+    let hung = HookManager::callback(|_event_data, _tool_name, _context| async {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(HookOutput::default())
+    });
+    manager.register(HookMatcherBuilder::new(Some("Bash")).add_hook(hung).build());
+    manager.set_timeout(Some(Duration::from_millis(50)));
+    manager.set_timeout_output(HookOutput {
+        decision: Some(HookDecision::Block),
+        system_message: Some("hook timed out".to_string()),
+        ..HookOutput::default()
+    });
+
+    let output = tokio::time::timeout(
+        Duration::from_secs(5),
+        manager.invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({}),
+            Some("Bash".to_string()),
+            HookContext {},
+        ),
+    )
+    .await
+    .expect("hook timeout not enforced")
+    .unwrap();
+    assert_eq!(output.decision, Some(HookDecision::Block));
+    assert_eq!(output.system_message.as_deref(), Some("hook timed out"));
+}
+
+#[tokio::test]
+async fn test_hooks_rewrite_tool_input() {
+    use kodegen_claude_agent::HookEvent;
+
+    let add_flag = HookManager::callback(|event_data, _tool_name, _context| async move {
+        let command = event_data["tool_input"]["command"].as_str().unwrap_or("");
+        Ok(HookOutput {
+            updated_input: Some(serde_json::json!({"command": format!("{command} --dry-run")})),
+            ..HookOutput::default()
+        })
+    });
+    let mut manager = HookManager::new();
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(add_flag.clone())
+            .add_hook(add_flag)
+            .build(),
+    );
+
+    let output = manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({"tool_name": "Bash", "tool_input": {"command": "make"}}),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+    let expected = serde_json::json!({"command": "make --dry-run --dry-run"});
+    assert_eq!(output.updated_input, Some(expected.clone()));
+
+    let response = output.to_response(HookEvent::PreToolUse);
+    assert!(response.get("updatedInput").is_none());
+    assert_eq!(
+        response["hookSpecificOutput"],
+        serde_json::json!({"hookEventName": "PreToolUse", "updatedInput": expected})
+    );
+}
+
+#[tokio::test]
+async fn test_hook_error_policy() {
+    use kodegen_claude_agent::{ClaudeError, HookDecision, HookErrorPolicy, HookEvent};
+
+    async fn run(policy: Option<HookErrorPolicy>) -> kodegen_claude_agent::Result<HookOutput> {
+        let failing = HookManager::callback(|_event_data, _tool_name, _context| async {
+            Err(ClaudeError::invalid_config("guard unavailable"))
+        });
+        let mut builder = HookMatcherBuilder::new(Some("Bash")).add_hook(failing);
+        if let Some(policy) = policy {
+            builder = builder.on_error(policy);
+        }
+        let mut manager = HookManager::new();
+        manager.register(builder.build());
+        manager
+            .invoke_event(
+                HookEvent::PreToolUse,
+                serde_json::json!({}),
+                Some("Bash".to_string()),
+                HookContext {},
+            )
+            .await
+    }
+
+    // Without a policy a failing hook fails closed
+    assert_eq!(run(None).await.unwrap().decision, Some(HookDecision::Block));
+    assert!(
+        run(Some(HookErrorPolicy::Allow))
+            .await
+            .unwrap()
+            .decision
+            .is_none()
+    );
+    assert_eq!(
+        run(Some(HookErrorPolicy::Block)).await.unwrap().decision,
+        Some(HookDecision::Block)
+    );
+    let fallback = HookOutput {
+        system_message: Some("guard skipped".to_string()),
+        ..HookOutput::default()
+    };
+    let output = run(Some(HookErrorPolicy::DefaultOutput(fallback)))
+        .await
+        .unwrap();
+    assert_eq!(output.system_message.as_deref(), Some("guard skipped"));
+}
+
+#[tokio::test]
+async fn test_failing_hook_does_not_discard_block() {
+    use kodegen_claude_agent::{ClaudeError, HookDecision, HookErrorPolicy, HookEvent};
+
+    let failing = || {
+        HookManager::callback(|_event_data, _tool_name, _context| async {
+            Err(ClaudeError::invalid_config("guard unavailable"))
+        })
+    };
+    let blocking = || {
+        HookManager::callback(|_event_data, _tool_name, _context| async {
+            Ok(HookOutput {
+                decision: Some(HookDecision::Block),
+                system_message: Some("denied".to_string()),
+                ..HookOutput::default()
+            })
+        })
+    };
+    let invoke = |manager: HookManager| async move {
+        manager
+            .invoke_event(
+                HookEvent::PreToolUse,
+                serde_json::json!({}),
+                Some("Bash".to_string()),
+                HookContext {},
+            )
+            .await
+            .unwrap()
+    };
+
+    // A failing hook allowed to fail open does not stop a later block
+    let mut manager = HookManager::new();
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(failing())
+            .on_error(HookErrorPolicy::Allow)
+            .build(),
+    );
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(blocking())
+            .build(),
+    );
+    let output = invoke(manager).await;
+    assert_eq!(output.decision, Some(HookDecision::Block));
+    assert_eq!(output.system_message.as_deref(), Some("denied"));
+
+    // Concurrent hooks: a failure without a policy blocks rather than erroring
+    let mut manager = HookManager::new();
+    manager.set_parallel(true);
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(failing())
+            .build(),
+    );
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(blocking())
+            .build(),
+    );
+    let output = invoke(manager).await;
+    assert_eq!(output.decision, Some(HookDecision::Block));
+}
+
+#[tokio::test]
+async fn test_hook_stats_count_invocations_and_blocks() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+
+    let guard = HookManager::callback(|event_data, _tool_name, _context| async move {
+        let blocked = event_data["tool_input"]["command"] == "rm -rf /";
+        Ok(HookOutput {
+            decision: blocked.then_some(HookDecision::Block),
+            ..HookOutput::default()
+        })
+    });
+    let mut manager = HookManager::new();
+    let id = manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(guard)
+            .build(),
+    );
+
+    for command in ["ls", "rm -rf /", "pwd"] {
+        manager
+            .invoke_event(
+                HookEvent::PreToolUse,
+                serde_json::json!({"tool_input": {"command": command}}),
+                Some("Bash".to_string()),
+                HookContext {},
+            )
+            .await
+            .unwrap();
+    }
+    // Not matched, so not counted
+    manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({}),
+            Some("Write".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+
+    let stats = &manager.stats()[&id];
+    assert_eq!(stats.invocations, 3);
+    assert_eq!(stats.blocked, 1);
+    assert_eq!(stats.errors, 0);
+    assert!(stats.max_latency >= stats.mean_latency().unwrap());
+}
+
+#[tokio::test]
+async fn test_hooks_inject_context() {
+    use kodegen_claude_agent::HookEvent;
+
+    fn provider(context: &'static str) -> kodegen_claude_agent::HookCallback {
+        HookManager::callback(move |_event_data, _tool_name, _context| async move {
+            Ok(HookOutput {
+                additional_context: Some(context.to_string()),
+                ..HookOutput::default()
+            })
+        })
+    }
+    let mut manager = HookManager::new();
+    manager.register_for(
+        HookEvent::UserPromptSubmit,
+        HookMatcherBuilder::new(None::<String>)
+            .add_hook(provider("branch: main"))
+            .add_hook(provider("os: linux"))
+            .build(),
+    );
+
+    let output = manager
+        .invoke_event(
+            HookEvent::UserPromptSubmit,
+            serde_json::json!({"prompt": "hi"}),
+            None,
+            HookContext {},
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        output.additional_context.as_deref(),
+        Some("branch: main\n\nos: linux")
+    );
+
+    let response = output.to_response(HookEvent::UserPromptSubmit);
+    assert!(response.get("additionalContext").is_none());
+    assert_eq!(
+        response["hookSpecificOutput"],
+        serde_json::json!({
+            "hookEventName": "UserPromptSubmit",
+            "additionalContext": "branch: main\n\nos: linux",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_named_hooks_toggle_at_runtime() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+
+    let block = HookManager::callback(|_event_data, _tool_name, _context| async {
+        Ok(HookOutput {
+            decision: Some(HookDecision::Block),
+            ..HookOutput::default()
+        })
+    });
+    let mut manager = HookManager::new();
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .name("safety")
+            .add_hook(block)
+            .build(),
+    );
+    assert_eq!(manager.is_enabled("safety"), Some(true));
+    assert_eq!(manager.is_enabled("audit"), None);
+    assert_eq!(
+        decision(&manager, HookEvent::PreToolUse).await,
+        Some(HookDecision::Block)
+    );
+
+    assert!(manager.disable("safety"));
+    assert_eq!(manager.is_enabled("safety"), Some(false));
+    assert_eq!(decision(&manager, HookEvent::PreToolUse).await, None);
+
+    assert!(manager.enable("safety"));
+    assert_eq!(
+        decision(&manager, HookEvent::PreToolUse).await,
+        Some(HookDecision::Block)
+    );
+
+    assert!(!manager.disable("audit"));
+    assert!(manager.remove_named("safety"));
+    assert!(manager.is_empty());
+    assert!(!manager.remove_named("safety"));
+}
+
+/// A hook returning `decision` and `message`
+fn deciding(
+    decision: Option<kodegen_claude_agent::HookDecision>,
+    message: &'static str,
+) -> kodegen_claude_agent::HookCallback {
+    HookManager::callback(move |_event_data, _tool_name, _context| async move {
+        Ok(HookOutput {
+            decision,
+            system_message: Some(message.to_string()),
+            ..HookOutput::default()
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_hook_merge_strategies() {
+    use kodegen_claude_agent::{HookDecision, HookEvent, HookMergeStrategy};
+
+    async fn merged(strategy: HookMergeStrategy) -> HookOutput {
+        let mut manager = HookManager::new();
+        manager.set_merge_strategy(strategy);
+        manager.register(
+            HookMatcherBuilder::new(Some("Bash"))
+                .add_hook(deciding(Some(HookDecision::Approve), "approved"))
+                .add_hook(deciding(Some(HookDecision::Block), "blocked"))
+                .add_hook(deciding(None, "audited"))
+                .build(),
+        );
+        manager
+            .invoke_event(
+                HookEvent::PreToolUse,
+                serde_json::json!({}),
+                Some("Bash".to_string()),
+                HookContext {},
+            )
+            .await
+            .unwrap()
+    }
+
+    let output = merged(HookMergeStrategy::BlockWins).await;
+    assert_eq!(output.decision, Some(HookDecision::Block));
+    assert_eq!(output.system_message.as_deref(), Some("blocked"));
+
+    let output = merged(HookMergeStrategy::FirstDecision).await;
+    assert_eq!(output.decision, Some(HookDecision::Approve));
+    assert_eq!(output.system_message.as_deref(), Some("approved"));
+
+    let output = merged(HookMergeStrategy::Collect).await;
+    assert_eq!(output.decision, Some(HookDecision::Block));
+    assert_eq!(
+        output.system_message.as_deref(),
+        Some("approved\nblocked\naudited")
+    );
+}
+
+#[tokio::test]
+async fn test_hooks_after_block_still_run() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let audited = Arc::new(AtomicUsize::new(0));
+    let audit = {
+        let audited = Arc::clone(&audited);
+        HookManager::callback(move |_event_data, _tool_name, _context| {
+            let audited = Arc::clone(&audited);
+            async move {
+                audited.fetch_add(1, Ordering::SeqCst);
+                Ok(HookOutput {
+                    system_message: Some("audited".to_string()),
+                    ..HookOutput::default()
+                })
+            }
+        })
+    };
+    let mut manager = HookManager::new();
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(deciding(Some(HookDecision::Block), "blocked"))
+            .add_hook(audit)
+            .build(),
+    );
+    let output = manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({}),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+
+    // The audit hook ran, but the block and its reason stand
+    assert_eq!(audited.load(Ordering::SeqCst), 1);
+    assert_eq!(output.decision, Some(HookDecision::Block));
+    assert_eq!(output.system_message.as_deref(), Some("blocked"));
+}
+
+#[tokio::test]
+async fn test_parallel_hooks_run_concurrently() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+    use std::time::{Duration, Instant};
+
+    fn slow(decision: Option<HookDecision>) -> kodegen_claude_agent::HookCallback {
+        HookManager::callback(move |_event_data, _tool_name, _context| async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(HookOutput {
+                decision,
+                ..HookOutput::default()
+            })
+        })
+    }
+    let mut manager = HookManager::new();
+    manager.set_parallel(true);
+    manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .add_hook(slow(None))
+            .add_hook(slow(None))
+            .add_hook(slow(Some(HookDecision::Block)))
+            .build(),
+    );
+
+    let started = Instant::now();
+    let output = manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({}),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_millis(550));
+    assert_eq!(output.decision, Some(HookDecision::Block));
+}
+
+#[tokio::test]
+async fn test_hook_outputs_cached_for_identical_inputs() {
+    use kodegen_claude_agent::HookEvent;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&runs);
+    let lookup = HookManager::callback(move |_event_data, _tool_name, _context| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        async { Ok(HookOutput::default()) }
+    });
+    let mut manager = HookManager::new();
+    let id = manager.register(
+        HookMatcherBuilder::new(Some("Read"))
+            .add_hook(lookup)
+            .cache(Duration::from_millis(200))
+            .build(),
+    );
+    let read = |path: &str, tool_use_id: &str| {
+        manager.invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({
+                "tool_name": "Read",
+                "tool_use_id": tool_use_id,
+                "tool_input": {"file_path": path},
+            }),
+            Some("Read".to_string()),
+            HookContext {},
+        )
+    };
+
+    read("a.rs", "t1").await.unwrap();
+    read("a.rs", "t2").await.unwrap();
+    read("b.rs", "t3").await.unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    assert_eq!(manager.stats()[&id].cache_hits, 1);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    read("a.rs", "t4").await.unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_shadow_hooks_are_not_enforced() {
+    use kodegen_claude_agent::{HookDecision, HookEvent};
+
+    let mut manager = HookManager::new();
+    let id = manager.register(
+        HookMatcherBuilder::new(Some("Bash"))
+            .name("new-guard")
+            .add_hook(deciding(Some(HookDecision::Block), "blocked"))
+            .shadow(true)
+            .build(),
+    );
+
+    let output = manager
+        .invoke_event(
+            HookEvent::PreToolUse,
+            serde_json::json!({}),
+            Some("Bash".to_string()),
+            HookContext {},
+        )
+        .await
+        .unwrap();
+    assert_eq!(output.decision, None);
+    assert!(output.system_message.is_none());
+    assert_eq!(manager.stats()[&id].blocked, 1);
+
+    assert!(manager.set_shadow("new-guard", false));
+    assert_eq!(
+        decision(&manager, HookEvent::PreToolUse).await,
+        Some(HookDecision::Block)
+    );
+}
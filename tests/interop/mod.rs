@@ -0,0 +1,100 @@
+//! Wire-format compatibility tests
+//!
+//! Fixtures live in `tests/fixtures/interop/<version>/<kind>/`; see the
+//! README there for the file format. Every fixture must parse, and fixtures
+//! marked `roundtrip` must serialize back to the same JSON.
+
+pub mod test_hooks;
+pub mod test_messages;
+pub mod test_permissions;
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Wire-format versions with fixtures
+const VERSIONS: &[&str] = &["v1"];
+
+/// One fixture file
+pub struct Fixture {
+    /// Path relative to the fixture root, for assertion messages
+    pub name: String,
+    /// Whether the payload must survive a parse/serialize round trip
+    pub roundtrip: bool,
+    /// The wire payload
+    pub payload: Value,
+}
+
+/// All fixtures of `kind`, across versions
+pub fn fixtures(kind: &str) -> Vec<Fixture> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/interop");
+    let mut fixtures = Vec::new();
+
+    for version in VERSIONS {
+        let dir = root.join(version).join(kind);
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("cannot read {}: {e}", dir.display()))
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let name = format!(
+                "{version}/{kind}/{}",
+                path.file_name().unwrap().to_string_lossy()
+            );
+            let mut file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("{name}: invalid JSON: {e}"));
+            fixtures.push(Fixture {
+                roundtrip: file["roundtrip"]
+                    .as_bool()
+                    .unwrap_or_else(|| panic!("{name}: missing `roundtrip`")),
+                payload: file["payload"].take(),
+                name,
+            });
+        }
+    }
+
+    assert!(!fixtures.is_empty(), "no {kind} fixtures found");
+    fixtures
+}
+
+/// Drop `null` members, which the wire treats like absent ones
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), normalize(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Parse every fixture as `T` and check round trips
+///
+/// Returns the parsed values, in fixture order, for further assertions.
+pub fn check<T: Serialize + DeserializeOwned>(fixtures: &[Fixture]) -> Vec<T> {
+    fixtures
+        .iter()
+        .map(|fixture| {
+            let parsed: T = serde_json::from_value(fixture.payload.clone())
+                .unwrap_or_else(|e| panic!("{}: does not parse: {e}", fixture.name));
+            if fixture.roundtrip {
+                let produced = serde_json::to_value(&parsed).unwrap();
+                assert_eq!(
+                    normalize(&produced),
+                    normalize(&fixture.payload),
+                    "{}: serializes differently",
+                    fixture.name
+                );
+            }
+            parsed
+        })
+        .collect()
+}
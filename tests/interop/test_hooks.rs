@@ -0,0 +1,41 @@
+//! Hook input and output fixtures
+
+use kodegen_claude_agent::types::hooks::{HookEvent, HookOutput};
+
+use super::fixtures;
+
+#[test]
+fn test_hook_input_fixtures() {
+    for fixture in fixtures("hooks")
+        .into_iter()
+        .filter(|f| f.name.ends_with("_input.json"))
+    {
+        let event: HookEvent = serde_json::from_value(fixture.payload["hook_event_name"].clone())
+            .unwrap_or_else(|e| panic!("{}: unknown hook event: {e}", fixture.name));
+        assert!(
+            fixture.payload["session_id"].is_string(),
+            "{}: missing session_id",
+            fixture.name
+        );
+        if matches!(event, HookEvent::PreToolUse | HookEvent::PostToolUse) {
+            assert!(fixture.payload["tool_name"].is_string(), "{}", fixture.name);
+            assert!(
+                fixture.payload["tool_input"].is_object(),
+                "{}",
+                fixture.name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_hook_output_fixtures() {
+    let outputs: Vec<_> = fixtures("hooks")
+        .into_iter()
+        .filter(|f| f.name.contains("/output_"))
+        .collect();
+    let outputs: Vec<HookOutput> = super::check(&outputs);
+
+    assert!(outputs.iter().any(|o| o.decision.is_some()));
+    assert!(outputs.iter().any(|o| o.hook_specific_output.is_some()));
+}
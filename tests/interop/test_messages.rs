@@ -0,0 +1,21 @@
+//! Stream message fixtures
+
+use kodegen_claude_agent::types::messages::Message;
+
+use super::{check, fixtures};
+
+#[test]
+fn test_message_fixtures() {
+    let messages: Vec<Message> = check(&fixtures("messages"));
+
+    for message in &messages {
+        assert!(
+            kodegen_claude_agent::parse_message(serde_json::to_value(message).unwrap()).is_ok()
+        );
+    }
+    assert!(
+        messages
+            .iter()
+            .any(|m| matches!(m, Message::StreamEvent { .. }))
+    );
+}
@@ -0,0 +1,54 @@
+//! Permission suggestion fixtures
+
+use kodegen_claude_agent::types::permissions::{
+    PermissionBehavior, PermissionUpdate, ToolPermissionContext,
+};
+
+use super::{check, fixtures};
+
+#[test]
+fn test_permission_update_fixtures() {
+    let updates: Vec<PermissionUpdate> = check(&fixtures("permissions"));
+
+    let Some(PermissionUpdate::AddRules {
+        rules: Some(rules),
+        behavior,
+        ..
+    }) = updates
+        .iter()
+        .find(|u| matches!(u, PermissionUpdate::AddRules { .. }))
+    else {
+        panic!("no addRules fixture");
+    };
+    assert_eq!(rules[0].tool_name, "Bash");
+    assert_eq!(rules[0].rule_content.as_deref(), Some("npm test"));
+    assert_eq!(*behavior, Some(PermissionBehavior::Allow));
+}
+
+#[test]
+fn test_permission_context_accepts_cli_suggestions() {
+    let suggestions: Vec<_> = fixtures("permissions")
+        .into_iter()
+        .map(|f| f.payload)
+        .collect();
+    let context: ToolPermissionContext =
+        serde_json::from_value(serde_json::json!({ "suggestions": suggestions })).unwrap();
+    assert_eq!(context.suggestions.len(), 6);
+}
+
+#[test]
+fn test_rule_value_accepts_snake_case() {
+    let update: PermissionUpdate = serde_json::from_value(serde_json::json!({
+        "type": "addRules",
+        "rules": [{ "tool_name": "Read", "rule_content": "./src/**" }]
+    }))
+    .unwrap();
+    let PermissionUpdate::AddRules {
+        rules: Some(rules), ..
+    } = update
+    else {
+        panic!("wrong variant");
+    };
+    assert_eq!(rules[0].tool_name, "Read");
+    assert_eq!(rules[0].rule_content.as_deref(), Some("./src/**"));
+}
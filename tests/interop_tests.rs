@@ -0,0 +1,3 @@
+//! Interop tests - wire-format fixtures shared with the other agent SDKs
+
+mod interop;
@@ -0,0 +1,51 @@
+//! Manager module tests
+
+#[cfg(unix)]
+pub mod test_attach;
+#[cfg(unix)]
+pub mod test_audit;
+#[cfg(unix)]
+pub mod test_buffer;
+#[cfg(unix)]
+pub mod test_clock;
+#[cfg(unix)]
+pub mod test_events;
+#[cfg(unix)]
+pub mod test_group;
+#[cfg(unix)]
+pub mod test_history;
+#[cfg(unix)]
+pub mod test_hooks;
+#[cfg(unix)]
+pub mod test_memory;
+#[cfg(unix)]
+pub mod test_permissions;
+#[cfg(unix)]
+pub mod test_pool;
+#[cfg(unix)]
+pub mod test_projects;
+#[cfg(unix)]
+pub mod test_quota;
+#[cfg(unix)]
+pub mod test_reaper;
+#[cfg(unix)]
+pub mod test_swarm;
+#[cfg(unix)]
+pub mod test_terminate;
+#[cfg(unix)]
+pub mod test_watchdog;
+
+#[cfg(unix)]
+use std::path::Path;
+
+#[cfg(unix)]
+use kodegen_claude_agent::types::{ContainerTransportConfig, TransportConfig};
+
+#[cfg(unix)]
+pub use crate::common::Fixture;
+
+/// Transport starting the session through the fake container `runtime`
+#[cfg(unix)]
+pub fn container(runtime: &Path) -> TransportConfig {
+    TransportConfig::Container(ContainerTransportConfig::new("image").runtime(runtime))
+}
@@ -0,0 +1,153 @@
+//! Unit tests for attaching a terminal to a managed session
+//!
+//! A fake container runtime answers every stdin line with a reply and a
+//! result, so no real CLI is needed
+
+use std::time::Duration;
+
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{AgentManager, DETACH_COMMAND, SpawnSessionRequest};
+use kodegen_claude_agent::types::SessionGroup;
+use tokio::io::{AsyncWriteExt, BufReader};
+
+use super::{Fixture, container};
+
+/// Fake runtime that answers each input line with "got it"
+const RUNTIME: &str = "while read -r line; do\necho '{\"type\":\"assistant\",\"message\":{\"model\":\"m\",\"content\":[{\"type\":\"text\",\"text\":\"got it\"}]}}'\necho \"$RESULT\"\ndone\n";
+
+async fn wait_for_messages(manager: &AgentManager, session_id: &str, count: usize) {
+    for _ in 0..100 {
+        let output = manager.get_output(session_id, 0, 100).await.unwrap();
+        if output.total_messages >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("session {session_id} never reached {count} messages");
+}
+
+#[tokio::test]
+async fn test_attach_takes_and_returns_control() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new();
+    let request = SpawnSessionRequest {
+        prompt: "start".to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+    wait_for_messages(&manager, &session_id, 3).await;
+
+    let (mut terminal, input) = tokio::io::duplex(1024);
+    let mut output = Vec::new();
+    let attach = manager.attach(&session_id, BufReader::new(input), &mut output);
+    let drive = async {
+        while !manager.is_attached(&session_id).await.unwrap() {
+            tokio::task::yield_now().await;
+        }
+        // The orchestrator is locked out while a terminal is attached
+        assert!(matches!(
+            manager.send_message(&session_id, "from orchestrator").await,
+            Err(ClaudeError::SessionAttached(_))
+        ));
+
+        terminal.write_all(b"hello\n\n").await.unwrap();
+        wait_for_messages(&manager, &session_id, 6).await;
+        terminal
+            .write_all(format!("{DETACH_COMMAND}\n").as_bytes())
+            .await
+            .unwrap();
+    };
+    let (result, ()) = tokio::join!(attach, drive);
+    result.unwrap();
+
+    let printed = String::from_utf8(output).unwrap();
+    assert!(printed.contains("got it\n[turn complete]\n"), "{printed}");
+    assert!(!manager.is_attached(&session_id).await.unwrap());
+
+    // Control is back with the manager
+    manager
+        .send_message(&session_id, "from orchestrator")
+        .await
+        .unwrap();
+    wait_for_messages(&manager, &session_id, 9).await;
+    manager.terminate_session(&session_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_only_one_terminal_attaches() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new();
+    let request = SpawnSessionRequest {
+        prompt: "start".to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+
+    let (mut terminal, input) = tokio::io::duplex(64);
+    let first = manager.attach(&session_id, BufReader::new(input), tokio::io::sink());
+    let second = async {
+        while !manager.is_attached(&session_id).await.unwrap() {
+            tokio::task::yield_now().await;
+        }
+        let result = manager
+            .attach(&session_id, BufReader::new(&b""[..]), tokio::io::sink())
+            .await;
+        // End of input detaches the first terminal
+        terminal.shutdown().await.unwrap();
+        drop(terminal);
+        result
+    };
+    let (first, second) = tokio::join!(first, second);
+    first.unwrap();
+    assert!(matches!(second, Err(ClaudeError::SessionAttached(_))));
+
+    manager.terminate_session(&session_id).await.unwrap();
+    assert!(matches!(
+        manager.is_attached(&session_id).await,
+        Err(ClaudeError::SessionNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_attached_lines_respect_group_budget() {
+    let fixture = Fixture::new();
+    // Spends the whole budget on its first turn, then reads stdin
+    let runtime = fixture.script(
+        "runtime",
+        "echo '{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"s1\",\"total_cost_usd\":0.5}'\nwhile read -r line; do :; done\n",
+    );
+    let manager = AgentManager::new();
+    let group_id = manager
+        .create_group(SessionGroup::new("team").budget_usd(0.5))
+        .await
+        .unwrap();
+    let request = SpawnSessionRequest {
+        prompt: "start".to_string(),
+        transport: container(&runtime),
+        group: Some(group_id.clone()),
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+    for _ in 0..100 {
+        if manager.group_cost(&group_id).await.unwrap().total_cost_usd > 0.0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let result = manager
+        .attach(
+            &session_id,
+            BufReader::new(&b"more\n"[..]),
+            tokio::io::sink(),
+        )
+        .await;
+    assert!(matches!(result, Err(ClaudeError::GroupBudgetExceeded(_))));
+    assert!(!manager.is_attached(&session_id).await.unwrap());
+
+    manager.terminate_session(&session_id).await.unwrap();
+}
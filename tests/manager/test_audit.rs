@@ -0,0 +1,137 @@
+//! Unit tests for the transcript hash chain
+//!
+//! The session test runs a fake container runtime that reports a few
+//! messages, so no real CLI is needed
+
+use std::time::Duration;
+
+use chrono::Utc;
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{
+    AgentManager, GENESIS_HASH, SpawnSessionRequest, entry_hash, verify_transcript,
+};
+use kodegen_claude_agent::types::agent::SerializedMessage;
+use serde_json::json;
+
+use super::{Fixture, container};
+
+/// Fake runtime that reports two system messages and a result
+const RUNTIME: &str = "echo '{\"type\":\"system\",\"subtype\":\"init\"}'\necho '{\"type\":\"system\",\"subtype\":\"status\"}'\necho \"$RESULT\"\nwhile read -r line; do :; done\n";
+
+/// Build a hashed transcript of `count` entries
+fn chained(count: usize) -> Vec<SerializedMessage> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    (0..count)
+        .map(|i| {
+            let mut message = SerializedMessage {
+                message_type: "assistant".to_string(),
+                content: json!({"text": format!("step {i}")}),
+                turn: 0,
+                timestamp: Utc::now(),
+                prev_hash: None,
+                hash: None,
+            };
+            let hash = entry_hash(&prev_hash, &message);
+            message.prev_hash = Some(std::mem::replace(&mut prev_hash, hash.clone()));
+            message.hash = Some(hash);
+            message
+        })
+        .collect()
+}
+
+#[test]
+fn test_verify_transcript_detects_tampering() {
+    let transcript = chained(4);
+    let head = verify_transcript(&transcript).unwrap();
+    assert_eq!(head, transcript[3].hash);
+    assert_eq!(verify_transcript(&[]).unwrap(), None);
+
+    // Any contiguous window verifies to the same head
+    assert_eq!(verify_transcript(&transcript[2..]).unwrap(), head);
+
+    // A JSON round trip (as in an export) preserves the chain
+    let exported = serde_json::to_string(&transcript).unwrap();
+    let imported: Vec<SerializedMessage> = serde_json::from_str(&exported).unwrap();
+    assert_eq!(verify_transcript(&imported).unwrap(), head);
+
+    let mut edited = transcript.clone();
+    edited[1].content = json!({"text": "rewritten"});
+    assert!(matches!(
+        verify_transcript(&edited),
+        Err(ClaudeError::TranscriptIntegrity { index: 1, .. })
+    ));
+
+    let mut dropped = transcript.clone();
+    dropped.remove(1);
+    assert!(matches!(
+        verify_transcript(&dropped),
+        Err(ClaudeError::TranscriptIntegrity { index: 1, .. })
+    ));
+
+    let mut unhashed = transcript;
+    unhashed[2].hash = None;
+    assert!(matches!(
+        verify_transcript(&unhashed),
+        Err(ClaudeError::TranscriptIntegrity { index: 2, .. })
+    ));
+}
+
+#[test]
+fn test_entry_hash_ignores_key_order() {
+    let message = |content: &str| SerializedMessage {
+        message_type: "assistant".to_string(),
+        content: serde_json::from_str(content).unwrap(),
+        turn: 1,
+        timestamp: chrono::DateTime::UNIX_EPOCH,
+        prev_hash: None,
+        hash: None,
+    };
+
+    let sorted = message(r#"{"a": {"c": [{"e": 1, "f": 2}], "d": true}, "b": "x"}"#);
+    let shuffled = message(r#"{"b": "x", "a": {"d": true, "c": [{"f": 2, "e": 1}]}}"#);
+    assert_eq!(
+        entry_hash(GENESIS_HASH, &sorted),
+        entry_hash(GENESIS_HASH, &shuffled)
+    );
+}
+
+#[tokio::test]
+async fn test_session_transcript_is_chained() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new();
+    let request = SpawnSessionRequest {
+        prompt: "hello".to_string(),
+        transport: container(&runtime),
+        max_turns: 5,
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+
+    for _ in 0..100 {
+        if manager
+            .get_output(&session_id, 0, 10)
+            .await
+            .unwrap()
+            .total_messages
+            >= 4
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let output = manager.get_output(&session_id, 0, 10).await.unwrap().output;
+    assert_eq!(output.len(), 4);
+    assert_eq!(output[0].prev_hash.as_deref(), Some(GENESIS_HASH));
+    let head = verify_transcript(&output).unwrap();
+    assert!(head.is_some());
+
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert_eq!(info.transcript_head, head);
+
+    let terminated = manager.terminate_session(&session_id).await.unwrap();
+    assert_eq!(terminated.transcript_head, head);
+    let archived = manager.get_session_info(&session_id).await.unwrap();
+    assert_eq!(archived.transcript_head, head);
+}
@@ -0,0 +1,91 @@
+//! Unit tests for the per-session transcript buffer size
+//!
+//! A fake CLI, started through a transport pool so the session is local,
+//! answers every input line with several status messages and a result
+
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::transport::TransportPool;
+
+use super::Fixture;
+
+/// Fake CLI writing ten messages per input line
+const CHATTY_CLI: &str = "echo '{\"type\":\"system\",\"subtype\":\"init\"}'\nwhile read -r line; do for i in 1 2 3 4 5 6 7 8 9; do echo '{\"type\":\"system\",\"subtype\":\"status\"}'; done; echo \"$RESULT\"; done\n";
+
+/// Spawn a session on `cli` and wait until its buffer has dropped messages
+async fn spawn_and_fill(manager: &AgentManager, template: SpawnSessionRequest) -> String {
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            ..template
+        })
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        let events = manager.events(&session_id).await.unwrap();
+        if events
+            .iter()
+            .any(|event| event.name == "transcript.truncated")
+        {
+            return session_id;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("transcript never truncated");
+}
+
+#[tokio::test]
+async fn test_request_buffer_size_overrides_manager_default() {
+    let fixture = Fixture::new();
+    let template = SpawnSessionRequest {
+        max_turns: 5,
+        buffer_size: Some(4),
+        ..Default::default()
+    };
+    let pool = TransportPool::new(
+        template.options(),
+        Some(fixture.script("claude", CHATTY_CLI)),
+        1,
+    )
+    .unwrap();
+    let manager = AgentManager::new()
+        .with_buffer_size(1000)
+        .with_transport_pool(pool);
+
+    let session_id = spawn_and_fill(&manager, template).await;
+    let output = manager.get_output(&session_id, 0, 50).await.unwrap();
+    assert_eq!(output.output.len(), 4);
+    let events = manager.events(&session_id).await.unwrap();
+    let truncated = events
+        .iter()
+        .find(|event| event.name == "transcript.truncated")
+        .unwrap();
+    assert_eq!(truncated.attributes["capacity"], 4);
+
+    manager.terminate_session(&session_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_manager_buffer_size_default() {
+    let fixture = Fixture::new();
+    let template = SpawnSessionRequest {
+        max_turns: 5,
+        ..Default::default()
+    };
+    let pool = TransportPool::new(
+        template.options(),
+        Some(fixture.script("claude", CHATTY_CLI)),
+        1,
+    )
+    .unwrap();
+    let manager = AgentManager::new()
+        .with_buffer_size(6)
+        .with_transport_pool(pool);
+
+    let session_id = spawn_and_fill(&manager, template).await;
+    let output = manager.get_output(&session_id, 0, 50).await.unwrap();
+    assert_eq!(output.output.len(), 6);
+
+    manager.terminate_session(&session_id).await.unwrap();
+}
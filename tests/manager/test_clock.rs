@@ -0,0 +1,102 @@
+//! Unit tests for the manager's injectable clock
+//!
+//! Sessions run a fake container runtime that reports one message; time is
+//! then paused and advanced through `TokioClock`, so working detection and
+//! retention are checked without waiting for their real thresholds
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{AgentManager, Clock, SpawnSessionRequest, TokioClock};
+
+use super::{Fixture, container};
+
+/// Fake runtime that reports one message and reads stdin until EOF
+const RUNTIME: &str =
+    "echo '{\"type\":\"system\",\"subtype\":\"init\"}'\nwhile read -r line; do :; done\n";
+
+/// Let background tasks woken by `advance` run
+async fn settle() {
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_tokio_clock_follows_paused_time() {
+    tokio::time::pause();
+    let clock = TokioClock::new();
+    let start = clock.now();
+    let start_utc = clock.utc_now();
+
+    tokio::time::advance(Duration::from_secs(90)).await;
+
+    assert_eq!(clock.elapsed(start), Duration::from_secs(90));
+    assert_eq!((clock.utc_now() - start_utc).num_seconds(), 90);
+    assert_eq!(
+        clock.elapsed(clock.now() + Duration::from_secs(1)),
+        Duration::ZERO
+    );
+}
+
+#[tokio::test]
+async fn test_working_and_retention_follow_clock() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new_with_clock(Arc::new(TokioClock::new()));
+    let request = SpawnSessionRequest {
+        prompt: "hello".to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+
+    // The prompt is recorded at spawn; wait for the runtime's own message
+    for _ in 0..100 {
+        if manager
+            .get_session_info(&session_id)
+            .await
+            .unwrap()
+            .message_count
+            > 1
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    tokio::time::pause();
+    assert!(manager.is_working(&session_id).await.unwrap());
+    tokio::time::advance(Duration::from_secs(3)).await;
+    assert!(!manager.is_working(&session_id).await.unwrap());
+    assert!(
+        manager
+            .get_session_info(&session_id)
+            .await
+            .unwrap()
+            .runtime_ms
+            >= 3000
+    );
+
+    manager.terminate_session(&session_id).await.unwrap();
+    assert!(
+        manager
+            .get_session_info(&session_id)
+            .await
+            .unwrap()
+            .is_complete
+    );
+
+    // Completed sessions are kept for a minute, then cleaned up
+    tokio::time::advance(Duration::from_secs(30)).await;
+    settle().await;
+    assert!(manager.get_session_info(&session_id).await.is_ok());
+
+    tokio::time::advance(Duration::from_secs(120)).await;
+    settle().await;
+    assert!(matches!(
+        manager.get_session_info(&session_id).await,
+        Err(ClaudeError::SessionNotFound(_))
+    ));
+}
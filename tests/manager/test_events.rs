@@ -0,0 +1,107 @@
+//! Unit tests for the session event log
+//!
+//! A fake CLI, started through a transport pool so the session is local,
+//! announces itself twice (as a restarted CLI does) and answers every input
+//! line with a line of noise and a result
+
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::transport::TransportPool;
+use kodegen_claude_agent::types::{EventSeverity, SessionEvent};
+
+use super::Fixture;
+
+/// Poll the session's events until one named `name` appears
+async fn wait_for_event(manager: &AgentManager, session_id: &str, name: &str) -> Vec<SessionEvent> {
+    for _ in 0..100 {
+        let events = manager.events(session_id).await.unwrap();
+        if events.iter().any(|event| event.name == name) {
+            return events;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("no {name} event");
+}
+
+#[tokio::test]
+async fn test_session_events_are_recorded() {
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "init='{\"type\":\"system\",\"subtype\":\"init\"}'\necho \"$init\"\necho \"$init\"\nwhile read -r line; do echo 'npm WARN deprecated'; echo \"$RESULT\"; done\n",
+    );
+
+    let template = SpawnSessionRequest {
+        max_turns: 5,
+        ..Default::default()
+    };
+    let pool = TransportPool::new(template.options(), Some(cli), 1).unwrap();
+    let manager = AgentManager::new().with_transport_pool(pool);
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            label: "events".to_string(),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let events = wait_for_event(&manager, &session_id, "output.skipped").await;
+    assert_eq!(events[0].name, "session.spawned");
+    assert_eq!(events[0].severity, EventSeverity::Info);
+    assert_eq!(events[0].attributes["label"], "events");
+    assert_eq!(events[0].attributes["pooled"], true);
+    let restarted = events.iter().find(|e| e.name == "cli.restarted").unwrap();
+    assert_eq!(restarted.severity, EventSeverity::Warn);
+    let skipped = events.iter().find(|e| e.name == "output.skipped").unwrap();
+    assert!(skipped.body.contains("npm WARN deprecated"));
+
+    // Skipped output does not end the session
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert!(!info.is_complete);
+    manager.send_message(&session_id, "again").await.unwrap();
+
+    manager.terminate_session(&session_id).await.unwrap();
+    let events = manager.events(&session_id).await.unwrap();
+    let terminated = events.last().unwrap();
+    assert_eq!(terminated.name, "session.terminated");
+    assert_eq!(
+        serde_json::to_value(terminated).unwrap()["severity"],
+        "INFO"
+    );
+    assert!(
+        events
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp)
+    );
+
+    assert!(manager.events("missing").await.is_err());
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cli_exit_is_recorded_and_ends_session() {
+    let fixture = Fixture::new();
+    let cli = fixture.script("claude", "read -r line\nexit 0\n");
+
+    let template = SpawnSessionRequest::default();
+    let pool = TransportPool::new(template.options(), Some(cli), 1).unwrap();
+    let manager = AgentManager::new().with_transport_pool(pool);
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let events = wait_for_event(&manager, &session_id, "cli.exited").await;
+    let exited = events.iter().find(|e| e.name == "cli.exited").unwrap();
+    assert_eq!(exited.severity, EventSeverity::Error);
+    assert!(exited.attributes["code"].is_null());
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert!(info.is_complete);
+
+    manager.shutdown().await.unwrap();
+}
@@ -0,0 +1,221 @@
+//! Unit tests for `AgentManager` session groups
+//!
+//! Members run fake container runtimes that report a result with a cost and
+//! log what they receive on stdin, so no real CLI is needed
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::types::SessionGroup;
+
+use super::{Fixture, container};
+
+/// Fake runtime that reports `result` at `cost` and logs stdin to `<name>.log`
+fn fake_member(fixture: &Fixture, name: &str, result: &str, cost: f64) -> PathBuf {
+    let log = fixture.path(&format!("{name}.log"));
+    fixture.script(
+        name,
+        &format!(
+            "echo '{{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"{name}\",\"total_cost_usd\":{cost},\"result\":\"{result}\"}}'\nwhile read -r line; do echo \"$line\" >> '{}'; done\n",
+            log.display()
+        ),
+    )
+}
+
+fn member_request(group_id: &str, runtime: &Path) -> SpawnSessionRequest {
+    SpawnSessionRequest {
+        prompt: "work on the task".to_string(),
+        transport: container(runtime),
+        group: Some(group_id.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Poll until `check` passes or a few seconds elapse
+async fn eventually<F, Fut>(mut check: F)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    for _ in 0..100 {
+        if check().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("condition not met in time");
+}
+
+#[tokio::test]
+async fn test_group_budget_and_shared_context() {
+    let fixture = Fixture::new();
+    let manager = AgentManager::new();
+    let group_id = manager
+        .create_group(
+            SessionGroup::new("parser team")
+                .shared_label("TICKET-42")
+                .budget_usd(0.75)
+                .share_context(true),
+        )
+        .await
+        .unwrap();
+
+    let first_runtime = fake_member(&fixture, "first", "found bug in parser", 0.5);
+    let first = manager
+        .spawn_session(member_request(&group_id, &first_runtime))
+        .await
+        .unwrap();
+    eventually(|| async { manager.group_cost(&group_id).await.unwrap().total_cost_usd > 0.0 })
+        .await;
+
+    // Still within budget: the second member sees the first one's result
+    let second_runtime = fake_member(&fixture, "second", "wrote regression test", 0.5);
+    let second = manager
+        .spawn_session(member_request(&group_id, &second_runtime))
+        .await
+        .unwrap();
+    let second_log = fixture.path("second.log");
+    eventually(|| async { second_log.exists() }).await;
+    let received = std::fs::read_to_string(&second_log).unwrap();
+    assert!(received.contains("found bug in parser"), "{received}");
+    assert!(received.contains("work on the task"), "{received}");
+
+    eventually(|| async { manager.group_cost(&group_id).await.unwrap().total_cost_usd >= 1.0 })
+        .await;
+    let cost = manager.group_cost(&group_id).await.unwrap();
+    assert_eq!(cost.sessions.len(), 2);
+    assert_eq!(cost.remaining_usd, Some(0.0));
+
+    // Over budget: no new members and no follow-up messages
+    let third_runtime = fake_member(&fixture, "third", "unused", 0.1);
+    assert!(matches!(
+        manager
+            .spawn_session(member_request(&group_id, &third_runtime))
+            .await,
+        Err(ClaudeError::GroupBudgetExceeded(_))
+    ));
+    assert!(matches!(
+        manager.send_message(&first, "more").await,
+        Err(ClaudeError::GroupBudgetExceeded(_))
+    ));
+
+    let group = manager.get_group(&group_id).await.unwrap();
+    assert_eq!(group.members, vec![first.clone(), second.clone()]);
+    assert_eq!(group.labels, vec!["TICKET-42".to_string()]);
+    let info = manager.get_session_info(&first).await.unwrap();
+    assert_eq!(info.label, "parser team");
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_terminate_group_keeps_cost() {
+    let fixture = Fixture::new();
+    let manager = AgentManager::new();
+    let group_id = manager
+        .create_group(SessionGroup::new("team"))
+        .await
+        .unwrap();
+    let other_group = manager
+        .create_group(SessionGroup::new("other"))
+        .await
+        .unwrap();
+
+    let runtime = fake_member(&fixture, "member", "done", 0.25);
+    for _ in 0..2 {
+        manager
+            .spawn_session(member_request(&group_id, &runtime))
+            .await
+            .unwrap();
+    }
+    manager
+        .spawn_session(member_request(&other_group, &runtime))
+        .await
+        .unwrap();
+    eventually(|| async { manager.group_cost(&group_id).await.unwrap().total_cost_usd >= 0.5 })
+        .await;
+
+    let listed = manager.list_group_sessions(&group_id, 1).await.unwrap();
+    assert_eq!(listed.agents.len(), 2);
+    assert_eq!(listed.total_active, 2);
+
+    let terminated = manager.terminate_group(&group_id).await.unwrap();
+    assert_eq!(terminated.len(), 2);
+
+    let listed = manager.list_group_sessions(&group_id, 1).await.unwrap();
+    assert_eq!(listed.total_completed, 2);
+    assert!((manager.group_cost(&group_id).await.unwrap().total_cost_usd - 0.5).abs() < 1e-9);
+    assert_eq!(manager.list_groups().await.len(), 2);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_group_not_found() {
+    let manager = AgentManager::new();
+    assert!(matches!(
+        manager.get_group("missing").await,
+        Err(ClaudeError::GroupNotFound(_))
+    ));
+    assert!(matches!(
+        manager
+            .spawn_session(member_request("missing", Path::new("/bin/true")))
+            .await,
+        Err(ClaudeError::GroupNotFound(_))
+    ));
+    assert!(matches!(
+        manager
+            .create_group(SessionGroup::new("bad").budget_usd(-1.0))
+            .await,
+        Err(ClaudeError::InvalidAgentConfiguration(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_group_blackboard_shared_through_mcp() {
+    let fixture = Fixture::new();
+    let manager = AgentManager::new();
+    let group_id = manager
+        .create_group(SessionGroup::new("team"))
+        .await
+        .unwrap();
+    let blackboard = manager.group_blackboard(&group_id).await.unwrap();
+    blackboard.set("plan", serde_json::json!("split the parser"), "orchestrator");
+
+    // Member posts a finding through its blackboard MCP server and logs its
+    // command line and what it receives
+    let log = fixture.path("poster.log");
+    let runtime = fixture.script(
+        "poster",
+        &format!(
+            "echo \"$@\" > '{log}'\necho '{{\"type\":\"response\",\"status\":\"mcp_message\",\"id\":\"m1\",\"server_name\":\"blackboard\",\"message\":{{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{{\"name\":\"set\",\"arguments\":{{\"key\":\"finding\",\"value\":\"lexer bug\"}}}}}}}}'\nwhile read -r line; do echo \"$line\" >> '{log}'; done\n",
+            log = log.display()
+        ),
+    );
+
+    let mut request = member_request(&group_id, &runtime);
+    request.allowed_tools = vec!["Read".to_string()];
+    let session_id = manager.spawn_session(request).await.unwrap();
+
+    eventually(|| async { blackboard.get("finding").is_some() }).await;
+    let entry = blackboard.get("finding").unwrap();
+    assert_eq!(entry.value, "lexer bug");
+    assert_eq!(entry.author, session_id);
+
+    eventually(|| async {
+        std::fs::read_to_string(&log).is_ok_and(|logged| logged.contains("mcp_response"))
+    })
+    .await;
+    let logged = std::fs::read_to_string(&log).unwrap();
+    assert!(logged.contains("\"blackboard\""), "{logged}");
+    assert!(logged.contains("mcp__blackboard__set"), "{logged}");
+
+    assert!(matches!(
+        manager.group_blackboard("missing").await,
+        Err(ClaudeError::GroupNotFound(_))
+    ));
+
+    manager.shutdown().await.unwrap();
+}
@@ -0,0 +1,218 @@
+//! Unit tests for spawning sessions that continue a prior transcript
+//!
+//! The session tests run a fake container runtime or CLI that logs the prompt
+//! it receives, so no real CLI is needed
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use kodegen_claude_agent::manager::{AgentManager, ProjectsTranscript, SpawnSessionRequest};
+use kodegen_claude_agent::types::agent::SerializedMessage;
+use serde_json::{Value, json};
+
+use super::{Fixture, container};
+
+/// Fake runtime that logs its first stdin line to `prompt.log`
+fn fake_runtime(fixture: &Fixture) -> (PathBuf, PathBuf) {
+    let log = fixture.path("prompt.log");
+    let runtime = fixture.script(
+        "runtime",
+        &format!(
+            "read -r line\nprintf '%s\\n' \"$line\" > '{}'\necho \"$RESULT\"\nwhile read -r line; do :; done\n",
+            log.display()
+        ),
+    );
+    (runtime, log)
+}
+
+fn message(message_type: &str, content: Value) -> SerializedMessage {
+    SerializedMessage {
+        message_type: message_type.to_string(),
+        content,
+        turn: 0,
+        timestamp: Utc::now(),
+        prev_hash: None,
+        hash: None,
+    }
+}
+
+fn history() -> Vec<SerializedMessage> {
+    vec![
+        message(
+            "user",
+            json!({"type": "user", "message": {"role": "user", "content": "What is 2 + 2?"}}),
+        ),
+        message(
+            "assistant",
+            json!({"type": "assistant", "message": {"model": "m", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Calculator", "input": {}},
+                {"type": "text", "text": "It is 4."}
+            ]}}),
+        ),
+        message("result", json!({"type": "result"})),
+    ]
+}
+
+#[test]
+fn test_with_session_id_updates_entries() {
+    let transcript = ProjectsTranscript::from_messages(&history(), Some(Path::new("/tmp/p")))
+        .unwrap()
+        .with_session_id("new-id");
+    assert_eq!(transcript.session_id, "new-id");
+    assert_eq!(transcript.entries.len(), 2);
+    assert!(
+        transcript
+            .entries
+            .iter()
+            .all(|entry| entry["sessionId"] == "new-id")
+    );
+    assert!(transcript.relative_path().ends_with("new-id.jsonl"));
+}
+
+#[test]
+fn test_request_resume_sets_option() {
+    let request = SpawnSessionRequest {
+        resume: Some("abc".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        request.options().resume().map(|id| id.as_str()),
+        Some("abc")
+    );
+}
+
+#[tokio::test]
+async fn test_history_is_replayed_in_prompt() {
+    let fixture = Fixture::new();
+    let (runtime, log) = fake_runtime(&fixture);
+    let manager = AgentManager::new();
+    let request = SpawnSessionRequest {
+        prompt: "And times 3?".to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    };
+    let session_id = manager
+        .spawn_with_history(&history(), request)
+        .await
+        .unwrap();
+
+    let mut prompt = String::new();
+    for _ in 0..100 {
+        if let Ok(line) = std::fs::read_to_string(&log)
+            && !line.is_empty()
+        {
+            prompt = line;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    manager.terminate_session(&session_id).await.unwrap();
+
+    let sent: Value = serde_json::from_str(prompt.trim()).unwrap();
+    let text = sent["message"]["content"].as_str().unwrap();
+    assert!(text.contains("**User:** What is 2 + 2?"), "{text}");
+    assert!(
+        text.contains("**Assistant:** [called tool Calculator]\nIt is 4."),
+        "{text}"
+    );
+    assert!(text.ends_with("---\n\nAnd times 3?"), "{text}");
+}
+
+/// Spawn a session continuing `history()` on a fake local CLI started in
+/// `cwd`, returning the config directory, the CLI's arguments and the
+/// prompt it received
+async fn resume_locally(fixture: &Fixture, cwd: String) -> (PathBuf, Vec<String>, Value) {
+    let launch = fixture.path("launch.log");
+    let log = fixture.path("prompt.log");
+    // Logs its config directory and arguments, then its first stdin line
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "{{ printf '%s\\n' \"$CLAUDE_CONFIG_DIR\"; for arg; do printf '%s\\n' \"$arg\"; done; }} > '{}'\nread -r line\nprintf '%s\\n' \"$line\" > '{}'\necho \"$RESULT\"\nwhile read -r line; do :; done\n",
+            launch.display(),
+            log.display()
+        ),
+    );
+    let claude_dir = fixture.path("claude-config");
+    let manager = AgentManager::new()
+        .with_cli_path(cli)
+        .with_claude_config_dir(&claude_dir);
+    let request = SpawnSessionRequest {
+        prompt: "And times 3?".to_string(),
+        cwd: Some(cwd),
+        ..Default::default()
+    };
+    let session_id = manager
+        .spawn_with_history(&history(), request)
+        .await
+        .unwrap();
+
+    let mut prompt = String::new();
+    for _ in 0..100 {
+        if let Ok(line) = std::fs::read_to_string(&log)
+            && !line.is_empty()
+        {
+            prompt = line;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    manager.terminate_session(&session_id).await.unwrap();
+
+    let launch = std::fs::read_to_string(&launch).unwrap();
+    let mut lines = launch.lines();
+    assert_eq!(lines.next(), Some(claude_dir.to_str().unwrap()));
+    let args = lines.map(ToString::to_string).collect();
+    (
+        claude_dir,
+        args,
+        serde_json::from_str(prompt.trim()).unwrap(),
+    )
+}
+
+/// Entries of the transcript the CLI was told to resume, looked up under
+/// the project directory of `cwd`
+fn resumed_entries(claude_dir: &Path, args: &[String], cwd: &Path) -> Vec<Value> {
+    let resume = args.iter().position(|arg| arg == "--resume").unwrap();
+    let transcript = ProjectsTranscript::from_messages(&history(), Some(cwd))
+        .unwrap()
+        .with_session_id(&args[resume + 1]);
+    let written = std::fs::read_to_string(claude_dir.join(transcript.relative_path())).unwrap();
+    written
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_history_is_resumed_by_local_cli() {
+    let fixture = Fixture::new();
+    let cwd = fixture.dir().canonicalize().unwrap();
+    let (claude_dir, args, sent) =
+        resume_locally(&fixture, cwd.to_string_lossy().into_owned()).await;
+
+    // The CLI resumes the written transcript from the manager's config directory
+    let entries = resumed_entries(&claude_dir, &args, &cwd);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["message"]["content"], "What is 2 + 2?");
+
+    // The prompt is sent as is, without a replay of the history
+    assert_eq!(sent["message"]["content"], "And times 3?");
+}
+
+#[tokio::test]
+async fn test_relative_cwd_is_resumed_from_its_absolute_project() {
+    let fixture = Fixture::new();
+    let cwd = fixture.dir().canonicalize().unwrap();
+    // The same directory, relative to the test's working directory
+    let here = std::env::current_dir().unwrap();
+    let relative = Path::new(&"../".repeat(here.components().count() - 1))
+        .join(cwd.strip_prefix("/").unwrap());
+    let (claude_dir, args, _) =
+        resume_locally(&fixture, relative.to_string_lossy().into_owned()).await;
+
+    let entries = resumed_entries(&claude_dir, &args, &cwd);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["cwd"], cwd.to_string_lossy().as_ref());
+}
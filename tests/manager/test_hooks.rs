@@ -0,0 +1,32 @@
+//! Unit tests for hooks on managed sessions
+
+use std::collections::HashMap;
+
+use kodegen_claude_agent::hooks::{HookManager, HookMatcherBuilder};
+use kodegen_claude_agent::manager::SpawnSessionRequest;
+use kodegen_claude_agent::{HookEvent, HookOutput};
+
+#[test]
+fn test_request_hooks_reach_options() {
+    let audit = HookManager::callback(|_event_data, _tool_name, _context| async {
+        Ok(HookOutput::default())
+    });
+    let request = SpawnSessionRequest {
+        hooks: Some(HashMap::from([(
+            HookEvent::PreToolUse,
+            vec![
+                HookMatcherBuilder::new(Some("Bash"))
+                    .add_hook(audit)
+                    .build(),
+            ],
+        )])),
+        ..Default::default()
+    };
+
+    let options = request.options();
+    let matchers = &options.hooks().unwrap()[&HookEvent::PreToolUse];
+    assert_eq!(matchers.len(), 1);
+    assert_eq!(matchers[0].matcher.as_deref(), Some("Bash"));
+
+    assert!(SpawnSessionRequest::default().options().hooks().is_none());
+}
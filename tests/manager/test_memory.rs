@@ -0,0 +1,103 @@
+//! Unit tests for memory tracking of session CLI processes
+//!
+//! A fake CLI, started through a transport pool so the session is local,
+//! answers every input line with a result and otherwise stays idle
+
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, MemoryTracking, SpawnSessionRequest};
+use kodegen_claude_agent::transport::TransportPool;
+use kodegen_claude_agent::types::TerminationReason;
+
+use super::Fixture;
+
+/// Fake CLI answering every input line with a result
+const CLI: &str = "while read -r line; do echo \"$RESULT\"; done\n";
+
+/// Manager whose local sessions run the fake CLI
+fn manager(fixture: &Fixture) -> (AgentManager, SpawnSessionRequest) {
+    let template = SpawnSessionRequest {
+        max_turns: 5,
+        ..Default::default()
+    };
+    let pool =
+        TransportPool::new(template.options(), Some(fixture.script("claude", CLI)), 1).unwrap();
+    (AgentManager::new().with_transport_pool(pool), template)
+}
+
+#[tokio::test]
+async fn test_memory_is_sampled() {
+    let fixture = Fixture::new();
+    let (manager, template) = manager(&fixture);
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            memory_tracking: Some(MemoryTracking::new().sample_every(Duration::from_millis(20))),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let mut info = manager.get_session_info(&session_id).await.unwrap();
+    for _ in 0..100 {
+        if info.memory_bytes.is_some_and(|bytes| bytes > 0) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        info = manager.get_session_info(&session_id).await.unwrap();
+    }
+    let rss = info.memory_bytes.unwrap();
+    assert!(rss > 0);
+    assert!(info.peak_memory_bytes.unwrap() >= rss);
+    assert!(info.termination_reason.is_none());
+
+    let health = manager.get_session_health(&session_id).await.unwrap();
+    assert!(health.memory_bytes.unwrap() > 0);
+
+    manager.terminate_session(&session_id).await.unwrap();
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert!(info.memory_bytes.is_none());
+    assert!(info.peak_memory_bytes.unwrap() >= rss);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_session_over_memory_cap_is_stopped() {
+    let fixture = Fixture::new();
+    let (manager, template) = manager(&fixture);
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            memory_tracking: Some(
+                MemoryTracking::new()
+                    .cap(1)
+                    .sample_every(Duration::from_millis(20)),
+            ),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let mut info = manager.get_session_info(&session_id).await.unwrap();
+    for _ in 0..100 {
+        if info.termination_reason.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        info = manager.get_session_info(&session_id).await.unwrap();
+    }
+    assert_eq!(
+        info.termination_reason,
+        Some(TerminationReason::MemoryLimitExceeded)
+    );
+    assert!(info.is_complete);
+
+    let response = manager.terminate_session(&session_id).await.unwrap();
+    assert_eq!(
+        response.reason,
+        Some(TerminationReason::MemoryLimitExceeded)
+    );
+
+    manager.shutdown().await.unwrap();
+}
@@ -0,0 +1,39 @@
+//! Unit tests for permission control of managed sessions
+
+use kodegen_claude_agent::manager::SpawnSessionRequest;
+use kodegen_claude_agent::permissions::PermissionManager;
+use kodegen_claude_agent::types::permissions::PermissionMode;
+use kodegen_claude_agent::{PermissionPolicy, PermissionResult, PermissionResultDeny};
+
+#[test]
+fn test_request_permissions_reach_options() {
+    let request = SpawnSessionRequest {
+        can_use_tool: Some(
+            PermissionManager::callback(|_tool, _input, _context| async {
+                Ok(PermissionResult::Deny(PermissionResultDeny {
+                    message: "read only".to_string(),
+                    interrupt: false,
+                }))
+            })
+            .into(),
+        ),
+        permission_mode: Some(PermissionMode::Plan),
+        permission_policy: Some(
+            PermissionPolicy::from_json(r#"{"rules": [{"tool": "Read", "action": "allow"}]}"#)
+                .unwrap(),
+        ),
+        ..Default::default()
+    };
+
+    assert!(format!("{request:?}").contains("can_use_tool: Some(<callback>)"));
+    let options = request.options();
+    assert!(options.can_use_tool().is_some());
+    assert_eq!(options.permission_mode(), Some(PermissionMode::Plan));
+    assert_eq!(options.permission_policy().unwrap().rules.len(), 1);
+    assert!(format!("{request:?}").contains("<callback>"));
+
+    let options = SpawnSessionRequest::default().options();
+    assert!(options.can_use_tool().is_none());
+    assert!(options.permission_mode().is_none());
+    assert!(options.permission_policy().is_none());
+}
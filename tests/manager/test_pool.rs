@@ -0,0 +1,78 @@
+//! Unit tests for spawning sessions on pooled CLI processes
+//!
+//! A fake CLI logs each start and answers every input line with a result,
+//! so the test can tell warm processes from cold starts
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::transport::TransportPool;
+
+use super::Fixture;
+
+/// Fake CLI that appends to `starts` when started
+fn fake_cli(fixture: &Fixture) -> (PathBuf, PathBuf) {
+    let starts = fixture.path("starts");
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "echo started >> '{}'\nwhile read -r line; do echo '{{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"s1\",\"result\":\"pooled\"}}'; done\n",
+            starts.display()
+        ),
+    );
+    (cli, starts)
+}
+
+#[tokio::test]
+async fn test_spawn_uses_matching_pool() {
+    let fixture = Fixture::new();
+    let (cli, starts) = fake_cli(&fixture);
+    let template = SpawnSessionRequest {
+        model: Some("haiku".to_string()),
+        max_turns: 1,
+        ..Default::default()
+    };
+    let pool = TransportPool::new(template.options(), Some(cli), 1).unwrap();
+    pool.warm().await.unwrap();
+    let manager = AgentManager::new().with_transport_pool(pool.clone());
+
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "hello".to_string(),
+            label: "pooled".to_string(),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let mut result = None;
+    for _ in 0..100 {
+        let output = manager.get_output(&session_id, 0, 10).await.unwrap().output;
+        result = output.into_iter().find(|msg| msg.message_type == "result");
+        if result.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(result.unwrap().content["result"], "pooled");
+
+    // The warm process was used and a replacement started
+    for _ in 0..100 {
+        if pool.idle_count().await == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(pool.idle_count().await, 1);
+    for _ in 0..100 {
+        if std::fs::read_to_string(&starts).unwrap().lines().count() == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(std::fs::read_to_string(&starts).unwrap().lines().count(), 2);
+
+    manager.shutdown().await.unwrap();
+    assert_eq!(pool.idle_count().await, 0);
+}
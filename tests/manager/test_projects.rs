@@ -0,0 +1,155 @@
+//! Unit tests for exporting sessions in Claude Code's "projects" format
+//!
+//! The session test runs a fake container runtime that reports an init
+//! message and one exchange, so no real CLI is needed
+
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::Utc;
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{
+    AgentManager, ProjectsTranscript, SpawnSessionRequest, project_dir_name,
+};
+use kodegen_claude_agent::types::agent::SerializedMessage;
+use serde_json::{Value, json};
+
+use super::{Fixture, container};
+
+/// Fake runtime that reports an init message, a reply and a result
+const RUNTIME: &str = "echo '{\"type\":\"system\",\"subtype\":\"init\",\"cwd\":\"/work/my.project\",\"session_id\":\"0b7c5e1a-9d2f-4e31-8c6a-5f1e2d3c4b5a\"}'\necho '{\"type\":\"assistant\",\"message\":{\"model\":\"claude-sonnet-4-5\",\"content\":[{\"type\":\"text\",\"text\":\"Done.\"}]}}'\necho '{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"0b7c5e1a-9d2f-4e31-8c6a-5f1e2d3c4b5a\"}'\nwhile read -r line; do :; done\n";
+
+fn message(message_type: &str, content: Value) -> SerializedMessage {
+    SerializedMessage {
+        message_type: message_type.to_string(),
+        content,
+        turn: 0,
+        timestamp: Utc::now(),
+        prev_hash: None,
+        hash: None,
+    }
+}
+
+#[test]
+fn test_project_dir_name() {
+    assert_eq!(
+        project_dir_name(Path::new("/home/user/my.project")),
+        "-home-user-my-project"
+    );
+}
+
+#[test]
+fn test_transcript_links_user_and_assistant_entries() {
+    let messages = vec![
+        message("system_init", json!({"type": "system", "subtype": "init"})),
+        message(
+            "user",
+            json!({"type": "user", "message": {"role": "user", "content": "Hi"}}),
+        ),
+        message(
+            "assistant",
+            json!({"type": "assistant", "message": {"model": "m", "content": [{"type": "text", "text": "Hello"}]}}),
+        ),
+        message("result", json!({"type": "result"})),
+    ];
+
+    let transcript =
+        ProjectsTranscript::from_messages(&messages, Some(Path::new("/tmp/p"))).unwrap();
+    assert_eq!(transcript.entries.len(), 2);
+
+    let user = &transcript.entries[0];
+    let assistant = &transcript.entries[1];
+    assert_eq!(user["parentUuid"], Value::Null);
+    assert_eq!(user["type"], "user");
+    assert_eq!(user["message"]["content"], "Hi");
+    assert_eq!(user["cwd"], "/tmp/p");
+    assert_eq!(assistant["parentUuid"], user["uuid"]);
+    assert_eq!(assistant["message"]["role"], "assistant");
+    assert_eq!(assistant["message"]["content"][0]["text"], "Hello");
+    assert_eq!(assistant["sessionId"], transcript.session_id.as_str());
+
+    let jsonl = transcript.to_jsonl();
+    assert_eq!(jsonl.lines().count(), 2);
+    assert!(
+        jsonl
+            .lines()
+            .all(|line| serde_json::from_str::<Value>(line).is_ok())
+    );
+}
+
+#[test]
+fn test_transcript_requires_cwd() {
+    let messages = vec![message(
+        "user",
+        json!({"type": "user", "message": {"role": "user", "content": "Hi"}}),
+    )];
+    assert!(matches!(
+        ProjectsTranscript::from_messages(&messages, None),
+        Err(ClaudeError::InvalidConfig(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_export_session() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new();
+    let request = SpawnSessionRequest {
+        prompt: "hello".to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    };
+    let session_id = manager.spawn_session(request).await.unwrap();
+
+    for _ in 0..100 {
+        if manager
+            .get_output(&session_id, 0, 10)
+            .await
+            .unwrap()
+            .total_messages
+            >= 4
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    manager.send_message(&session_id, "and now?").await.unwrap();
+    manager.terminate_session(&session_id).await.unwrap();
+
+    let transcript = manager
+        .export_projects_transcript(&session_id, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        transcript.session_id,
+        "0b7c5e1a-9d2f-4e31-8c6a-5f1e2d3c4b5a"
+    );
+    assert_eq!(transcript.cwd, Path::new("/work/my.project"));
+    // The prompts sent by the manager are part of the conversation
+    let entries = &transcript.entries;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["type"], "user");
+    assert_eq!(entries[0]["parentUuid"], Value::Null);
+    assert_eq!(entries[0]["message"]["content"], "hello");
+    assert_eq!(entries[1]["type"], "assistant");
+    assert_eq!(entries[1]["parentUuid"], entries[0]["uuid"]);
+    assert_eq!(entries[2]["type"], "user");
+    assert_eq!(entries[2]["message"]["content"], "and now?");
+
+    let claude_dir = fixture.path("claude");
+    let path = transcript.write(&claude_dir).unwrap();
+    assert_eq!(
+        path,
+        claude_dir.join("projects/-work-my-project/0b7c5e1a-9d2f-4e31-8c6a-5f1e2d3c4b5a.jsonl")
+    );
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written, transcript.to_jsonl());
+
+    // An existing transcript is never overwritten
+    let again = manager
+        .export_projects_transcript(&session_id, None)
+        .await
+        .unwrap();
+    assert_eq!(again.write(&claude_dir).unwrap(), path);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+}
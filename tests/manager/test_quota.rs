@@ -0,0 +1,115 @@
+//! Unit tests for disk quotas on session working directories
+//!
+//! A fake CLI, started through a transport pool so the session is local,
+//! writes a file into its working directory for every input line
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, DiskQuota, SpawnSessionRequest};
+use kodegen_claude_agent::transport::TransportPool;
+use kodegen_claude_agent::types::TerminationReason;
+
+use super::Fixture;
+
+/// Fake CLI that writes 4 KiB to `out.bin` in its working directory
+fn fake_cli(fixture: &Fixture) -> (PathBuf, PathBuf) {
+    let workdir = fixture.path("work");
+    std::fs::create_dir_all(&workdir).unwrap();
+    let cli = fixture.script(
+        "claude",
+        "while read -r line; do head -c 4096 /dev/zero > out.bin; echo \"$RESULT\"; done\n",
+    );
+    (cli, workdir)
+}
+
+#[tokio::test]
+async fn test_session_over_quota_is_stopped() {
+    let fixture = Fixture::new();
+    let (cli, workdir) = fake_cli(&fixture);
+    let template = SpawnSessionRequest {
+        cwd: Some(workdir.to_string_lossy().into_owned()),
+        max_turns: 5,
+        ..Default::default()
+    };
+    let pool = TransportPool::new(template.options(), Some(cli), 1).unwrap();
+    let manager = AgentManager::new().with_transport_pool(pool);
+
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "write".to_string(),
+            disk_quota: Some(DiskQuota::new(1000).check_every(Duration::from_millis(20))),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    let mut info = manager.get_session_info(&session_id).await.unwrap();
+    for _ in 0..100 {
+        if info.termination_reason.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        info = manager.get_session_info(&session_id).await.unwrap();
+    }
+    assert_eq!(
+        info.termination_reason,
+        Some(TerminationReason::DiskQuotaExceeded)
+    );
+    assert!(info.is_complete);
+    assert!(info.disk_usage_bytes.unwrap() >= 4096);
+
+    let response = manager.terminate_session(&session_id).await.unwrap();
+    assert_eq!(response.reason, Some(TerminationReason::DiskQuotaExceeded));
+
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert_eq!(
+        info.termination_reason,
+        Some(TerminationReason::DiskQuotaExceeded)
+    );
+    assert!(info.disk_usage_bytes.unwrap() >= 4096);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_quota_without_cwd_is_ignored() {
+    let fixture = Fixture::new();
+    let out = fixture.path("out.bin");
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "while read -r line; do head -c 4096 /dev/zero > '{}'; echo \"$RESULT\"; done\n",
+            out.display()
+        ),
+    );
+    let template = SpawnSessionRequest {
+        max_turns: 5,
+        ..Default::default()
+    };
+    let pool = TransportPool::new(template.options(), Some(cli), 1).unwrap();
+    let manager = AgentManager::new().with_transport_pool(pool);
+
+    let session_id = manager
+        .spawn_session(SpawnSessionRequest {
+            prompt: "write".to_string(),
+            disk_quota: Some(DiskQuota::new(1000).check_every(Duration::from_millis(20))),
+            ..template
+        })
+        .await
+        .unwrap();
+
+    for _ in 0..100 {
+        if out.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert_eq!(info.termination_reason, None);
+    assert_eq!(info.disk_usage_bytes, None);
+
+    manager.shutdown().await.unwrap();
+}
@@ -0,0 +1,96 @@
+//! Unit tests for the orphan process reaper
+//!
+//! Sessions run a fake container runtime that records its PID, so no real
+//! CLI is needed
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+
+use super::{Fixture, container};
+
+/// Fake runtime that records its PID and waits for input
+fn fake_runtime(fixture: &Fixture) -> (PathBuf, PathBuf) {
+    let pid_file = fixture.path("pid");
+    let runtime = fixture.script(
+        "runtime",
+        &format!(
+            "echo $$ > '{}'\nwhile read -r line; do :; done\n",
+            pid_file.display()
+        ),
+    );
+    (runtime, pid_file)
+}
+
+fn request(runtime: &Path) -> SpawnSessionRequest {
+    SpawnSessionRequest {
+        prompt: "hello".to_string(),
+        transport: container(runtime),
+        ..Default::default()
+    }
+}
+
+async fn wait_for_pid(pid_file: &Path) -> String {
+    for _ in 0..100 {
+        if let Ok(pid) = std::fs::read_to_string(pid_file)
+            && !pid.trim().is_empty()
+        {
+            return pid.trim().to_string();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("runtime never recorded its PID");
+}
+
+fn is_running(pid: &str) -> bool {
+    std::process::Command::new("kill")
+        .args(["-0", pid])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap()
+        .success()
+}
+
+#[tokio::test]
+async fn test_reaps_processes_of_dropped_manager() {
+    let fixture = Fixture::new();
+    let (runtime, pid_file) = fake_runtime(&fixture);
+    let leaking = AgentManager::new();
+    leaking.spawn_session(request(&runtime)).await.unwrap();
+    let pid = wait_for_pid(&pid_file).await;
+
+    // Dropped without shutdown: the session's task keeps the CLI running
+    drop(leaking);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(is_running(&pid));
+
+    let manager = AgentManager::new();
+    assert!(manager.reap_orphans().await >= 1);
+    assert!(manager.orphans_reaped() >= 1);
+
+    let mut running = true;
+    for _ in 0..100 {
+        running = is_running(&pid);
+        if !running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(!running, "orphan {pid} still running");
+}
+
+#[tokio::test]
+async fn test_live_sessions_are_not_reaped() {
+    let fixture = Fixture::new();
+    let (runtime, pid_file) = fake_runtime(&fixture);
+    let manager = AgentManager::new().with_reaper_interval(Duration::from_millis(20));
+    let session_id = manager.spawn_session(request(&runtime)).await.unwrap();
+    let pid = wait_for_pid(&pid_file).await;
+
+    manager.reap_orphans().await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(is_running(&pid));
+
+    manager.terminate_session(&session_id).await.unwrap();
+}
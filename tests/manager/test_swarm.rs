@@ -0,0 +1,103 @@
+//! Unit tests for `AgentManager` swarms
+//!
+//! Members run fake container runtimes that report a result with a cost and
+//! log what they receive on stdin, so no real CLI is needed
+
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest, Swarm};
+
+use super::{Fixture, container};
+
+/// Member that reports `result` at `cost` and logs stdin to `<name>.log`
+fn member(fixture: &Fixture, name: &str, result: &str, cost: f64) -> SpawnSessionRequest {
+    let log = fixture.path(&format!("{name}.log"));
+    let runtime = fixture.script(
+        name,
+        &format!(
+            "echo '{{\"type\":\"result\",\"subtype\":\"success\",\"duration_ms\":1,\"duration_api_ms\":1,\"is_error\":false,\"num_turns\":1,\"session_id\":\"{name}\",\"total_cost_usd\":{cost},\"result\":\"{result}\"}}'\nwhile read -r line; do echo \"$line\" >> '{}'; done\n",
+            log.display()
+        ),
+    );
+    SpawnSessionRequest {
+        prompt: format!("{name} task"),
+        label: name.to_string(),
+        transport: container(&runtime),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_swarm_runs_stages_in_order() {
+    let fixture = Fixture::new();
+    let manager = AgentManager::new();
+
+    // An implementer that never answers is reported, not fatal
+    let mut broken = member(&fixture, "broken", "unused", 0.0);
+    broken.transport = container(&fixture.script("broken", "read -r line\nexit 0\n"));
+
+    let swarm = Swarm::new("feature")
+        .stage(
+            "plan",
+            vec![member(&fixture, "planner", "plan: two items", 0.25)],
+        )
+        .stage(
+            "implement",
+            vec![
+                member(&fixture, "first", "did item one", 0.5),
+                member(&fixture, "second", "did item two", 0.5),
+                broken,
+            ],
+        )
+        .stage(
+            "review",
+            vec![member(&fixture, "reviewer", "looks good", 0.25)],
+        )
+        .stage_timeout(Duration::from_secs(2));
+
+    let report = manager.run_swarm(swarm).await.unwrap();
+
+    let names: Vec<&str> = report.stages.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["plan", "implement", "review"]);
+    let implement = &report.stages[1].members;
+    assert_eq!(implement[0].result.as_deref(), Some("did item one"));
+    assert_eq!(implement[1].label, "second");
+    assert!(implement[2].result.is_none());
+    assert!(implement[2].error.is_some());
+    assert_eq!(report.final_result(), Some("looks good"));
+
+    // Later stages see the results of earlier ones
+    let received = std::fs::read_to_string(fixture.path("reviewer.log")).unwrap();
+    for result in [
+        "plan: two items",
+        "did item one",
+        "did item two",
+        "reviewer task",
+    ] {
+        assert!(received.contains(result), "{received}");
+    }
+    let received = std::fs::read_to_string(fixture.path("first.log")).unwrap();
+    assert!(received.contains("plan: two items"), "{received}");
+
+    assert!((report.cost.total_cost_usd - 1.5).abs() < 1e-9);
+    assert_eq!(report.cost.sessions.len(), 5);
+    let group = manager.get_group(&report.group_id).await.unwrap();
+    assert_eq!(group.members.len(), 5);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_plan_implement_review_template() {
+    let swarm = Swarm::plan_implement_review("Add a --verbose flag", 3).budget_usd(2.0);
+
+    let stages: Vec<(&str, usize)> = swarm
+        .stages
+        .iter()
+        .map(|stage| (stage.name.as_str(), stage.members.len()))
+        .collect();
+    assert_eq!(stages, [("plan", 1), ("implement", 3), ("review", 1)]);
+    assert_eq!(swarm.group.budget_usd, Some(2.0));
+    assert!(swarm.stages[1].members[1].prompt.contains("items 2, 5, 8"));
+    assert!(swarm.stages[2].members[0].role.is_some());
+}
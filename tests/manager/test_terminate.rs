@@ -0,0 +1,225 @@
+//! Unit tests for `AgentManager` session termination
+//!
+//! Sessions run a fake container runtime that consumes stdin until it is
+//! closed, so no real CLI is needed
+
+use std::path::Path;
+use std::sync::Arc;
+
+use kodegen_claude_agent::error::ClaudeError;
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::types::SequentialIdGenerator;
+
+use super::{Fixture, container};
+
+/// Fake runtime that reads stdin until EOF
+const RUNTIME: &str = "while read -r line; do :; done\n";
+
+async fn spawn(manager: &AgentManager, runtime: &Path) -> String {
+    let request = SpawnSessionRequest {
+        prompt: "hello".to_string(),
+        transport: container(runtime),
+        ..Default::default()
+    };
+    manager.spawn_session(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_concurrent_terminate_is_idempotent() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = Arc::new(AgentManager::new());
+    let session_id = spawn(&manager, &runtime).await;
+
+    let terminates: Vec<_> = (0..4)
+        .map(|_| {
+            let manager = Arc::clone(&manager);
+            let session_id = session_id.clone();
+            tokio::spawn(async move { manager.terminate_session(&session_id).await })
+        })
+        .collect();
+
+    let mut responses = Vec::new();
+    for terminate in terminates {
+        responses.push(terminate.await.unwrap().unwrap());
+    }
+    for response in &responses {
+        assert_eq!(response.session_id, session_id);
+        assert!(response.success);
+        assert_eq!(response.runtime_ms, responses[0].runtime_ms);
+        assert_eq!(response.total_messages, responses[0].total_messages);
+    }
+
+    let info = manager.get_session_info(&session_id).await.unwrap();
+    assert!(info.is_complete);
+    assert!(info.completion_time.is_some());
+}
+
+#[tokio::test]
+async fn test_terminate_then_shutdown_keeps_completed_record() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new();
+    let first = spawn(&manager, &runtime).await;
+    let second = spawn(&manager, &runtime).await;
+
+    let terminated = manager.terminate_session(&first).await.unwrap();
+    manager.shutdown().await.unwrap();
+    manager.shutdown().await.unwrap();
+
+    for session_id in [&first, &second] {
+        let info = manager.get_session_info(session_id).await.unwrap();
+        assert!(info.is_complete, "{session_id} should be completed");
+        assert!(info.completion_time.is_some());
+    }
+
+    // A repeated terminate reports the original result
+    let again = manager.terminate_session(&first).await.unwrap();
+    assert_eq!(again.runtime_ms, terminated.runtime_ms);
+
+    assert!(matches!(
+        manager.send_message(&second, "too late").await,
+        Err(ClaudeError::SessionNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_sequential_session_ids() {
+    let fixture = Fixture::new();
+    let runtime = fixture.script("runtime", RUNTIME);
+    let manager = AgentManager::new()
+        .with_id_generator(Arc::new(SequentialIdGenerator::with_prefix("snap-")));
+
+    assert_eq!(spawn(&manager, &runtime).await, "snap-session-1");
+    assert_eq!(spawn(&manager, &runtime).await, "snap-session-2");
+
+    let listed = manager.list_sessions(false, 0).await.unwrap();
+    let mut ids: Vec<_> = listed.agents.into_iter().map(|agent| agent.session_id).collect();
+    ids.sort();
+    assert_eq!(ids, ["snap-session-1", "snap-session-2"]);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_terminate_unknown_session() {
+    let manager = AgentManager::new();
+    assert!(matches!(
+        manager.terminate_session("missing").await,
+        Err(ClaudeError::SessionNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_terminate_lets_writing_turn_finish() {
+    let fixture = Fixture::new();
+    // Starts answering the prompt, then finishes the turn a moment later
+    let runtime = fixture.script(
+        "runtime",
+        concat!(
+            "read -r line\n",
+            r#"echo '{"type":"assistant","message":{"model":"fake","content":[{"type":"text","text":"Writing"}]}}'"#,
+            "\nsleep 1\n",
+            r#"echo '{"type":"result","subtype":"success","duration_ms":0,"duration_api_ms":0,"is_error":false,"num_turns":1,"session_id":"s1"}'"#,
+            "\nwhile read -r line; do :; done\n",
+        ),
+    );
+
+    let manager = AgentManager::new();
+    let session_id = spawn(&manager, &runtime).await;
+    for _ in 0..100 {
+        let output = manager.get_output(&session_id, 0, 10).await.unwrap();
+        // The prompt, then the start of the reply
+        if output.total_messages > 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let terminated = manager.terminate_session(&session_id).await.unwrap();
+    assert_eq!(terminated.final_turn_count, 1);
+    assert_eq!(terminated.total_messages, 3);
+
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cancelled_terminate_still_completes() {
+    let fixture = Fixture::new();
+    // Starts answering the prompt, then finishes the turn a moment later
+    let runtime = fixture.script(
+        "runtime",
+        concat!(
+            "read -r line\n",
+            r#"echo '{"type":"assistant","message":{"model":"fake","content":[{"type":"text","text":"Writing"}]}}'"#,
+            "\nsleep 1\n",
+            r#"echo '{"type":"result","subtype":"success","duration_ms":0,"duration_api_ms":0,"is_error":false,"num_turns":1,"session_id":"s1"}'"#,
+            "\nwhile read -r line; do :; done\n",
+        ),
+    );
+
+    let manager = AgentManager::new();
+    let session_id = spawn(&manager, &runtime).await;
+    for _ in 0..100 {
+        let output = manager.get_output(&session_id, 0, 10).await.unwrap();
+        if output.total_messages > 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    // The first caller gives up while the turn drains
+    let cancelled = tokio::time::timeout(
+        std::time::Duration::from_millis(100),
+        manager.terminate_session(&session_id),
+    )
+    .await;
+    assert!(cancelled.is_err());
+
+    // The shutdown carries on without it
+    let terminated = manager.terminate_session(&session_id).await.unwrap();
+    assert_eq!(terminated.final_turn_count, 1);
+    assert!(matches!(
+        manager.send_message(&session_id, "more").await,
+        Err(ClaudeError::SessionNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_sessions_concurrently() {
+    let fixture = Fixture::new();
+    // Starts answering the prompt, then finishes the turn two seconds later
+    let runtime = fixture.script(
+        "runtime",
+        concat!(
+            "read -r line\n",
+            r#"echo '{"type":"assistant","message":{"model":"fake","content":[{"type":"text","text":"Writing"}]}}'"#,
+            "\nsleep 2\n",
+            r#"echo '{"type":"result","subtype":"success","duration_ms":0,"duration_api_ms":0,"is_error":false,"num_turns":1,"session_id":"s1"}'"#,
+            "\nwhile read -r line; do :; done\n",
+        ),
+    );
+
+    let manager = AgentManager::new();
+    let mut session_ids = Vec::new();
+    for _ in 0..3 {
+        session_ids.push(spawn(&manager, &runtime).await);
+    }
+    for session_id in &session_ids {
+        for _ in 0..100 {
+            let output = manager.get_output(session_id, 0, 10).await.unwrap();
+            if output.total_messages > 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
+    let started = std::time::Instant::now();
+    manager.shutdown().await.unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    for session_id in &session_ids {
+        let terminated = manager.terminate_session(session_id).await.unwrap();
+        assert_eq!(terminated.final_turn_count, 1);
+    }
+}
@@ -0,0 +1,83 @@
+//! Unit tests for the health watchdog and health-aware working detection
+//!
+//! Sessions run a fake container runtime that never finishes its turn; one
+//! answers health pings, the other ignores them
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use kodegen_claude_agent::manager::{AgentManager, SpawnSessionRequest};
+use kodegen_claude_agent::types::EventSeverity;
+
+use super::{Fixture, container};
+
+/// Answers a ping request read into `$line`
+const ANSWER_PINGS: &str = r#"case "$line" in *'"method":"ping"'*) id=$(echo "$line" | sed 's/.*"id":"\([^"]*\)".*/\1/'); echo "{\"type\":\"response\",\"status\":\"success\",\"id\":\"$id\",\"data\":null}";; esac"#;
+
+/// Fake runtime that announces itself, then runs `on_line` per input line
+fn fake_runtime(fixture: &Fixture, on_line: &str) -> PathBuf {
+    fixture.script(
+        "runtime",
+        &format!(
+            "echo '{{\"type\":\"system\",\"subtype\":\"init\"}}'\nwhile read -r line; do {on_line}\ndone\n"
+        ),
+    )
+}
+
+fn request(runtime: &Path) -> SpawnSessionRequest {
+    SpawnSessionRequest {
+        prompt: "Run the long build".to_string(),
+        transport: container(runtime),
+        health_check_interval: Some(Duration::from_millis(100)),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_silent_turn_on_responsive_cli_is_working() {
+    let fixture = Fixture::new();
+    let runtime = fake_runtime(&fixture, ANSWER_PINGS);
+    let manager = AgentManager::new();
+    let session_id = manager.spawn_session(request(&runtime)).await.unwrap();
+
+    // Past the output threshold, the answered pings keep the session working
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    let health = manager.get_session_health(&session_id).await.unwrap();
+    assert!(health.pings_answered > 0);
+    assert!(manager.is_working(&session_id).await.unwrap());
+    assert!(manager.get_session_info(&session_id).await.unwrap().working);
+
+    manager.terminate_session(&session_id).await.unwrap();
+    manager.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_watchdog_flags_silent_cli() {
+    let fixture = Fixture::new();
+    let runtime = fake_runtime(&fixture, ":");
+    let manager = AgentManager::new();
+    let session_id = manager.spawn_session(request(&runtime)).await.unwrap();
+
+    let mut flagged = None;
+    for _ in 0..100 {
+        let events = manager.events(&session_id).await.unwrap();
+        flagged = events.into_iter().find(|event| event.name == "cli.silent");
+        if flagged.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let flagged = flagged.expect("no cli.silent event");
+    assert_eq!(flagged.severity, EventSeverity::Warn);
+    assert!(flagged.attributes["consecutive_failures"].as_u64().unwrap() >= 3);
+    assert!(
+        manager
+            .get_session_health(&session_id)
+            .await
+            .unwrap()
+            .wedged
+    );
+
+    manager.terminate_session(&session_id).await.unwrap();
+    manager.shutdown().await.unwrap();
+}
@@ -0,0 +1,4 @@
+//! Manager tests - mirrors src/manager/
+
+mod common;
+mod manager;
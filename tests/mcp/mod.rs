@@ -0,0 +1,4 @@
+//! MCP module tests
+
+pub mod test_blackboard;
+pub mod test_server;
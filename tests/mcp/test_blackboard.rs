@@ -0,0 +1,119 @@
+//! Unit tests for `Blackboard`
+
+use kodegen_claude_agent::mcp::Blackboard;
+use serde_json::json;
+
+fn call(tool: &str, arguments: serde_json::Value) -> serde_json::Value {
+    json!({
+        "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+        "params": {"name": tool, "arguments": arguments}
+    })
+}
+
+#[test]
+fn test_blackboard_store() {
+    let board = Blackboard::new();
+    assert!(board.is_empty());
+
+    board.set(
+        "findings/parser",
+        json!("off-by-one in lexer"),
+        "orchestrator",
+    );
+    board.set("findings/codegen", json!({"ok": true}), "orchestrator");
+    board.set("plan", json!(["a", "b"]), "orchestrator");
+
+    let shared = board.clone();
+    assert_eq!(shared.len(), 3);
+    assert_eq!(shared.get("plan").unwrap().value, json!(["a", "b"]));
+    assert_eq!(
+        shared.keys("findings/"),
+        vec!["findings/codegen", "findings/parser"]
+    );
+    assert_eq!(shared.keys("").len(), 3);
+
+    assert!(board.remove("plan").is_some());
+    assert!(shared.get("plan").is_none());
+}
+
+#[test]
+fn test_blackboard_claim_keeps_first_holder() {
+    let board = Blackboard::new();
+
+    assert!(board.claim("file:src/lib.rs", json!(1), "agent-1").is_ok());
+    let held = board
+        .claim("file:src/lib.rs", json!(2), "agent-2")
+        .unwrap_err();
+    assert_eq!(held.author, "agent-1");
+    // The holder may claim again; a plain `set` still overwrites
+    assert!(board.claim("file:src/lib.rs", json!(3), "agent-1").is_ok());
+    assert_eq!(board.get("file:src/lib.rs").unwrap().value, json!(3));
+}
+
+#[tokio::test]
+async fn test_blackboard_claim_through_mcp() {
+    let board = Blackboard::new();
+    let first = board.mcp_server("agent-1");
+    let second = board.mcp_server("agent-2");
+
+    let claimed = first
+        .handle_message(call("claim", json!({"key": "file:src/lib.rs"})))
+        .await
+        .unwrap();
+    assert_eq!(
+        claimed["result"]["content"][0]["text"],
+        "Claimed file:src/lib.rs"
+    );
+    let refused = second
+        .handle_message(call("claim", json!({"key": "file:src/lib.rs"})))
+        .await
+        .unwrap();
+    assert_eq!(refused["result"]["isError"], true);
+    assert_eq!(
+        refused["result"]["content"][0]["text"],
+        "file:src/lib.rs is held by agent-1"
+    );
+    assert_eq!(board.get("file:src/lib.rs").unwrap().author, "agent-1");
+}
+
+#[tokio::test]
+async fn test_blackboard_mcp_server() {
+    let board = Blackboard::new();
+    let server = board.mcp_server("agent-1");
+    assert_eq!(server.name(), "blackboard");
+
+    let stored = server
+        .handle_message(call("set", json!({"key": "result", "value": 42})))
+        .await
+        .unwrap();
+    assert_eq!(stored["result"]["content"][0]["text"], "Stored result");
+
+    let entry = board.get("result").unwrap();
+    assert_eq!(entry.value, json!(42));
+    assert_eq!(entry.author, "agent-1");
+
+    let read = server
+        .handle_message(call("get", json!({"key": "result"})))
+        .await
+        .unwrap();
+    let text = read["result"]["content"][0]["text"].as_str().unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(text).unwrap()["value"],
+        42
+    );
+
+    let missing = server
+        .handle_message(call("get", json!({"key": "nothing"})))
+        .await
+        .unwrap();
+    assert_eq!(missing["result"]["isError"], true);
+
+    let listed = server
+        .handle_message(call("list", json!({})))
+        .await
+        .unwrap();
+    let text = listed["result"]["content"][0]["text"].as_str().unwrap();
+    let listing: serde_json::Value = serde_json::from_str(text).unwrap();
+    assert_eq!(listing[0]["key"], "result");
+    assert_eq!(listing[0]["author"], "agent-1");
+}
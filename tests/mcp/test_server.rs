@@ -0,0 +1,110 @@
+//! Unit tests for `SdkMcpServer`
+
+use kodegen_claude_agent::ClaudeError;
+use kodegen_claude_agent::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
+use serde_json::json;
+
+fn calculator() -> SdkMcpServer {
+    SdkMcpServer::new("calculator")
+        .version("2.1.0")
+        .tool(SdkMcpTool::new(
+            "add",
+            "Add two numbers",
+            json!({"type": "object", "properties": {
+                "a": {"type": "number"},
+                "b": {"type": "number"}
+            }}),
+            |input| {
+                Box::pin(async move {
+                    let sum =
+                        input["a"].as_f64().unwrap_or(0.0) + input["b"].as_f64().unwrap_or(0.0);
+                    Ok(ToolResult::text(format!("Sum: {sum}")))
+                })
+            },
+        ))
+        .tool(SdkMcpTool::new(
+            "fail",
+            "Always fails",
+            json!({"type": "object"}),
+            |_| Box::pin(async { Err(ClaudeError::invalid_config("boom")) }),
+        ))
+}
+
+#[tokio::test]
+async fn test_initialize_and_list_tools() {
+    let server = calculator();
+
+    let init = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}))
+        .await
+        .unwrap();
+    assert_eq!(init["id"], 1);
+    assert_eq!(init["result"]["serverInfo"]["name"], "calculator");
+    assert_eq!(init["result"]["serverInfo"]["version"], "2.1.0");
+
+    let list = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+        .await
+        .unwrap();
+    let tools = list["result"]["tools"].as_array().unwrap();
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0]["name"], "add");
+    assert_eq!(tools[0]["inputSchema"]["properties"]["a"]["type"], "number");
+
+    assert_eq!(
+        server.tool_names(),
+        vec!["mcp__calculator__add", "mcp__calculator__fail"]
+    );
+}
+
+#[tokio::test]
+async fn test_tool_calls() {
+    let server = calculator();
+
+    let sum = server
+        .handle_message(json!({
+            "jsonrpc": "2.0", "id": "a", "method": "tools/call",
+            "params": {"name": "add", "arguments": {"a": 2, "b": 3}}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(sum["id"], "a");
+    assert_eq!(sum["result"]["content"][0]["text"], "Sum: 5");
+    assert!(sum["result"].get("isError").is_none());
+
+    let failed = server
+        .handle_message(json!({
+            "jsonrpc": "2.0", "id": "b", "method": "tools/call",
+            "params": {"name": "fail", "arguments": {}}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(failed["result"]["isError"], true);
+
+    let unknown = server
+        .handle_message(json!({
+            "jsonrpc": "2.0", "id": "c", "method": "tools/call",
+            "params": {"name": "multiply"}
+        }))
+        .await
+        .unwrap();
+    assert_eq!(unknown["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn test_notifications_and_unknown_methods() {
+    let server = calculator();
+
+    assert!(
+        server
+            .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .is_none()
+    );
+
+    let unknown = server
+        .handle_message(json!({"jsonrpc": "2.0", "id": 9, "method": "resources/list"}))
+        .await
+        .unwrap();
+    assert_eq!(unknown["error"]["code"], -32601);
+}
@@ -0,0 +1,3 @@
+//! MCP tests - mirrors src/mcp/
+
+mod mcp;
@@ -29,3 +29,26 @@ fn test_parse_invalid_message() {
     let result = parse_message(data);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_parse_compact_boundary() {
+    use kodegen_claude_agent::{CompactBoundary, CompactTrigger};
+
+    let message = parse_message(json!({
+        "type": "system",
+        "subtype": "compact_boundary",
+        "session_id": "s1",
+        "compact_metadata": {"trigger": "auto", "pre_tokens": 150_000}
+    }))
+    .unwrap();
+    assert_eq!(
+        message.compact_boundary(),
+        Some(CompactBoundary {
+            trigger: CompactTrigger::Auto,
+            pre_tokens: Some(150_000),
+        })
+    );
+
+    let init = parse_message(json!({"type": "system", "subtype": "init"})).unwrap();
+    assert_eq!(init.compact_boundary(), None);
+}
@@ -1,3 +1,11 @@
 //! Permissions module tests
 
 pub mod test_permissions;
+pub mod test_bash_guard;
+pub mod test_grants;
+pub mod test_policy;
+pub mod test_presets;
+pub mod test_rate_limits;
+pub mod test_remembered;
+pub mod test_sandbox;
+pub mod test_telemetry;
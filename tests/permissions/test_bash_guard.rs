@@ -0,0 +1,143 @@
+//! Unit tests for `BashGuard`
+
+use kodegen_claude_agent::permissions::{BashGuard, PermissionManager};
+use kodegen_claude_agent::{PermissionResult, ToolName, ToolPermissionContext};
+use serde_json::json;
+
+#[test]
+fn test_denied_flags_in_any_list_segment() {
+    let guard = BashGuard::new().deny_flag("rm", "-rf").deny_flag("git", "--force");
+
+    assert!(guard.check("rm file.txt").is_ok());
+    assert!(guard.check("ls; rm -rf /").is_err());
+    assert!(guard.check("ls && rm -f -r build").is_err());
+    assert!(guard.check("true || /bin/rm -fr build").is_err());
+    assert!(guard.check("git push --force=true origin").is_err());
+    assert!(guard.check("git push --force-with-lease").is_ok());
+    // After `--` words are operands, not flags
+    assert!(guard.check("rm -- -rf").is_ok());
+}
+
+#[test]
+fn test_flag_sets_subcommands_and_prefixes() {
+    let guard = BashGuard::new()
+        .deny_flags("rm", &[&["-r", "-R", "--recursive"], &["-f", "--force"]])
+        .deny_flag("git push", "-f")
+        .deny_arg_prefix("git push", "+");
+
+    assert_eq!(
+        guard.check("rm -R --force build"),
+        Err("Flags `-R` and `--force` of `rm` are not allowed together".to_string())
+    );
+    assert!(guard.check("rm -r build").is_ok());
+    assert!(guard.check("git push -f").is_err());
+    assert!(guard.check("git clean -f").is_ok());
+    assert_eq!(
+        guard.check("git push origin +main"),
+        Err("Argument `+main` of `git` is not allowed".to_string())
+    );
+}
+
+#[test]
+fn test_quotes_are_tokenized() {
+    let guard = BashGuard::new().deny(["rm"]);
+
+    assert!(guard.check("echo 'rm -rf /; rm x'").is_ok());
+    assert!(guard.check("echo \"a; rm x\"").is_ok());
+    assert!(guard.check(r"echo a\; rm x").is_ok());
+    assert!(guard.check("echo 'unterminated").is_err());
+}
+
+#[test]
+fn test_allowlist_and_wrappers() {
+    let guard = BashGuard::new().allow(["ls", "cargo", "env", "grep"]);
+
+    assert!(guard.check("RUST_LOG=debug cargo test 2>&1 | grep FAILED > out.txt").is_ok());
+    assert!(guard.check("env -i cargo build").is_ok());
+    assert!(guard.check("env python3 -c 1").is_err());
+    assert!(guard.check("curl example.com").is_err());
+}
+
+#[test]
+fn test_substitution_is_blocked_by_default() {
+    let guard = BashGuard::new();
+
+    assert!(guard.check("echo $(whoami)").is_err());
+    assert!(guard.check("echo \"`whoami`\"").is_err());
+    assert!(guard.check("diff <(ls a) <(ls b)").is_err());
+    assert!(guard.check("echo '$(whoami)'").is_ok());
+    assert!(guard.check("echo $HOME").is_ok());
+    assert!(guard.allow_substitution(true).check("echo $(whoami)").is_ok());
+}
+
+#[test]
+fn test_expansions_in_checked_words_are_blocked_by_default() {
+    let guard = BashGuard::new().deny(["sudo"]).deny_flag("rm", "-rf");
+
+    assert!(guard.check("x=rm; $x -rf /").is_err());
+    assert!(guard.check("rm${IFS}-rf${IFS}/").is_err());
+    assert!(guard.check(r"$'\x72m' -rf /").is_err());
+    assert!(guard.check("\"$SHELL\" build.sh").is_err());
+    assert!(guard.check("rm -r$F /").is_err());
+    assert!(guard.check("nice -n 5 $CMD").is_err());
+    // Single quotes, literal `$` and unchecked arguments are fine
+    assert!(guard.check("echo '$x' $HOME").is_ok());
+    assert!(guard.check("rm -r '$x' $ build").is_ok());
+    assert!(guard.allow_substitution(true).check("$x -rf /").is_ok());
+}
+
+#[test]
+fn test_command_strings_are_blocked_by_default() {
+    let guard = BashGuard::new();
+
+    assert!(guard.check("sh -c 'rm -rf /'").is_err());
+    assert!(guard.check("/bin/bash -lc 'sudo reboot'").is_err());
+    assert!(guard.check("ls | xargs zsh -c 'echo {}'").is_err());
+    assert!(guard.check("eval \"$CMD\"").is_err());
+    assert!(guard.check("env -S 'sudo reboot'").is_err());
+    assert!(guard.check("env -u HOME -S 'sudo reboot'").is_err());
+    assert!(guard.check("bash build.sh").is_ok());
+    assert!(guard.allow_substitution(true).check("sh -c 'make all'").is_ok());
+}
+
+#[test]
+fn test_wrapper_option_values_are_skipped() {
+    let guard = BashGuard::new().deny(["sudo", "reboot"]);
+
+    assert!(guard.check("nice -n 5 sudo reboot").is_err());
+    assert!(guard.check("nice --adjustment 5 reboot").is_err());
+    assert!(guard.check("time -o times.txt reboot").is_err());
+    assert!(guard.check("xargs -n 1 -I {} reboot").is_err());
+    assert!(guard.check("env -u HOME -C /tmp reboot").is_err());
+    assert!(guard.check("exec -a init reboot").is_err());
+    assert!(guard.check("nohup -- reboot").is_err());
+    assert!(guard.check("nice -n 5 cargo build").is_ok());
+}
+
+#[tokio::test]
+async fn test_guard_callback() {
+    let mut manager = PermissionManager::new();
+    manager.set_callback(BashGuard::new().deny(["sudo"]).callback());
+
+    let ask = |tool: &str, input: serde_json::Value| {
+        manager.can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+    };
+    match ask("Bash", json!({"command": "sudo reboot"})).await.unwrap() {
+        PermissionResult::Deny(deny) => assert_eq!(deny.message, "Command `sudo` is not allowed"),
+        other => panic!("Expected deny, got {other:?}"),
+    }
+    assert!(matches!(
+        ask("Bash", json!({"command": "ls"})).await.unwrap(),
+        PermissionResult::Allow(_)
+    ));
+    assert!(matches!(
+        ask("Read", json!({"file_path": "a"})).await.unwrap(),
+        PermissionResult::Allow(_)
+    ));
+}
@@ -0,0 +1,91 @@
+//! Unit tests for time-limited permission grants
+
+use std::time::Duration;
+
+use kodegen_claude_agent::permissions::PermissionManager;
+use kodegen_claude_agent::{
+    PermissionPolicy, PermissionResult, PolicyAction, PolicyRule, ToolName, ToolPermissionContext,
+};
+use serde_json::json;
+
+/// Result of asking `manager` about `tool` with `input`
+async fn ask(
+    manager: &PermissionManager,
+    tool: &str,
+    input: serde_json::Value,
+) -> PermissionResult {
+    manager
+        .can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap()
+}
+
+/// Manager whose policy denies everything
+fn deny_all() -> PermissionManager {
+    let mut manager = PermissionManager::new();
+    manager.set_policy(Some(PermissionPolicy {
+        default: PolicyAction::Deny,
+        rules: vec![],
+    }));
+    manager
+}
+
+#[tokio::test]
+async fn test_grant_allows_until_expiry() {
+    let manager = deny_all();
+    manager.grant_tool("Bash", Duration::from_millis(100));
+
+    assert!(manager.has_grant_for("Bash", &json!({})));
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "ls"})).await,
+        PermissionResult::Allow(_)
+    ));
+    assert!(matches!(
+        ask(&manager, "Write", json!({})).await,
+        PermissionResult::Deny(_)
+    ));
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert!(manager.grants().is_empty());
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "ls"})).await,
+        PermissionResult::Deny(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_newest_grant_wins() {
+    let manager = deny_all();
+    manager.grant_tool("Bash", Duration::from_secs(60));
+    manager.grant(
+        PolicyRule {
+            tool: "Bash".to_string(),
+            input: [("command".to_string(), "rm *".to_string())].into(),
+            action: PolicyAction::Deny,
+            message: Some("No deletions".to_string()),
+        },
+        Duration::from_secs(60),
+    );
+
+    match ask(&manager, "Bash", json!({"command": "rm -rf /"})).await {
+        PermissionResult::Deny(deny) => assert_eq!(deny.message, "No deletions"),
+        other => panic!("Expected deny, got {other:?}"),
+    }
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "ls"})).await,
+        PermissionResult::Allow(_)
+    ));
+    assert_eq!(manager.grants().len(), 2);
+
+    manager.revoke_grants();
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "ls"})).await,
+        PermissionResult::Deny(_)
+    ));
+}
@@ -23,6 +23,7 @@ async fn test_permission_manager_default_allow() {
     match result {
         PermissionResult::Allow(_) => {}
         PermissionResult::Deny(_) => panic!("Expected allow"),
+        other => panic!("Expected allow, got {other:?}"),
     }
 }
 
@@ -45,6 +46,7 @@ async fn test_permission_manager_disallowed() {
     match result {
         PermissionResult::Allow(_) => panic!("Expected deny"),
         PermissionResult::Deny(_) => {}
+        other => panic!("Expected deny, got {other:?}"),
     }
 }
 
@@ -68,6 +70,7 @@ async fn test_permission_manager_allowed_list() {
     match result {
         PermissionResult::Allow(_) => {}
         PermissionResult::Deny(_) => panic!("Expected allow"),
+        other => panic!("Expected allow, got {other:?}"),
     }
 
     // Should deny other_tool
@@ -85,5 +88,22 @@ async fn test_permission_manager_allowed_list() {
     match result {
         PermissionResult::Allow(_) => panic!("Expected deny"),
         PermissionResult::Deny(_) => {}
+        other => panic!("Expected deny, got {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_permission_manager_can_decide() {
+    let mut manager = PermissionManager::new();
+    let tool = ToolName::new("Bash");
+    let input = serde_json::json!({"command": "ls"});
+    assert!(!manager.can_decide(&tool, &input).await);
+
+    manager.grant_tool("Bash", std::time::Duration::from_secs(60));
+    assert!(manager.can_decide(&tool, &input).await);
+    assert!(!manager.can_decide(&ToolName::new("Read"), &input).await);
+
+    manager.revoke_grants();
+    manager.set_rate_limit(tool.clone(), 0, std::time::Duration::from_secs(60));
+    assert!(manager.can_decide(&tool, &input).await);
+}
@@ -0,0 +1,149 @@
+//! Unit tests for `PermissionPolicy`
+
+use kodegen_claude_agent::permissions::PermissionManager;
+use kodegen_claude_agent::{
+    PermissionPolicy, PermissionResult, PolicyAction, ToolName, ToolPermissionContext,
+};
+use serde_json::json;
+
+const POLICY: &str = r#"{
+    "default": "ask",
+    "rules": [
+        {"tool": "Read|Glob", "action": "allow"},
+        {"tool": "Bash", "input": {"command": "rm *"}, "action": "deny", "message": "No deletions"},
+        {"tool": "Bash", "input": {"command": "git ?tatus*"}, "action": "allow"}
+    ]
+}"#;
+
+/// Result of asking `manager` about `tool` with `input`
+async fn ask(
+    manager: &PermissionManager,
+    tool: &str,
+    input: serde_json::Value,
+) -> PermissionResult {
+    manager
+        .can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[test]
+fn test_policy_evaluates_first_matching_rule() {
+    let policy = PermissionPolicy::from_json(POLICY).unwrap();
+
+    assert_eq!(policy.evaluate("Read", &json!({})), PolicyAction::Allow);
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "rm -rf target"})),
+        PolicyAction::Deny
+    );
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "rm */cache"})),
+        PolicyAction::Deny
+    );
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "git status --short"})),
+        PolicyAction::Allow
+    );
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "cargo build"})),
+        PolicyAction::Ask
+    );
+    assert_eq!(policy.evaluate("Write", &json!({})), PolicyAction::Ask);
+}
+
+#[tokio::test]
+async fn test_policy_applies_in_permission_manager() {
+    let mut manager = PermissionManager::new();
+    manager.set_policy(Some(PermissionPolicy::from_json(POLICY).unwrap()));
+
+    assert!(matches!(
+        ask(&manager, "Glob", json!({})).await,
+        PermissionResult::Allow(_)
+    ));
+    match ask(&manager, "Bash", json!({"command": "rm -rf /"})).await {
+        PermissionResult::Deny(deny) => assert_eq!(deny.message, "No deletions"),
+        other => panic!("Expected deny, got {other:?}"),
+    }
+    // `ask` without a callback cannot be approved
+    assert!(matches!(
+        ask(&manager, "Write", json!({})).await,
+        PermissionResult::Deny(_)
+    ));
+
+    manager.set_callback(PermissionManager::callback(
+        |_tool, _input, _context| async {
+            Ok(PermissionResult::Allow(
+                kodegen_claude_agent::PermissionResultAllow {
+                    updated_input: None,
+                    updated_permissions: None,
+                },
+            ))
+        },
+    ));
+    assert!(matches!(
+        ask(&manager, "Write", json!({})).await,
+        PermissionResult::Allow(_)
+    ));
+}
+
+#[test]
+fn test_policy_validation_names_rule() {
+    let err = PermissionPolicy::from_json(
+        r#"{"rules": [{"tool": "Read", "action": "allow"}, {"tool": "Bash|", "action": "deny"}]}"#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("rule 2"), "{err}");
+
+    let err = PermissionPolicy::from_json(r#"{"rules": [{"tool": "Read", "action": "maybe"}]}"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("maybe"), "{err}");
+}
+
+#[test]
+fn test_policy_from_path() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let json_path = dir.path().join("policy.json");
+    std::fs::write(&json_path, POLICY).unwrap();
+    let policy = PermissionPolicy::from_path(&json_path).unwrap();
+    assert_eq!(policy.rules.len(), 3);
+
+    let yaml_path = dir.path().join("policy.yaml");
+    std::fs::write(&yaml_path, "rules: []").unwrap();
+    assert!(PermissionPolicy::from_path(&yaml_path).is_err());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_policy_from_toml() {
+    let policy = PermissionPolicy::from_toml(
+        r#"
+        default = "deny"
+
+        [[rules]]
+        tool = "Bash"
+        input = { command = "ls*" }
+        action = "allow"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(policy.default, PolicyAction::Deny);
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "ls -la"})),
+        PolicyAction::Allow
+    );
+    assert_eq!(
+        policy.evaluate("Bash", &json!({"command": "pwd"})),
+        PolicyAction::Deny
+    );
+
+    let err =
+        PermissionPolicy::from_toml("[[rules]]\ntool = \"\"\naction = \"deny\"\n").unwrap_err();
+    assert!(err.to_string().contains("rule 1"), "{err}");
+}
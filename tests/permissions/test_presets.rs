@@ -0,0 +1,96 @@
+//! Unit tests for permission presets
+
+use kodegen_claude_agent::permissions::{PermissionManager, PermissionManagerBuilder, Preset};
+use kodegen_claude_agent::{PermissionResult, ToolName, ToolPermissionContext};
+use serde_json::json;
+
+/// Whether `manager` allows `tool` with `input`
+async fn allows(manager: &PermissionManager, tool: &str, input: serde_json::Value) -> bool {
+    let result = manager
+        .can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    matches!(result, PermissionResult::Allow(_))
+}
+
+#[tokio::test]
+async fn test_read_only_preset() {
+    let manager = PermissionManagerBuilder::new()
+        .preset(Preset::ReadOnly)
+        .build();
+
+    assert!(manager.has_callback());
+    assert!(allows(&manager, "Read", json!({"file_path": "a"})).await);
+    assert!(allows(&manager, "Grep", json!({"pattern": "x"})).await);
+    assert!(!allows(&manager, "Write", json!({"file_path": "a"})).await);
+    assert!(!allows(&manager, "Bash", json!({"command": "ls"})).await);
+    assert!(!allows(&manager, "mcp__db__query", json!({})).await);
+}
+
+#[tokio::test]
+async fn test_read_only_callback_denies_without_tool_lists() {
+    let mut manager = PermissionManager::new();
+    manager.set_callback(Preset::ReadOnly.callback());
+
+    assert!(allows(&manager, "Read", json!({"file_path": "a"})).await);
+    assert!(!allows(&manager, "Write", json!({"file_path": "a"})).await);
+    assert!(!allows(&manager, "Bash", json!({"command": "ls"})).await);
+}
+
+#[tokio::test]
+async fn test_dev_preset_guards_bash() {
+    let manager = PermissionManagerBuilder::new().preset(Preset::Dev).build();
+
+    assert!(allows(&manager, "Write", json!({"file_path": "a"})).await);
+    assert!(allows(&manager, "Bash", json!({"command": "cargo test"})).await);
+    assert!(!allows(&manager, "Bash", json!({"command": "sudo rm x"})).await);
+    assert!(!allows(&manager, "Bash", json!({"command": "rm -rf target"})).await);
+    assert!(!allows(&manager, "Bash", json!({"command": "git push --force"})).await);
+}
+
+#[tokio::test]
+async fn test_dev_preset_denies_every_spelling() {
+    let manager = PermissionManagerBuilder::new().preset(Preset::Dev).build();
+    let bash = |command: &str| allows(&manager, "Bash", json!({ "command": command }));
+
+    for command in [
+        "rm -R -f target",
+        "rm -Rf target",
+        "rm --recursive --force target",
+        "rm -r --force target",
+        "git push -f origin main",
+        "git push --force-with-lease origin main",
+        "git push origin +main",
+        "git -C repo push -f",
+        "x=rm; $x -rf /",
+        "rm${IFS}-rf${IFS}/",
+        r"$'\x72m' -rf /",
+    ] {
+        assert!(!bash(command).await, "{command} should be denied");
+    }
+    for command in [
+        "rm -r target",
+        "rm -f a.txt",
+        "git push origin main",
+        "git clean -f",
+    ] {
+        assert!(bash(command).await, "{command} should be allowed");
+    }
+}
+
+#[tokio::test]
+async fn test_unrestricted_preset_and_overrides() {
+    let manager = PermissionManagerBuilder::new()
+        .preset(Preset::Unrestricted)
+        .disallowed_tools(vec![ToolName::new("WebFetch")])
+        .build();
+
+    assert!(allows(&manager, "Bash", json!({"command": "sudo reboot"})).await);
+    assert!(!allows(&manager, "WebFetch", json!({})).await);
+}
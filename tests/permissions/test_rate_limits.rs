@@ -0,0 +1,86 @@
+//! Unit tests for per-tool rate limits
+
+use std::time::Duration;
+
+use kodegen_claude_agent::permissions::{PermissionManager, PermissionManagerBuilder};
+use kodegen_claude_agent::{
+    PermissionPolicy, PermissionResult, PolicyAction, PolicyRule, ToolName, ToolPermissionContext,
+};
+use serde_json::json;
+
+/// Result of asking `manager` about `tool` with `input`
+async fn ask(
+    manager: &PermissionManager,
+    tool: &str,
+    input: serde_json::Value,
+) -> PermissionResult {
+    manager
+        .can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_rate_limit_denies_beyond_limit() {
+    let manager = PermissionManagerBuilder::new()
+        .rate_limit(ToolName::new("Bash"), 2, Duration::from_millis(200))
+        .build();
+
+    for _ in 0..2 {
+        assert!(matches!(
+            ask(&manager, "Bash", json!({})).await,
+            PermissionResult::Allow(_)
+        ));
+    }
+    assert!(manager.is_rate_limited(&ToolName::new("Bash")));
+    match ask(&manager, "Bash", json!({})).await {
+        PermissionResult::Deny(deny) => {
+            assert!(deny.message.contains("rate limited"), "{}", deny.message)
+        }
+        other => panic!("Expected deny, got {other:?}"),
+    }
+    // Other tools are not limited
+    assert!(matches!(
+        ask(&manager, "Read", json!({})).await,
+        PermissionResult::Allow(_)
+    ));
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(matches!(
+        ask(&manager, "Bash", json!({})).await,
+        PermissionResult::Allow(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_denied_calls_do_not_count() {
+    let manager = PermissionManagerBuilder::new()
+        .rate_limit(ToolName::new("Bash"), 1, Duration::from_secs(60))
+        .policy(PermissionPolicy {
+            default: PolicyAction::Allow,
+            rules: vec![PolicyRule {
+                tool: "Bash".to_string(),
+                input: [("command".to_string(), "rm *".to_string())].into(),
+                action: PolicyAction::Deny,
+                message: None,
+            }],
+        })
+        .build();
+
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "rm -rf /"})).await,
+        PermissionResult::Deny(_)
+    ));
+    assert!(!manager.is_rate_limited(&ToolName::new("Bash")));
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "ls"})).await,
+        PermissionResult::Allow(_)
+    ));
+    assert!(manager.is_rate_limited(&ToolName::new("Bash")));
+}
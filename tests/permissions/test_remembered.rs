@@ -0,0 +1,167 @@
+//! Unit tests for answers the permission callback asks to remember
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use kodegen_claude_agent::permissions::PermissionManager;
+use kodegen_claude_agent::{
+    PermissionResult, PermissionResultDeny, ToolName, ToolPermissionContext,
+};
+use serde_json::json;
+
+/// Result of asking `manager` about `tool` with `input`
+async fn ask(
+    manager: &PermissionManager,
+    tool: &str,
+    input: serde_json::Value,
+) -> PermissionResult {
+    manager
+        .can_use_tool(
+            ToolName::new(tool),
+            input,
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap()
+}
+
+/// Manager whose callback allows Read always and denies `rm` commands always,
+/// counting how often it is asked
+fn counting_manager(calls: Arc<AtomicUsize>) -> PermissionManager {
+    let mut manager = PermissionManager::new();
+    manager.set_callback(PermissionManager::callback(
+        move |tool: ToolName, _input, _context| {
+            let calls = Arc::clone(&calls);
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(if tool.as_str() == "Read" {
+                    PermissionResult::AllowAlways {
+                        input: BTreeMap::new(),
+                    }
+                } else {
+                    PermissionResult::DenyAlways {
+                        message: "No deletions".to_string(),
+                        input: BTreeMap::from([("command".to_string(), "rm *".to_string())]),
+                    }
+                })
+            }
+        },
+    ));
+    manager
+}
+
+#[tokio::test]
+async fn test_allow_always_is_remembered() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let manager = counting_manager(Arc::clone(&calls));
+
+    for path in ["a.rs", "b.rs", "c.rs"] {
+        assert!(matches!(
+            ask(&manager, "Read", json!({"file_path": path})).await,
+            PermissionResult::Allow(_)
+        ));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(manager.remembered().len(), 1);
+}
+
+#[tokio::test]
+async fn test_deny_always_is_remembered_per_input_pattern() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let manager = counting_manager(Arc::clone(&calls));
+
+    for command in ["rm -rf target", "rm Cargo.lock"] {
+        match ask(&manager, "Bash", json!({"command": command})).await {
+            PermissionResult::Deny(deny) => assert_eq!(deny.message, "No deletions"),
+            other => panic!("Expected deny, got {other:?}"),
+        }
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // A command outside the remembered pattern asks again
+    ask(&manager, "Bash", json!({"command": "ls"})).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_clear_remembered_asks_again() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let manager = counting_manager(Arc::clone(&calls));
+
+    ask(&manager, "Read", json!({})).await;
+    manager.clear_remembered();
+    assert!(manager.remembered().is_empty());
+    ask(&manager, "Read", json!({})).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_always_results_serialize() {
+    let result = PermissionResult::DenyAlways {
+        message: "No".to_string(),
+        input: BTreeMap::new(),
+    };
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        json!({"type": "denyAlways", "message": "No"})
+    );
+}
+
+#[tokio::test]
+async fn test_remembered_answer_does_not_skip_bash_guard() {
+    use kodegen_claude_agent::permissions::BashGuard;
+
+    // The inner callback allows every call, always
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&calls);
+    let next = PermissionManager::callback(move |_tool: ToolName, _input, _context| {
+        counted.fetch_add(1, Ordering::SeqCst);
+        async {
+            Ok(PermissionResult::AllowAlways {
+                input: BTreeMap::new(),
+            })
+        }
+    });
+    let mut manager = PermissionManager::new();
+    manager.set_callback(BashGuard::new().deny(["rm"]).callback_then(next));
+
+    for _ in 0..2 {
+        assert!(matches!(
+            ask(&manager, "Bash", json!({"command": "ls"})).await,
+            PermissionResult::Allow(_)
+        ));
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // The guard still sees every command
+    assert!(matches!(
+        ask(&manager, "Bash", json!({"command": "rm -rf /"})).await,
+        PermissionResult::Deny(_)
+    ));
+    assert!(manager.remembered().is_empty());
+}
+
+#[tokio::test]
+async fn test_set_callback_forgets_remembered_answers() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut manager = counting_manager(Arc::clone(&calls));
+    ask(&manager, "Read", json!({})).await;
+    assert_eq!(manager.remembered().len(), 1);
+
+    manager.set_callback(PermissionManager::callback(
+        |_tool, _input, _context| async {
+            Ok(PermissionResult::Deny(PermissionResultDeny {
+                message: "No".to_string(),
+                interrupt: false,
+            }))
+        },
+    ));
+    assert!(manager.remembered().is_empty());
+    assert!(matches!(
+        ask(&manager, "Read", json!({})).await,
+        PermissionResult::Deny(_)
+    ));
+}
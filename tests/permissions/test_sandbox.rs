@@ -0,0 +1,146 @@
+//! Unit tests for `PathSandbox`
+
+use kodegen_claude_agent::permissions::{PathSandbox, PermissionManagerBuilder};
+use kodegen_claude_agent::{PermissionResult, ToolName, ToolPermissionContext};
+use serde_json::json;
+
+#[test]
+fn test_sandbox_checks_path_fields() {
+    let dir = tempfile::tempdir().unwrap();
+    let extra = tempfile::tempdir().unwrap();
+    let sandbox = PathSandbox::new(dir.path(), [extra.path()]);
+
+    assert_eq!(
+        sandbox.violation(&json!({"file_path": "src/main.rs"})),
+        None
+    );
+    assert_eq!(
+        sandbox.violation(&json!({"path": extra.path().join("notes.md")})),
+        None
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"file_path": "../outside.rs"}))
+            .is_some()
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"notebook_path": "/etc/passwd"}))
+            .is_some()
+    );
+    assert!(!sandbox.contains("sub/../../escape"));
+}
+
+#[test]
+fn test_sandbox_checks_command_words() {
+    let dir = tempfile::tempdir().unwrap();
+    let sandbox = PathSandbox::new(dir.path(), Vec::<std::path::PathBuf>::new());
+
+    assert_eq!(
+        sandbox.violation(&json!({"command": "cargo test --manifest-path=./Cargo.toml"})),
+        None
+    );
+    assert_eq!(
+        sandbox.violation(&json!({"command": "curl https://example.com/a/b"})),
+        None
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"command": "cat /etc/shadow"}))
+            .is_some()
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"command": "echo hi >/tmp/x"}))
+            .is_some()
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"command": "ls && rm -rf ../*"}))
+            .is_some()
+    );
+    // Quotes and escapes are removed as the shell would
+    assert!(
+        sandbox
+            .violation(&json!({"command": "cat '/etc/my file' a\\ b"}))
+            .is_some_and(|path| path.ends_with("etc/my file"))
+    );
+}
+
+#[test]
+fn test_sandbox_skips_executables_and_device_redirects() {
+    let dir = tempfile::tempdir().unwrap();
+    let sandbox = PathSandbox::new(dir.path(), Vec::<std::path::PathBuf>::new());
+
+    for command in [
+        "cargo build 2>/dev/null",
+        "cargo build > /dev/null 2>&1",
+        "/usr/bin/env python script.py",
+        "RUST_LOG=debug ./target/debug/app",
+        "ls | /usr/bin/sort",
+    ] {
+        assert_eq!(
+            sandbox.violation(&json!({"command": command})),
+            None,
+            "{command}"
+        );
+    }
+    assert!(
+        sandbox
+            .violation(&json!({"command": "/usr/bin/env cat /etc/shadow"}))
+            .is_some()
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"command": "cargo build 2> /tmp/log"}))
+            .is_some()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sandbox_follows_symlinks() {
+    let dir = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+    let sandbox = PathSandbox::new(dir.path(), Vec::<std::path::PathBuf>::new());
+
+    assert!(
+        sandbox
+            .violation(&json!({"file_path": "link/secret"}))
+            .is_some()
+    );
+    assert!(
+        sandbox
+            .violation(&json!({"command": "cat link/secret"}))
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn test_permission_manager_denies_outside_sandbox() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = PermissionManagerBuilder::new()
+        .sandbox(PathSandbox::new(
+            dir.path(),
+            Vec::<std::path::PathBuf>::new(),
+        ))
+        .build();
+
+    let result = manager
+        .can_use_tool(
+            ToolName::new("Write"),
+            json!({"file_path": "/etc/hosts", "content": ""}),
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    match result {
+        PermissionResult::Deny(deny) => {
+            assert!(deny.message.contains("outside"), "{}", deny.message)
+        }
+        other => panic!("Expected deny, got {other:?}"),
+    }
+}
@@ -0,0 +1,77 @@
+//! Unit tests for permission telemetry
+
+use kodegen_claude_agent::permissions::PermissionManagerBuilder;
+use kodegen_claude_agent::{
+    DecisionSource, PermissionPolicy, PermissionResult, ToolName, ToolPermissionContext,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_decisions_are_announced_and_counted() {
+    let manager = PermissionManagerBuilder::new()
+        .disallowed_tools(vec![ToolName::new("WebFetch")])
+        .policy(
+            PermissionPolicy::from_json(
+                r#"{"default": "allow", "rules": [
+                    {"tool": "Bash", "input": {"command": "rm *"}, "action": "deny", "message": "No deletions"}
+                ]}"#,
+            )
+            .unwrap(),
+        )
+        .build();
+    let mut events = manager.subscribe();
+
+    for (tool, input) in [
+        ("Read", json!({})),
+        ("Bash", json!({"command": "rm -rf /"})),
+        ("Bash", json!({"command": "ls"})),
+        ("WebFetch", json!({})),
+    ] {
+        let result = manager
+            .can_use_tool(
+                ToolName::new(tool),
+                input,
+                ToolPermissionContext {
+                    suggestions: vec![],
+                },
+            )
+            .await
+            .unwrap();
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.tool_name, tool);
+        assert_eq!(event.allowed, matches!(result, PermissionResult::Allow(_)));
+    }
+
+    let stats = manager.stats();
+    assert_eq!((stats.allowed, stats.denied), (2, 2));
+    assert_eq!(stats.by_tool["Bash"].allowed, 1);
+    assert_eq!(stats.by_tool["Bash"].denied, 1);
+    assert_eq!(stats.denied_by[&DecisionSource::Policy], 1);
+    assert_eq!(stats.denied_by[&DecisionSource::DisallowedTools], 1);
+
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["denied_by"]["disallowed_tools"], 1);
+}
+
+#[tokio::test]
+async fn test_denial_event_carries_reason_and_source() {
+    let manager = PermissionManagerBuilder::new()
+        .disallowed_tools(vec![ToolName::new("Bash")])
+        .build();
+    let mut events = manager.subscribe();
+
+    manager
+        .can_use_tool(
+            ToolName::new("Bash"),
+            json!({}),
+            ToolPermissionContext {
+                suggestions: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    let event = events.recv().await.unwrap();
+    assert!(!event.allowed);
+    assert_eq!(event.source, DecisionSource::DisallowedTools);
+    assert_eq!(event.reason.as_deref(), Some("Tool Bash is disallowed"));
+}
@@ -0,0 +1,43 @@
+//! Schema tests - mirrors src/schema.rs
+
+use kodegen_claude_agent::schema::{schemas, write_schemas};
+
+#[test]
+fn test_schemas_cover_public_json_types() {
+    let schemas = schemas();
+    for name in [
+        "SerializedMessage",
+        "GetOutputResponse",
+        "AgentInfo",
+        "ListSessionsResponse",
+        "TerminateResponse",
+        "SessionHealth",
+        "ControlMessage",
+        "Capabilities",
+    ] {
+        assert!(schemas.contains_key(name), "missing schema for {name}");
+    }
+
+    let properties = schemas["SerializedMessage"]
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .unwrap();
+    for field in ["message_type", "content", "timestamp", "turn", "hash"] {
+        assert!(properties.contains_key(field), "missing property {field}");
+    }
+}
+
+#[test]
+fn test_write_schemas() {
+    let dir = std::env::temp_dir().join(format!("kodegen-schema-test-{}", std::process::id()));
+    let written = write_schemas(&dir).unwrap();
+
+    assert_eq!(written.len(), schemas().len());
+    let path = dir.join("AgentInfo.schema.json");
+    assert!(written.contains(&path));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["title"], "AgentInfo");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
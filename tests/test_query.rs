@@ -19,3 +19,314 @@ async fn test_simple_query() {
         }
     }
 }
+
+#[test]
+fn test_parse_structured_output() {
+    use kodegen_claude_agent::{ClaudeError, parse_structured_output};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Capital {
+        country: String,
+        city: String,
+    }
+    let paris = Capital {
+        country: "France".to_string(),
+        city: "Paris".to_string(),
+    };
+
+    let bare = r#" {"country": "France", "city": "Paris"} "#;
+    assert_eq!(parse_structured_output::<Capital>(bare).unwrap(), paris);
+
+    let fenced = "Here you go:\n```json\n{\"country\": \"France\", \"city\": \"Paris\"}\n```\n";
+    assert_eq!(parse_structured_output::<Capital>(fenced).unwrap(), paris);
+
+    let prose = r#"The answer is {"country": "France", "city": "Paris"}."#;
+    assert_eq!(parse_structured_output::<Capital>(prose).unwrap(), paris);
+
+    let list = "Cities: [1, 2, 3]";
+    assert_eq!(
+        parse_structured_output::<Vec<u32>>(list).unwrap(),
+        [1, 2, 3]
+    );
+
+    let wrong_shape = r#"{"country": "France"}"#;
+    assert!(matches!(
+        parse_structured_output::<Capital>(wrong_shape),
+        Err(ClaudeError::JsonDecode(_))
+    ));
+    assert!(parse_structured_output::<Capital>("Paris").is_err());
+}
+
+#[tokio::test]
+async fn test_query_with_mock_transport() {
+    use kodegen_claude_agent::transport::mock::{self, MockTransport};
+    use kodegen_claude_agent::{Message, query_with_transport};
+
+    // Without `finish_after_replies` the stream must end at the result itself
+    let transport =
+        MockTransport::new().reply(vec![mock::assistant_text("4"), mock::result("s1", 1)]);
+    let handle = transport.handle();
+
+    let stream = query_with_transport("What is 2+2?", transport)
+        .await
+        .unwrap();
+    let messages: Vec<_> = stream.map(Result::unwrap).collect().await;
+
+    assert_eq!(messages.len(), 2);
+    assert!(matches!(messages[0], Message::Assistant { .. }));
+    assert!(matches!(messages[1], Message::Result { .. }));
+
+    let written = handle.written_json();
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0]["type"], "user");
+    assert_eq!(written[0]["message"]["content"], "What is 2+2?");
+    assert!(handle.input_ended());
+    assert!(handle.is_closed());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_query_blocking_reports_launch_failure() {
+    use std::sync::Arc;
+
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError, query_blocking};
+
+    // Refuse the launch so no CLI is started
+    let options = ClaudeAgentOptions::builder()
+        .pre_spawn(Arc::new(|_| {
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+    assert!(query_blocking("What is 2+2?", Some(options)).is_err());
+}
+
+#[tokio::test]
+async fn test_query_batch_reports_every_prompt() {
+    use std::sync::Arc;
+
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError, query_batch};
+
+    // Refuse every launch so no CLI is started; each query fails on its own
+    let options = ClaudeAgentOptions::builder()
+        .pre_spawn(Arc::new(|_| {
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+
+    let prompts = ["What is 1+1?", "What is 2+2?", "What is 3+3?"];
+    let results: Vec<_> = query_batch(prompts, Some(options), 2).collect().await;
+
+    let mut indices: Vec<_> = results.iter().map(|(index, _)| *index).collect();
+    indices.sort_unstable();
+    assert_eq!(indices, [0, 1, 2]);
+    assert!(results.iter().all(|(_, messages)| messages.is_err()));
+}
+
+#[tokio::test]
+async fn test_query_text_reports_launch_failure() {
+    use std::sync::Arc;
+
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError, query_text};
+
+    // Refuse the launch so no CLI is started
+    let options = ClaudeAgentOptions::builder()
+        .pre_spawn(Arc::new(|_| {
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+    assert!(query_text("What is 2+2?", Some(options)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_query_continue_resumes_session() {
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError, query_continue};
+
+    // Capture the command line, then refuse the launch so no CLI is started
+    let args = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&args);
+    let options = ClaudeAgentOptions::builder()
+        .continue_conversation(true)
+        .cli_path("claude")
+        .pre_spawn(Arc::new(move |cmd| {
+            *seen.lock().unwrap() = cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+
+    let result = query_continue("session-1", "And in French", Some(options)).await;
+    assert!(result.is_err());
+
+    let args = args.lock().unwrap();
+    let resume = args.iter().position(|arg| arg == "--resume").unwrap();
+    assert_eq!(args[resume + 1], "session-1");
+    assert!(!args.iter().any(|arg| arg == "--continue"));
+}
+
+#[tokio::test]
+async fn test_query_with_sdk_tools_uses_streaming_session() {
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::mcp::{SdkMcpServer, SdkMcpTool, ToolResult};
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError};
+    use serde_json::json;
+
+    let server = SdkMcpServer::new("greeter").tool(SdkMcpTool::new(
+        "greet",
+        "Greet someone",
+        json!({"type": "object"}),
+        |_| Box::pin(async { Ok(ToolResult::text("Hello!")) }),
+    ));
+
+    // Capture the command line, then refuse the launch so no CLI is started
+    let args = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&args);
+    let options = ClaudeAgentOptions::builder()
+        .sdk_mcp_server(server)
+        .cli_path("claude")
+        .pre_spawn(Arc::new(move |cmd| {
+            *seen.lock().unwrap() = cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+
+    assert!(query("Greet Ada", Some(options)).await.is_err());
+
+    let args = args.lock().unwrap();
+    // The prompt is sent over stdin so tool calls can be answered
+    let input = args.iter().position(|arg| arg == "--input-format").unwrap();
+    assert_eq!(args[input + 1], "stream-json");
+    let config = args.iter().position(|arg| arg == "--mcp-config").unwrap();
+    assert!(args[config + 1].contains("greeter"));
+    assert!(!args.iter().any(|arg| arg == "Greet Ada"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_query_reports_progress_before_result() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::transport::mock;
+    use kodegen_claude_agent::{ClaudeAgentOptions, Message};
+
+    // Fake CLI printing two assistant messages and the result
+    let dir = tempfile::tempdir().unwrap();
+    let cli = dir.path().join("claude");
+    let lines = [
+        mock::assistant_text("thinking"),
+        mock::assistant_text("4"),
+        mock::result("s1", 1),
+    ]
+    .map(|line| format!("echo '{line}'\n"))
+    .concat();
+    std::fs::write(&cli, format!("#!/bin/sh\n{lines}")).unwrap();
+    std::fs::set_permissions(&cli, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let progress = Arc::clone(&seen);
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(&cli)
+        .on_progress(Arc::new(move |message| {
+            progress.lock().unwrap().push(message.clone());
+        }))
+        .build();
+
+    let messages: Vec<_> = query("What is 2+2?", Some(options))
+        .await
+        .unwrap()
+        .map(Result::unwrap)
+        .collect()
+        .await;
+
+    assert_eq!(messages.len(), 3);
+    assert!(matches!(messages[2], Message::Result { .. }));
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 2);
+    assert!(
+        seen.iter()
+            .all(|message| matches!(message, Message::Assistant { .. }))
+    );
+}
+
+#[cfg(feature = "tools")]
+#[tokio::test]
+async fn test_query_prompt_renders_template() {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::{
+        ClaudeAgentOptions, ClaudeError, PromptInput, PromptRenderer, PromptTemplateInput,
+        query_prompt,
+    };
+    use kodegen_mcp_schema::prompt::TemplateParamValue;
+
+    /// Renders `review` as "Review <file>"
+    struct Templates;
+
+    impl PromptRenderer for Templates {
+        async fn render(
+            &self,
+            name: &str,
+            parameters: HashMap<String, TemplateParamValue>,
+        ) -> Result<String, String> {
+            match (name, parameters.get("file")) {
+                ("review", Some(TemplateParamValue::String(file))) => Ok(format!("Review {file}")),
+                _ => Err(format!("unknown template {name}")),
+            }
+        }
+    }
+
+    // Capture the command line, then refuse the launch so no CLI is started
+    let args = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&args);
+    let options = ClaudeAgentOptions::builder()
+        .cli_path("claude")
+        .pre_spawn(Arc::new(move |cmd| {
+            *seen.lock().unwrap() = cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            Err(ClaudeError::invalid_config("no CLI in tests"))
+        }))
+        .build();
+
+    let prompt = PromptInput::Template(PromptTemplateInput {
+        name: "review".to_string(),
+        parameters: HashMap::from([(
+            "file".to_string(),
+            TemplateParamValue::String("src/lib.rs".to_string()),
+        )]),
+    });
+    assert!(
+        query_prompt(&prompt, &Templates, Some(options.clone()))
+            .await
+            .is_err()
+    );
+    assert!(
+        args.lock()
+            .unwrap()
+            .iter()
+            .any(|arg| arg == "Review src/lib.rs")
+    );
+
+    // A template that cannot be rendered fails before any launch
+    args.lock().unwrap().clear();
+    let missing = PromptInput::Template(PromptTemplateInput {
+        name: "missing".to_string(),
+        parameters: HashMap::new(),
+    });
+    assert!(matches!(
+        query_prompt(&missing, &Templates, Some(options)).await,
+        Err(ClaudeError::PromptTemplateError { .. })
+    ));
+    assert!(args.lock().unwrap().is_empty());
+}
@@ -1,3 +1,14 @@
 //! Transport module tests
 
+#[cfg(unix)]
+pub mod test_container;
+#[cfg(feature = "http")]
+pub mod test_http;
+pub mod test_mock;
+#[cfg(unix)]
+pub mod test_pool;
+pub mod test_quoting;
+pub mod test_record;
+#[cfg(unix)]
+pub mod test_ssh;
 pub mod test_subprocess;
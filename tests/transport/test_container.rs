@@ -0,0 +1,48 @@
+//! Unit tests for `ContainerTransport`
+//!
+//! Uses a fake container runtime that reports the arguments it was started
+//! with, so the generated `run` command can be checked without docker
+
+use kodegen_claude_agent::transport::{ContainerTransport, PromptInput, Transport};
+use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+use kodegen_claude_agent::types::{ContainerMount, ContainerTransportConfig};
+
+use crate::common::Fixture;
+
+#[tokio::test]
+async fn test_container_transport_builds_run_command() {
+    let fixture = Fixture::new();
+
+    // Fake runtime: echo its arguments and a forwarded variable as JSON
+    let runtime = fixture.script(
+        "docker",
+        "printf '{\"type\":\"probe\",\"args\":\"%s\",\"custom\":\"%s\"}\\n' \"$*\" \"$CUSTOM_VAR\"\n",
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .cwd(fixture.dir())
+        .add_env("CUSTOM_VAR", "forwarded")
+        .build();
+    let config = ContainerTransportConfig::new("claude-sandbox:latest")
+        .runtime(&runtime)
+        .network("none")
+        .mount(ContainerMount::read_only("/etc/hosts", "/etc/hosts"));
+
+    let mut transport = ContainerTransport::new(PromptInput::Stream, options, config);
+    transport.connect().await.unwrap();
+
+    let mut rx = transport.read_messages();
+    let message = rx.recv().await.unwrap().unwrap();
+    let args = message["args"].as_str().unwrap();
+    let cwd = fixture.dir().to_string_lossy();
+
+    assert!(args.starts_with("run --rm -i --network none"));
+    assert!(args.contains(&format!("-v {cwd}:{cwd} -w {cwd}")));
+    assert!(args.contains("-v /etc/hosts:/etc/hosts:ro"));
+    assert!(args.contains("-e CUSTOM_VAR"));
+    assert!(args.contains("claude-sandbox:latest claude "));
+    assert!(!args.contains("forwarded"));
+    assert_eq!(message["custom"], "forwarded");
+
+    transport.close().await.unwrap();
+}
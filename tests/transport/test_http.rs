@@ -0,0 +1,115 @@
+//! Unit tests for `HttpTransport`
+//!
+//! Runs the transport against a minimal in-process HTTP server
+
+use kodegen_claude_agent::transport::{HttpTransport, Transport};
+use kodegen_claude_agent::types::HttpTransportConfig;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Read one HTTP request, returning the request line and body
+async fn read_request(stream: &mut TcpStream) -> (String, String) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .find_map(|l| {
+            l.to_ascii_lowercase()
+                .strip_prefix("content-length:")
+                .map(|v| v.trim().parse::<usize>().unwrap())
+        })
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let request_line = head.lines().next().unwrap_or_default().to_string();
+    let body = String::from_utf8_lossy(&buf[header_end..header_end + content_length]).to_string();
+    (request_line, body)
+}
+
+/// Serve `/events` with a fixed SSE body and record `/messages` bodies
+async fn spawn_server(events: &'static str) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (posted_tx, posted_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                break;
+            };
+            let posted_tx = posted_tx.clone();
+            tokio::spawn(async move {
+                let (request_line, body) = read_request(&mut stream).await;
+                let response = if request_line.starts_with("GET /events") {
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{events}",
+                        events.len()
+                    )
+                } else {
+                    let _ = posted_tx.send(body);
+                    "HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    (format!("http://{addr}"), posted_rx)
+}
+
+#[tokio::test]
+async fn test_http_transport_reads_sse_messages() {
+    let events = ": keep-alive\n\ndata: {\"type\":\"system\",\"subtype\":\"init\"}\n\nevent: message\ndata: {\"type\":\"result\",\ndata: \"ok\":true}\n\n";
+    let (endpoint, _posted) = spawn_server(events).await;
+
+    let mut transport = HttpTransport::new(HttpTransportConfig::new(endpoint), None);
+    transport.connect().await.unwrap();
+    assert!(transport.is_ready());
+
+    let mut rx = transport.read_messages();
+    let first = rx.recv().await.unwrap().unwrap();
+    assert_eq!(first["subtype"], "init");
+    let second = rx.recv().await.unwrap().unwrap();
+    assert_eq!(second["type"], "result");
+    assert_eq!(second["ok"], true);
+    assert!(rx.recv().await.is_none());
+
+    transport.close().await.unwrap();
+    assert!(!transport.is_ready());
+}
+
+#[tokio::test]
+async fn test_http_transport_posts_writes() {
+    let (endpoint, mut posted) = spawn_server("").await;
+
+    let mut transport = HttpTransport::new(
+        HttpTransportConfig::new(format!("{endpoint}/")).header("authorization", "Bearer test"),
+        None,
+    );
+    transport.connect().await.unwrap();
+    transport.write("{\"type\":\"user\"}\n").await.unwrap();
+    assert_eq!(posted.recv().await.unwrap(), "{\"type\":\"user\"}");
+
+    transport.end_input().await.unwrap();
+    assert!(transport.write("{}\n").await.is_err());
+}
+
+#[tokio::test]
+async fn test_http_transport_rejects_invalid_endpoint() {
+    let mut transport = HttpTransport::new(HttpTransportConfig::new("not a url"), None);
+    assert!(transport.connect().await.is_err());
+}
@@ -0,0 +1,103 @@
+//! Unit tests for `MockTransport`
+
+use kodegen_claude_agent::transport::Transport;
+use kodegen_claude_agent::transport::mock::{self, MockTransport};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_mock_transport_plays_back_replies_after_writes() {
+    let mut transport = MockTransport::new()
+        .message(json!({"type": "system", "subtype": "init"}))
+        .reply(vec![mock::assistant_text("first")])
+        .reply(vec![mock::assistant_text("second"), mock::result("s1", 2)])
+        .finish_after_replies();
+    let handle = transport.handle();
+
+    transport.connect().await.unwrap();
+    assert!(transport.is_ready());
+    let mut rx = transport.read_messages();
+
+    assert_eq!(rx.recv().await.unwrap().unwrap()["subtype"], "init");
+    assert!(rx.try_recv().is_err());
+
+    transport.write("{\"n\":1}\n").await.unwrap();
+    let first = rx.recv().await.unwrap().unwrap();
+    assert_eq!(first["message"]["content"][0]["text"], "first");
+
+    transport.write("{\"n\":2}\n").await.unwrap();
+    assert_eq!(
+        rx.recv().await.unwrap().unwrap()["message"]["content"][0]["text"],
+        "second"
+    );
+    assert_eq!(rx.recv().await.unwrap().unwrap()["type"], "result");
+    assert!(rx.recv().await.is_none(), "stream ends after the script");
+
+    assert_eq!(handle.written(), vec!["{\"n\":1}", "{\"n\":2}"]);
+    assert_eq!(handle.written_json()[1]["n"], 2);
+}
+
+#[tokio::test]
+async fn test_mock_transport_handle_drives_stream() {
+    let mut transport = MockTransport::new();
+    let handle = transport.handle();
+
+    assert!(
+        transport.write("early").await.is_err(),
+        "write before connect"
+    );
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    handle.push(mock::assistant_text("pushed"));
+    assert_eq!(
+        rx.recv().await.unwrap().unwrap()["message"]["content"][0]["text"],
+        "pushed"
+    );
+
+    transport.end_input().await.unwrap();
+    assert!(handle.input_ended());
+    assert!(transport.write("late").await.is_err());
+
+    handle.finish();
+    assert!(rx.recv().await.is_none());
+
+    transport.close().await.unwrap();
+    assert!(handle.is_closed());
+    assert!(!transport.is_ready());
+}
+
+#[cfg(feature = "client")]
+#[tokio::test]
+async fn test_mock_transport_with_client() {
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient, ContentBlock, Message};
+
+    let transport = MockTransport::new().reply(vec![
+        mock::assistant_text("Hello back"),
+        mock::result("session-1", 1),
+    ]);
+    let handle = transport.handle();
+
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Hello").await.unwrap();
+
+    match client.next_message().await.unwrap().unwrap() {
+        Message::Assistant { message, .. } => match &message.content[0] {
+            ContentBlock::Text { text } => assert_eq!(text, "Hello back"),
+            other => panic!("unexpected content block: {other:?}"),
+        },
+        other => panic!("unexpected message: {other:?}"),
+    }
+    match client.next_message().await.unwrap().unwrap() {
+        Message::Result { session_id, .. } => assert_eq!(session_id.as_str(), "session-1"),
+        other => panic!("unexpected message: {other:?}"),
+    }
+
+    let written = handle.written_json();
+    assert_eq!(written[0]["type"], "user");
+    assert_eq!(written[0]["message"]["content"], "Hello");
+
+    client.close().await.unwrap();
+    assert!(handle.is_closed());
+}
@@ -0,0 +1,120 @@
+//! Unit tests for `TransportPool`
+//!
+//! A fake CLI logs each start to a file and answers every input line with
+//! one message, so warm and cold starts can be counted
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use kodegen_claude_agent::transport::{Transport, TransportPool};
+use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+use crate::common::Fixture;
+
+/// Write a fake CLI that appends to `starts` when started and echoes input
+fn fake_cli(fixture: &Fixture) -> (PathBuf, PathBuf) {
+    let starts = fixture.path("starts");
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "echo started >> '{}'\nwhile read -r line; do echo '{{\"type\":\"system\",\"subtype\":\"echo\"}}'; done\n",
+            starts.display()
+        ),
+    );
+    (cli, starts)
+}
+
+/// Wait until the fake CLI has been started `count` times
+async fn wait_starts(starts: &Path, count: usize) {
+    for _ in 0..100 {
+        let started = std::fs::read_to_string(starts)
+            .map(|s| s.lines().count())
+            .unwrap_or(0);
+        if started == count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("fake CLI never reached {count} starts");
+}
+
+/// Wait until the pool has `count` idle processes
+async fn wait_idle(pool: &TransportPool, count: usize) {
+    for _ in 0..100 {
+        if pool.idle_count().await == count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("pool never reached {count} idle processes");
+}
+
+#[tokio::test]
+async fn test_pool_hands_out_warm_transports() {
+    let fixture = Fixture::new();
+    let (cli, starts) = fake_cli(&fixture);
+    let options = ClaudeAgentOptions::builder().max_turns(2).build();
+    let pool = TransportPool::new(options, Some(cli), 2).unwrap();
+    assert_eq!(pool.idle_count().await, 0);
+
+    pool.warm().await.unwrap();
+    assert_eq!(pool.idle_count().await, 2);
+
+    let mut transport = pool.acquire().await.unwrap();
+    assert!(transport.is_ready());
+    let mut rx = transport.read_messages();
+    transport.write("{\"type\":\"user\"}\n").await.unwrap();
+    let message = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(message["subtype"], "echo");
+
+    // The taken process is replaced in the background
+    wait_idle(&pool, 2).await;
+    wait_starts(&starts, 3).await;
+
+    transport.close().await.unwrap();
+    pool.close().await;
+    assert_eq!(pool.idle_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_pool_matches_launch_options() {
+    let fixture = Fixture::new();
+    let (cli, _) = fake_cli(&fixture);
+    let options = ClaudeAgentOptions::builder()
+        .model("sonnet")
+        .max_turns(2)
+        .build();
+    let pool = TransportPool::new(options.clone(), Some(cli), 1).unwrap();
+
+    assert!(pool.matches(&options));
+    assert!(
+        pool.matches(
+            &ClaudeAgentOptions::builder()
+                .model("sonnet")
+                .max_turns(2)
+                .read_timeout(Duration::from_secs(5))
+                .build()
+        )
+    );
+    assert!(
+        !pool.matches(
+            &ClaudeAgentOptions::builder()
+                .model("opus")
+                .max_turns(2)
+                .build()
+        )
+    );
+    assert!(
+        !pool.matches(
+            &ClaudeAgentOptions::builder()
+                .model("sonnet")
+                .max_turns(2)
+                .cwd("/tmp")
+                .build()
+        )
+    );
+}
@@ -0,0 +1,194 @@
+//! Unit tests for CLI argument quoting
+//!
+//! Every adversarial value must come out of the matching parser exactly as
+//! it went in. The Windows parsers here model `CommandLineToArgvW` and
+//! `cmd.exe` caret handling, so the matrix also runs on Unix hosts.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+use kodegen_claude_agent::ClaudeError;
+use kodegen_claude_agent::transport::subprocess::quoting;
+
+#[cfg(unix)]
+use crate::common::Fixture;
+
+/// Values that commonly break argument passing
+const ADVERSARIAL: &[&str] = &[
+    "",
+    "plain",
+    "two words",
+    "\"quoted\"",
+    "trailing backslash\\",
+    "\\\\server\\share\\",
+    "back\\\"slash quote",
+    r#"{"mcpServers":{"x":{"command":"a b","args":["--flag=\"v\"","C:\\dir\\"]}}}"#,
+    "%PATH% !VAR! ^caret & | < > ( ) [ ]",
+    "semi;colon,comma*star?mark `tick`",
+    "tab\there",
+    "unicode: héllo — 日本語 🚀",
+    "'single' and ‘curly’ quotes",
+    "$HOME $(id) \\$escaped",
+    "multi\nline\r\nprompt",
+];
+
+/// Split a command line into arguments with the MSVC rules
+fn parse_msvc(line: &str) -> Vec<String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut args = Vec::new();
+    let mut i = 0;
+    loop {
+        while i < chars.len() && matches!(chars[i], ' ' | '\t') {
+            i += 1;
+        }
+        if i >= chars.len() {
+            return args;
+        }
+
+        let mut arg = String::new();
+        let mut in_quotes = false;
+        while i < chars.len() {
+            match chars[i] {
+                ' ' | '\t' if !in_quotes => break,
+                '\\' => {
+                    let start = i;
+                    while i < chars.len() && chars[i] == '\\' {
+                        i += 1;
+                    }
+                    let count = i - start;
+                    if chars.get(i) == Some(&'"') {
+                        arg.extend(std::iter::repeat_n('\\', count / 2));
+                        if count % 2 == 1 {
+                            arg.push('"');
+                            i += 1;
+                        }
+                    } else {
+                        arg.extend(std::iter::repeat_n('\\', count));
+                    }
+                }
+                '"' => {
+                    if in_quotes && chars.get(i + 1) == Some(&'"') {
+                        arg.push('"');
+                        i += 2;
+                    } else {
+                        in_quotes = !in_quotes;
+                        i += 1;
+                    }
+                }
+                c => {
+                    arg.push(c);
+                    i += 1;
+                }
+            }
+        }
+        args.push(arg);
+    }
+}
+
+/// Remove one level of `cmd.exe` caret escapes
+fn caret_unescape(line: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '^' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[test]
+fn test_windows_quoting_round_trips() {
+    for value in ADVERSARIAL {
+        let quoted = quoting::windows(value);
+        assert_eq!(parse_msvc(&quoted), [*value], "quoted as {quoted}");
+    }
+    assert_eq!(quoting::windows("plain"), "plain");
+}
+
+#[test]
+fn test_cmd_shim_quoting_round_trips() {
+    let shim = Path::new(r"C:\npm\claude.cmd");
+    let values: Vec<&str> = ADVERSARIAL
+        .iter()
+        .copied()
+        .filter(|value| !value.contains(['\n', '\r']))
+        .collect();
+    let args: Vec<OsString> = values.iter().map(OsString::from).collect();
+
+    let line = quoting::batch_command_line(shim, &args).unwrap();
+
+    // cmd.exe /c removes one level of escapes and runs the shim, which
+    // re-parses its arguments (%*) before Node splits them
+    let line = caret_unescape(&line);
+    let (command, rest) = line.split_once(' ').unwrap();
+    assert_eq!(command, r"C:\npm\claude.cmd");
+    assert_eq!(parse_msvc(&caret_unescape(rest)), values);
+}
+
+#[test]
+fn test_cmd_shim_rejects_line_breaks() {
+    for value in ["multi\nline", "carriage\rreturn", "nul\0byte"] {
+        let err = quoting::cmd_shim(value).unwrap_err();
+        assert!(matches!(err, ClaudeError::InvalidConfig(_)));
+    }
+    let args = [OsString::from("ok"), OsString::from("system\nprompt")];
+    assert!(quoting::batch_command_line(Path::new("claude.cmd"), &args).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_posix_quoting_round_trips() {
+    for value in ADVERSARIAL {
+        let script = format!("printf '%s' {}", quoting::posix(value));
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&script)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), *value);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_receives_arguments_intact() {
+    use kodegen_claude_agent::transport::{PromptInput, SubprocessTransport, Transport};
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: writes its arguments NUL-separated, then reports in
+    let fixture = Fixture::new();
+    let argv = fixture.path("argv");
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "printf '%s\\0' \"$@\" > {}\necho '{{\"type\":\"system\",\"subtype\":\"init\"}}'\n",
+            quoting::posix(&argv.to_string_lossy())
+        ),
+    );
+
+    for value in ADVERSARIAL {
+        let options = ClaudeAgentOptions::builder().system_prompt(*value).build();
+        let mut transport =
+            SubprocessTransport::new(PromptInput::from(*value), options, Some(cli.clone()))
+                .unwrap();
+        transport.connect().await.unwrap();
+        let mut rx = transport.read_messages();
+        rx.recv().await.unwrap().unwrap();
+        transport.close().await.unwrap();
+
+        let recorded = std::fs::read(&argv).unwrap();
+        let args: Vec<&[u8]> = recorded.split(|b| *b == 0).collect();
+        let system_prompt = args
+            .iter()
+            .position(|arg| *arg == b"--system-prompt")
+            .map(|i| args[i + 1]);
+        assert_eq!(system_prompt, Some(value.as_bytes()));
+        // The prompt follows `--` as the last argument
+        assert_eq!(args[args.len() - 2], value.as_bytes());
+    }
+}
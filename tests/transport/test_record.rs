@@ -0,0 +1,129 @@
+//! Unit tests for `RecordingTransport` and `ReplayTransport`
+
+use kodegen_claude_agent::transport::Transport;
+use kodegen_claude_agent::transport::mock::{self, MockTransport};
+use kodegen_claude_agent::transport::record::{
+    FixtureEvent, RecordingTransport, ReplayTransport, TransportFixture,
+};
+use serde_json::json;
+
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "kodegen-fixture-{name}-{}.jsonl",
+        std::process::id()
+    ))
+}
+
+#[tokio::test]
+async fn test_recording_captures_exchange() {
+    let path = fixture_path("record");
+    let inner = MockTransport::new()
+        .message(json!({"type": "system", "subtype": "init"}))
+        .reply(vec![mock::assistant_text("hi"), mock::result("s1", 1)])
+        .finish_after_replies();
+    let mut transport = RecordingTransport::create(inner, &path).unwrap();
+
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+    assert_eq!(rx.recv().await.unwrap().unwrap()["subtype"], "init");
+    transport.write("{\"type\":\"user\"}\n").await.unwrap();
+    while rx.recv().await.is_some() {}
+    transport.close().await.unwrap();
+
+    let fixture = TransportFixture::load(&path).unwrap();
+    assert_eq!(fixture.events.len(), 4);
+    assert_eq!(
+        fixture.events[1],
+        FixtureEvent::Sent {
+            data: "{\"type\":\"user\"}".to_string()
+        }
+    );
+    assert!(matches!(
+        fixture.events[3],
+        FixtureEvent::Received { ref message } if message["type"] == "result"
+    ));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_replay_serves_fixture_in_order() {
+    let path = fixture_path("replay");
+    let fixture = TransportFixture {
+        events: vec![
+            FixtureEvent::Received {
+                message: json!({"type": "system", "subtype": "init"}),
+            },
+            FixtureEvent::Sent {
+                data: "first".to_string(),
+            },
+            FixtureEvent::Received {
+                message: mock::assistant_text("one"),
+            },
+            FixtureEvent::Sent {
+                data: "second".to_string(),
+            },
+            FixtureEvent::Received {
+                message: mock::result("s1", 2),
+            },
+        ],
+    };
+    fixture.save(&path).unwrap();
+    assert_eq!(TransportFixture::load(&path).unwrap(), fixture);
+
+    let mut transport = ReplayTransport::from_path(&path).unwrap().strict(true);
+    let handle = transport.handle();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    assert_eq!(rx.recv().await.unwrap().unwrap()["subtype"], "init");
+    transport.write("first\n").await.unwrap();
+    assert_eq!(rx.recv().await.unwrap().unwrap()["type"], "assistant");
+    assert!(transport.write("other\n").await.is_err(), "strict mismatch");
+    assert_eq!(handle.written(), vec!["first"]);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "client")]
+#[tokio::test]
+async fn test_record_then_replay_with_client() {
+    use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeSDKClient, Message};
+
+    let path = fixture_path("roundtrip");
+
+    // Record a run against a scripted CLI
+    let inner = MockTransport::new().reply(vec![
+        mock::assistant_text("recorded"),
+        mock::result("session-1", 1),
+    ]);
+    let transport = RecordingTransport::create(inner, &path).unwrap();
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Hello").await.unwrap();
+    while let Some(message) = client.next_message().await {
+        if matches!(message.unwrap(), Message::Result { .. }) {
+            break;
+        }
+    }
+    client.close().await.unwrap();
+
+    // Replay it strictly: the client must send the same prompt
+    let transport = ReplayTransport::from_path(&path).unwrap().strict(true);
+    let mut client = ClaudeSDKClient::with_transport(ClaudeAgentOptions::default(), transport)
+        .await
+        .unwrap();
+    client.send_message("Hello").await.unwrap();
+    let mut types = Vec::new();
+    while let Some(message) = client.next_message().await {
+        match message.unwrap() {
+            Message::Assistant { .. } => types.push("assistant"),
+            Message::Result { .. } => types.push("result"),
+            _ => {}
+        }
+    }
+    assert_eq!(types, vec!["assistant", "result"]);
+    client.close().await.unwrap();
+
+    let _ = std::fs::remove_file(&path);
+}
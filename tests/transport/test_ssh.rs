@@ -0,0 +1,59 @@
+//! Unit tests for `SshTransport`
+//!
+//! Uses a fake `ssh` that runs the remote command locally, so quoting of the
+//! working directory, environment and CLI arguments is exercised end to end
+
+use kodegen_claude_agent::transport::{PromptInput, SshTransport, Transport};
+use kodegen_claude_agent::types::SshTransportConfig;
+use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+use crate::common::Fixture;
+
+#[tokio::test]
+async fn test_ssh_transport_runs_remote_command() {
+    let fixture = Fixture::new();
+    let work_dir = fixture.path("work dir's");
+    std::fs::create_dir_all(&work_dir).unwrap();
+
+    // Fake ssh: records its arguments; the remote command is the last one
+    let args = fixture.path("args");
+    let ssh = fixture.script(
+        "ssh",
+        &format!(
+            "printf '%s\\n' \"$@\" > '{}'\nfor a; do last=$a; done\nexec sh -c \"$last\"\n",
+            args.display()
+        ),
+    );
+
+    // Fake remote CLI: report where and how it was started
+    let claude = fixture.script(
+        "claude",
+        "printf '{\"type\":\"probe\",\"pwd\":\"%s\",\"entrypoint\":\"%s\",\"custom\":\"%s\"}\\n' \"$PWD\" \"$CLAUDE_CODE_ENTRYPOINT\" \"$CUSTOM_VAR\"\n",
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .cwd(work_dir.clone())
+        .add_env("CUSTOM_VAR", "a 'quoted' value")
+        .build();
+    let config = SshTransportConfig::new("build-host")
+        .ssh_path(&ssh)
+        .remote_cli_path(claude.to_string_lossy());
+
+    let mut transport = SshTransport::new(PromptInput::Stream, options, config);
+    transport.connect().await.unwrap();
+    assert!(transport.is_ready());
+
+    let mut rx = transport.read_messages();
+    let message = rx.recv().await.unwrap().unwrap();
+    assert_eq!(message["type"], "probe");
+    assert_eq!(message["pwd"], work_dir.to_string_lossy().as_ref());
+    assert_eq!(message["entrypoint"], "sdk-rust");
+    assert_eq!(message["custom"], "a 'quoted' value");
+
+    // Values travel over stdin, never on the ssh command line
+    let args = std::fs::read_to_string(&args).unwrap();
+    assert!(!args.contains("quoted"), "{args}");
+    assert!(!args.contains("sdk-rust"), "{args}");
+
+    transport.close().await.unwrap();
+}
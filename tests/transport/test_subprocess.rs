@@ -4,6 +4,8 @@
 
 use kodegen_claude_agent::transport::{PromptInput, SubprocessTransport};
 
+use crate::common::Fixture;
+
 #[test]
 fn test_find_cli() {
     // This will succeed if claude is installed
@@ -12,8 +14,476 @@ fn test_find_cli() {
     println!("CLI search result: {result:?}");
 }
 
+#[test]
+fn test_cli_search_locations() {
+    let locations = SubprocessTransport::cli_search_locations();
+    assert!(!locations.is_empty());
+    assert!(locations.iter().all(|path| path.is_absolute()));
+
+    let names: Vec<_> = locations
+        .iter()
+        .filter_map(|path| path.file_name()?.to_str())
+        .collect();
+    if cfg!(windows) {
+        assert!(names.contains(&"claude.cmd"), "{locations:?}");
+        assert!(names.contains(&"claude.exe"), "{locations:?}");
+    } else {
+        assert!(names.iter().all(|name| *name == "claude"), "{locations:?}");
+        assert!(locations.contains(&std::path::PathBuf::from("/usr/local/bin/claude")));
+    }
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn test_cmd_shim_is_started_through_cmd() {
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake npm shim: batch files cannot be spawned directly
+    let fixture = Fixture::new();
+    let cli = fixture.path("claude.cmd");
+    std::fs::write(
+        &cli,
+        "@echo off\r\necho {\"type\":\"system\",\"subtype\":\"init\"}\r\n",
+    )
+    .unwrap();
+
+    let options = ClaudeAgentOptions::builder().build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let message = rx.recv().await.unwrap().unwrap();
+    assert_eq!(message["subtype"], "init");
+
+    transport.close().await.unwrap();
+}
+
 #[test]
 fn test_prompt_input_conversions() {
     let _prompt1: PromptInput = "hello".into();
     let _prompt2: PromptInput = String::from("world").into();
 }
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_reconnect_resumes_after_crash() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::ReconnectPolicy;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: crash after announcing a session, echo stdin once resumed
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "case \"$*\" in\n  *'--resume sess-1'*)\n    echo '{\"type\":\"resumed\"}'\n    while read -r line; do echo \"$line\"; done ;;\n  *)\n    echo '{\"type\":\"system\",\"session_id\":\"sess-1\"}'\n    exit 1 ;;\nesac\n",
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .reconnect(ReconnectPolicy::new(2).initial_backoff(Duration::from_millis(10)))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let first = rx.recv().await.unwrap().unwrap();
+    assert_eq!(first["session_id"], "sess-1");
+    let resumed = rx.recv().await.unwrap().unwrap();
+    assert_eq!(resumed["type"], "resumed");
+
+    transport.write("{\"type\":\"echo\"}\n").await.unwrap();
+    let echoed = rx.recv().await.unwrap().unwrap();
+    assert_eq!(echoed["type"], "echo");
+
+    transport.close().await.unwrap();
+}
+
+#[test]
+fn test_reconnect_backoff_is_capped() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::types::ReconnectPolicy;
+
+    let policy = ReconnectPolicy::new(5)
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(350));
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff(2), Duration::from_millis(350));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_configurable_read_timeout_and_shutdown_grace() {
+    use std::time::{Duration, Instant};
+
+    use kodegen_claude_agent::error::ClaudeError;
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: never writes and ignores stdin closing
+    let fixture = Fixture::new();
+    let cli = fixture.script("claude", "exec sleep 30\n");
+
+    let options = ClaudeAgentOptions::builder()
+        .read_timeout(Duration::from_millis(100))
+        .shutdown_grace(Duration::from_millis(100))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let started = Instant::now();
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        Err(ClaudeError::Timeout(_))
+    ));
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let started = Instant::now();
+    transport.close().await.unwrap();
+    assert!(
+        started.elapsed() < Duration::from_secs(3),
+        "close waited {:?}",
+        started.elapsed()
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_metrics_count_parse_errors() {
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: an oversized garbage line followed by a valid message
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "echo '{}'\necho '{{\"type\":\"system\",\"subtype\":\"init\"}}'\nwhile read -r line; do :; done\n",
+            "x".repeat(300)
+        ),
+    );
+
+    let options = ClaudeAgentOptions::builder().max_buffer_size(256).build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    assert!(rx.recv().await.unwrap().is_err());
+    assert_eq!(rx.recv().await.unwrap().unwrap()["subtype"], "init");
+
+    let metrics = transport.metrics().unwrap().snapshot();
+    assert_eq!(metrics.parse_errors, 1);
+    assert_eq!(metrics.messages_read, 1);
+    assert!(metrics.bytes_read > 300);
+    assert_eq!(metrics.messages_written, 0);
+    assert!(metrics.last_response_latency.is_none());
+
+    transport.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stderr_captured_into_process_error() {
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::error::ClaudeError;
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: more stderr than is kept, then a failing exit
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "for i in 1 2 3 4 5 6 7 8 9 10; do echo \"noise line $i\" >&2; done\necho 'fatal: API key missing' >&2\nexit 3\n",
+    );
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+    let options = ClaudeAgentOptions::builder()
+        .max_stderr_size(64)
+        .stderr_callback(Arc::new(move |line: &str| {
+            sink.lock().unwrap().push(line.to_string());
+        }))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    match rx.recv().await.unwrap() {
+        Err(ClaudeError::Process {
+            exit_code, stderr, ..
+        }) => {
+            assert_eq!(exit_code, 3);
+            let stderr = stderr.unwrap();
+            assert!(stderr.ends_with("fatal: API key missing"), "{stderr}");
+            assert!(!stderr.contains("noise line 1\n"), "{stderr}");
+            assert!(stderr.len() <= 64);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let lines = lines.lock().unwrap().clone();
+    assert_eq!(lines.len(), 11);
+    assert_eq!(lines[0], "noise line 1");
+    assert!(transport.stderr_output().ends_with("fatal: API key missing"));
+
+    transport.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_multiline_and_large_messages_are_framed() {
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: a pretty-printed message whose strings contain braces, two
+    // messages on one line, then a large message spread over many lines
+    let fixture = Fixture::new();
+    let large: String = (0..20_000)
+        .map(|i| format!("\"line {i} }}\",\n"))
+        .collect();
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "cat <<'EOF'\n{{\n  \"type\": \"system\",\n  \"text\": \"a }} \\\" [ brace\"\n}}\n{{\"n\":1}} {{\"n\":2}}\n{{\"lines\": [\n{large}\"end\"]}}\nEOF\nwhile read -r line; do :; done\n"
+        ),
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .max_buffer_size(4 * 1024 * 1024)
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let first = rx.recv().await.unwrap().unwrap();
+    assert_eq!(first["type"], "system");
+    assert_eq!(first["text"], "a } \" [ brace");
+    assert_eq!(rx.recv().await.unwrap().unwrap()["n"], 1);
+    assert_eq!(rx.recv().await.unwrap().unwrap()["n"], 2);
+
+    let started = std::time::Instant::now();
+    let large = rx.recv().await.unwrap().unwrap();
+    assert_eq!(large["lines"].as_array().unwrap().len(), 20_001);
+    assert_eq!(large["lines"][19_999], "line 19999 }");
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+    let metrics = transport.metrics().unwrap().snapshot();
+    assert_eq!(metrics.messages_read, 4);
+    assert_eq!(metrics.parse_errors, 0);
+
+    transport.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_timed_out_writes_are_delivered_in_order_on_close() {
+    use std::time::Duration;
+
+    use kodegen_claude_agent::error::ClaudeError;
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: does not read stdin for a while, then copies it to a file
+    let fixture = Fixture::new();
+    let received = fixture.path("received");
+    let cli = fixture.script(
+        "claude",
+        &format!("sleep 1\ncat > '{}'\n", received.display()),
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .write_timeout(Duration::from_millis(50))
+        .shutdown_grace(Duration::from_secs(10))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+
+    // Larger than a pipe buffer, so the write cannot finish before the CLI reads
+    let large = format!("{{\"data\":\"{}\"}}\n", "x".repeat(256 * 1024));
+    assert!(matches!(
+        transport.write(&large).await,
+        Err(ClaudeError::Timeout(_))
+    ));
+    assert!(matches!(
+        transport.write("{\"n\":2}\n").await,
+        Err(ClaudeError::Timeout(_))
+    ));
+
+    transport.flush().await.unwrap();
+    transport.close().await.unwrap();
+
+    let contents = std::fs::read_to_string(&received).unwrap();
+    assert_eq!(contents, format!("{large}{{\"n\":2}}\n"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_pre_spawn_hook_rewrites_and_vetoes_command() {
+    use std::sync::Arc;
+
+    use kodegen_claude_agent::error::ClaudeError;
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: reports the injected variable and its last argument
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "for arg; do last=$arg; done\necho \"{\\\"type\\\":\\\"system\\\",\\\"subtype\\\":\\\"init\\\",\\\"token\\\":\\\"$INJECTED_TOKEN\\\",\\\"last\\\":\\\"$last\\\"}\"\nwhile read -r line; do :; done\n",
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .pre_spawn(Arc::new(|cmd| {
+            cmd.env("INJECTED_TOKEN", "secret").arg("--policy-checked");
+            Ok(())
+        }))
+        .build();
+    let mut transport =
+        SubprocessTransport::new(PromptInput::Stream, options, Some(cli.clone())).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let message = rx.recv().await.unwrap().unwrap();
+    assert_eq!(message["token"], "secret");
+    assert_eq!(message["last"], "--policy-checked");
+    transport.close().await.unwrap();
+
+    // A hook error refuses the launch
+    let options = ClaudeAgentOptions::builder()
+        .pre_spawn(Arc::new(|_| {
+            Err(ClaudeError::invalid_config("denied by policy"))
+        }))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    let err = transport.connect().await.unwrap_err();
+    assert!(matches!(err, ClaudeError::InvalidConfig(ref msg) if msg == "denied by policy"));
+    assert!(!transport.is_ready());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_process_priority_lowers_cli_niceness() {
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::Priority;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: reports its nice value once the first line arrives
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "read -r line\nnice=$(ps -o ni= -p $$ | tr -d ' ')\necho \"{\\\"type\\\":\\\"system\\\",\\\"subtype\\\":\\\"init\\\",\\\"nice\\\":$nice}\"\nwhile read -r line; do :; done\n",
+    );
+
+    let options = ClaudeAgentOptions::builder()
+        .process_priority(Priority::Idle)
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+    transport.write("{}\n").await.unwrap();
+
+    let message = rx.recv().await.unwrap().unwrap();
+    assert_eq!(message["nice"], 19);
+    transport.close().await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_long_system_prompt_is_passed_through_a_file() {
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: records its arguments and a copy of the system prompt file
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        &format!(
+            "cd '{}'\nprintf '%s\\0' \"$@\" > argv\nwhile [ $# -gt 0 ]; do\n  [ \"$1\" = --system-prompt-file ] && cat \"$2\" > prompt\n  shift\ndone\necho '{{\"type\":\"system\",\"subtype\":\"init\"}}'\n",
+            fixture.dir().display()
+        ),
+    );
+
+    // Longer than Linux accepts for a single argument
+    let system_prompt = "Be thorough. ".repeat(15_000);
+    let options = ClaudeAgentOptions::builder()
+        .system_prompt(system_prompt.as_str())
+        .model("claude-sonnet-4-5")
+        .build();
+    let mut transport =
+        SubprocessTransport::new(PromptInput::from("hi"), options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+    assert_eq!(rx.recv().await.unwrap().unwrap()["type"], "system");
+
+    let argv = std::fs::read_to_string(fixture.path("argv")).unwrap();
+    let args: Vec<&str> = argv.split('\0').collect();
+    assert!(!args.contains(&"--system-prompt"));
+    let file = args
+        .iter()
+        .position(|arg| *arg == "--system-prompt-file")
+        .map(|i| std::path::PathBuf::from(args[i + 1]))
+        .unwrap();
+    // Short values stay on the command line
+    assert!(
+        args.windows(2)
+            .any(|pair| pair == ["--model", "claude-sonnet-4-5"])
+    );
+    assert_eq!(
+        std::fs::read_to_string(fixture.path("prompt")).unwrap(),
+        system_prompt
+    );
+
+    transport.close().await.unwrap();
+    assert!(!file.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_invalid_utf8_output_is_replaced_and_counted() {
+    use std::sync::{Arc, Mutex};
+
+    use kodegen_claude_agent::transport::Transport;
+    use kodegen_claude_agent::types::options::ClaudeAgentOptions;
+
+    // Fake CLI: invalid UTF-8 on stderr and inside a JSON string, then a
+    // clean message
+    let fixture = Fixture::new();
+    let cli = fixture.script(
+        "claude",
+        "printf 'tool said \\377\\n' >&2\nprintf '{\"type\":\"user\",\"text\":\"bin \\376\\377 ary \\300\"}\\n'\necho '{\"type\":\"system\",\"subtype\":\"init\"}'\nwhile read -r line; do :; done\n",
+    );
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let captured = Arc::clone(&lines);
+    let options = ClaudeAgentOptions::builder()
+        .stderr_callback(Arc::new(move |line: &str| {
+            captured.lock().unwrap().push(line.to_string());
+        }))
+        .build();
+    let mut transport = SubprocessTransport::new(PromptInput::Stream, options, Some(cli)).unwrap();
+    transport.connect().await.unwrap();
+    let mut rx = transport.read_messages();
+
+    let repaired = rx.recv().await.unwrap().unwrap();
+    assert_eq!(repaired["text"], "bin \u{FFFD}\u{FFFD} ary \u{FFFD}");
+    assert_eq!(rx.recv().await.unwrap().unwrap()["subtype"], "init");
+
+    // Stderr is drained on its own task
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+    while lines.lock().unwrap().is_empty() && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(*lines.lock().unwrap(), ["tool said \u{FFFD}"]);
+
+    let metrics = transport.metrics().unwrap().snapshot();
+    assert_eq!(metrics.invalid_utf8, 4);
+    assert_eq!(metrics.messages_read, 2);
+    assert_eq!(metrics.parse_errors, 0);
+
+    transport.close().await.unwrap();
+}
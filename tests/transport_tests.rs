@@ -1,3 +1,4 @@
 //! Transport tests - mirrors src/transport/
 
+mod common;
 mod transport;
@@ -0,0 +1,7 @@
+//! Types module tests
+
+pub mod test_delta;
+pub mod test_identifiers;
+pub mod test_permissions;
+pub mod test_retry;
+pub mod test_role;
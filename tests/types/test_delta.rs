@@ -0,0 +1,72 @@
+//! Unit tests for decoding partial-message stream events
+
+use kodegen_claude_agent::{Delta, DeltaDecoder, TextDelta, ThinkingDelta, ToolUseDelta};
+use serde_json::json;
+
+#[test]
+fn test_decoder_types_deltas() {
+    let mut decoder = DeltaDecoder::new();
+    let events = [
+        json!({"type": "message_start", "message": {"id": "m1"}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Hmm"}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Hi"}}),
+        json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "t1", "name": "Read", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"path\":"}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "\"a.rs\"}"}}),
+        json!({"type": "message_stop"}),
+    ];
+    let deltas: Vec<Delta> = events.iter().filter_map(|e| decoder.decode(e)).collect();
+
+    let tool = |partial_json: &str| {
+        Delta::ToolUse(ToolUseDelta {
+            index: 2,
+            id: "t1".to_string(),
+            name: "Read".to_string(),
+            partial_json: partial_json.to_string(),
+        })
+    };
+    assert_eq!(
+        deltas,
+        [
+            Delta::Thinking(ThinkingDelta {
+                index: 0,
+                thinking: "Hmm".to_string()
+            }),
+            Delta::Text(TextDelta {
+                index: 1,
+                text: "Hi".to_string()
+            }),
+            tool(""),
+            tool("{\"path\":"),
+            tool("\"a.rs\"}"),
+        ]
+    );
+
+    let input: String = deltas
+        .iter()
+        .filter_map(|delta| match delta {
+            Delta::ToolUse(tool) => Some(tool.partial_json.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&input).unwrap(),
+        json!({"path": "a.rs"})
+    );
+}
+
+#[test]
+fn test_delta_serializes_with_type_tag() {
+    let delta = Delta::Text(TextDelta {
+        index: 0,
+        text: "Hi".to_string(),
+    });
+    assert_eq!(
+        serde_json::to_value(&delta).unwrap(),
+        json!({"type": "text", "index": 0, "text": "Hi"})
+    );
+}
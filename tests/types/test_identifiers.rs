@@ -0,0 +1,30 @@
+//! Unit tests for identifier generators
+
+use std::sync::Arc;
+
+use kodegen_claude_agent::types::{
+    IdGenerator, RandomIdGenerator, SequentialIdGenerator, SessionId,
+};
+
+#[test]
+fn test_sequential_ids_are_stable() {
+    let ids = SequentialIdGenerator::new();
+    assert_eq!(ids.session_id(), SessionId::new("session-1"));
+    assert_eq!(ids.session_id(), SessionId::new("session-2"));
+    assert_eq!(ids.request_id().as_str(), "req-1");
+    assert_eq!(ids.request_id().as_str(), "req-2");
+
+    let prefixed = SequentialIdGenerator::with_prefix("replay-");
+    assert_eq!(prefixed.session_id().as_str(), "replay-session-1");
+    assert_eq!(prefixed.request_id().as_str(), "replay-req-1");
+}
+
+#[test]
+fn test_random_ids() {
+    let ids: Arc<dyn IdGenerator> = Arc::new(RandomIdGenerator::new());
+    let first = ids.session_id();
+    assert_ne!(first, ids.session_id());
+    assert!(uuid::Uuid::parse_str(first.as_str()).is_ok());
+    assert_eq!(ids.request_id().as_str(), "req-1");
+    assert_eq!(ids.request_id().as_str(), "req-2");
+}
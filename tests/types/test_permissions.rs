@@ -0,0 +1,27 @@
+//! Unit tests for the permission update wire format
+
+use kodegen_claude_agent::types::permissions::{
+    PermissionBehavior, PermissionRuleValue, PermissionUpdate,
+};
+use serde_json::json;
+
+#[test]
+fn test_rule_update_serializes_like_the_cli() {
+    let update = PermissionUpdate::AddRules {
+        rules: Some(vec![PermissionRuleValue {
+            tool_name: "Bash".to_string(),
+            rule_content: Some("npm test".to_string()),
+        }]),
+        behavior: Some(PermissionBehavior::Allow),
+        destination: None,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&update).unwrap(),
+        json!({
+            "type": "addRules",
+            "rules": [{ "toolName": "Bash", "ruleContent": "npm test" }],
+            "behavior": "allow",
+        })
+    );
+}
@@ -0,0 +1,45 @@
+//! Unit tests for the `query()` retry policy
+
+use std::time::Duration;
+
+use kodegen_claude_agent::{ClaudeAgentOptions, ClaudeError, RetryPolicy};
+
+#[test]
+fn test_retry_policy_backoff_grows_to_cap() {
+    let policy = RetryPolicy::new(5)
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(300))
+        .multiplier(2.0);
+
+    assert_eq!(policy.backoff(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff(2), Duration::from_millis(300));
+    assert_eq!(policy.backoff(10), Duration::from_millis(300));
+}
+
+#[test]
+fn test_retry_policy_classifies_errors() {
+    let policy = RetryPolicy::new(3);
+    let crashed = ClaudeError::process("CLI crashed", 1, None);
+
+    assert!(policy.should_retry(1, &crashed));
+    assert!(policy.should_retry(2, &ClaudeError::timeout("no output")));
+    // The budget is spent after the third attempt
+    assert!(!policy.should_retry(3, &crashed));
+    // Errors that would only repeat are not retried
+    assert!(!policy.should_retry(1, &ClaudeError::cli_not_found()));
+    assert!(!policy.should_retry(1, &ClaudeError::invalid_config("bad flag")));
+
+    let connection_only = policy.retry_on(|error| matches!(error, ClaudeError::Connection(_)));
+    assert!(connection_only.should_retry(1, &ClaudeError::connection("refused")));
+    assert!(!connection_only.should_retry(1, &crashed));
+}
+
+#[test]
+fn test_retry_policy_is_an_option() {
+    let options = ClaudeAgentOptions::builder()
+        .retry(RetryPolicy::new(4))
+        .build();
+    assert_eq!(options.retry().map(|policy| policy.max_attempts), Some(4));
+    assert!(ClaudeAgentOptions::default().retry().is_none());
+}
@@ -0,0 +1,36 @@
+//! Unit tests for `AgentRole`
+//!
+//! Tests role selection by name and the template defaults
+
+use kodegen_claude_agent::types::AgentRole;
+
+#[test]
+fn test_role_round_trips_by_name() {
+    for role in AgentRole::ALL {
+        assert_eq!(role.name().parse::<AgentRole>().unwrap(), role);
+        assert_eq!(role.to_string(), role.name());
+
+        let json = serde_json::to_value(role).unwrap();
+        assert_eq!(json, role.name());
+        assert_eq!(serde_json::from_value::<AgentRole>(json).unwrap(), role);
+
+        assert!(!role.system_prompt().is_empty());
+        assert!(!role.allowed_tools().is_empty());
+    }
+
+    let err = "architect".parse::<AgentRole>().unwrap_err();
+    assert!(err.to_string().contains("code-reviewer"));
+}
+
+#[cfg(feature = "manager")]
+#[test]
+fn test_spawn_request_for_role() {
+    use kodegen_claude_agent::manager::SpawnSessionRequest;
+
+    let request = SpawnSessionRequest::for_role(AgentRole::TestWriter, "cover the parser");
+    assert_eq!(request.role, Some(AgentRole::TestWriter));
+    assert_eq!(request.prompt, "cover the parser");
+    assert_eq!(request.max_turns, AgentRole::TestWriter.default_max_turns());
+    assert!(request.system_prompt.is_none());
+    assert!(request.allowed_tools.is_empty());
+}
@@ -0,0 +1,3 @@
+//! Types tests - mirrors src/types/
+
+mod types;